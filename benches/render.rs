@@ -0,0 +1,62 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use sorting_animations::array::{ArrayState, MAX_HIGHLIGHTS};
+use sorting_animations::view::{Style, View};
+
+/// The size of a maximized window's canvas, which bounds the bars drawn.
+const BOUNDS: iced::Rectangle = iced::Rectangle {
+    x: 0.0,
+    y: 0.0,
+    width: 1920.0,
+    height: 900.0,
+};
+
+/// A shuffled array in the middle of a run, with a full set of highlights.
+fn mid_run(size: usize) -> ArrayState {
+    let mut array = ArrayState::new(size);
+    array.shuffle();
+
+    for i in 0..MAX_HIGHLIGHTS {
+        array.swap(i * size / MAX_HIGHLIGHTS, size - 1 - i);
+        array.highlight_last_step();
+    }
+    array.cmp_two(0, size / 2);
+
+    array
+}
+
+fn render_frame(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render_frame");
+
+    for size in [10_000, 100_000] {
+        let array = mid_run(size);
+
+        for view in View::values() {
+            group.bench_with_input(
+                BenchmarkId::new(view.to_string(), size),
+                &array,
+                |b, array| {
+                    b.iter(|| {
+                        view.draw(
+                            BOUNDS,
+                            1.0,
+                            array.numbers(),
+                            array.steps(),
+                            array.highlights(),
+                            None,
+                            array.range(),
+                            array.watched(),
+                            Some(size / 2),
+                            &[],
+                            Style::default(),
+                        )
+                    })
+                },
+            );
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, render_frame);
+criterion_main!(benches);
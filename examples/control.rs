@@ -0,0 +1,56 @@
+//! A scripted demo driving the animations through their control socket, which has to be enabled
+//! with `--control-port`:
+//!
+//! ```text
+//! cargo run -- --control-port 7878
+//! cargo run --example control -- 7878
+//! ```
+
+use std::io::{self, BufRead, Write};
+use std::{env, net, thread, time};
+
+const DEFAULT_PORT: u16 = 7878;
+
+/// The demo, one command per line as the socket takes them.
+const DEMO: &[&str] = &[
+    r#"{"command":"pause"}"#,
+    r#"{"command":"set_sort","sort":"QuickSort"}"#,
+    r#"{"command":"set_numbers","count":300}"#,
+    r#"{"command":"shuffle","seed":42}"#,
+    r#"{"command":"step","count":25}"#,
+    r#"{"command":"set_speed","speed":40}"#,
+    r#"{"command":"play"}"#,
+];
+
+fn main() -> io::Result<()> {
+    let port = match env::args().nth(1) {
+        Some(port) => port
+            .parse()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid port"))?,
+        None => DEFAULT_PORT,
+    };
+
+    let stream = net::TcpStream::connect((net::Ipv4Addr::LOCALHOST, port))?;
+    let mut writer = stream.try_clone()?;
+    let mut lines = io::BufReader::new(stream).lines();
+    let mut send = |command: &str| -> io::Result<String> {
+        writeln!(writer, "{command}")?;
+        lines
+            .next()
+            .unwrap_or_else(|| Err(io::ErrorKind::UnexpectedEof.into()))
+    };
+
+    for command in DEMO {
+        println!("> {command}\n< {}", send(command)?);
+    }
+
+    // Follows the run until it stops
+    loop {
+        thread::sleep(time::Duration::from_millis(500));
+        let stats = send(r#"{"command":"query_stats"}"#)?;
+        println!("< {stats}");
+        if !stats.contains(r#""playing":true"#) {
+            return Ok(());
+        }
+    }
+}
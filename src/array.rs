@@ -1,115 +1,977 @@
-use crate::gui;
-use iced::canvas;
-use std::cmp;
+use std::collections::VecDeque;
+use std::{cmp, io, ops, sync, time};
 
-#[derive(Clone, Copy)]
+/// The most steps sampled for highlighting between two publishes of a running sort.
+pub const MAX_HIGHLIGHTS: usize = 64;
+/// The buckets of the distances between two indices, the last of which takes the distances of a
+/// million numbers and more.
+pub const DISTANCE_BUCKETS: usize = 20;
+/// The most annotations the log keeps, dropping the oldest ones.
+pub const MAX_ANNOTATIONS: usize = 200;
+/// The most annotations logged within a second, so a sort announcing something at every step
+/// can't flood the log. The others are only counted, unless they are paused on.
+pub const MAX_ANNOTATIONS_PER_SECOND: usize = 20;
+/// How long a swapped or shifted number slides into its place while tweening.
+pub const TWEEN_DURATION: time::Duration = time::Duration::from_millis(100);
+/// The most numbers set sliding between two frames, beyond which the sort is too fast to follow
+/// them and they are put in their places at once.
+pub const MAX_TWEENS: usize = 8;
+
+/// An element of the array an operation touched, with its value at that moment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Element {
+    pub index: usize,
+    pub value: usize,
+}
+
+/// The last operation on the array, along with the values it involved.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Step {
-    ComparisonTwo(usize, usize),
-    Comparison(usize),
-    AccessTwo(usize, usize),
-    Access(usize),
+    ComparisonTwo(Element, Element),
+    /// An element compared to a value from outside the array.
+    Comparison(Element, usize),
+    /// Swapped elements, with their values before the swap.
+    AccessTwo(Element, Element),
+    /// A read element.
+    Access(Element),
+    /// A written element, with the value written.
+    Write(Element),
     None,
 }
 
-pub type ArrayView = iced::Element<'static, crate::Message>;
-
 impl Step {
     pub fn contains(&self, index: usize) -> bool {
-        match self {
-            Step::ComparisonTwo(x, y) | Step::AccessTwo(x, y) => *x == index || *y == index,
-            Step::Comparison(x) | Step::Access(x) => *x == index,
-            Step::None => false,
-        }
+        self.indices().any(|x| x == index)
     }
 
     pub fn is_comparison(&self) -> bool {
-        matches!(self, Step::Comparison(_) | Step::ComparisonTwo(_, _))
+        matches!(self, Step::Comparison(..) | Step::ComparisonTwo(..))
     }
 
     pub fn is_access(&self) -> bool {
-        matches!(self, Step::Access(_) | Step::AccessTwo(_, _))
+        matches!(self, Step::Access(_) | Step::AccessTwo(..) | Step::Write(_))
     }
 
-    pub fn values(&self) -> Vec<usize> {
+    /// The touched elements, without allocating.
+    pub fn elements(&self) -> [Option<Element>; 2] {
         match *self {
-            Step::ComparisonTwo(x, y) | Step::AccessTwo(x, y) => vec![x, y],
-            Step::Comparison(x) | Step::Access(x) => vec![x],
-            Step::None => Vec::new(),
+            Step::ComparisonTwo(x, y) | Step::AccessTwo(x, y) => [Some(x), Some(y)],
+            Step::Comparison(x, _) | Step::Access(x) | Step::Write(x) => [Some(x), None],
+            Step::None => [None, None],
+        }
+    }
+
+    pub fn indices(&self) -> impl Iterator<Item = usize> {
+        self.elements()
+            .into_iter()
+            .flatten()
+            .map(|element| element.index)
+    }
+
+    /// The values of the touched elements, as read or written by the operation.
+    pub fn values(&self) -> impl Iterator<Item = usize> {
+        self.elements()
+            .into_iter()
+            .flatten()
+            .map(|element| element.value)
+    }
+}
+
+impl std::fmt::Display for Element {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] = {}", self.index, self.value)
+    }
+}
+
+impl std::fmt::Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Step::ComparisonTwo(x, y) => write!(f, "Compared {x} with {y}"),
+            Step::Comparison(x, value) => write!(f, "Compared {x} with {value}"),
+            Step::AccessTwo(x, y) => write!(f, "Swapped {x} with {y}"),
+            Step::Access(x) => write!(f, "Read {x}"),
+            Step::Write(x) => write!(f, "Wrote {x}"),
+            Step::None => Ok(()),
+        }
+    }
+}
+
+/// A labeled value a sort reports about how far it got, like the gap of shell sort.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Phase {
+    pub label: &'static str,
+    pub value: usize,
+}
+
+impl std::fmt::Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.label, self.value)
+    }
+}
+
+/// What a sort marks within the numbers it works on, drawn over them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Marker {
+    /// The two neighbouring runs a merge sort merges, `start..middle` and `middle..end`.
+    Merge {
+        start: usize,
+        middle: usize,
+        end: usize,
+    },
+    /// The pivot a quick sort partitions around, along with the indices its scans from the left
+    /// and from the right reached.
+    Partition {
+        pivot: usize,
+        left: usize,
+        right: usize,
+    },
+}
+
+impl Marker {
+    /// The marker as it is within the numbers starting at the offset.
+    pub fn offset(self, offset: usize) -> Marker {
+        match self {
+            Marker::Merge { start, middle, end } => Marker::Merge {
+                start: start + offset,
+                middle: middle + offset,
+                end: end + offset,
+            },
+            Marker::Partition { pivot, left, right } => Marker::Partition {
+                pivot: pivot + offset,
+                left: left + offset,
+                right: right + offset,
+            },
+        }
+    }
+}
+
+/// How the numbers can be arranged before sorting them, always keeping them within `1..=size`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Arrangement {
+    Shuffled,
+    Reversed,
+    Sorted,
+    AllEqual,
+    /// The two sorted halves interleaved, `1, n/2 + 1, 2, n/2 + 2, ...`.
+    Interleaved,
+    /// Two sorted runs spanning all the numbers one after the other, the odd ones then the even
+    /// ones.
+    TwoRuns,
+}
+
+impl Arrangement {
+    pub const VALUES: [Arrangement; 6] = [
+        Arrangement::Shuffled,
+        Arrangement::Reversed,
+        Arrangement::Sorted,
+        Arrangement::AllEqual,
+        Arrangement::Interleaved,
+        Arrangement::TwoRuns,
+    ];
+}
+
+impl std::fmt::Display for Arrangement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Arrangement::AllEqual => write!(f, "All equal"),
+            Arrangement::TwoRuns => write!(f, "Two runs"),
+            _ => write!(f, "{self:?}"),
+        }
+    }
+}
+
+/// How many comparisons and swaps of two indices there were by the distance between them.
+///
+/// The buckets double in width, the first counts neighbors and the same index twice, the next one
+/// distances 2 and 3, then 4 to 7 and so on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Distances([u64; DISTANCE_BUCKETS]);
+
+impl Distances {
+    pub fn buckets(&self) -> &[u64; DISTANCE_BUCKETS] {
+        &self.0
+    }
+
+    /// The shortest distance the bucket counts.
+    pub fn shortest(bucket: usize) -> usize {
+        1 << bucket
+    }
+
+    fn count(&mut self, a: usize, b: usize) {
+        let distance = a.abs_diff(b).max(1);
+        let bucket = (usize::BITS - 1 - distance.leading_zeros()) as usize;
+        self.0[bucket.min(DISTANCE_BUCKETS - 1)] += 1;
+    }
+}
+
+/// A milestone a sort announced, like the pivot it chose.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Annotation {
+    /// How long after the log started it was logged.
+    pub at: time::Duration,
+    /// The operations performed before it.
+    pub operation: u64,
+    pub text: String,
+    /// Whether it matched what the annotations are paused on.
+    pub matched: bool,
+}
+
+impl std::fmt::Display for Annotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:.3} s\t#{}\t{}",
+            self.at.as_secs_f64(),
+            self.operation,
+            self.text
+        )
+    }
+}
+
+/// The annotations of the running sort, oldest first, along with those the rate limit dropped.
+#[derive(Clone, Debug)]
+pub struct Annotations {
+    entries: VecDeque<Annotation>,
+    started: crate::Instant,
+    /// When the second the rate limit counts in started, and the annotations logged within it.
+    window: (crate::Instant, usize),
+    dropped: u64,
+}
+
+impl Default for Annotations {
+    fn default() -> Annotations {
+        let now = crate::Instant::now();
+
+        Annotations {
+            entries: VecDeque::new(),
+            started: now,
+            window: (now, 0),
+            dropped: 0,
+        }
+    }
+}
+
+impl Annotations {
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &Annotation> + ExactSizeIterator {
+        self.entries.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// How many annotations the rate limit dropped.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Writes the annotations as text, one per line.
+    pub fn write_text(&self, mut writer: impl io::Write) -> io::Result<()> {
+        for annotation in &self.entries {
+            writeln!(writer, "{annotation}")?;
+        }
+        if self.dropped > 0 {
+            writeln!(writer, "({} more dropped)", self.dropped)?;
+        }
+
+        writer.flush()
+    }
+
+    fn push(&mut self, operation: u64, text: String, matched: bool, now: crate::Instant) {
+        if now.duration_since(self.window.0) >= time::Duration::from_secs(1) {
+            self.window = (now, 0);
+        }
+        if self.window.1 >= MAX_ANNOTATIONS_PER_SECOND && !matched {
+            self.dropped += 1;
+            return;
+        }
+        self.window.1 += 1;
+
+        if self.entries.len() == MAX_ANNOTATIONS {
+            self.entries.pop_front();
         }
+        self.entries.push_back(Annotation {
+            at: now.duration_since(self.started),
+            operation,
+            text,
+            matched,
+        });
+    }
+}
+
+/// What a sort reports about the space it needs besides the array, as it allocates and frees
+/// its buffers and recurses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpaceReport {
+    AuxAlloc(usize),
+    AuxFree(usize),
+    Depth(usize),
+}
+
+/// The auxiliary bytes and recursion depth of the running sort, along with the most it needed
+/// of either so far.
+///
+/// Sorts that don't report them need none as far as anyone can tell.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Space {
+    pub aux_bytes: usize,
+    pub depth: usize,
+    pub peak_aux_bytes: usize,
+    pub peak_depth: usize,
+}
+
+impl Space {
+    pub fn apply(&mut self, report: SpaceReport) {
+        match report {
+            SpaceReport::AuxAlloc(bytes) => {
+                self.aux_bytes += bytes;
+                self.peak_aux_bytes = self.peak_aux_bytes.max(self.aux_bytes);
+            }
+            // Frees of buffers allocated before the stats were reset aren't counted twice
+            SpaceReport::AuxFree(bytes) => self.aux_bytes = self.aux_bytes.saturating_sub(bytes),
+            SpaceReport::Depth(depth) => {
+                self.depth = depth;
+                self.peak_depth = self.peak_depth.max(depth);
+            }
+        }
+    }
+}
+
+/// The statistics of the array read at once, so they are consistent with each other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Stats {
+    pub comparisons: u64,
+    /// Counted from the trace once a recorded run completes, as only then it is known.
+    pub wasted_comparisons: Option<u64>,
+    pub reads: u64,
+    pub writes: u64,
+    pub last_step: Step,
+    pub size: usize,
+    pub placed: usize,
+    /// How often the numbers were changed, telling apart the stats of different numbers.
+    pub generation: u64,
+    /// None unless counted.
+    pub distances: Option<Distances>,
+    pub space: Space,
+}
+
+impl Stats {
+    /// The share of the numbers already where sorting puts them in percent, 100 when there are no
+    /// numbers.
+    pub fn placed_percent(&self) -> usize {
+        (self.placed * 100).checked_div(self.size).unwrap_or(100)
     }
 }
 
+/// The array being sorted together with its statistics.
+///
+/// The numbers are shared copy-on-write, so cloning the state for publishing or rendering is
+/// cheap and only the first write after a clone copies them.
+///
+/// Sorts running on several worker threads at once leave a step per worker, where operations
+/// are attributed to worker 0 unless performed with `as_worker`.
+///
+/// Besides the last steps, a running sort samples some of the earlier steps of its batch into
+/// the highlights, so operations before the last one of a tick can be drawn as well.
+///
+/// How many numbers are already where sorting puts them is counted along, adjusting the count
+/// for the indices an operation writes.
+///
+/// Operations touching a watched index are counted as well, so whoever paces the sort can slow
+/// down for them.
+///
+/// Every change of the numbers starts a new generation and widens the dirty range, so whoever
+/// draws or copies them can tell whether and where they changed since they last looked.
+///
+/// Sorts only sort the selected range of the numbers, all of them unless selected otherwise.
 #[derive(Clone)]
 pub struct ArrayState {
-    numbers: Vec<usize>,
-    view: gui::View,
+    numbers: sync::Arc<Vec<usize>>,
+    /// The numbers as they are once sorted, computed whenever they are replaced.
+    sorted: sync::Arc<Vec<usize>>,
+    placed: usize,
+    /// The largest value the numbers are spread up to when ordered.
+    max_value: usize,
     step: Step,
+    steps: Vec<Step>,
+    highlights: Vec<(usize, Step)>,
+    /// The watched indices, sorted.
+    watched: Vec<usize>,
+    watch_hits: u64,
+    /// The range sorts sort, if not all the numbers.
+    range: Option<ops::Range<usize>>,
+    /// The phase the running sort reported last.
+    phase: Option<Phase>,
+    /// The marker of each worker of the running sort, as it reported it last.
+    markers: Vec<Option<Marker>>,
+    /// The indices the running sort marked, like the cursors of its pass.
+    marks: Vec<usize>,
+    worker: usize,
+    /// How often the numbers were changed, once per operation changing them.
+    generation: u64,
+    /// The indices changed since the dirty range was last taken, if any.
+    dirty: Option<ops::Range<usize>>,
     comparisons: u64,
+    /// The comparisons of two numbers that never moved again, once the trace of the run told.
+    wasted: Option<u64>,
     reads: u64,
     writes: u64,
+    /// The distances of the comparisons and swaps, unless they aren't counted.
+    distances: Option<Distances>,
+    /// The space the running sort reported, shared by all its workers.
+    space: Space,
+    /// The operations since the stats were reset, which the annotations are numbered by.
+    operations: u64,
+    /// Shared copy-on-write like the numbers, as the log is only appended to now and then.
+    annotations: sync::Arc<Annotations>,
+    /// What annotations are paused on, lowercase, if anything.
+    pause_on: Option<String>,
+    pause_hits: u64,
+    /// Whether moved numbers slide into their places when drawn.
+    tweening: bool,
+    tweens: Vec<Tween>,
+    /// The numbers set sliding since the last frame.
+    tweened: usize,
+}
+
+/// A number sliding into its place, only where it is drawn.
+#[derive(Clone, Copy, Debug)]
+struct Tween {
+    index: usize,
+    /// The places away from its index it started at.
+    from: f32,
+    left: time::Duration,
 }
 
 impl ArrayState {
-    pub fn new(size: usize, view: gui::View) -> ArrayState {
-        ArrayState {
-            numbers: (1..=size).collect(),
-            view,
+    pub fn new(size: usize) -> ArrayState {
+        ArrayState::from_numbers((1..=size).collect())
+    }
+
+    pub fn from_numbers(numbers: Vec<usize>) -> ArrayState {
+        let mut array_state = ArrayState {
+            numbers: sync::Arc::default(),
+            sorted: sync::Arc::default(),
+            placed: 0,
+            max_value: numbers.iter().copied().max().unwrap_or(0),
             step: Step::None,
+            steps: Vec::new(),
+            highlights: Vec::new(),
+            watched: Vec::new(),
+            watch_hits: 0,
+            range: None,
+            phase: None,
+            markers: Vec::new(),
+            marks: Vec::new(),
+            worker: 0,
+            generation: 0,
+            dirty: None,
             comparisons: 0,
+            wasted: None,
             reads: 0,
             writes: 0,
-        }
-    }
+            distances: Some(Distances::default()),
+            space: Space::default(),
+            operations: 0,
+            annotations: sync::Arc::default(),
+            pause_on: None,
+            pause_hits: 0,
+            tweening: false,
+            tweens: Vec::new(),
+            tweened: 0,
+        };
+        array_state.replace_numbers(numbers);
 
-    pub fn initialize(&mut self, size: usize) {
-        self.numbers = (1..=size).collect();
-        self.step = Step::None;
+        array_state
     }
 
-    pub fn get_view(&self) -> gui::View {
-        self.view
+    /// Replaces the numbers with `size` ordered ones spread evenly over `1..=max_value`, so there
+    /// are duplicates if it is less than the size and gaps if it is more.
+    pub fn initialize(&mut self, size: usize, max_value: usize) {
+        self.max_value = max_value;
+        self.replace_numbers(spread(size, max_value));
+        self.clear_step();
     }
 
-    pub fn set_view(&mut self, view: gui::View) {
-        self.view = view;
+    pub fn shuffle(&mut self) {
+        self.shuffle_with(&mut rand::thread_rng());
     }
 
-    pub fn array_view(&self) -> ArrayView {
-        iced::Canvas::new(self.clone())
-            .width(iced::Length::Fill)
-            .height(iced::Length::Fill)
-            .into()
+    /// Shuffles the numbers the same way every time for the seed.
+    pub fn shuffle_seeded(&mut self, seed: u64) {
+        use rand::SeedableRng;
+
+        self.shuffle_with(&mut rand::rngs::StdRng::seed_from_u64(seed));
     }
 
-    pub fn shuffle(&mut self) {
+    fn shuffle_with(&mut self, rng: &mut impl rand::Rng) {
         use rand::prelude::SliceRandom;
 
-        self.numbers.shuffle(&mut rand::thread_rng());
-        self.step = Step::None;
+        self.numbers_mut().shuffle(rng);
+        self.changed(0..self.size());
+        self.count_placed();
+        self.clear_step();
     }
 
     pub fn reverse(&mut self) {
-        self.numbers.reverse();
-        self.step = Step::None;
+        self.numbers_mut().reverse();
+        self.changed(0..self.size());
+        self.count_placed();
+        self.clear_step();
+    }
+
+    /// Replaces the numbers with arbitrary ones, which are ordered up to the largest of them.
+    pub fn load_numbers(&mut self, numbers: Vec<usize>) {
+        self.max_value = numbers.iter().copied().max().unwrap_or(0);
+        self.replace_numbers(numbers);
+        self.clear_step();
+    }
+
+    /// Puts the numbers back in order, as they are initialized.
+    pub fn order(&mut self) {
+        self.initialize(self.size(), self.max_value);
+    }
+
+    pub fn arrange(&mut self, arrangement: Arrangement) {
+        let size = self.size();
+        let ordered = spread(size, self.max_value);
+        // The first half, or the odd numbers, which is the larger one for an odd size
+        let half = size.div_ceil(2);
+
+        match arrangement {
+            Arrangement::Shuffled => self.shuffle(),
+            Arrangement::Reversed => self.reverse(),
+            Arrangement::Sorted => self.order(),
+            Arrangement::AllEqual => self.equalize(),
+            Arrangement::Interleaved => {
+                self.replace_numbers(
+                    (0..size)
+                        .map(|i| {
+                            if i % 2 == 0 {
+                                ordered[i / 2]
+                            } else {
+                                ordered[half + i / 2]
+                            }
+                        })
+                        .collect(),
+                );
+                self.clear_step();
+            }
+            Arrangement::TwoRuns => {
+                let (odd, even): (Vec<_>, Vec<_>) =
+                    ordered.iter().enumerate().partition(|(i, _)| i % 2 == 0);
+
+                self.replace_numbers(odd.into_iter().chain(even).map(|(_, &n)| n).collect());
+                self.clear_step();
+            }
+        }
+    }
+
+    /// Sets every number to the same value, halfway up.
+    pub fn equalize(&mut self) {
+        let size = self.size();
+
+        self.replace_numbers(vec![self.max_value / 2 + 1; size]);
+        self.clear_step();
     }
 
     pub fn size(&self) -> usize {
         self.numbers.len()
     }
+
+    pub fn max_value(&self) -> usize {
+        self.max_value
+    }
+
+    pub fn numbers(&self) -> &[usize] {
+        &self.numbers
+    }
+
+    /// The numbers shared with this state, for rendering them without a copy.
+    pub fn shared_numbers(&self) -> sync::Arc<Vec<usize>> {
+        self.numbers.clone()
+    }
+
+    /// How many numbers are where sorting puts them, comparing the value to the one sorted into
+    /// the index so equal numbers are placed wherever one of them belongs.
+    pub fn placed(&self) -> usize {
+        self.placed
+    }
+
+    fn numbers_mut(&mut self) -> &mut Vec<usize> {
+        sync::Arc::make_mut(&mut self.numbers)
+    }
+
+    fn replace_numbers(&mut self, numbers: Vec<usize>) {
+        let mut sorted = numbers.clone();
+        sorted.sort_unstable();
+
+        self.numbers = sync::Arc::new(numbers);
+        self.sorted = sync::Arc::new(sorted);
+        self.count_placed();
+        self.changed(0..self.size());
+        self.clear_tweens();
+    }
+
+    /// How often the numbers were changed so far, by any operation or rearrangement changing them.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// The indices changed since the last call, covering all of them if they were rearranged or
+    /// replaced, or none if nothing changed.
+    pub fn take_dirty(&mut self) -> Option<ops::Range<usize>> {
+        self.dirty.take()
+    }
+
+    /// Starts a new generation, widening the dirty range to the changed indices.
+    fn changed(&mut self, indices: ops::Range<usize>) {
+        self.generation += 1;
+        self.dirty = Some(match self.dirty.take() {
+            Some(dirty) => dirty.start.min(indices.start)..dirty.end.max(indices.end),
+            None => indices,
+        });
+    }
+
+    /// Counts the placed numbers from scratch, after rearranging all of them.
+    fn count_placed(&mut self) {
+        self.placed = (0..self.size())
+            .filter(|&index| self.is_placed(index))
+            .count();
+    }
+
+    fn is_placed(&self, index: usize) -> bool {
+        self.numbers[index] == self.sorted[index]
+    }
 }
 
 impl ArrayState {
+    /// The step of the last operation, by whichever worker performed it.
     pub fn last_step(&self) -> Step {
         self.step
     }
+
+    /// The step of the last operation of every worker, indexed by worker.
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// How many workers operated on the array since its steps were last cleared.
+    pub fn workers(&self) -> usize {
+        self.steps.len()
+    }
+
     pub fn clear_step(&mut self) {
         self.step = Step::None;
+        self.steps.clear();
+        self.highlights.clear();
+        self.clear_tweens();
+    }
+
+    /// The sampled steps along with their workers, oldest first.
+    pub fn highlights(&self) -> &[(usize, Step)] {
+        &self.highlights
+    }
+
+    /// Samples the step of the last operation, dropping the oldest once `MAX_HIGHLIGHTS` are kept.
+    pub fn highlight_last_step(&mut self) {
+        if self.highlights.len() == MAX_HIGHLIGHTS {
+            self.highlights.remove(0);
+        }
+
+        self.highlights.push((self.worker, self.step));
+    }
+
+    pub fn clear_highlights(&mut self) {
+        self.highlights.clear();
+    }
+
+    /// Clears the step of a worker that finished.
+    pub fn clear_worker_step(&mut self, worker: usize) {
+        if let Some(step) = self.steps.get_mut(worker) {
+            *step = Step::None;
+        }
+    }
+
+    /// Performs operations attributing their steps to the worker.
+    pub fn as_worker<T>(&mut self, worker: usize, f: impl FnOnce(&mut ArrayState) -> T) -> T {
+        self.worker = worker;
+        let result = f(self);
+        self.worker = 0;
+
+        result
+    }
+
+    fn record(&mut self, step: Step) {
+        if self.steps.len() <= self.worker {
+            self.steps.resize(self.worker + 1, Step::None);
+        }
+
+        self.steps[self.worker] = step;
+        self.step = step;
+        self.operations += 1;
+
+        if !self.watched.is_empty() && step.indices().any(|index| self.is_watched(index)) {
+            self.watch_hits += 1;
+        }
+    }
+
+    pub fn watched(&self) -> &[usize] {
+        &self.watched
+    }
+
+    pub fn is_watched(&self, index: usize) -> bool {
+        self.watched.binary_search(&index).is_ok()
+    }
+
+    /// Watches the index, or stops watching it if it already is.
+    pub fn toggle_watched(&mut self, index: usize) {
+        match self.watched.binary_search(&index) {
+            Ok(position) => {
+                self.watched.remove(position);
+            }
+            Err(position) => self.watched.insert(position, index),
+        }
+    }
+
+    pub fn clear_watched(&mut self) {
+        self.watched.clear();
+    }
+
+    /// The range sorts sort, clamped to the numbers.
+    pub fn range(&self) -> ops::Range<usize> {
+        clamp_range(self.range.clone(), self.size())
+    }
+
+    /// The range sorts sort as selected, if not all the numbers.
+    pub fn selected_range(&self) -> Option<ops::Range<usize>> {
+        self.range.clone()
+    }
+
+    /// Selects the range sorts sort, or all the numbers.
+    pub fn set_range(&mut self, range: Option<ops::Range<usize>>) {
+        self.range = range;
+    }
+
+    /// The phase the running sort reported last, if it reports any.
+    pub fn phase(&self) -> Option<Phase> {
+        self.phase
+    }
+
+    pub fn set_phase(&mut self, phase: Option<Phase>) {
+        self.phase = phase;
+    }
+
+    /// The markers of the workers of the running sort, if it marks anything.
+    pub fn markers(&self) -> impl Iterator<Item = Marker> + '_ {
+        self.markers.iter().flatten().copied()
+    }
+
+    /// Sets the marker of the current worker.
+    pub fn set_marker(&mut self, marker: Option<Marker>) {
+        if self.markers.len() <= self.worker {
+            self.markers.resize(self.worker + 1, None);
+        }
+
+        self.markers[self.worker] = marker;
+    }
+
+    /// Clears the markers of the workers along with the marked indices.
+    pub fn clear_markers(&mut self) {
+        self.markers.clear();
+        self.marks.clear();
+    }
+
+    /// The indices the running sort marked last, highlighted until it marks others.
+    pub fn marks(&self) -> &[usize] {
+        &self.marks
+    }
+
+    pub fn set_marks(&mut self, marks: &[usize]) {
+        self.marks.clear();
+        self.marks.extend_from_slice(marks);
+    }
+
+    /// How many operations touched a watched index so far.
+    pub fn watch_hits(&self) -> u64 {
+        self.watch_hits
+    }
+
+    /// The statistics, read together.
+    pub fn stats_snapshot(&self) -> Stats {
+        Stats {
+            comparisons: self.comparisons,
+            wasted_comparisons: self.wasted,
+            reads: self.reads,
+            writes: self.writes,
+            last_step: self.step,
+            size: self.size(),
+            placed: self.placed,
+            generation: self.generation,
+            distances: self.distances,
+            space: self.space,
+        }
+    }
+
+    /// Sets whether the distances of comparisons and swaps are counted, starting over from none.
+    ///
+    /// Counting them costs a little on every comparison and swap, which some may rather save.
+    pub fn set_count_distances(&mut self, count: bool) {
+        self.distances = count.then(Distances::default);
+    }
+
+    pub fn distances(&self) -> Option<&Distances> {
+        self.distances.as_ref()
+    }
+
+    /// Sets whether swapped and shifted numbers slide into their places when drawn, instead of
+    /// jumping there. The numbers themselves are moved at once either way.
+    pub fn set_tweening(&mut self, tweening: bool) {
+        self.tweening = tweening;
+        if !tweening {
+            self.clear_tweens();
+        }
+    }
+
+    pub fn tweening(&self) -> bool {
+        self.tweening
+    }
+
+    /// Whether any number is still sliding into its place.
+    pub fn sliding(&self) -> bool {
+        !self.tweens.is_empty()
+    }
+
+    /// How many places away from its index every sliding number is drawn, easing out as it
+    /// arrives.
+    pub fn offsets(&self) -> Vec<(usize, f32)> {
+        self.tweens
+            .iter()
+            .map(|tween| {
+                let left = tween.left.as_secs_f32() / TWEEN_DURATION.as_secs_f32();
+
+                (tween.index, tween.from * left * left)
+            })
+            .collect()
+    }
+
+    /// Moves the sliding numbers on by the time since the last frame, putting them all in their
+    /// places at once if more than `MAX_TWEENS` were set sliding since.
+    pub fn advance_tweens(&mut self, elapsed: time::Duration) {
+        if self.tweened > MAX_TWEENS {
+            self.tweens.clear();
+        } else {
+            for tween in &mut self.tweens {
+                tween.left = tween.left.saturating_sub(elapsed);
+            }
+            self.tweens.retain(|tween| !tween.left.is_zero());
+        }
+        self.tweened = 0;
+    }
+
+    /// Puts the sliding numbers in their places, counting the ones set sliding from none again.
+    fn clear_tweens(&mut self) {
+        self.tweens.clear();
+        self.tweened = 0;
+    }
+
+    /// Sets the number at the index sliding from `from` places away, replacing its last slide.
+    fn tween(&mut self, index: usize, from: f32) {
+        if !self.tweening {
+            return;
+        }
+
+        self.tweened += 1;
+        if self.tweened > MAX_TWEENS {
+            return;
+        }
+
+        self.tweens.retain(|tween| tween.index != index);
+        self.tweens.push(Tween {
+            index,
+            from,
+            left: TWEEN_DURATION,
+        });
+    }
+
+    pub fn space(&self) -> Space {
+        self.space
+    }
+
+    pub fn report_space(&mut self, report: SpaceReport) {
+        self.space.apply(report);
+    }
+
+    /// The operations since the stats were reset.
+    pub fn operations(&self) -> u64 {
+        self.operations
+    }
+
+    /// The annotations of the running sort, since it started.
+    pub fn annotations(&self) -> &sync::Arc<Annotations> {
+        &self.annotations
+    }
+
+    /// Logs the annotation along with the operations so far, unless the rate limit drops it.
+    ///
+    /// Annotations containing what they are paused on are always logged, counting a pause hit.
+    pub fn annotate(&mut self, text: String) {
+        let matched = self
+            .pause_on
+            .as_ref()
+            .is_some_and(|pause_on| text.to_lowercase().contains(pause_on));
+        if matched {
+            self.pause_hits += 1;
+        }
+
+        let (operations, now) = (self.operations, crate::Instant::now());
+        sync::Arc::make_mut(&mut self.annotations).push(operations, text, matched, now);
+    }
+
+    /// What annotations are paused on, matched ignoring case.
+    pub fn pause_on(&self) -> Option<&str> {
+        self.pause_on.as_deref()
+    }
+
+    /// Pauses on annotations containing the text, or on none. Nothing is paused on if it's empty.
+    pub fn set_pause_on(&mut self, text: Option<&str>) {
+        self.pause_on = text.filter(|text| !text.is_empty()).map(str::to_lowercase);
+    }
+
+    /// How many annotations matched what they are paused on so far, so whoever plays the sort
+    /// can pause once there was another one.
+    pub fn pause_hits(&self) -> u64 {
+        self.pause_hits
+    }
+
+    /// Starts the log over, as a sort starts.
+    pub fn clear_annotations(&mut self) {
+        self.annotations = sync::Arc::default();
+    }
+
+    /// Forgets the space of a sort that ended without freeing it, keeping the peaks.
+    pub fn clear_space(&mut self) {
+        self.space.aux_bytes = 0;
+        self.space.depth = 0;
     }
 
     pub fn comparisons(&self) -> u64 {
         self.comparisons
     }
 
+    /// How many comparisons of the completed run compared two numbers which were both in their
+    /// final places already, so comparing them told the sort nothing it needed.
+    ///
+    /// Whether a number moves again is only known once the run is over, so this is only known
+    /// for recorded runs, counted from their trace by `Trace::wasted_comparisons`.
+    pub fn wasted_comparisons(&self) -> Option<u64> {
+        self.wasted
+    }
+
+    pub fn set_wasted_comparisons(&mut self, wasted: u64) {
+        self.wasted = Some(wasted);
+    }
+
     pub fn reads(&self) -> u64 {
         self.reads
     }
@@ -120,13 +982,22 @@ impl ArrayState {
 
     pub fn reset_stats(&mut self) {
         self.comparisons = 0;
+        self.wasted = None;
         self.reads = 0;
         self.writes = 0;
+        if let Some(ref mut distances) = self.distances {
+            *distances = Distances::default();
+        }
+        self.space = Space::default();
+        self.operations = 0;
     }
 
     pub fn cmp_two(&mut self, a: usize, b: usize) -> cmp::Ordering {
-        self.step = Step::ComparisonTwo(a, b);
+        self.record(Step::ComparisonTwo(self.element(a), self.element(b)));
         self.comparisons += 1;
+        if let Some(ref mut distances) = self.distances {
+            distances.count(a, b);
+        }
         self.reads += 2;
         self.numbers[a].cmp(&self.numbers[b])
     }
@@ -134,32 +1005,488 @@ impl ArrayState {
     pub fn cmp(&mut self, index: usize, value: usize) -> cmp::Ordering {
         self.comparisons += 1;
         self.reads += 1;
-        self.step = Step::Comparison(index);
+        self.record(Step::Comparison(self.element(index), value));
         self.numbers[index].cmp(&value)
     }
 
     pub fn swap(&mut self, a: usize, b: usize) {
         self.reads += 2;
         self.writes += 2;
-        self.step = Step::AccessTwo(a, b);
-        self.numbers.swap(a, b);
+        self.record(Step::AccessTwo(self.element(a), self.element(b)));
+        if let Some(ref mut distances) = self.distances {
+            distances.count(a, b);
+        }
+
+        self.unplace(a);
+        if b != a {
+            self.unplace(b);
+        }
+        self.numbers_mut().swap(a, b);
+        self.changed(a.min(b)..a.max(b) + 1);
+        self.place(a);
+        if b != a {
+            self.place(b);
+            self.tween(a, b as f32 - a as f32);
+            self.tween(b, a as f32 - b as f32);
+        }
     }
 
     pub fn get(&mut self, index: usize) -> usize {
         self.reads += 1;
-        self.step = Step::Access(index);
+        self.record(Step::Access(self.element(index)));
         self.numbers[index]
     }
 
     pub fn set(&mut self, index: usize, value: usize) {
         self.writes += 1;
-        self.step = Step::Access(index);
-        self.numbers[index] = value;
+        self.record(Step::Write(Element { index, value }));
+
+        self.unplace(index);
+        // Copying a neighbour over, like shifting the numbers along, slides the copy from there
+        let shifted = [index.wrapping_sub(1), index + 1]
+            .into_iter()
+            .find(|&neighbour| {
+                self.numbers.get(neighbour) == Some(&value) && self.numbers[index] != value
+            });
+        self.numbers_mut()[index] = value;
+        self.changed(index..index + 1);
+        self.place(index);
+        if let Some(neighbour) = shifted {
+            self.tween(index, neighbour as f32 - index as f32);
+        }
+    }
+
+    /// Stops counting the number at the index before it is overwritten.
+    fn unplace(&mut self, index: usize) {
+        self.placed -= self.is_placed(index) as usize;
+    }
+
+    /// Counts the number written to the index.
+    fn place(&mut self, index: usize) {
+        self.placed += self.is_placed(index) as usize;
+    }
+
+    fn element(&self, index: usize) -> Element {
+        Element {
+            index,
+            value: self.numbers[index],
+        }
     }
 }
 
-impl canvas::Program<crate::Message> for ArrayState {
-    fn draw(&self, bounds: iced::Rectangle, _: canvas::Cursor) -> Vec<canvas::Geometry> {
-        self.view.draw(bounds, &self.numbers, self.step)
+/// `size` ordered numbers spread evenly over `1..=max_value`, including both ends.
+fn spread(size: usize, max_value: usize) -> Vec<usize> {
+    let steps = cmp::max(size.saturating_sub(1), 1);
+
+    (0..size)
+        .map(|i| 1 + i * max_value.saturating_sub(1) / steps)
+        .collect()
+}
+
+/// The range within `0..size`, all of it if there is none, and empty if it starts past its end.
+pub fn clamp_range(range: Option<ops::Range<usize>>, size: usize) -> ops::Range<usize> {
+    match range {
+        Some(range) => {
+            let end = range.end.min(size);
+            range.start.min(end)..end
+        }
+        None => 0..size,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_carry_values() {
+        let mut array = ArrayState::from_numbers(vec![3, 1, 2]);
+
+        array.swap(0, 2);
+        assert_eq!(array.last_step().values().collect::<Vec<_>>(), [3, 2]);
+        assert_eq!(
+            array.last_step().to_string(),
+            "Swapped [0] = 3 with [2] = 2"
+        );
+
+        array.set(1, 7);
+        assert_eq!(
+            array.last_step(),
+            Step::Write(Element { index: 1, value: 7 })
+        );
+
+        array.cmp(1, 5);
+        assert_eq!(array.last_step().to_string(), "Compared [1] = 7 with 5");
+        assert_eq!(array.last_step().indices().collect::<Vec<_>>(), [1]);
+    }
+
+    #[test]
+    fn resets_keep_size() {
+        let mut array = ArrayState::from_numbers(vec![3, 1, 2, 5, 4]);
+
+        array.swap(0, 1);
+        array.equalize();
+        assert_eq!(array.numbers(), [3; 5]);
+        assert_eq!(array.last_step(), Step::None);
+
+        array.swap(0, 1);
+        array.order();
+        assert_eq!(array.numbers(), [1, 2, 3, 4, 5]);
+        assert_eq!(array.last_step(), Step::None);
+    }
+
+    #[test]
+    fn arrangements_permute_numbers() {
+        let mut array = ArrayState::new(5);
+
+        array.arrange(Arrangement::Interleaved);
+        assert_eq!(array.numbers(), [1, 4, 2, 5, 3]);
+        array.arrange(Arrangement::TwoRuns);
+        assert_eq!(array.numbers(), [1, 3, 5, 2, 4]);
+
+        for size in [0, 1, 2, 7, 100] {
+            for arrangement in Arrangement::VALUES {
+                let mut array = ArrayState::new(size);
+                array.arrange(arrangement);
+
+                let mut numbers = array.numbers().to_vec();
+                assert_eq!(numbers.len(), size, "{arrangement}");
+                assert!(
+                    numbers.iter().all(|n| (1..=size).contains(n)),
+                    "{arrangement}"
+                );
+
+                if arrangement != Arrangement::AllEqual {
+                    numbers.sort_unstable();
+                    assert_eq!(numbers, (1..=size).collect::<Vec<_>>(), "{arrangement}");
+                }
+            }
+        }
+
+        // Seeded shuffles repeat
+        let (mut first, mut second) = (ArrayState::new(100), ArrayState::new(100));
+        first.shuffle_seeded(7);
+        second.shuffle_seeded(7);
+        assert_eq!(first.numbers(), second.numbers());
+        second.shuffle_seeded(8);
+        assert_ne!(first.numbers(), second.numbers());
+    }
+
+    #[test]
+    fn distances_are_bucketed() {
+        let mut array = ArrayState::new(2000);
+
+        for (a, b) in [(0, 1), (5, 4), (3, 3), (0, 2), (10, 13), (0, 4), (1999, 0)] {
+            array.cmp_two(a, b);
+        }
+        array.swap(0, 7);
+        let mut expected = [0; DISTANCE_BUCKETS];
+        expected[..3].copy_from_slice(&[3, 2, 2]);
+        expected[10] = 1;
+        assert_eq!(array.distances().unwrap().buckets(), &expected);
+        assert_eq!(Distances::shortest(10), 1024);
+        assert_eq!(array.stats_snapshot().distances, array.distances().copied());
+
+        // Only comparisons and swaps of two indices count
+        array.cmp(0, 5);
+        array.get(3);
+        array.reset_stats();
+        assert_eq!(array.distances(), Some(&Distances::default()));
+
+        array.set_count_distances(false);
+        array.cmp_two(0, 1);
+        assert_eq!(array.stats_snapshot().distances, None);
+    }
+
+    #[test]
+    fn space_keeps_its_peaks() {
+        let mut array = ArrayState::new(10);
+
+        for report in [
+            SpaceReport::Depth(1),
+            SpaceReport::AuxAlloc(80),
+            SpaceReport::Depth(3),
+            SpaceReport::AuxAlloc(16),
+            SpaceReport::AuxFree(96),
+            SpaceReport::Depth(2),
+            SpaceReport::AuxAlloc(40),
+        ] {
+            array.report_space(report);
+        }
+        let space = Space {
+            aux_bytes: 40,
+            depth: 2,
+            peak_aux_bytes: 96,
+            peak_depth: 3,
+        };
+        assert_eq!(array.space(), space);
+        assert_eq!(array.stats_snapshot().space, space);
+
+        array.reset_stats();
+        array.report_space(SpaceReport::AuxFree(40));
+        assert_eq!(array.space(), Space::default());
+    }
+
+    #[test]
+    fn annotations_are_rate_limited_and_bounded() {
+        let mut annotations = Annotations::default();
+        let start = annotations.started;
+        let second = time::Duration::from_secs(1);
+
+        for i in 0..MAX_ANNOTATIONS_PER_SECOND + 5 {
+            annotations.push(i as u64, format!("Pivot chosen: index {i}"), false, start);
+        }
+        assert_eq!(annotations.entries().len(), MAX_ANNOTATIONS_PER_SECOND);
+        assert_eq!(annotations.dropped(), 5);
+        // Unless paused on
+        annotations.push(25, String::from("Pivot chosen: index 25"), true, start);
+        assert_eq!(annotations.entries().len(), MAX_ANNOTATIONS_PER_SECOND + 1);
+
+        // The next second logs again, until the oldest have to make room
+        for s in 1..=20 {
+            for i in 0..MAX_ANNOTATIONS_PER_SECOND {
+                annotations.push(i as u64, format!("{s}"), false, start + second * s);
+            }
+        }
+        assert_eq!(annotations.entries().len(), MAX_ANNOTATIONS);
+        assert_eq!(annotations.entries().next().unwrap().text, "11");
+        let last = annotations.entries().last().unwrap();
+        assert_eq!(last.at, second * 20);
+
+        let mut text = Vec::new();
+        annotations.write_text(&mut text).unwrap();
+        let text = String::from_utf8(text).unwrap();
+        assert_eq!(text.lines().count(), MAX_ANNOTATIONS + 1);
+        assert!(text.starts_with("11.000 s\t#0\t11\n"));
+        assert!(text.ends_with("(5 more dropped)\n"));
+    }
+
+    #[test]
+    fn annotations_count_the_operations_before_them() {
+        let mut array = ArrayState::new(10);
+        array.cmp_two(0, 1);
+        array.set(2, 5);
+        array.annotate(String::from("Gap reduced to 4"));
+        let logged = array.clone();
+
+        array.get(3);
+        array.annotate(String::from("Gap reduced to 1"));
+        let operations: Vec<_> = array.annotations().entries().map(|a| a.operation).collect();
+        assert_eq!(operations, [2, 3]);
+        // Copies keep the log they had
+        assert_eq!(logged.annotations().entries().len(), 1);
+
+        array.clear_annotations();
+        assert!(array.annotations().is_empty());
+    }
+
+    #[test]
+    fn matching_annotations_hit_the_pause() {
+        let mut array = ArrayState::new(10);
+        array.annotate(String::from("Gap reduced to 4"));
+        assert_eq!(array.pause_hits(), 0);
+
+        array.set_pause_on(Some("GAP reduced"));
+        assert_eq!(array.pause_on(), Some("gap reduced"));
+        for text in [
+            "Gap reduced to 2",
+            "Pivot chosen: index 3",
+            "Gap reduced to 1",
+        ] {
+            array.annotate(String::from(text));
+        }
+        assert_eq!(array.pause_hits(), 2);
+        let matched: Vec<_> = array.annotations().entries().map(|a| a.matched).collect();
+        assert_eq!(matched, [false, true, false, true]);
+
+        array.set_pause_on(Some(""));
+        array.annotate(String::from("Gap reduced to 1"));
+        assert_eq!((array.pause_on(), array.pause_hits()), (None, 2));
+    }
+
+    #[test]
+    fn stats_snapshot_matches_accessors() {
+        let mut array = ArrayState::from_numbers(vec![2, 1, 3]);
+        let generation = array.stats_snapshot().generation;
+
+        array.cmp_two(0, 1);
+        array.swap(0, 1);
+        array.cmp_two(1, 2);
+        let stats = array.stats_snapshot();
+        assert_eq!(
+            (stats.comparisons, stats.reads, stats.writes),
+            (array.comparisons(), array.reads(), array.writes())
+        );
+        assert_eq!((stats.comparisons, stats.wasted_comparisons), (2, None));
+        assert_eq!((stats.size, stats.placed), (3, 3));
+        assert_eq!(stats.placed_percent(), 100);
+        assert_eq!(stats.last_step, array.last_step());
+
+        assert_eq!(stats.generation, generation + 1);
+        array.initialize(5, 5);
+        assert_eq!(array.stats_snapshot().generation, generation + 2);
+    }
+
+    #[test]
+    fn generation_counts_changes() {
+        let mut array = ArrayState::new(8);
+        let generation = array.generation();
+        assert_eq!(array.take_dirty(), Some(0..8));
+        assert_eq!(array.take_dirty(), None);
+
+        // Only operations changing the numbers count, each of them once
+        array.cmp_two(1, 2);
+        array.cmp(3, 4);
+        array.get(5);
+        assert_eq!(array.generation(), generation);
+        assert_eq!(array.take_dirty(), None);
+
+        array.swap(5, 2);
+        assert_eq!(array.generation(), generation + 1);
+        array.set(6, 1);
+        assert_eq!(array.generation(), generation + 2);
+        assert_eq!(array.take_dirty(), Some(2..7));
+
+        array.swap(4, 4);
+        assert_eq!(array.generation(), generation + 3);
+        assert_eq!(array.take_dirty(), Some(4..5));
+
+        for (rearrange, expected) in [
+            (ArrayState::shuffle as fn(&mut ArrayState), 4),
+            (ArrayState::reverse, 5),
+            (ArrayState::order, 6),
+            (ArrayState::equalize, 7),
+        ] {
+            rearrange(&mut array);
+            assert_eq!(array.generation(), generation + expected);
+            assert_eq!(array.take_dirty(), Some(0..8));
+        }
+
+        array.initialize(3, 3);
+        assert_eq!(array.generation(), generation + 8);
+        assert_eq!(array.take_dirty(), Some(0..3));
+        array.arrange(Arrangement::Shuffled);
+        assert_eq!(array.generation(), generation + 9);
+    }
+
+    #[test]
+    fn placed_numbers_are_counted_along() {
+        let mut array = ArrayState::from_numbers(vec![3, 1, 2, 2]);
+        assert_eq!(array.placed(), 1);
+
+        array.swap(0, 3);
+        assert_eq!(array.placed(), 2);
+        array.swap(1, 1);
+        assert_eq!(array.placed(), 2);
+        // Either of the equal numbers is placed at either of their indices
+        array.swap(0, 1);
+        assert_eq!(array.numbers(), [1, 2, 2, 3]);
+        assert_eq!(array.placed(), 4);
+        array.set(0, 7);
+        assert_eq!(array.placed(), 3);
+        array.set(0, 1);
+        assert_eq!(array.placed(), 4);
+
+        array.reverse();
+        assert_eq!(array.placed(), 2);
+        array.order();
+        assert_eq!(array.placed(), 4);
+    }
+
+    #[test]
+    fn values_spread_over_range() {
+        let mut array = ArrayState::new(4);
+        assert_eq!(array.max_value(), 4);
+
+        array.initialize(5, 3);
+        assert_eq!(array.numbers(), [1, 1, 2, 2, 3]);
+        array.initialize(3, 101);
+        assert_eq!(array.numbers(), [1, 51, 101]);
+        array.initialize(1, 10);
+        assert_eq!(array.numbers(), [1]);
+
+        // Rearranging keeps the range
+        array.initialize(4, 8);
+        array.arrange(Arrangement::Interleaved);
+        assert_eq!(array.numbers(), [1, 5, 3, 8]);
+        array.arrange(Arrangement::Sorted);
+        assert_eq!(array.numbers(), [1, 3, 5, 8]);
+        array.arrange(Arrangement::AllEqual);
+        assert_eq!(array.numbers(), [5; 4]);
+    }
+
+    #[test]
+    fn swapped_numbers_slide_into_place() {
+        let mut array = ArrayState::from_numbers(vec![1, 2, 3, 4]);
+
+        // Nothing slides unless tweening
+        array.swap(0, 3);
+        assert!(!array.sliding());
+
+        array.set_tweening(true);
+        array.swap(0, 3);
+        assert_eq!(array.offsets(), [(0, 3.0), (3, -3.0)]);
+        assert_eq!(array.numbers(), [1, 2, 3, 4]);
+
+        // Easing out, and gone once the tween is over
+        array.advance_tweens(TWEEN_DURATION / 2);
+        assert_eq!(array.offsets(), [(0, 0.75), (3, -0.75)]);
+        array.advance_tweens(TWEEN_DURATION);
+        assert!(!array.sliding());
+
+        // Copying a neighbour over slides the copy from there
+        array.set(1, 1);
+        assert_eq!(array.offsets(), [(1, -1.0)]);
+        array.set_tweening(false);
+        assert!(!array.sliding());
+    }
+
+    #[test]
+    fn tweening_gives_way_to_fast_sorts() {
+        let mut array = ArrayState::new(100);
+        array.set_tweening(true);
+
+        for index in 0..MAX_TWEENS {
+            array.swap(index, index + 50);
+        }
+        assert_eq!(array.offsets().len(), MAX_TWEENS);
+        array.advance_tweens(time::Duration::ZERO);
+        assert!(!array.sliding());
+
+        // A slower frame slides again
+        array.swap(0, 1);
+        array.advance_tweens(time::Duration::ZERO);
+        assert_eq!(array.offsets().len(), 2);
+        assert!(array.tweening());
+
+        // A fast frame cut short by a shuffle doesn't keep the next one from sliding
+        for index in 0..=MAX_TWEENS {
+            array.swap(index, index + 50);
+        }
+        array.shuffle();
+        assert!(!array.sliding());
+        array.swap(0, 1);
+        assert_eq!(array.offsets().len(), 2);
+    }
+
+    #[test]
+    fn range_is_clamped_to_numbers() {
+        let mut array = ArrayState::new(10);
+        assert_eq!(array.range(), 0..10);
+
+        array.set_range(Some(2..6));
+        assert_eq!(array.range(), 2..6);
+        array.set_range(Some(4..20));
+        assert_eq!(array.range(), 4..10);
+
+        // Shrinking the numbers shrinks the range along, down to an empty one
+        array.set_range(Some(4..8));
+        array.initialize(6, 6);
+        assert_eq!(array.range(), 4..6);
+        array.initialize(3, 3);
+        assert_eq!(array.range(), 3..3);
+
+        array.set_range(None);
+        assert_eq!(array.range(), 0..3);
     }
 }
@@ -0,0 +1,201 @@
+//! A sonifier opened only once the tone is to be heard, and let go of again after a long pause.
+
+use super::Sonifier;
+use crate::settings::{Settings, Waveform};
+use std::time;
+
+/// Opens the sonifier it wraps on the first unmute, so the audio device isn't taken by a session
+/// that never plays the tone, and drops it once the tone was paused for the release time.
+///
+/// What it is told while closed is kept for when it opens.
+pub struct Lazy<S> {
+    open: Box<dyn FnMut() -> Option<S>>,
+    sonifier: Option<S>,
+    /// Whether opening failed, which isn't tried again.
+    failed: bool,
+    release: Option<time::Duration>,
+    paused_since: Option<crate::Instant>,
+    frequency: Option<f32>,
+    volume: f32,
+    waveform: Waveform,
+}
+
+impl<S: Sonifier> Lazy<S> {
+    pub fn new(open: impl FnMut() -> Option<S> + 'static, settings: &Settings) -> Lazy<S> {
+        Lazy {
+            open: Box::new(open),
+            sonifier: None,
+            failed: false,
+            release: settings.audio_release,
+            paused_since: None,
+            frequency: None,
+            volume: settings.volume,
+            waveform: settings.waveform,
+        }
+    }
+
+    /// Whether the sonifier is open.
+    pub fn is_open(&self) -> bool {
+        self.sonifier.is_some()
+    }
+}
+
+impl<S: Sonifier> Sonifier for Lazy<S> {
+    fn set_frequency(&mut self, frequency: f32) {
+        self.frequency = Some(frequency);
+        if let Some(ref mut sonifier) = self.sonifier {
+            sonifier.set_frequency(frequency);
+        }
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        if enabled {
+            self.paused_since = None;
+            self.open();
+        } else if self.sonifier.is_some() {
+            self.paused_since.get_or_insert_with(crate::Instant::now);
+        }
+
+        if let Some(ref mut sonifier) = self.sonifier {
+            sonifier.set_enabled(enabled);
+        }
+    }
+
+    fn set_volume(&mut self, volume: f32) {
+        self.volume = volume;
+        if let Some(ref mut sonifier) = self.sonifier {
+            sonifier.set_volume(volume);
+        }
+    }
+
+    fn set_waveform(&mut self, waveform: Waveform) {
+        self.waveform = waveform;
+        if let Some(ref mut sonifier) = self.sonifier {
+            sonifier.set_waveform(waveform);
+        }
+    }
+
+    /// Until opening it failed.
+    fn available(&self) -> bool {
+        !self.failed
+    }
+
+    fn open(&mut self) -> bool {
+        if self.sonifier.is_none() && !self.failed {
+            self.sonifier = (self.open)().map(|mut sonifier| {
+                sonifier.set_volume(self.volume);
+                sonifier.set_waveform(self.waveform);
+                if let Some(frequency) = self.frequency {
+                    sonifier.set_frequency(frequency);
+                }
+                sonifier.set_enabled(false);
+
+                sonifier
+            });
+            self.failed = self.sonifier.is_none();
+        }
+
+        !self.failed
+    }
+
+    fn release_idle(&mut self, now: crate::Instant) {
+        let released = self
+            .paused_since
+            .zip(self.release)
+            .is_some_and(|(since, release)| now >= since + release);
+
+        if released {
+            self.sonifier = None;
+            self.paused_since = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell, rc};
+
+    /// Counts how often it is opened, and whether it is open, sharing the counts with the test.
+    #[derive(Clone, Default)]
+    struct Counted {
+        opened: rc::Rc<cell::Cell<usize>>,
+        open: rc::Rc<cell::Cell<bool>>,
+        volume: rc::Rc<cell::Cell<f32>>,
+    }
+
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.open.set(false);
+        }
+    }
+
+    impl Sonifier for Counted {
+        fn set_frequency(&mut self, _frequency: f32) {}
+
+        fn set_enabled(&mut self, _enabled: bool) {}
+
+        fn set_volume(&mut self, volume: f32) {
+            self.volume.set(volume);
+        }
+
+        fn set_waveform(&mut self, _waveform: Waveform) {}
+
+        fn available(&self) -> bool {
+            true
+        }
+    }
+
+    fn lazy(counted: &Counted) -> Lazy<Counted> {
+        let counted = counted.clone();
+
+        Lazy::new(
+            move || {
+                counted.opened.set(counted.opened.get() + 1);
+                counted.open.set(true);
+                Some(counted.clone())
+            },
+            &Settings::default(),
+        )
+    }
+
+    #[test]
+    fn opens_on_unmute_and_lets_go_after_a_pause() {
+        let counted = Counted::default();
+        let mut lazy = lazy(&counted);
+
+        // Nothing is opened until the tone is to be heard, but the volume is kept
+        lazy.set_volume(0.5);
+        lazy.set_enabled(false);
+        lazy.release_idle(crate::Instant::now());
+        assert_eq!(counted.opened.get(), 0);
+        assert!(lazy.open());
+        assert_eq!((counted.opened.get(), counted.volume.get()), (1, 0.5));
+        lazy.set_enabled(true);
+        assert_eq!(counted.opened.get(), 1);
+
+        // A short pause keeps it open, a long one lets go of it
+        lazy.set_enabled(false);
+        let paused = crate::Instant::now();
+        lazy.release_idle(paused + time::Duration::from_secs(59));
+        assert!(lazy.is_open());
+        lazy.release_idle(paused + time::Duration::from_secs(61));
+        assert!(!lazy.is_open() && !counted.open.get());
+
+        // Playing again opens it again
+        lazy.set_enabled(true);
+        assert!(lazy.is_open() && counted.open.get());
+        assert_eq!(counted.opened.get(), 2);
+    }
+
+    #[test]
+    fn failing_to_open_leaves_it_unavailable() {
+        let mut lazy = Lazy::new(|| None::<Counted>, &Settings::default());
+
+        assert!(lazy.available());
+        assert!(!lazy.open());
+        assert!(!lazy.available());
+        lazy.set_enabled(true);
+        assert!(!lazy.is_open());
+    }
+}
@@ -0,0 +1,181 @@
+//! The tone following the values of the sorted numbers, played by a `Sonifier`.
+//!
+//! The frontends only tell the sonifier what happens, so the tone can be played on an audio
+//! device, or not at all when there is none.
+
+use crate::array;
+use crate::settings::{Settings, Waveform};
+
+mod lazy;
+#[cfg(feature = "gui")]
+mod speaker;
+#[cfg(feature = "gui")]
+mod tone;
+
+pub use lazy::Lazy;
+#[cfg(feature = "gui")]
+pub use speaker::Speaker;
+
+/// Plays a tone whose pitch follows the steps of the sort.
+pub trait Sonifier {
+    /// Changes the pitch of the tone, in Hz.
+    fn set_frequency(&mut self, frequency: f32);
+
+    /// Plays the tone, or pauses it.
+    fn set_enabled(&mut self, enabled: bool);
+
+    /// From silent at 0 to full at 1.
+    fn set_volume(&mut self, volume: f32);
+
+    fn set_waveform(&mut self, waveform: Waveform);
+
+    /// Whether the tone can be heard at all, which it can't without an audio device.
+    fn available(&self) -> bool;
+
+    /// Gets ready to play the tone, whether it can.
+    fn open(&mut self) -> bool {
+        self.available()
+    }
+
+    /// Lets go of what it plays on once the tone was paused for long enough.
+    fn release_idle(&mut self, _now: crate::Instant) {}
+
+    /// Follows the values of the step, like the bars they are drawn as. A step without values
+    /// keeps the pitch of the one before.
+    fn on_step(&mut self, step: &array::Step, max_value: usize, settings: &Settings) {
+        if let Some(frequency) = frequency(step, max_value, settings) {
+            self.set_frequency(frequency);
+        }
+    }
+
+    /// Applies the volume and waveform of the settings.
+    fn apply(&mut self, settings: &Settings) {
+        self.set_volume(settings.volume);
+        self.set_waveform(settings.waveform);
+    }
+}
+
+/// Plays nothing, in place of an audio device which couldn't be opened.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Silent;
+
+impl Sonifier for Silent {
+    fn set_frequency(&mut self, _frequency: f32) {}
+
+    fn set_enabled(&mut self, _enabled: bool) {}
+
+    fn set_volume(&mut self, _volume: f32) {}
+
+    fn set_waveform(&mut self, _waveform: Waveform) {}
+
+    fn available(&self) -> bool {
+        false
+    }
+}
+
+/// The speaker, opened once the tone is unmuted, or silence with the audio turned off.
+#[cfg(feature = "gui")]
+pub fn speaker(settings: &Settings) -> Box<dyn Sonifier> {
+    if settings.audio {
+        Box::new(Lazy::new(Speaker::try_default, settings))
+    } else {
+        Box::new(Silent)
+    }
+}
+
+/// The pitch the values of the step are heard at, rising with their mean from the minimum
+/// frequency of the settings at 0 to the maximum at the largest value. None without values.
+pub fn frequency(step: &array::Step, max_value: usize, settings: &Settings) -> Option<f32> {
+    let count = step.values().count();
+    if count == 0 {
+        return None;
+    }
+
+    let value = step.values().sum::<usize>() as f32 / count as f32 / max_value as f32;
+    let (min, max) = (settings.min_frequency, settings.max_frequency);
+
+    Some(min + (max - min) * value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array::{Element, Step};
+
+    /// Remembers what it was told instead of playing it.
+    #[derive(Default)]
+    struct Recorder {
+        frequencies: Vec<f32>,
+        enabled: bool,
+        volume: f32,
+        waveform: Option<Waveform>,
+    }
+
+    impl Sonifier for Recorder {
+        fn set_frequency(&mut self, frequency: f32) {
+            self.frequencies.push(frequency);
+        }
+
+        fn set_enabled(&mut self, enabled: bool) {
+            self.enabled = enabled;
+        }
+
+        fn set_volume(&mut self, volume: f32) {
+            self.volume = volume;
+        }
+
+        fn set_waveform(&mut self, waveform: Waveform) {
+            self.waveform = Some(waveform);
+        }
+
+        fn available(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn pitch_follows_the_values() {
+        let settings = Settings {
+            min_frequency: 200.0,
+            max_frequency: 600.0,
+            ..Settings::default()
+        };
+        let element = |value| Element { index: 0, value };
+        let mut recorder = Recorder::default();
+
+        for step in [
+            Step::Access(element(10)),
+            Step::Write(element(0)),
+            // The mean of the elements, without the value from outside the array
+            Step::ComparisonTwo(element(2), element(4)),
+            Step::Comparison(element(5), 10),
+            Step::None,
+            Step::AccessTwo(element(10), element(10)),
+        ] {
+            recorder.on_step(&step, 10, &settings);
+        }
+        assert_eq!(recorder.frequencies, [600.0, 200.0, 320.0, 400.0, 600.0]);
+
+        recorder.apply(&Settings {
+            volume: 0.5,
+            waveform: Waveform::Square,
+            ..settings
+        });
+        assert_eq!(
+            (recorder.volume, recorder.waveform),
+            (0.5, Some(Waveform::Square))
+        );
+        recorder.set_enabled(true);
+        assert!(recorder.enabled);
+    }
+
+    #[test]
+    fn silence_can_not_be_opened() {
+        let mut silent = Silent;
+
+        assert!(!silent.open());
+        // Whatever it is told, it stays silent
+        silent.set_enabled(true);
+        silent.on_step(&array::Step::None, 1, &Settings::default());
+    }
+}
@@ -0,0 +1,57 @@
+//! The tone played on the default audio device with rodio.
+
+use super::tone::{self, Tone, WaveformHandle};
+use super::Sonifier;
+use crate::settings::Waveform;
+
+/// Plays the tone on the default audio device, which stays open as long as the speaker lives.
+pub struct Speaker {
+    sink: rodio::Sink,
+    waveform: WaveformHandle,
+    _stream: rodio::OutputStream,
+}
+
+impl Speaker {
+    /// Opens the default audio device with the tone paused, none if there is no device, like in
+    /// browsers so far.
+    pub fn try_default() -> Option<Speaker> {
+        let (stream, handle) = rodio::OutputStream::try_default().ok()?;
+        let sink = rodio::Sink::try_new(&handle).ok()?;
+        let (tone, waveform) = Tone::new(Waveform::Sine);
+        sink.append(tone);
+        sink.pause();
+
+        Some(Speaker {
+            sink,
+            waveform,
+            _stream: stream,
+        })
+    }
+}
+
+impl Sonifier for Speaker {
+    /// Speeds the tone up or slows it down, so it needn't be generated again.
+    fn set_frequency(&mut self, frequency: f32) {
+        self.sink.set_speed(frequency / tone::BASE_FREQUENCY);
+    }
+
+    fn set_enabled(&mut self, enabled: bool) {
+        if !enabled {
+            self.sink.pause();
+        } else if self.sink.is_paused() {
+            self.sink.play();
+        }
+    }
+
+    fn set_volume(&mut self, volume: f32) {
+        self.sink.set_volume(volume);
+    }
+
+    fn set_waveform(&mut self, waveform: Waveform) {
+        self.waveform.set(waveform);
+    }
+
+    fn available(&self) -> bool {
+        true
+    }
+}
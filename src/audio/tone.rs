@@ -0,0 +1,80 @@
+//! The tone following the values of the sorted numbers.
+
+use crate::settings::Waveform;
+use std::sync::{self, atomic};
+use std::time;
+
+/// The pitch of the tone before the sink speeds it up or slows it down, in Hz.
+pub const BASE_FREQUENCY: f32 = 440.0;
+const SAMPLE_RATE: u32 = 48_000;
+
+/// An endless tone at `BASE_FREQUENCY`, whose waveform can be switched while it plays.
+pub struct Tone {
+    waveform: sync::Arc<atomic::AtomicUsize>,
+    sample: u32,
+}
+
+/// Switches the waveform of the tone it was created with.
+#[derive(Clone, Debug)]
+pub struct WaveformHandle(sync::Arc<atomic::AtomicUsize>);
+
+impl WaveformHandle {
+    pub fn set(&self, waveform: Waveform) {
+        let index = Waveform::VALUES.iter().position(|&w| w == waveform);
+        self.0.store(index.unwrap_or(0), atomic::Ordering::Relaxed);
+    }
+}
+
+impl Tone {
+    pub fn new(waveform: Waveform) -> (Tone, WaveformHandle) {
+        let handle = WaveformHandle(sync::Arc::default());
+        handle.set(waveform);
+
+        (
+            Tone {
+                waveform: handle.0.clone(),
+                sample: 0,
+            },
+            handle,
+        )
+    }
+}
+
+impl Iterator for Tone {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        use std::f32::consts::TAU;
+
+        // Wraps after a whole number of periods, so the phase doesn't lose precision
+        self.sample = (self.sample + 1) % SAMPLE_RATE;
+        let phase = (BASE_FREQUENCY * self.sample as f32 / SAMPLE_RATE as f32).fract();
+
+        let waveform = Waveform::VALUES[self.waveform.load(atomic::Ordering::Relaxed)];
+        // The harsher waveforms are quieter, so they sound about as loud as the sine
+        Some(match waveform {
+            Waveform::Sine => (phase * TAU).sin(),
+            Waveform::Square => 0.5 * if phase < 0.5 { 1.0 } else { -1.0 },
+            Waveform::Triangle => 4.0 * (phase - 0.5).abs() - 1.0,
+            Waveform::Sawtooth => 0.5 * (2.0 * phase - 1.0),
+        })
+    }
+}
+
+impl rodio::Source for Tone {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn total_duration(&self) -> Option<time::Duration> {
+        None
+    }
+}
@@ -0,0 +1,311 @@
+//! A terminal frontend, animating the sorts as bars of unicode blocks.
+//!
+//! It runs the same engine as the window, only without any sound, in the language of the
+//! settings.
+
+use crossterm::{event, execute, terminal};
+use ratatui::prelude::*;
+use ratatui::widgets::Paragraph;
+use sorting_animations::i18n::Language;
+use sorting_animations::settings::Settings;
+use sorting_animations::{array, sorting, DELAY_TIME};
+use std::{io, panic, thread};
+
+const INITIAL_NUMBERS: usize = 100;
+const MAX_SPEED: u32 = 100;
+/// The blocks filling a cell from the bottom in eighths.
+const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+fn main() -> io::Result<()> {
+    let language = match Settings::load() {
+        Ok((settings, _)) => settings.language,
+        Err(error) => {
+            eprintln!("{error}");
+            std::process::exit(2);
+        }
+    };
+
+    // Panics of the sort threads are shown on the stats line, only the main thread can leave the
+    // terminal in raw mode behind
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if thread::current().name() == Some("main") {
+            restore_terminal();
+            hook(info);
+        }
+    }));
+
+    terminal::enable_raw_mode()?;
+    execute!(io::stdout(), terminal::EnterAlternateScreen)?;
+    let result = run(
+        &mut Terminal::new(CrosstermBackend::new(io::stdout()))?,
+        language,
+    );
+    restore_terminal();
+
+    result
+}
+
+fn run(terminal: &mut Terminal<impl Backend>, language: Language) -> io::Result<()> {
+    let mut app = App::new(language);
+
+    loop {
+        terminal.draw(|frame| app.draw(frame))?;
+
+        if event::poll(DELAY_TIME)? {
+            if let event::Event::Key(key) = event::read()? {
+                if key.kind == event::KeyEventKind::Press && !app.handle(key.code) {
+                    return Ok(());
+                }
+            }
+        }
+
+        app.tick();
+    }
+}
+
+fn restore_terminal() {
+    terminal::disable_raw_mode().unwrap_or_default();
+    execute!(io::stdout(), terminal::LeaveAlternateScreen).unwrap_or_default();
+}
+
+struct App {
+    sorter: sorting::Sorter,
+    registry: sorting::Registry,
+    /// The index of the sort in the registry.
+    sort: usize,
+    playing: bool,
+    finished: bool,
+    speed: u32,
+    language: Language,
+}
+
+impl App {
+    fn new(language: Language) -> App {
+        let mut app = App {
+            sorter: sorting::Sorter::new(array::ArrayState::new(INITIAL_NUMBERS)),
+            registry: sorting::Registry::default(),
+            sort: 0,
+            playing: false,
+            finished: false,
+            speed: 1,
+            language,
+        };
+        app.initialize_sort(0);
+
+        app
+    }
+
+    /// Handles a pressed key, returning whether to keep running.
+    fn handle(&mut self, key: event::KeyCode) -> bool {
+        match key {
+            event::KeyCode::Char(' ') => {
+                self.begin_run();
+
+                self.playing = !self.playing;
+                if !self.playing {
+                    self.sorter.drain().unwrap_or_default();
+                }
+            }
+            event::KeyCode::Char('s') => {
+                self.begin_run();
+
+                self.sorter.step().unwrap_or_default();
+            }
+            event::KeyCode::Char('+') => self.set_speed((self.speed * 2).min(MAX_SPEED)),
+            event::KeyCode::Char('-') => self.set_speed((self.speed / 2).max(1)),
+            event::KeyCode::Char('r') => {
+                self.initialize_sort(self.sort);
+                self.sorter.shuffle();
+            }
+            event::KeyCode::Left | event::KeyCode::Right => {
+                let len = self.registry.algorithms().len();
+                let sort = match key {
+                    event::KeyCode::Left => self.sort + len - 1,
+                    _ => self.sort + 1,
+                };
+                self.initialize_sort(sort % len);
+            }
+            event::KeyCode::Char('q') | event::KeyCode::Esc => return false,
+            _ => {}
+        }
+
+        true
+    }
+
+    /// Sets the speed, which the budget already granted follows right away.
+    fn set_speed(&mut self, speed: u32) {
+        if self.playing {
+            let factor = speed as f32 / self.speed as f32;
+            self.sorter.rescale(factor).unwrap_or_default();
+        }
+        self.speed = speed;
+    }
+
+    fn tick(&mut self) {
+        if self.playing {
+            let speed = self.speed as f32 / MAX_SPEED as f32;
+            self.sorter.tick(speed).unwrap_or_default();
+        }
+
+        self.finished = !self.sorter.alive();
+        self.playing &= !self.finished;
+    }
+
+    fn initialize_sort(&mut self, sort: usize) {
+        self.sort = sort;
+        self.playing = false;
+        self.finished = false;
+
+        self.sorter.kill_sort();
+        self.sorter.clear_step();
+        self.sorter.reset_stats();
+        self.sorter
+            .set_sort(self.registry.algorithms()[sort].clone());
+        self.sorter.start_sort();
+    }
+
+    /// Sorts a freshly shuffled array once the last run finished.
+    fn begin_run(&mut self) {
+        if self.finished {
+            self.initialize_sort(self.sort);
+            self.sorter.shuffle();
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [bars, stats, help] = Layout::vertical([
+            Constraint::Min(0),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let stats_line = self.sorter.read_array(|array| {
+            frame.render_widget(Bars::new(array), bars);
+            let stats = array.stats_snapshot();
+
+            let language = self.language;
+            format!(
+                "{} | {} | {} | {} {} {} | {}",
+                self.sorter.sort().name(),
+                self.status(),
+                language.format("controls.speed", &[&self.speed]),
+                language.format("stats.comparisons", &[&stats.comparisons]),
+                language.format("stats.reads", &[&stats.reads]),
+                language.format("stats.writes", &[&stats.writes]),
+                language.format("stats.placed", &[&stats.placed_percent()]),
+            )
+        });
+
+        frame.render_widget(Paragraph::new(stats_line), stats);
+        let help_line = self.language.text("tui.help");
+        frame.render_widget(Paragraph::new(help_line).dark_gray(), help);
+    }
+
+    fn status(&self) -> String {
+        match self.sorter.panic() {
+            Some(panic) => panic.to_string(),
+            None if self.finished => self.language.text("run.Finished"),
+            None if self.playing => self.language.text("tui.playing"),
+            None => self.language.text("tui.paused"),
+        }
+    }
+}
+
+/// The numbers as bars growing from the bottom, colored like the window's bars where touched.
+///
+/// Every column shows a single number, so with more numbers than columns only some are drawn.
+struct Bars<'a> {
+    numbers: &'a [usize],
+    steps: &'a [array::Step],
+    max_value: usize,
+}
+
+impl<'a> Bars<'a> {
+    fn new(array: &'a array::ArrayState) -> Bars<'a> {
+        Bars {
+            numbers: array.numbers(),
+            steps: array.steps(),
+            max_value: array.max_value().max(1),
+        }
+    }
+
+    fn color(&self, index: usize) -> Color {
+        match self.steps.iter().find(|step| step.contains(index)) {
+            Some(step) if step.is_comparison() => Color::Green,
+            Some(_) => Color::Red,
+            None => Color::Reset,
+        }
+    }
+}
+
+impl Widget for Bars<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let (width, height) = (area.width as usize, area.height as usize);
+        if self.numbers.is_empty() || width == 0 {
+            return;
+        }
+
+        for x in 0..width {
+            let index = x * self.numbers.len() / width;
+            let eighths = self.numbers[index] * height * 8 / self.max_value;
+            let color = self.color(index);
+
+            for row in 0..height.min(eighths.div_ceil(8)) {
+                let block = BLOCKS[(eighths - row * 8).min(8) - 1];
+                let position = (area.x + x as u16, area.bottom() - 1 - row as u16);
+                buf[position].set_char(block).set_fg(color);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bars_fill_eighths() {
+        let mut array = array::ArrayState::new(4);
+        array.load_numbers(vec![1, 2, 3, 4]);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 4, 2));
+        Bars::new(&array).render(buf.area, &mut buf);
+        assert_eq!(buf, Buffer::with_lines(["  ▄█", "▄███"]));
+
+        // Wider than the numbers, every number spans several columns
+        let mut buf = Buffer::empty(Rect::new(0, 0, 8, 1));
+        Bars::new(&array).render(buf.area, &mut buf);
+        assert_eq!(buf, Buffer::with_lines(["▂▂▄▄▆▆██"]));
+    }
+
+    #[test]
+    fn keys_run_sort() {
+        let mut app = App::new(Language::ENGLISH);
+        app.handle(event::KeyCode::Char('r'));
+        for _ in 0..7 {
+            app.handle(event::KeyCode::Char('+'));
+        }
+        assert_eq!(app.speed, MAX_SPEED);
+
+        app.handle(event::KeyCode::Char(' '));
+        while !app.finished {
+            app.tick();
+        }
+
+        assert!(!app.playing);
+        assert_eq!(app.status(), "Finished");
+        app.language = "de".parse().unwrap();
+        assert_eq!(app.status(), "Fertig");
+        app.sorter.read_array(|array| {
+            assert_eq!(array.numbers(), (1..=INITIAL_NUMBERS).collect::<Vec<_>>());
+            assert!(array.comparisons() > 0);
+        });
+
+        // Playing again sorts a fresh shuffle
+        app.handle(event::KeyCode::Char(' '));
+        assert!(app.playing && !app.finished);
+        assert!(!app.handle(event::KeyCode::Char('q')));
+    }
+}
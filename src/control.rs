@@ -0,0 +1,464 @@
+//! The control socket, letting external tools like scripted demos or stream overlays drive the
+//! animations.
+//!
+//! It listens on localhost only, for a JSON object per line naming its `command`, and answers each
+//! with a JSON object per line, `{"ok":true}` with the stats for `query_stats`, or `{"ok":false,
+//! "error":"..."}`. The commands are:
+//!
+//! - `{"command":"set_sort","sort":"QuickSort"}`
+//! - `{"command":"set_numbers","count":200}`
+//! - `{"command":"shuffle"}`, or `{"command":"shuffle","seed":7}` to shuffle reproducibly
+//! - `{"command":"play"}` and `{"command":"pause"}`
+//! - `{"command":"step"}`, or `{"command":"step","count":10}` for at most `MAX_STEP_COUNT`
+//! - `{"command":"set_speed","speed":50}`
+//! - `{"command":"query_stats"}`
+
+use crate::array;
+use std::io::{self, BufRead, Write};
+use std::{net, sync::mpsc, thread};
+
+/// The most operations a single `step` command performs, as they are carried out at once.
+pub const MAX_STEP_COUNT: u64 = 10_000;
+
+/// A command of a control client.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Selects the sort by its name.
+    SetSort(String),
+    SetNumbers(usize),
+    /// Shuffles the numbers, the same way every time for a seed.
+    Shuffle(Option<u64>),
+    Play,
+    Pause,
+    Step(u64),
+    SetSpeed(u32),
+    QueryStats,
+}
+
+impl std::str::FromStr for Command {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Command, String> {
+        let object = Object::parse(line)?;
+
+        Ok(match object.string("command")? {
+            "set_sort" => Command::SetSort(object.string("sort")?.to_string()),
+            "set_numbers" => Command::SetNumbers(object.number("count")?),
+            "shuffle" => Command::Shuffle(object.optional_number("seed")?),
+            "play" => Command::Play,
+            "pause" => Command::Pause,
+            "step" => match object.optional_number("count")?.unwrap_or(1) {
+                count if count > MAX_STEP_COUNT => {
+                    return Err(format!("the count can be at most {MAX_STEP_COUNT}"));
+                }
+                count => Command::Step(count),
+            },
+            "set_speed" => Command::SetSpeed(object.number("speed")?),
+            "query_stats" => Command::QueryStats,
+            command => return Err(format!("unknown command `{command}`")),
+        })
+    }
+}
+
+/// The answer to a command.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Response {
+    Ok,
+    Error(String),
+    Stats {
+        stats: Box<array::Stats>,
+        playing: bool,
+    },
+}
+
+/// Written as the JSON object sent back to the client.
+impl std::fmt::Display for Response {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Response::Ok => write!(f, r#"{{"ok":true}}"#),
+            Response::Error(message) => write!(f, r#"{{"ok":false,"error":{}}}"#, quote(message)),
+            Response::Stats { stats, playing } => write!(
+                f,
+                concat!(
+                    r#"{{"ok":true,"playing":{},"size":{},"placed":{},"comparisons":{},"#,
+                    r#""wasted_comparisons":{},"reads":{},"writes":{},"generation":{}}}"#,
+                ),
+                playing,
+                stats.size,
+                stats.placed,
+                stats.comparisons,
+                // Unknown until a recorded run completes
+                stats
+                    .wasted_comparisons
+                    .map_or_else(|| String::from("null"), |wasted| wasted.to_string()),
+                stats.reads,
+                stats.writes,
+                stats.generation,
+            ),
+        }
+    }
+}
+
+/// A command waiting for its response, which the client is blocked on until it is answered.
+#[derive(Clone, Debug)]
+pub struct Request {
+    pub command: Command,
+    respond: mpsc::Sender<Response>,
+}
+
+impl Request {
+    pub fn new(command: Command) -> (Request, mpsc::Receiver<Response>) {
+        let (respond, response) = mpsc::channel();
+
+        (Request { command, respond }, response)
+    }
+
+    pub fn respond(self, response: Response) {
+        // A client that hung up doesn't need the response anymore
+        self.respond.send(response).ok();
+    }
+}
+
+/// Listens on the port of localhost, passing the commands of every client to `forward` one at a
+/// time, until it returns false once they can't be handled anymore.
+///
+/// Port 0 picks a free port, which is returned along with the address.
+pub fn listen(
+    port: u16,
+    forward: impl Fn(Request) -> bool + Clone + Send + 'static,
+) -> io::Result<net::SocketAddr> {
+    let listener = net::TcpListener::bind((net::Ipv4Addr::LOCALHOST, port))?;
+    let address = listener.local_addr()?;
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let forward = forward.clone();
+            thread::spawn(move || serve(stream, forward));
+        }
+    });
+
+    Ok(address)
+}
+
+/// Answers the commands of a client, until it disconnects.
+fn serve(stream: net::TcpStream, forward: impl Fn(Request) -> bool) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+
+    for line in io::BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match line.parse() {
+            Ok(command) => {
+                let (request, response) = Request::new(command);
+                if !forward(request) {
+                    return Ok(());
+                }
+                response
+                    .recv()
+                    .unwrap_or_else(|_| Response::Error(String::from("the command was dropped")))
+            }
+            Err(message) => Response::Error(message),
+        };
+        writeln!(writer, "{response}")?;
+    }
+
+    Ok(())
+}
+
+/// The string as a JSON string literal.
+fn quote(text: &str) -> String {
+    let mut quoted = String::from('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+/// A value of a JSON object, a string or else the literal as written, like a number.
+#[derive(Debug, PartialEq)]
+enum Value {
+    String(String),
+    Literal(String),
+}
+
+/// The fields of a flat JSON object, which is all the commands take.
+#[derive(Debug)]
+struct Object(Vec<(String, Value)>);
+
+impl Object {
+    fn parse(json: &str) -> Result<Object, String> {
+        let mut chars = json.trim().chars().peekable();
+        let mut fields = Vec::new();
+        let skip_whitespace = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        };
+
+        if chars.next() != Some('{') {
+            return Err(String::from("expected a JSON object"));
+        }
+        skip_whitespace(&mut chars);
+        if chars.next_if_eq(&'}').is_none() {
+            loop {
+                skip_whitespace(&mut chars);
+                if chars.next() != Some('"') {
+                    return Err(String::from("expected a quoted key"));
+                }
+                let key = string(&mut chars)?;
+                skip_whitespace(&mut chars);
+                if chars.next() != Some(':') {
+                    return Err(format!("expected `:` after `{key}`"));
+                }
+                skip_whitespace(&mut chars);
+                let value = if chars.next_if_eq(&'"').is_some() {
+                    Value::String(string(&mut chars)?)
+                } else {
+                    let mut literal = String::new();
+                    while let Some(c) = chars.next_if(|&c| c != ',' && c != '}') {
+                        literal.push(c);
+                    }
+                    Value::Literal(literal.trim().to_string())
+                };
+                fields.push((key, value));
+
+                skip_whitespace(&mut chars);
+                match chars.next() {
+                    Some(',') => {}
+                    Some('}') => break,
+                    _ => return Err(String::from("expected `,` or `}`")),
+                }
+            }
+        }
+        if chars.next().is_some() {
+            return Err(String::from("expected the object to end the line"));
+        }
+
+        Ok(Object(fields))
+    }
+
+    fn get(&self, key: &str) -> Option<&Value> {
+        self.0
+            .iter()
+            .find(|(field, _)| field == key)
+            .map(|(_, value)| value)
+    }
+
+    fn string(&self, key: &str) -> Result<&str, String> {
+        match self.get(key) {
+            Some(Value::String(text)) => Ok(text),
+            Some(_) => Err(format!("`{key}` has to be a string")),
+            None => Err(format!("missing `{key}`")),
+        }
+    }
+
+    fn number<T: std::str::FromStr>(&self, key: &str) -> Result<T, String> {
+        self.optional_number(key)?
+            .ok_or_else(|| format!("missing `{key}`"))
+    }
+
+    fn optional_number<T: std::str::FromStr>(&self, key: &str) -> Result<Option<T>, String> {
+        match self.get(key) {
+            Some(Value::Literal(literal)) => literal
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("invalid number `{literal}` for `{key}`")),
+            Some(Value::String(_)) => Err(format!("`{key}` has to be a number")),
+            None => Ok(None),
+        }
+    }
+}
+
+/// The rest of a string literal after its opening quote, unescaped.
+fn string(chars: &mut impl Iterator<Item = char>) -> Result<String, String> {
+    let mut text = String::new();
+
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(text),
+            Some('\\') => text.push(match chars.next() {
+                Some('n') => '\n',
+                Some('t') => '\t',
+                Some('r') => '\r',
+                Some('b') => '\u{8}',
+                Some('f') => '\u{c}',
+                Some('u') => {
+                    let hex: String = chars.take(4).collect();
+                    u32::from_str_radix(&hex, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or_else(|| format!("invalid escape `\\u{hex}`"))?
+                }
+                Some(c @ ('"' | '\\' | '/')) => c,
+                _ => return Err(String::from("invalid escape")),
+            }),
+            Some(c) => text.push(c),
+            None => return Err(String::from("unterminated string")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sorting;
+
+    #[test]
+    fn commands_are_parsed() {
+        for (line, command) in [
+            (
+                r#"{"command": "set_sort", "sort": "Quick\"Sort\""}"#,
+                Command::SetSort(String::from("Quick\"Sort\"")),
+            ),
+            (
+                r#"{"command":"set_numbers","count":200}"#,
+                Command::SetNumbers(200),
+            ),
+            (r#"{"command":"shuffle"}"#, Command::Shuffle(None)),
+            (
+                r#" { "seed" : 7 , "command" : "shuffle" } "#,
+                Command::Shuffle(Some(7)),
+            ),
+            (r#"{"command":"step"}"#, Command::Step(1)),
+            (r#"{"command":"step","count":10}"#, Command::Step(10)),
+            (
+                r#"{"command":"step","count":10000}"#,
+                Command::Step(MAX_STEP_COUNT),
+            ),
+            (
+                r#"{"command":"set_speed","speed":50}"#,
+                Command::SetSpeed(50),
+            ),
+            (r#"{"command":"query_stats"}"#, Command::QueryStats),
+        ] {
+            assert_eq!(line.parse(), Ok(command), "{line}");
+        }
+
+        for invalid in [
+            "",
+            "play",
+            "{}",
+            r#"{"command":"fly"}"#,
+            r#"{"command":"set_numbers"}"#,
+            r#"{"command":"set_numbers","count":-3}"#,
+            r#"{"command":"set_speed","speed":"fast"}"#,
+            r#"{"command":"play""#,
+            r#"{"command":"play"} {}"#,
+            r#"{"command":"step","count":10001}"#,
+            r#"{"command":"step","count":18446744073709551615}"#,
+        ] {
+            assert!(invalid.parse::<Command>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn responses_are_json() {
+        assert_eq!(Response::Ok.to_string(), r#"{"ok":true}"#);
+        assert_eq!(
+            Response::Error(String::from("no \"sort\"\n")).to_string(),
+            r#"{"ok":false,"error":"no \"sort\"\n"}"#
+        );
+
+        let stats = array::ArrayState::from_numbers(vec![2, 1, 3]).stats_snapshot();
+        let response = Response::Stats {
+            stats: Box::new(stats),
+            playing: false,
+        }
+        .to_string();
+        let object = Object::parse(&response).unwrap();
+        assert_eq!(object.number::<usize>("size"), Ok(3));
+        assert_eq!(object.get("playing"), Some(&Value::Literal("false".into())));
+    }
+
+    /// Drives a sort through the socket like the animations would, without a window.
+    #[test]
+    fn clients_drive_a_headless_run() {
+        let (requests, received) = mpsc::channel();
+        let address = listen(0, move |request| requests.send(request).is_ok()).unwrap();
+        assert!(address.ip().is_loopback());
+
+        let client = thread::spawn(move || {
+            let stream = net::TcpStream::connect(address).unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            let mut lines = io::BufReader::new(stream).lines();
+            let mut send = |line: &str| {
+                writeln!(writer, "{line}").unwrap();
+                lines.next().unwrap().unwrap()
+            };
+
+            let responses = [
+                send(r#"{"command":"set_sort","sort":"InsertionSort"}"#),
+                send(r#"{"command":"set_numbers","count":30}"#),
+                send(r#"{"command":"shuffle","seed":7}"#),
+                send(r#"{"command":"step","count":5}"#),
+                send(r#"{"command":"set_sort","sort":"SleepSort"}"#),
+                send("not json"),
+                send(r#"{"command":"step","count":18446744073709551615}"#),
+                send(r#"{"command":"step","count":10000}"#),
+            ];
+            let stats = send(r#"{"command":"query_stats"}"#);
+            (responses, stats)
+        });
+
+        let mut sorter = sorting::Sorter::new(array::ArrayState::new(10));
+        sorter.set_deterministic(true);
+        let registry = sorting::Registry::default();
+        // Only the commands the client sends are handled, the parse errors never arrive
+        for request in received.iter().take(7) {
+            let response = match request.command {
+                Command::SetSort(ref name) => match registry.get(name) {
+                    Some(sort) => {
+                        sorter.set_sort(sort.clone());
+                        Response::Ok
+                    }
+                    None => Response::Error(format!("no sort named `{name}`")),
+                },
+                Command::SetNumbers(count) => {
+                    sorter.initialize(count, count);
+                    Response::Ok
+                }
+                Command::Shuffle(Some(seed)) => {
+                    sorter.shuffle_seeded(seed);
+                    sorter.start_sort();
+                    Response::Ok
+                }
+                Command::Step(count) => {
+                    // Stepping stops once the sort is done
+                    for _ in 0..count {
+                        if sorter.step().is_err() || !sorter.alive() {
+                            break;
+                        }
+                    }
+                    Response::Ok
+                }
+                Command::QueryStats => Response::Stats {
+                    stats: Box::new(sorter.stats_snapshot()),
+                    playing: false,
+                },
+                _ => Response::Error(String::from("unexpected")),
+            };
+            request.respond(response);
+        }
+
+        let (responses, stats) = client.join().unwrap();
+        assert_eq!(responses[..4], [r#"{"ok":true}"#; 4]);
+        assert!(responses[4].contains("no sort named `SleepSort`"));
+        assert!(responses[5].starts_with(r#"{"ok":false"#));
+        assert!(responses[6].contains("the count can be at most 10000"));
+        assert_eq!(responses[7], r#"{"ok":true}"#);
+
+        let stats = Object::parse(&stats).unwrap();
+        assert_eq!(stats.number::<usize>("size"), Ok(30));
+        assert_eq!(stats.number::<usize>("placed"), Ok(30));
+        assert!(stats.number::<u64>("comparisons").unwrap() > 0);
+    }
+}
@@ -0,0 +1,872 @@
+use crate::gui::{
+    approximate, localized, Infeasible, Interrupting, Interruption, Localize, Localized,
+    NumberInput, Pending, Pixels, Ramp, Scale, SlowMotion, BLUE,
+};
+use crate::Message;
+use iced::{button, pick_list, slider, text_input};
+use sorting_animations::settings::Settings;
+use sorting_animations::view::{View, RED};
+use sorting_animations::{array, sorting};
+
+/// The width of the outline around the control focused by the keyboard.
+const FOCUS_OUTLINE_WIDTH: f32 = 2.0;
+
+/// The controls reachable from the keyboard, in the order Tab moves the focus through them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Focus {
+    Sort,
+    Play,
+    Step,
+    /// The toggle of the controls a compact window collapses.
+    More,
+    Shuffle,
+    Reverse,
+    Sorted,
+    AllEqual,
+    WorstCase,
+    Speed,
+    Numbers,
+    MaxValue,
+    View,
+    Mute,
+}
+
+impl Focus {
+    pub const ORDER: [Focus; 14] = [
+        Focus::Sort,
+        Focus::Play,
+        Focus::Step,
+        Focus::More,
+        Focus::Shuffle,
+        Focus::Reverse,
+        Focus::Sorted,
+        Focus::AllEqual,
+        Focus::WorstCase,
+        Focus::Speed,
+        Focus::Numbers,
+        Focus::MaxValue,
+        Focus::View,
+        Focus::Mute,
+    ];
+
+    /// The next control in the focus order, or the previous one, wrapping around.
+    fn moved(self, forward: bool) -> Focus {
+        let len = Focus::ORDER.len();
+        let index = Focus::ORDER.iter().position(|&f| f == self).unwrap_or(0);
+
+        Focus::ORDER[if forward {
+            (index + 1) % len
+        } else {
+            (index + len - 1) % len
+        }]
+    }
+}
+
+/// What a key does to the focus or the focused control.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Navigation {
+    /// Tab, or Shift+Tab for `Previous`.
+    Next,
+    Previous,
+    /// The arrow keys, adjusting a slider or picking another value of a pick list.
+    Increase,
+    Decrease,
+    /// Enter or Space, pressing a button or flipping a toggle.
+    Activate,
+    /// Escape, moving the focus off the controls.
+    Leave,
+}
+
+impl Navigation {
+    /// The navigation of a pressed key, unless a widget handled it, so the keys a focused text
+    /// input takes are left to it.
+    pub fn of(
+        key_code: iced::keyboard::KeyCode,
+        modifiers: iced::keyboard::Modifiers,
+        status: iced_native::event::Status,
+    ) -> Option<Navigation> {
+        use iced::keyboard::KeyCode;
+
+        if status == iced_native::event::Status::Captured {
+            return None;
+        }
+
+        match key_code {
+            KeyCode::Tab if modifiers.shift() => Some(Navigation::Previous),
+            KeyCode::Tab => Some(Navigation::Next),
+            KeyCode::Up | KeyCode::Right => Some(Navigation::Increase),
+            KeyCode::Down | KeyCode::Left => Some(Navigation::Decrease),
+            KeyCode::Enter | KeyCode::NumpadEnter | KeyCode::Space => Some(Navigation::Activate),
+            KeyCode::Escape => Some(Navigation::Leave),
+            _ => None,
+        }
+    }
+}
+
+/// Draws an outline around the focused control, and keeps the same room free around the others so
+/// moving the focus doesn't shift them.
+pub fn focusable<'a>(
+    content: impl Into<iced::Element<'a, Message>>,
+    focused: bool,
+) -> iced::Container<'a, Message> {
+    iced::Container::new(content)
+        .padding(FOCUS_OUTLINE_WIDTH as u16)
+        .style(FocusOutline(focused))
+}
+
+struct FocusOutline(bool);
+
+impl iced::container::StyleSheet for FocusOutline {
+    fn style(&self) -> iced::container::Style {
+        iced::container::Style {
+            border_width: FOCUS_OUTLINE_WIDTH,
+            border_color: if self.0 {
+                BLUE
+            } else {
+                iced::Color::TRANSPARENT
+            },
+            border_radius: 3.0,
+            ..iced::container::Style::default()
+        }
+    }
+}
+
+/// What the controls show of the application, read from it before they borrow their states.
+#[derive(Clone, Debug)]
+pub struct ControlsSnapshot {
+    pub playing: bool,
+    pub speed: u32,
+    pub numbers: NumberInput,
+    pub max_value: NumberInput,
+    pub range_start: NumberInput,
+    pub range_end: NumberInput,
+    pub view: View,
+    /// Whether the structure is drawn, none for sorts without one to draw.
+    pub structure: Option<bool>,
+    pub scale: Scale,
+    pub interruption: Interruption,
+    pub pending: Option<Pending>,
+    pub infeasible: Option<Infeasible>,
+}
+
+/// The controls of the sort and the view, which a small window collapses to the essential ones
+/// behind a toggle, so the array keeps most of the window.
+#[derive(Default)]
+pub struct Controls {
+    algorithms: pick_list::State<sorting::Algorithm>,
+    play: button::State,
+    step: button::State,
+    speed: slider::State,
+    ramp: pick_list::State<Localized<Ramp>>,
+    granularity: pick_list::State<Localized<sorting::Granularity>>,
+    highlights: slider::State,
+    numbers: text_input::State,
+    max_value: text_input::State,
+    range_start: text_input::State,
+    range_end: text_input::State,
+    shuffle: button::State,
+    reverse: button::State,
+    order: button::State,
+    equalize: button::State,
+    worst_case: button::State,
+    arrangement: pick_list::State<Localized<array::Arrangement>>,
+    view: pick_list::State<Localized<View>>,
+    scale: pick_list::State<Scale>,
+    slow_motion: pick_list::State<Localized<SlowMotion>>,
+    clear_watched: button::State,
+    interruption: pick_list::State<Localized<Interruption>>,
+    confirm: button::State,
+    cancel: button::State,
+    more: button::State,
+    /// The control the keyboard acts on, none until Tab is pressed.
+    focus: Option<Focus>,
+    compact: bool,
+    expanded: bool,
+}
+
+impl Controls {
+    /// Sets whether the window is too small to show every control at once.
+    pub fn set_compact(&mut self, compact: bool) {
+        self.compact = compact;
+    }
+
+    /// Shows or hides the controls a compact window collapses.
+    pub fn toggle_expanded(&mut self) {
+        self.expanded = !self.expanded;
+    }
+
+    pub fn compact(&self) -> bool {
+        self.compact
+    }
+
+    /// Whether only the essential controls are shown.
+    pub fn collapsed(&self) -> bool {
+        self.compact && !self.expanded
+    }
+
+    /// Whether the numbers field is being typed into, until clicked elsewhere.
+    pub fn numbers_focused(&self) -> bool {
+        self.numbers.is_focused()
+    }
+
+    /// The control the keyboard acts on, which is a text input as long as it is typed into, even
+    /// if it was clicked instead of tabbed to.
+    pub fn focus(&self) -> Option<Focus> {
+        if self.numbers.is_focused() {
+            Some(Focus::Numbers)
+        } else if self.max_value.is_focused() {
+            Some(Focus::MaxValue)
+        } else {
+            self.focus
+        }
+    }
+
+    /// Moves the focus to the next shown control, or the previous one.
+    pub fn move_focus(&mut self, forward: bool) {
+        let mut focus = match self.focus() {
+            Some(focus) => focus.moved(forward),
+            None if forward => Focus::ORDER[0],
+            None => Focus::ORDER[Focus::ORDER.len() - 1],
+        };
+        while !self.shown(focus) {
+            focus = focus.moved(forward);
+        }
+        self.set_focus(Some(focus));
+    }
+
+    /// Moves the focus off the controls, so the shortcuts work again.
+    pub fn leave_focus(&mut self) {
+        self.set_focus(None);
+    }
+
+    fn set_focus(&mut self, focus: Option<Focus>) {
+        self.focus = focus;
+        // The text inputs take the keys while focused, the others leave them to the subscription
+        for (input, state) in [
+            (Focus::Numbers, &mut self.numbers),
+            (Focus::MaxValue, &mut self.max_value),
+        ] {
+            if focus == Some(input) {
+                state.focus();
+                state.move_cursor_to_end();
+            } else {
+                state.unfocus();
+            }
+        }
+    }
+
+    /// Whether the control is shown, the collapsed ones are skipped by the focus.
+    fn shown(&self, focus: Focus) -> bool {
+        match focus {
+            Focus::Sort | Focus::Play | Focus::Step | Focus::Mute => true,
+            Focus::More => self.compact,
+            _ => !self.collapsed(),
+        }
+    }
+
+    #[cfg(test)]
+    pub fn focus_numbers(&mut self, focused: bool) {
+        if focused {
+            self.numbers.focus();
+        } else {
+            self.numbers.unfocus();
+        }
+    }
+
+    pub fn view<'a>(
+        &'a mut self,
+        sorter: &sorting::Sorter,
+        algorithms: &'a [sorting::Algorithm],
+        snapshot: &ControlsSnapshot,
+        settings: &Settings,
+    ) -> iced::Element<'a, Message> {
+        let ControlsSnapshot {
+            playing,
+            speed,
+            ref numbers,
+            ref max_value,
+            ref range_start,
+            ref range_end,
+            view,
+            structure,
+            scale,
+            interruption,
+            ref pending,
+            infeasible,
+        } = *snapshot;
+        let pending = pending.as_ref();
+        let (compact, expanded) = (self.compact, self.expanded);
+        let focus = self.focus();
+        let focused = |control: Focus| focus == Some(control);
+        let (padding, language) = (settings.padding, settings.language);
+        let text = |key: &str| iced::Text::new(language.text(key));
+        let label = |action: Interrupting| match pending {
+            Some(pending) if pending.confirming(&action) => language.text("controls.really"),
+            _ => action.localize(language),
+        };
+
+        let mut play_button = iced::Button::new(
+            &mut self.play,
+            text(match infeasible {
+                _ if playing => "controls.stop",
+                Some(Infeasible { warned: true, .. }) => "controls.play_anyway",
+                _ => "controls.play",
+            }),
+        );
+
+        let mut shuffle_button = iced::Button::new(
+            &mut self.shuffle,
+            iced::Text::new(label(Interrupting::Shuffle)),
+        );
+        let mut reverse_button = iced::Button::new(
+            &mut self.reverse,
+            iced::Text::new(label(Interrupting::Reverse)),
+        );
+        let mut order_button = iced::Button::new(&mut self.order, text("controls.sorted"));
+        let mut equalize_button = iced::Button::new(&mut self.equalize, text("controls.all_equal"));
+        let mut worst_case_button =
+            iced::Button::new(&mut self.worst_case, text("controls.worst_case"));
+        let mut step_button = iced::Button::new(&mut self.step, text("controls.step"));
+
+        // The sort can't start before the numbers are rearranged
+        if !sorter.arranging() {
+            play_button = play_button.on_press(Message::Play);
+        }
+        // Shuffling and reversing may interrupt the run, once confirmed or after it stopped
+        if !sorter.arranging() {
+            shuffle_button = shuffle_button.on_press(Message::Shuffle);
+            reverse_button = reverse_button.on_press(Message::Reverse);
+        }
+        if !playing && !sorter.arranging() {
+            order_button = order_button.on_press(Message::Order);
+            equalize_button = equalize_button.on_press(Message::Equalize);
+            // Only the built in sorts know what they are worst at
+            if let Some(arrangement) = sorter.sort().worst_case() {
+                worst_case_button =
+                    worst_case_button.on_press(Message::ArrangementSelected(arrangement));
+            }
+            step_button = step_button.on_press(Message::Step);
+        }
+
+        let algorithm_pick_list = focusable(
+            iced::PickList::new(
+                &mut self.algorithms,
+                algorithms,
+                Some(sorter.sort().clone()),
+                Message::SortSelected,
+            ),
+            focused(Focus::Sort),
+        );
+        // Outlined here, once their presses are set
+        let play_button = focusable(play_button, focused(Focus::Play));
+        let step_button = focusable(step_button, focused(Focus::Step));
+        let shuffle_button = focusable(shuffle_button, focused(Focus::Shuffle));
+        let reverse_button = focusable(reverse_button, focused(Focus::Reverse));
+        let order_button = focusable(order_button, focused(Focus::Sorted));
+        let equalize_button = focusable(equalize_button, focused(Focus::AllEqual));
+        let worst_case_button = focusable(worst_case_button, focused(Focus::WorstCase));
+
+        // The buttons confirm themselves, other actions are confirmed or canceled here
+        let mut pending_row = iced::Row::new()
+            .spacing(padding)
+            .align_items(iced::Alignment::Center);
+        if let Some(infeasible) = infeasible {
+            let duration = approximate(infeasible.duration, language);
+            pending_row = pending_row.push(
+                iced::Text::new(language.format("controls.infeasible", &[&duration])).color(RED),
+            );
+        }
+        match pending {
+            Some(pending) if pending.queued => {
+                let action = pending.action.localize(language);
+                pending_row = pending_row
+                    .push(iced::Text::new(
+                        language.format("controls.queued", &[&action]),
+                    ))
+                    .push(
+                        iced::Button::new(&mut self.cancel, text("controls.cancel"))
+                            .on_press(Message::CancelPending),
+                    );
+            }
+            Some(Pending {
+                action:
+                    action @ (Interrupting::Sort(_) | Interrupting::Numbers | Interrupting::Range),
+                ..
+            }) => {
+                pending_row = pending_row.push(
+                    iced::Button::new(
+                        &mut self.confirm,
+                        iced::Text::new(action.confirmation(language)),
+                    )
+                    .on_press(Message::ConfirmPending),
+                );
+            }
+            _ => {}
+        }
+
+        let pace_row = iced::Row::new()
+            .spacing(padding)
+            .align_items(iced::Alignment::Center)
+            .push(iced::Text::new(
+                language.format("controls.speed", &[&speed]),
+            ))
+            .push(
+                focusable(
+                    iced::Slider::new(
+                        &mut self.speed,
+                        1..=settings.max_speed,
+                        speed,
+                        Message::SpeedSelected,
+                    ),
+                    focused(Focus::Speed),
+                )
+                .width(iced::Length::Fill),
+            )
+            .push(iced::PickList::new(
+                &mut self.ramp,
+                localized(&Ramp::VALUES, language),
+                Some(Localized(Ramp::from(sorter.ramp()), language)),
+                |Localized(ramp, _)| Message::RampSelected(ramp),
+            ))
+            .push(text("controls.pace"))
+            .push(iced::PickList::new(
+                &mut self.granularity,
+                localized(&sorting::Granularity::VALUES, language),
+                Some(Localized(sorter.granularity(), language)),
+                |Localized(granularity, _)| Message::GranularitySelected(granularity),
+            ))
+            .push(iced::Text::new(
+                language.format("controls.highlights", &[&sorter.highlights()]),
+            ))
+            .push(iced::Slider::new(
+                &mut self.highlights,
+                1..=array::MAX_HIGHLIGHTS as u32,
+                sorter.highlights() as u32,
+                Message::HighlightsSelected,
+            ))
+            .push(iced::PickList::new(
+                &mut self.interruption,
+                localized(&Interruption::VALUES, language),
+                Some(Localized(interruption, language)),
+                |Localized(interruption, _)| Message::InterruptionSelected(interruption),
+            ));
+
+        let numbers_row = iced::Row::new()
+            .spacing(padding)
+            .align_items(iced::Alignment::Center)
+            .push(text("controls.numbers"))
+            .push(focusable(
+                numbers.view(
+                    &mut self.numbers,
+                    &language.text("controls.numbers_placeholder"),
+                    140,
+                    Message::NumbersInput,
+                    Message::NumbersSelected,
+                    language,
+                ),
+                focused(Focus::Numbers),
+            ))
+            .push(text("controls.up_to"))
+            // Left empty, the values go up to the number of elements
+            .push(focusable(
+                max_value.view(
+                    &mut self.max_value,
+                    &language.text("controls.max_value_placeholder"),
+                    100,
+                    Message::MaxValueInput,
+                    Message::NumbersSelected,
+                    language,
+                ),
+                focused(Focus::MaxValue),
+            ))
+            .push(text("controls.range"))
+            // Left empty, the range goes from the first or to the last number
+            .push(range_start.view(
+                &mut self.range_start,
+                &language.text("controls.from"),
+                60,
+                Message::RangeStartInput,
+                Message::RangeSelected,
+                language,
+            ))
+            .push(range_end.view(
+                &mut self.range_end,
+                &language.text("controls.to"),
+                60,
+                Message::RangeEndInput,
+                Message::RangeSelected,
+                language,
+            ))
+            .push(
+                // Only a menu of arrangements to apply, the numbers may have been sorted since
+                iced::PickList::new(
+                    &mut self.arrangement,
+                    localized(&array::Arrangement::VALUES, language),
+                    None,
+                    |Localized(arrangement, _)| Message::ArrangementSelected(arrangement),
+                )
+                .placeholder(language.text("controls.arrange")),
+            );
+
+        // Shift-clicking elements watches them
+        let mut clear_watched_button =
+            iced::Button::new(&mut self.clear_watched, text("controls.clear_watched"));
+        if sorter.read_array(|array| !array.watched().is_empty()) {
+            clear_watched_button = clear_watched_button.on_press(Message::ClearWatched);
+        }
+
+        let view_row = iced::Row::new()
+            .spacing(padding)
+            .align_items(iced::Alignment::Center)
+            .push(text("controls.view"))
+            .push(focusable(
+                iced::PickList::new(
+                    &mut self.view,
+                    localized(View::values(), language),
+                    Some(Localized(view, language)),
+                    |Localized(view, _)| Message::ViewSelected(view),
+                ),
+                focused(Focus::View),
+            ))
+            .push(iced::PickList::new(
+                &mut self.scale,
+                Scale::VALUES.as_slice(),
+                Some(scale),
+                Message::ScaleSelected,
+            ))
+            .push(iced::PickList::new(
+                &mut self.slow_motion,
+                localized(&SlowMotion::VALUES, language),
+                Some(Localized(SlowMotion::from(sorter.slow_motion()), language)),
+                |Localized(slow_motion, _)| Message::SlowMotionSelected(slow_motion),
+            ))
+            .push(clear_watched_button);
+        // Only sorts keeping a heap or merging runs have a structure to draw
+        let view_row = match structure {
+            Some(structure) => view_row.push(
+                iced::Toggler::new(
+                    structure,
+                    language.text("controls.structure"),
+                    Message::Structure,
+                )
+                .width(iced::Length::Shrink),
+            ),
+            None => view_row,
+        };
+
+        if compact {
+            let more_button = focusable(
+                iced::Button::new(
+                    &mut self.more,
+                    text(if expanded {
+                        "controls.less"
+                    } else {
+                        "controls.more"
+                    }),
+                )
+                .on_press(Message::ToggleControls),
+                focused(Focus::More),
+            );
+
+            let mut controls = iced::Column::new()
+                .spacing(padding)
+                .padding(padding)
+                .width(iced::Length::Fill)
+                .push(
+                    iced::Row::new()
+                        .spacing(padding)
+                        .push(algorithm_pick_list)
+                        .push(play_button)
+                        .push(step_button)
+                        .push(more_button)
+                        .push(pending_row),
+                );
+
+            if expanded {
+                controls = controls
+                    .push(
+                        iced::Row::new()
+                            .spacing(padding)
+                            .align_items(iced::Alignment::Center)
+                            .push(shuffle_button)
+                            .push(reverse_button)
+                            .push(order_button)
+                            .push(equalize_button)
+                            .push(worst_case_button),
+                    )
+                    .push(
+                        iced::Row::new()
+                            .spacing(padding)
+                            .align_items(iced::Alignment::Center)
+                            .push(numbers_row)
+                            .push(view_row),
+                    )
+                    .push(pace_row);
+            }
+
+            return controls.into();
+        }
+
+        let algorithm_controls = iced::Column::new()
+            .spacing(padding)
+            .padding(padding)
+            .width(iced::Length::Fill)
+            .push(
+                iced::Row::new()
+                    .spacing(padding)
+                    .push(algorithm_pick_list)
+                    .push(play_button)
+                    .push(shuffle_button)
+                    .push(reverse_button)
+                    .push(order_button)
+                    .push(equalize_button)
+                    .push(worst_case_button)
+                    .push(step_button)
+                    .push(pending_row),
+            )
+            .push(pace_row);
+
+        // Only as wide as its inputs, leaving the rest of a wide window to the sliders
+        let view_controls = iced::Column::new()
+            .spacing(padding)
+            .padding(padding)
+            .push(numbers_row)
+            .push(view_row);
+
+        iced::Row::new()
+            .spacing(5)
+            .align_items(iced::Alignment::Center)
+            .push(algorithm_controls)
+            .push(iced::Rule::vertical(5))
+            .push(view_controls)
+            .into()
+    }
+}
+
+/// What the trace controls show of the application.
+#[derive(Clone, Debug)]
+pub struct TraceSnapshot {
+    pub playing: bool,
+    pub recording: bool,
+    /// Whether the last run was recorded, so it can become the reference.
+    pub recorded: bool,
+    pub path: String,
+    /// The sort of the reference run, if one is set.
+    pub reference: Option<sorting::Sort>,
+    pub screenshots: bool,
+    pub screenshots_path: String,
+}
+
+/// The controls for recording, saving and loading traces, comparing runs against a recorded
+/// reference run, and capturing screenshots.
+#[derive(Default)]
+pub struct TraceControls {
+    path: text_input::State,
+    save: button::State,
+    load: button::State,
+    set_reference: button::State,
+    clear_reference: button::State,
+    screenshots_path: text_input::State,
+}
+
+impl TraceControls {
+    pub fn view(
+        &mut self,
+        snapshot: &TraceSnapshot,
+        settings: &Settings,
+    ) -> iced::Element<'_, Message> {
+        let TraceSnapshot {
+            playing,
+            recording,
+            recorded,
+            ref path,
+            reference,
+            screenshots,
+            ref screenshots_path,
+        } = *snapshot;
+        let (padding, language) = (settings.padding, settings.language);
+        let text = |key: &str| iced::Text::new(language.text(key));
+        let mut save_button = iced::Button::new(&mut self.save, text("trace.save"));
+        let mut load_button = iced::Button::new(&mut self.load, text("trace.load"));
+
+        if !playing {
+            save_button = save_button.on_press(Message::SaveTrace);
+            load_button = load_button.on_press(Message::LoadTrace);
+        }
+
+        // The recorded run becomes the reference, which later runs start on the input of
+        let mut set_reference_button =
+            iced::Button::new(&mut self.set_reference, text("trace.set_reference"));
+        let mut clear_reference_button =
+            iced::Button::new(&mut self.clear_reference, text("trace.clear_reference"));
+        if !playing && recorded {
+            set_reference_button = set_reference_button.on_press(Message::SetReference);
+        }
+        if reference.is_some() {
+            clear_reference_button = clear_reference_button.on_press(Message::ClearReference);
+        }
+
+        iced::Column::new()
+            .spacing(padding)
+            .padding(padding)
+            .width(iced::Length::Units(300))
+            .push(
+                iced::Row::new()
+                    .spacing(padding)
+                    .push(text("trace.trace"))
+                    .push(iced::TextInput::new(
+                        &mut self.path,
+                        &language.text("trace.path_placeholder"),
+                        path,
+                        Message::TraceInput,
+                    )),
+            )
+            .push(
+                iced::Row::new()
+                    .spacing(padding)
+                    .align_items(iced::Alignment::Center)
+                    .push(
+                        iced::Toggler::new(
+                            recording,
+                            language.text("trace.record"),
+                            Message::Record,
+                        )
+                        .width(iced::Length::Shrink),
+                    )
+                    .push(save_button)
+                    .push(load_button),
+            )
+            .push(
+                iced::Row::new()
+                    .spacing(padding)
+                    .align_items(iced::Alignment::Center)
+                    .push(set_reference_button)
+                    .push(iced::Text::new(match reference {
+                        Some(sort) => language.format("trace.reference", &[&sort]),
+                        None => language.text("trace.no_reference"),
+                    }))
+                    .push(clear_reference_button),
+            )
+            .push(
+                iced::Row::new()
+                    .spacing(padding)
+                    .align_items(iced::Alignment::Center)
+                    .push(text("trace.screenshots"))
+                    .push(iced::TextInput::new(
+                        &mut self.screenshots_path,
+                        &language.text("trace.screenshots_placeholder"),
+                        screenshots_path,
+                        Message::ScreenshotsInput,
+                    ))
+                    .push(
+                        iced::Toggler::new(
+                            screenshots,
+                            language.text("trace.milestones"),
+                            Message::Screenshots,
+                        )
+                        .width(iced::Length::Shrink),
+                    ),
+            )
+            .into()
+    }
+}
+
+/// Loading an image to sort a column of its pixels, and picking the column.
+#[derive(Default)]
+pub struct ImageControls {
+    path: text_input::State,
+    load: button::State,
+    close: button::State,
+    column: slider::State,
+}
+
+impl ImageControls {
+    pub fn view(
+        &mut self,
+        playing: bool,
+        path: &str,
+        pixels: Option<&Pixels>,
+        settings: &Settings,
+    ) -> iced::Element<'_, Message> {
+        let (padding, language) = (settings.padding, settings.language);
+        let mut load_button =
+            iced::Button::new(&mut self.load, iced::Text::new(language.text("image.load")));
+        let mut close_button = iced::Button::new(
+            &mut self.close,
+            iced::Text::new(language.text("image.close")),
+        );
+
+        if !playing {
+            load_button = load_button.on_press(Message::LoadImage);
+            close_button = close_button.on_press(Message::CloseImage);
+        }
+
+        let mut buttons = iced::Row::new()
+            .spacing(padding)
+            .align_items(iced::Alignment::Center)
+            .push(load_button);
+
+        if let Some(pixels) = pixels {
+            buttons = buttons
+                .push(close_button)
+                .push(iced::Text::new(
+                    language.format("image.column", &[&pixels.column()]),
+                ))
+                .push(iced::Slider::new(
+                    &mut self.column,
+                    0..=pixels.width() as u32 - 1,
+                    pixels.column() as u32,
+                    Message::ColumnSelected,
+                ));
+        }
+
+        iced::Column::new()
+            .spacing(padding)
+            .padding(padding)
+            .width(iced::Length::Units(300))
+            .push(
+                iced::Row::new()
+                    .spacing(padding)
+                    .push(iced::Text::new(language.text("image.image")))
+                    .push(iced::TextInput::new(
+                        &mut self.path,
+                        &language.text("image.path_placeholder"),
+                        path,
+                        Message::ImageInput,
+                    )),
+            )
+            .push(buttons)
+            .into()
+    }
+}
+
+/// The scrub bar over the operations of a replayed or recorded run.
+#[derive(Default)]
+pub struct Timeline {
+    position: slider::State,
+}
+
+impl Timeline {
+    pub fn view(
+        &mut self,
+        position: u32,
+        len: u32,
+        settings: &Settings,
+    ) -> iced::Element<'_, Message> {
+        let (padding, language) = (settings.padding, settings.language);
+        iced::Row::new()
+            .spacing(padding)
+            .padding([0, padding])
+            .align_items(iced::Alignment::Center)
+            .push(iced::Slider::new(
+                &mut self.position,
+                0..=len,
+                position,
+                Message::Scrub,
+            ))
+            .push(iced::Text::new(
+                language.format("timeline.operation", &[&position, &len]),
+            ))
+            .into()
+    }
+}
@@ -0,0 +1,477 @@
+//! The widgets and views of the window, composed by the application's `view`.
+//!
+//! The views draw the array and the panels around it, each in its own module, and the widgets are
+//! the smaller parts shared between them, like the stats of the run and the banners.
+
+use crate::Message;
+use iced::time;
+use sorting_animations::i18n::Language;
+use sorting_animations::settings::{Indicators, Theme, Waveform};
+use sorting_animations::{array, sorting};
+
+mod controls;
+mod views;
+mod widgets;
+
+pub use controls::{
+    focusable, Controls, ControlsSnapshot, Focus, ImageControls, Navigation, Timeline,
+    TraceControls, TraceSnapshot,
+};
+pub use sorting_animations::view::View;
+pub use views::{
+    array_view, budget_overlay, screenshot, CompletedRun, EventLog, EventsSnapshot, History,
+    InfoPanel, Measured, Pixels, SettingsPanel,
+};
+pub use widgets::{
+    error_banner, progress, worker_legend, NumberInput, StatsPanel, StatsSnapshot, Toggles,
+    WedgedBanner,
+};
+// Only named by the tests, the app gets them through the widgets built on them
+#[cfg(test)]
+pub use {views::ResultsChart, widgets::InputError};
+
+/// The segments of the reads in the results chart, between the comparisons and the writes.
+const BLUE: iced::Color = iced::Color {
+    r: 0.3,
+    g: 0.5,
+    b: 1.0,
+    a: 1.0,
+};
+/// How long an action cutting the playing run short waits to be confirmed.
+const CONFIRM_TIME: time::Duration = time::Duration::from_secs(2);
+/// The height of a line of the budget overlay.
+const OVERLAY_LINE_HEIGHT: f32 = 18.0;
+/// The background and text colors of the dark theme.
+const DARK_BACKGROUND: iced::Color = iced::Color {
+    r: 0.12,
+    g: 0.12,
+    b: 0.14,
+    a: 1.0,
+};
+const DARK_TEXT: iced::Color = iced::Color {
+    r: 0.9,
+    g: 0.9,
+    b: 0.9,
+    a: 1.0,
+};
+
+/// A value shown in the selected language.
+pub trait Localize {
+    fn localize(&self, language: Language) -> String;
+}
+
+/// A value of a pick list, shown in the language it was listed in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Localized<T>(pub T, pub Language);
+
+impl<T: Localize> std::fmt::Display for Localized<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0.localize(self.1))
+    }
+}
+
+/// The values of a pick list in the language.
+pub fn localized<T: Clone>(values: &[T], language: Language) -> Vec<Localized<T>> {
+    values
+        .iter()
+        .map(|value| Localized(value.clone(), language))
+        .collect()
+}
+
+/// The scale factor of the display in percent, drawing a number in every column of its pixels.
+///
+/// iced doesn't tell the application the scale factor of its window, so it has to be selected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Scale(u16);
+
+impl Scale {
+    pub const VALUES: [Scale; 4] = [Scale(100), Scale(125), Scale(150), Scale(200)];
+
+    pub fn factor(&self) -> f32 {
+        self.0 as f32 / 100.0
+    }
+}
+
+impl Default for Scale {
+    fn default() -> Scale {
+        Scale(100)
+    }
+}
+
+impl std::fmt::Display for Scale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}%", self.0)
+    }
+}
+
+/// How long the speed of a run eases in, so fast runs don't blow through their first phase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ramp(Option<u64>);
+
+impl Ramp {
+    pub const VALUES: [Ramp; 5] = [
+        Ramp(None),
+        Ramp(Some(1)),
+        Ramp(Some(3)),
+        Ramp(Some(5)),
+        Ramp(Some(10)),
+    ];
+
+    pub fn duration(&self) -> Option<time::Duration> {
+        self.0.map(time::Duration::from_secs)
+    }
+}
+
+impl From<Option<time::Duration>> for Ramp {
+    fn from(duration: Option<time::Duration>) -> Ramp {
+        Ramp(duration.map(|duration| duration.as_secs()))
+    }
+}
+
+impl Localize for Ramp {
+    fn localize(&self, language: Language) -> String {
+        match self.0 {
+            Some(seconds) => language.format("ramp.up", &[&seconds]),
+            None => language.text("ramp.none"),
+        }
+    }
+}
+
+/// The fraction of the speed left in slow motion, after an operation on a watched element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SlowMotion(u32);
+
+impl SlowMotion {
+    pub const VALUES: [SlowMotion; 4] =
+        [SlowMotion(1), SlowMotion(5), SlowMotion(10), SlowMotion(25)];
+
+    pub fn factor(&self) -> f32 {
+        self.0 as f32 / 100.0
+    }
+}
+
+impl From<f32> for SlowMotion {
+    fn from(factor: f32) -> SlowMotion {
+        SlowMotion((factor * 100.0).round() as u32)
+    }
+}
+
+impl Localize for SlowMotion {
+    fn localize(&self, language: Language) -> String {
+        language.format("slow_motion", &[&self.0])
+    }
+}
+
+/// What happens to an action that would cut the playing run short.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Interruption {
+    /// The action has to be done again within `CONFIRM_TIME`.
+    #[default]
+    Confirm,
+    /// The action waits for the run to stop.
+    Queue,
+}
+
+impl Interruption {
+    pub const VALUES: [Interruption; 2] = [Interruption::Confirm, Interruption::Queue];
+}
+
+impl Localize for Interruption {
+    fn localize(&self, language: Language) -> String {
+        language.text(match self {
+            Interruption::Confirm => "interruption.confirm",
+            Interruption::Queue => "interruption.queue",
+        })
+    }
+}
+
+/// An action that would cut the playing run short.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Interrupting {
+    Shuffle,
+    Reverse,
+    Sort(sorting::Algorithm),
+    Numbers,
+    Range,
+}
+
+impl Localize for Interrupting {
+    fn localize(&self, language: Language) -> String {
+        match self {
+            Interrupting::Shuffle => language.text("interrupting.shuffle"),
+            Interrupting::Reverse => language.text("interrupting.reverse"),
+            Interrupting::Sort(sort) => language.format("interrupting.sort", &[sort]),
+            Interrupting::Numbers => language.text("interrupting.numbers"),
+            Interrupting::Range => language.text("interrupting.range"),
+        }
+    }
+}
+
+impl Interrupting {
+    /// The question confirming the action, once it was asked for.
+    fn confirmation(&self, language: Language) -> String {
+        match self {
+            Interrupting::Shuffle => language.text("confirm.shuffle"),
+            Interrupting::Reverse => language.text("confirm.reverse"),
+            Interrupting::Sort(sort) => language.format("confirm.sort", &[sort]),
+            Interrupting::Numbers => language.text("confirm.numbers"),
+            Interrupting::Range => language.text("confirm.range"),
+        }
+    }
+}
+
+impl Localize for sorting::Distribution {
+    fn localize(&self, language: Language) -> String {
+        language.text(&format!("distribution.{self:?}"))
+    }
+}
+
+impl Localize for sorting::Granularity {
+    fn localize(&self, language: Language) -> String {
+        language.text(&format!("granularity.{self:?}"))
+    }
+}
+
+impl Localize for array::Arrangement {
+    fn localize(&self, language: Language) -> String {
+        language.text(&format!("arrangement.{self:?}"))
+    }
+}
+
+impl Localize for View {
+    fn localize(&self, language: Language) -> String {
+        language.text(&format!("view.{self:?}"))
+    }
+}
+
+impl Localize for Waveform {
+    fn localize(&self, language: Language) -> String {
+        language.text(&format!("waveform.{self:?}"))
+    }
+}
+
+impl Localize for Theme {
+    fn localize(&self, language: Language) -> String {
+        language.text(&format!("theme.{self:?}"))
+    }
+}
+
+impl Localize for Indicators {
+    fn localize(&self, language: Language) -> String {
+        language.text(&format!("indicators.{self:?}"))
+    }
+}
+
+impl Localize for array::Step {
+    fn localize(&self, language: Language) -> String {
+        match *self {
+            array::Step::ComparisonTwo(x, y) => language.format("step.compared", &[&x, &y]),
+            array::Step::Comparison(x, value) => language.format("step.compared", &[&x, &value]),
+            array::Step::AccessTwo(x, y) => language.format("step.swapped", &[&x, &y]),
+            array::Step::Access(x) => language.format("step.read", &[&x]),
+            array::Step::Write(x) => language.format("step.wrote", &[&x]),
+            array::Step::None => String::new(),
+        }
+    }
+}
+
+impl Localize for array::Phase {
+    /// The labels of scripts which no catalog knows are shown as they are.
+    fn localize(&self, language: Language) -> String {
+        let label = language.lookup(&format!("phase.{}", self.label));
+        format!("{}: {}", label.unwrap_or(self.label), self.value)
+    }
+}
+
+/// A run estimated to take too long at the current speed, which Play warns about first.
+#[derive(Clone, Copy, Debug)]
+pub struct Infeasible {
+    pub duration: time::Duration,
+    /// Whether Play already warned, so pressing it again plays the run anyway.
+    pub warned: bool,
+}
+
+/// The duration in the largest unit it takes at least one of, rounded down.
+pub fn approximate(duration: time::Duration, language: Language) -> String {
+    const UNITS: [(&str, u64); 5] = [
+        ("time.year", 365 * 24 * 60 * 60),
+        ("time.day", 24 * 60 * 60),
+        ("time.hour", 60 * 60),
+        ("time.minute", 60),
+        ("time.second", 1),
+    ];
+
+    let seconds = duration.as_secs();
+    let (unit, length) = UNITS
+        .into_iter()
+        .find(|&(_, length)| seconds >= length)
+        .unwrap_or(UNITS[UNITS.len() - 1]);
+    let count = seconds / length;
+
+    let plural = if count == 1 { "" } else { "s" };
+    language.format(&format!("{unit}{plural}"), &[&count])
+}
+
+/// The count with commas between every three digits, the same in every locale.
+pub fn thousands(count: u64) -> String {
+    let digits = count.to_string();
+    let mut grouped = String::with_capacity(digits.len() * 4 / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+
+    grouped
+}
+
+/// The count to three significant digits with an SI prefix, like "1.27 G", exact below a thousand.
+pub fn abbreviated(count: u64) -> String {
+    const PREFIXES: [&str; 6] = ["k", "M", "G", "T", "P", "E"];
+
+    if count < 1000 {
+        return count.to_string();
+    }
+
+    // Rounding may carry into the next prefix, like 999,999 into "1.00 M"
+    let mut value = count as f64;
+    let mut prefix = 0;
+    loop {
+        value /= 1000.0;
+        let decimals = match value {
+            value if value < 9.995 => 2,
+            value if value < 99.95 => 1,
+            _ => 0,
+        };
+        if value < 999.5 || prefix == PREFIXES.len() - 1 {
+            return format!("{value:.decimals$} {}", PREFIXES[prefix]);
+        }
+        prefix += 1;
+    }
+}
+
+/// Bytes abbreviated like counts, like "1.27 kB".
+pub fn bytes(bytes: usize) -> String {
+    match abbreviated(bytes as u64) {
+        exact if bytes < 1000 => format!("{exact} B"),
+        rounded => rounded + "B",
+    }
+}
+
+/// An action waiting to be confirmed, or queued until the playing run stops.
+#[derive(Clone, Debug)]
+pub struct Pending {
+    pub action: Interrupting,
+    pub since: time::Instant,
+    pub queued: bool,
+}
+
+impl Pending {
+    /// Whether the action was not confirmed in time.
+    pub fn expired(&self) -> bool {
+        !self.queued && self.since.elapsed() > CONFIRM_TIME
+    }
+
+    /// Whether the action waits for the confirmation of doing it again.
+    fn confirming(&self, action: &Interrupting) -> bool {
+        !self.queued && self.action == *action
+    }
+}
+
+/// The background and text colors of the theme, for the container around everything.
+pub struct Themed(pub Theme);
+
+impl iced::container::StyleSheet for Themed {
+    fn style(&self) -> iced::container::Style {
+        match self.0 {
+            Theme::Light => iced::container::Style::default(),
+            Theme::Dark => iced::container::Style {
+                text_color: Some(DARK_TEXT),
+                background: Some(iced::Background::Color(DARK_BACKGROUND)),
+                ..iced::container::Style::default()
+            },
+            Theme::HighContrast => iced::container::Style {
+                text_color: Some(iced::Color::WHITE),
+                background: Some(iced::Background::Color(iced::Color::BLACK)),
+                ..iced::container::Style::default()
+            },
+        }
+    }
+}
+
+/// The parts of the window, composed from the top down: the banners and the stats, the array
+/// beside the panels, the timeline and the logs, and the controls.
+///
+/// A small window collapses to the array and the essential controls, which leaves out the panels
+/// and the logs, but the settings stay reachable while they are open.
+pub struct Layout<'a> {
+    pub banners: Vec<iced::Element<'a, Message>>,
+    pub stats: iced::Element<'a, Message>,
+    /// The rows below the stats, like the progress of the run.
+    pub rows: Vec<iced::Element<'a, Message>>,
+    pub array: iced::Element<'a, Message>,
+    pub settings_panel: iced::Element<'a, Message>,
+    pub settings_open: bool,
+    pub info_panel: iced::Element<'a, Message>,
+    pub timeline: Option<iced::Element<'a, Message>>,
+    pub history: iced::Element<'a, Message>,
+    pub event_log: iced::Element<'a, Message>,
+    pub controls: iced::Element<'a, Message>,
+    pub trace_controls: iced::Element<'a, Message>,
+    pub image_controls: iced::Element<'a, Message>,
+    pub compact: bool,
+    pub collapsed: bool,
+}
+
+impl<'a> Layout<'a> {
+    pub fn view(self, theme: Theme) -> iced::Element<'a, Message> {
+        let mut content = self
+            .banners
+            .into_iter()
+            .chain([self.stats])
+            .chain(self.rows)
+            .fold(iced::Column::new(), |content, row| content.push(row));
+
+        // The panels sit beside the numbers, taking as little of their width as they can
+        let mut panels = iced::Row::new().push(self.array);
+        if !self.collapsed || self.settings_open {
+            panels = panels.push(self.settings_panel);
+        }
+        if !self.collapsed {
+            panels = panels.push(self.info_panel);
+        }
+        content = content.push(panels);
+
+        if let Some(timeline) = self.timeline {
+            content = content.push(timeline);
+        }
+        if !self.collapsed {
+            content = content.push(self.history).push(self.event_log);
+        }
+
+        let trace_controls = iced::Column::new()
+            .push(self.trace_controls)
+            .push(self.image_controls);
+        content = if !self.compact {
+            content.push(
+                iced::Row::new()
+                    .spacing(5)
+                    .align_items(iced::Alignment::Center)
+                    .push(self.controls)
+                    .push(iced::Rule::vertical(5))
+                    .push(trace_controls),
+            )
+        } else if self.collapsed {
+            content.push(self.controls)
+        } else {
+            content.push(self.controls).push(trace_controls)
+        };
+
+        iced::Container::new(content)
+            .width(iced::Length::Fill)
+            .height(iced::Length::Fill)
+            .style(Themed(theme))
+            .into()
+    }
+}
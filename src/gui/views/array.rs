@@ -0,0 +1,223 @@
+use crate::gui::{Scale, OVERLAY_LINE_HEIGHT};
+use crate::Message;
+use iced::canvas;
+use sorting_animations::i18n::Language;
+use sorting_animations::settings::Settings;
+use sorting_animations::view::{raster, Style, View};
+use sorting_animations::{array, sorting};
+use std::sync;
+
+/// The size of the screenshots, the same whatever the window's size so they fit together.
+const SCREENSHOT_SIZE: iced::Size = iced::Size::new(1280.0, 720.0);
+
+/// The canvas drawing the array in the selected view, outlining the watched and pinned indices.
+///
+/// Hovering an element reports its index, and clicking it pins it or, with shift held, watches
+/// it. The bars are tinted where they differ from the reference numbers, and with the structure
+/// shown, the tree of the heap or the markers and marks the sort reports are drawn over them.
+#[allow(clippy::too_many_arguments)]
+pub fn array_view(
+    array: &array::ArrayState,
+    reference: Option<sync::Arc<Vec<usize>>>,
+    view: View,
+    scale: Scale,
+    hovered: Option<usize>,
+    pinned: Option<usize>,
+    overlay: Vec<String>,
+    structure: bool,
+    settings: &Settings,
+) -> iced::Element<'static, Message> {
+    // The heap is sorted like any range, its indices counting from the range's start
+    let range = array.range();
+    let heap = array
+        .phase()
+        .filter(|phase| structure && phase.label == sorting::Sort::HEAP_PHASE)
+        .map(|phase| range.start..range.start + phase.value);
+    let (markers, marks) = if structure {
+        (array.markers().collect(), array.marks().to_vec())
+    } else {
+        (Vec::new(), Vec::new())
+    };
+
+    iced::Canvas::new(ArrayCanvas {
+        numbers: array.shared_numbers(),
+        view,
+        scale,
+        steps: array.steps().to_vec(),
+        highlights: array.highlights().to_vec(),
+        reference,
+        range: array.range(),
+        watched: array.watched().to_vec(),
+        offsets: array.offsets(),
+        hovered,
+        pinned,
+        overlay,
+        padding: settings.padding,
+        style: Style::of(settings),
+        heap,
+        markers,
+        marks,
+    })
+    .width(iced::Length::Fill)
+    .height(iced::Length::Fill)
+    .into()
+}
+
+/// The lines of the overlay showing how the last tick's budget turned into operations.
+pub fn budget_overlay(
+    stats: Option<sorting::TickStats>,
+    max_ticks: u64,
+    language: Language,
+) -> Vec<String> {
+    let mut lines = vec![language.format("budget.full_speed", &[&max_ticks])];
+    match stats {
+        Some(stats) => lines.extend([
+            language.format("budget.granted", &[&stats.granted]),
+            language.format("budget.performed", &[&stats.performed]),
+            language.format("budget.remaining", &[&stats.remaining]),
+            language.format("budget.waited", &[&format!("{:.2?}", stats.waited)]),
+        ]),
+        None => lines.push(language.text("budget.waiting")),
+    }
+
+    lines
+}
+
+/// The array drawn in the view as a PNG, with the highlights but without the outlines meant for
+/// the user.
+pub fn screenshot(array: &array::ArrayState, view: View, style: Style) -> Vec<u8> {
+    let bounds = iced::Rectangle::new(iced::Point::ORIGIN, SCREENSHOT_SIZE);
+    let geometry = view.draw(
+        bounds,
+        1.0,
+        array.numbers(),
+        array.steps(),
+        array.highlights(),
+        None,
+        array.range(),
+        &[],
+        None,
+        &[],
+        style,
+    );
+
+    raster::Image::rasterize(geometry, SCREENSHOT_SIZE).to_png()
+}
+
+struct ArrayCanvas {
+    numbers: sync::Arc<Vec<usize>>,
+    view: View,
+    scale: Scale,
+    steps: Vec<array::Step>,
+    highlights: Vec<(usize, array::Step)>,
+    reference: Option<sync::Arc<Vec<usize>>>,
+    /// The range the sort sorts, the rest is dimmed.
+    range: std::ops::Range<usize>,
+    watched: Vec<usize>,
+    /// The places away from their indices the sliding numbers are drawn.
+    offsets: Vec<(usize, f32)>,
+    hovered: Option<usize>,
+    pinned: Option<usize>,
+    /// Lines of text drawn over the array.
+    overlay: Vec<String>,
+    /// The space around the lines of the overlay.
+    padding: u16,
+    style: Style,
+    /// The numbers in the heap of a sort keeping one, whose tree is drawn over the bars.
+    heap: Option<std::ops::Range<usize>>,
+    /// The markers the workers of the sort reported, drawn over the bars.
+    markers: Vec<array::Marker>,
+    /// The indices the sort marked, drawn over the bars along with the markers.
+    marks: Vec<usize>,
+}
+
+impl canvas::Program<Message> for ArrayCanvas {
+    fn update(
+        &mut self,
+        event: canvas::Event,
+        bounds: iced::Rectangle,
+        cursor: canvas::Cursor,
+    ) -> (iced::canvas::event::Status, Option<Message>) {
+        use iced::{canvas::event::Status, mouse};
+
+        let index = cursor.position().and_then(|position| {
+            self.view
+                .index_at(bounds, self.scale.factor(), self.numbers.len(), position)
+        });
+
+        match event {
+            canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => match index {
+                Some(index) => (Status::Captured, Some(Message::Pin(index))),
+                None => (Status::Ignored, None),
+            },
+            // Only changes are reported, the cursor moves a lot
+            canvas::Event::Mouse(_) if index != self.hovered => {
+                (Status::Ignored, Some(Message::Hover(index)))
+            }
+            _ => (Status::Ignored, None),
+        }
+    }
+
+    fn draw(&self, bounds: iced::Rectangle, _: canvas::Cursor) -> Vec<canvas::Geometry> {
+        let mut geometry = self.view.draw(
+            bounds,
+            self.scale.factor(),
+            &self.numbers,
+            &self.steps,
+            &self.highlights,
+            self.reference.as_deref().map(Vec::as_slice),
+            self.range.clone(),
+            &self.watched,
+            self.pinned,
+            &self.offsets,
+            self.style,
+        );
+
+        if let Some(ref heap) = self.heap {
+            geometry.extend(self.view.draw_heap(
+                bounds,
+                self.scale.factor(),
+                &self.numbers,
+                heap.clone(),
+            ));
+        }
+        geometry.extend(self.view.draw_markers(
+            bounds,
+            self.scale.factor(),
+            &self.numbers,
+            &self.markers,
+            &self.marks,
+        ));
+
+        if !self.overlay.is_empty() {
+            // The text stays readable on top of the bars
+            let mut frame = canvas::Frame::new(bounds.size());
+            let width = self.overlay.iter().map(String::len).max().unwrap_or(0) as f32 * 8.0;
+            frame.fill_rectangle(
+                iced::Point::ORIGIN,
+                iced::Size::new(
+                    width + self.padding as f32,
+                    self.overlay.len() as f32 * OVERLAY_LINE_HEIGHT + self.padding as f32,
+                ),
+                iced::Color {
+                    a: 0.8,
+                    ..iced::Color::WHITE
+                },
+            );
+            for (line, content) in self.overlay.iter().enumerate() {
+                frame.fill_text(canvas::Text {
+                    content: content.clone(),
+                    position: iced::Point::new(
+                        self.padding as f32 / 2.0,
+                        self.padding as f32 / 2.0 + line as f32 * OVERLAY_LINE_HEIGHT,
+                    ),
+                    size: 16.0,
+                    ..canvas::Text::default()
+                });
+            }
+            geometry.push(frame.into_geometry());
+        }
+
+        geometry
+    }
+}
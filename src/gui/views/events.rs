@@ -0,0 +1,130 @@
+use crate::gui::{thousands, BLUE};
+use crate::Message;
+use iced::{button, scrollable, text_input};
+use sorting_animations::array;
+use sorting_animations::settings::Settings;
+use std::sync;
+
+/// What the event log shows, taken before the panel borrows its state.
+#[derive(Clone, Debug)]
+pub struct EventsSnapshot {
+    pub annotations: sync::Arc<array::Annotations>,
+    /// Whether playing pauses on the annotations containing the filter.
+    pub pause_on: bool,
+    pub filter: String,
+}
+
+/// The annotations of the running sort in a panel which can be collapsed, newest first, with
+/// those paused on highlighted.
+#[derive(Default)]
+pub struct EventLog {
+    scroll: scrollable::State,
+    toggle: button::State,
+    export: button::State,
+    filter: text_input::State,
+    expanded: bool,
+}
+
+impl EventLog {
+    pub fn toggle_expanded(&mut self) {
+        self.expanded = !self.expanded;
+    }
+
+    pub fn view(
+        &mut self,
+        snapshot: &EventsSnapshot,
+        settings: &Settings,
+    ) -> iced::Element<'_, Message> {
+        let EventsSnapshot {
+            ref annotations,
+            pause_on,
+            ref filter,
+        } = *snapshot;
+        let (padding, language) = (settings.padding, settings.language);
+        let toggle_button = iced::Button::new(
+            &mut self.toggle,
+            iced::Text::new(language.format(
+                if self.expanded {
+                    "events.hide"
+                } else {
+                    "events.show"
+                },
+                &[&annotations.entries().len()],
+            )),
+        )
+        .on_press(Message::ToggleEventLog);
+        let mut export_button = iced::Button::new(
+            &mut self.export,
+            iced::Text::new(language.text("events.export")),
+        );
+        if !annotations.is_empty() {
+            export_button = export_button.on_press(Message::ExportEventLog);
+        }
+
+        let header = iced::Row::new()
+            .spacing(padding)
+            .align_items(iced::Alignment::Center)
+            .push(toggle_button)
+            .push(export_button)
+            .push(
+                iced::Toggler::new(
+                    pause_on,
+                    language.text("events.pause_on") + "  ",
+                    Message::PauseOnAnnotation,
+                )
+                .width(iced::Length::Shrink),
+            )
+            .push(
+                iced::TextInput::new(
+                    &mut self.filter,
+                    &language.text("events.filter_placeholder"),
+                    filter,
+                    Message::AnnotationFilterInput,
+                )
+                .width(iced::Length::Units(200)),
+            );
+        if !self.expanded {
+            return iced::Container::new(header).padding([0, padding]).into();
+        }
+
+        let cell =
+            |text: String, width: u16| iced::Text::new(text).width(iced::Length::Units(width));
+        let mut entries = annotations.entries().rev().fold(
+            iced::Column::new().spacing(5),
+            |entries, annotation| {
+                entries.push(
+                    iced::Row::new()
+                        .spacing(padding)
+                        .push(cell(
+                            language.format(
+                                "history.seconds",
+                                &[&format!("{:.1}", annotation.at.as_secs_f32())],
+                            ),
+                            70,
+                        ))
+                        .push(cell(format!("#{}", thousands(annotation.operation)), 110))
+                        .push(match annotation.matched {
+                            true => iced::Text::new(annotation.text.as_str()).color(BLUE),
+                            false => iced::Text::new(annotation.text.as_str()),
+                        }),
+                )
+            },
+        );
+        if annotations.dropped() > 0 {
+            entries = entries.push(iced::Text::new(
+                language.format("events.dropped", &[&annotations.dropped()]),
+            ));
+        }
+
+        iced::Column::new()
+            .spacing(padding)
+            .padding([0, padding])
+            .push(header)
+            .push(
+                iced::Scrollable::new(&mut self.scroll)
+                    .max_height(150)
+                    .push(entries),
+            )
+            .into()
+    }
+}
@@ -0,0 +1,366 @@
+use crate::gui::{abbreviated, bytes, thousands, Localize, BLUE};
+use crate::Message;
+use iced::{button, canvas, scrollable};
+use sorting_animations::i18n::Language;
+use sorting_animations::settings::Settings;
+use sorting_animations::view::{GREEN, RED};
+use sorting_animations::{array, sorting};
+use std::{io, sync, time};
+
+/// The height of an algorithm's bar in the results chart, along with the space to the next.
+const RESULT_HEIGHT: f32 = 24.0;
+/// The width of the algorithms' names left of their bars in the results chart.
+const RESULT_LABEL_WIDTH: f32 = 160.0;
+/// The width of the annotated stats right of the bars in the results chart.
+const RESULT_STATS_WIDTH: f32 = 260.0;
+
+/// A run completed this session, with the numbers it started on to run it again.
+#[derive(Clone, Debug)]
+pub struct CompletedRun {
+    pub sort: sorting::Algorithm,
+    pub numbers: sync::Arc<Vec<usize>>,
+    pub seed: u64,
+    pub comparisons: u64,
+    pub reads: u64,
+    pub writes: u64,
+    /// How long the run took from its first operation on, including pauses.
+    pub duration: time::Duration,
+    /// The distances of its comparisons and swaps, if they were counted.
+    pub distances: Option<array::Distances>,
+    /// The most auxiliary memory and recursion depth it reported needing at once.
+    pub peak_aux_bytes: usize,
+    pub peak_depth: usize,
+}
+
+impl CompletedRun {
+    pub fn distribution(&self) -> sorting::Distribution {
+        sorting::Distribution::of(&self.numbers)
+    }
+}
+
+/// The runs completed this session, in a table which can be collapsed.
+#[derive(Default)]
+pub struct History {
+    runs: Vec<CompletedRun>,
+    /// The re-run buttons of the runs.
+    rerun: Vec<button::State>,
+    scroll: scrollable::State,
+    toggle: button::State,
+    export: button::State,
+    expanded: bool,
+}
+
+impl History {
+    pub fn push(&mut self, run: CompletedRun) {
+        self.runs.push(run);
+        self.rerun.push(button::State::default());
+    }
+
+    #[cfg(test)]
+    pub fn runs(&self) -> &[CompletedRun] {
+        &self.runs
+    }
+
+    /// The last run of every sort on the input of the last run, fewest comparisons first.
+    pub fn results(&self) -> Vec<&CompletedRun> {
+        let Some(last) = self.runs.last() else {
+            return Vec::new();
+        };
+
+        let mut results: Vec<&CompletedRun> = Vec::new();
+        for run in self.runs.iter().rev() {
+            if run.numbers == last.numbers && results.iter().all(|result| result.sort != run.sort) {
+                results.push(run);
+            }
+        }
+        results.sort_by_key(|run| run.comparisons);
+
+        results
+    }
+
+    pub fn toggle_expanded(&mut self) {
+        self.expanded = !self.expanded;
+    }
+
+    /// Writes the runs as comma separated values, a header naming the columns.
+    pub fn write_csv(&self, mut writer: impl io::Write) -> io::Result<()> {
+        // A column for every bucket of the distances, named after the shortest one it counts
+        let distances: String = (0..array::DISTANCE_BUCKETS)
+            .map(|bucket| format!(",distance_{}", array::Distances::shortest(bucket)))
+            .collect();
+        writeln!(
+            writer,
+            "sort,size,distribution,seed,comparisons,reads,writes,peak_aux_bytes,peak_depth,\
+             duration_ms{distances}"
+        )?;
+
+        for run in &self.runs {
+            // Scripts name their sorts freely, quotes are doubled within quotes
+            let sort = match run.sort.name() {
+                name if name.contains([',', '"', '\n']) => {
+                    format!("\"{}\"", name.replace('"', "\"\""))
+                }
+                name => name.to_string(),
+            };
+
+            // Left empty if they weren't counted
+            let distances: String = match run.distances {
+                Some(distances) => distances
+                    .buckets()
+                    .iter()
+                    .map(|count| format!(",{count}"))
+                    .collect(),
+                None => ",".repeat(array::DISTANCE_BUCKETS),
+            };
+            writeln!(
+                writer,
+                "{sort},{},{:?},{},{},{},{},{},{},{}{distances}",
+                run.numbers.len(),
+                run.distribution(),
+                run.seed,
+                run.comparisons,
+                run.reads,
+                run.writes,
+                run.peak_aux_bytes,
+                run.peak_depth,
+                run.duration.as_millis(),
+            )?;
+        }
+
+        writer.flush()
+    }
+
+    pub fn view(&mut self, playing: bool, settings: &Settings) -> iced::Element<'_, Message> {
+        let (padding, language) = (settings.padding, settings.language);
+        let chart = ResultsChart::new(&self.results(), settings);
+        let toggle_button = iced::Button::new(
+            &mut self.toggle,
+            iced::Text::new(language.format(
+                if self.expanded {
+                    "history.hide"
+                } else {
+                    "history.show"
+                },
+                &[&self.runs.len()],
+            )),
+        )
+        .on_press(Message::ToggleHistory);
+        let mut export_button = iced::Button::new(
+            &mut self.export,
+            iced::Text::new(language.text("history.export")),
+        );
+        if !self.runs.is_empty() {
+            export_button = export_button.on_press(Message::ExportHistory);
+        }
+
+        let header = iced::Row::new()
+            .spacing(padding)
+            .align_items(iced::Alignment::Center)
+            .push(toggle_button)
+            .push(export_button);
+        if !self.expanded {
+            return iced::Container::new(header).padding([0, padding]).into();
+        }
+
+        let cell =
+            |text: String, width: u16| iced::Text::new(text).width(iced::Length::Units(width));
+        let heading = |key: &str, width: u16| cell(language.text(key), width);
+        let table = self.runs.iter().zip(&mut self.rerun).rev().fold(
+            iced::Column::new().spacing(5).push(
+                iced::Row::new()
+                    .spacing(padding)
+                    .push(heading("history.sort", 150))
+                    .push(heading("history.size", 70))
+                    .push(heading("history.distribution", 90))
+                    .push(heading("history.seed", 170))
+                    .push(heading("history.comparisons", 100))
+                    .push(heading("history.reads", 100))
+                    .push(heading("history.writes", 100))
+                    .push(heading("history.aux_memory", 90))
+                    .push(heading("history.depth", 50))
+                    .push(heading("history.duration", 70)),
+            ),
+            |table, (run, rerun)| {
+                let mut rerun_button =
+                    iced::Button::new(rerun, iced::Text::new(language.text("history.rerun")));
+                if !playing {
+                    rerun_button = rerun_button.on_press(Message::Rerun(Box::new(run.clone())));
+                }
+
+                table.push(
+                    iced::Row::new()
+                        .spacing(padding)
+                        .align_items(iced::Alignment::Center)
+                        .push(cell(run.sort.name().to_string(), 150))
+                        .push(cell(run.numbers.len().to_string(), 70))
+                        .push(cell(run.distribution().localize(language), 90))
+                        .push(cell(run.seed.to_string(), 170))
+                        .push(cell(thousands(run.comparisons), 100))
+                        .push(cell(thousands(run.reads), 100))
+                        .push(cell(thousands(run.writes), 100))
+                        .push(cell(bytes(run.peak_aux_bytes), 90))
+                        .push(cell(run.peak_depth.to_string(), 50))
+                        .push(cell(
+                            language.format(
+                                "history.seconds",
+                                &[&format!("{:.1}", run.duration.as_secs_f32())],
+                            ),
+                            70,
+                        ))
+                        .push(rerun_button),
+                )
+            },
+        );
+
+        let mut column = iced::Column::new()
+            .spacing(padding)
+            .padding([0, padding])
+            .push(header)
+            .push(
+                iced::Scrollable::new(&mut self.scroll)
+                    .max_height(200)
+                    .push(table),
+            );
+
+        // Once several sorts ran on the same input, they are ranked against each other
+        if chart.results.len() > 1 {
+            column = column.push(chart.view());
+        }
+
+        column.into()
+    }
+}
+
+/// The sorts that ran on the same input ranked by their comparisons, with their reads and
+/// writes stacked onto them.
+///
+/// Clicking a sort's bar selects it.
+pub struct ResultsChart {
+    /// The sorts with their comparisons, reads and writes.
+    results: Vec<(sorting::Algorithm, [u64; 3])>,
+    /// The space between the bars and their stats.
+    padding: u16,
+    language: Language,
+}
+
+impl ResultsChart {
+    pub fn new(runs: &[&CompletedRun], settings: &Settings) -> ResultsChart {
+        ResultsChart {
+            results: runs
+                .iter()
+                .map(|run| (run.sort.clone(), [run.comparisons, run.reads, run.writes]))
+                .collect(),
+            padding: settings.padding,
+            language: settings.language,
+        }
+    }
+
+    pub fn view(self) -> iced::Element<'static, Message> {
+        // A row for every sort and one for the axis
+        let height = (self.results.len() + 1) as f32 * RESULT_HEIGHT;
+
+        iced::Canvas::new(self)
+            .width(iced::Length::Fill)
+            .height(iced::Length::Units(height as u16))
+            .into()
+    }
+
+    /// The sort whose row is at the point, relative to the chart's top left corner.
+    fn sort_at(&self, point: iced::Point) -> Option<&sorting::Algorithm> {
+        let row = (point.y / RESULT_HEIGHT) as usize;
+
+        self.results.get(row).map(|(sort, _)| sort)
+    }
+}
+
+impl canvas::Program<Message> for ResultsChart {
+    fn update(
+        &mut self,
+        event: canvas::Event,
+        bounds: iced::Rectangle,
+        cursor: canvas::Cursor,
+    ) -> (iced::canvas::event::Status, Option<Message>) {
+        use iced::{canvas::event::Status, mouse};
+
+        let sort = cursor
+            .position_in(&bounds)
+            .and_then(|position| self.sort_at(position));
+
+        match (event, sort) {
+            (
+                canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)),
+                Some(sort),
+            ) => (Status::Captured, Some(Message::SortSelected(sort.clone()))),
+            _ => (Status::Ignored, None),
+        }
+    }
+
+    fn draw(&self, bounds: iced::Rectangle, _: canvas::Cursor) -> Vec<canvas::Geometry> {
+        let mut frame = canvas::Frame::new(bounds.size());
+        let text = |content: String, position: iced::Point| canvas::Text {
+            content,
+            position,
+            color: iced::Color::BLACK,
+            size: 14.0,
+            vertical_alignment: iced::alignment::Vertical::Center,
+            ..canvas::Text::default()
+        };
+
+        let width = (bounds.width - RESULT_LABEL_WIDTH - RESULT_STATS_WIDTH).max(0.0);
+        let max = self
+            .results
+            .iter()
+            .map(|(_, stats)| stats.iter().sum::<u64>())
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let scale = width / max as f32;
+
+        for (row, (sort, stats)) in self.results.iter().enumerate() {
+            let top = row as f32 * RESULT_HEIGHT;
+            let center = top + RESULT_HEIGHT / 2.0;
+            frame.fill_text(text(sort.name().to_string(), iced::Point::new(0.0, center)));
+
+            let mut x = RESULT_LABEL_WIDTH;
+            for (&value, color) in stats.iter().zip([GREEN, BLUE, RED]) {
+                let length = value as f32 * scale;
+                frame.fill_rectangle(
+                    iced::Point::new(x, top + 2.0),
+                    iced::Size::new(length, RESULT_HEIGHT - 4.0),
+                    color,
+                );
+                x += length;
+            }
+
+            let [comparisons, reads, writes] = stats.map(abbreviated);
+            frame.fill_text(text(
+                self.language
+                    .format("chart.stats", &[&comparisons, &reads, &writes]),
+                iced::Point::new(x + self.padding as f32 / 2.0, center),
+            ));
+        }
+
+        // The axis along the bottom, from none to the most operations
+        let axis = self.results.len() as f32 * RESULT_HEIGHT;
+        frame.stroke(
+            &canvas::Path::line(
+                iced::Point::new(RESULT_LABEL_WIDTH, axis),
+                iced::Point::new(RESULT_LABEL_WIDTH + width, axis),
+            ),
+            canvas::Stroke::default(),
+        );
+        frame.fill_text(text(
+            String::from("0"),
+            iced::Point::new(RESULT_LABEL_WIDTH, axis + RESULT_HEIGHT / 2.0),
+        ));
+        frame.fill_text(canvas::Text {
+            horizontal_alignment: iced::alignment::Horizontal::Right,
+            ..text(
+                self.language.format("chart.operations", &[&max]),
+                iced::Point::new(RESULT_LABEL_WIDTH + width, axis + RESULT_HEIGHT / 2.0),
+            )
+        });
+
+        vec![frame.into_geometry()]
+    }
+}
@@ -0,0 +1,181 @@
+use crate::gui::{thousands, BLUE, OVERLAY_LINE_HEIGHT};
+use crate::Message;
+use iced::{button, canvas};
+use sorting_animations::settings::Settings;
+use sorting_animations::{array, sorting};
+
+/// The width of the expanded info panel.
+const INFO_WIDTH: u16 = 280;
+/// The width of the labels of the info panel's rows.
+const INFO_LABEL_WIDTH: u16 = 90;
+/// The height of the histogram of the distances in the info panel, with its labels.
+const DISTANCE_CHART_HEIGHT: u16 = 100;
+
+/// The operations of the live run against the estimate for its size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Measured {
+    pub operations: u64,
+    pub estimate: u64,
+    pub size: usize,
+}
+
+/// The selected sort's complexity and properties in a side panel which can be collapsed, along
+/// with how the live run measures up to its estimate.
+#[derive(Default)]
+pub struct InfoPanel {
+    toggle: button::State,
+    expanded: bool,
+}
+
+impl InfoPanel {
+    pub fn toggle_expanded(&mut self) {
+        self.expanded = !self.expanded;
+    }
+
+    pub fn view(
+        &mut self,
+        sort: &sorting::Algorithm,
+        measured: Option<Measured>,
+        distances: Option<array::Distances>,
+        settings: &Settings,
+    ) -> iced::Element<'_, Message> {
+        let (padding, language) = (settings.padding, settings.language);
+        let toggle_button = iced::Button::new(
+            &mut self.toggle,
+            iced::Text::new(language.text(if self.expanded {
+                "info.hide"
+            } else {
+                "info.show"
+            })),
+        )
+        .on_press(Message::ToggleInfo);
+        if !self.expanded {
+            return iced::Container::new(toggle_button).padding(padding).into();
+        }
+
+        let row = |key: &str, value: String| {
+            iced::Row::new()
+                .push(
+                    iced::Text::new(language.text(key))
+                        .width(iced::Length::Units(INFO_LABEL_WIDTH)),
+                )
+                .push(iced::Text::new(value))
+        };
+        let yes_no = |flag: bool| language.text(if flag { "info.yes" } else { "info.no" });
+
+        let mut column = iced::Column::new()
+            .spacing(padding)
+            .padding(padding)
+            .width(iced::Length::Units(INFO_WIDTH))
+            .push(toggle_button)
+            .push(iced::Text::new(sort.name()).size(24));
+        column = match sort.info() {
+            Some(info) => {
+                let [best, average, worst] = info.time;
+                column
+                    .push(row("info.best", format!("O({best})")))
+                    .push(row("info.average", format!("O({average})")))
+                    .push(row("info.worst", format!("O({worst})")))
+                    .push(row("info.memory", format!("O({})", info.space)))
+                    .push(row("info.stable", yes_no(info.stable)))
+                    .push(row("info.in_place", yes_no(info.in_place)))
+                    .push(iced::Text::new(
+                        language.text(&format!("sort.{}", sort.name())),
+                    ))
+            }
+            None => column.push(iced::Text::new(language.text("info.unknown"))),
+        };
+
+        // Both bars share a scale, so the longer one is full
+        if let Some(measured) = measured {
+            let max = measured.operations.max(measured.estimate).max(1) as f32;
+            let bar = |key: &str, operations: u64| {
+                iced::Row::new()
+                    .align_items(iced::Alignment::Center)
+                    .push(
+                        iced::Text::new(language.text(key))
+                            .width(iced::Length::Units(INFO_LABEL_WIDTH)),
+                    )
+                    .push(
+                        iced::ProgressBar::new(0.0..=max, operations as f32)
+                            .height(iced::Length::Units(10)),
+                    )
+            };
+
+            column = column
+                .push(iced::Text::new(language.format(
+                    "info.measured_vs_estimated",
+                    &[
+                        &thousands(measured.operations),
+                        &thousands(measured.estimate),
+                        &thousands(measured.size as u64),
+                    ],
+                )))
+                .push(bar("info.measured", measured.operations))
+                .push(bar("info.estimated", measured.estimate));
+        }
+        if let Some(distances) = distances {
+            column = column
+                .push(iced::Text::new(language.text("info.distances")))
+                .push(DistanceChart(distances).view());
+        }
+
+        column.into()
+    }
+}
+
+/// The histogram of the distances between the indices the live run compared and swapped, a bar
+/// for every bucket up to the longest distance counted.
+struct DistanceChart(array::Distances);
+
+impl DistanceChart {
+    fn view(self) -> iced::Element<'static, Message> {
+        iced::Canvas::new(self)
+            .width(iced::Length::Fill)
+            .height(iced::Length::Units(DISTANCE_CHART_HEIGHT))
+            .into()
+    }
+}
+
+impl canvas::Program<Message> for DistanceChart {
+    fn draw(&self, bounds: iced::Rectangle, _: canvas::Cursor) -> Vec<canvas::Geometry> {
+        let mut frame = canvas::Frame::new(bounds.size());
+        let buckets = self.0.buckets();
+        let used = buckets
+            .iter()
+            .rposition(|&count| count > 0)
+            .map_or(1, |last| last + 1);
+        let max = buckets.iter().copied().max().unwrap_or(0).max(1);
+
+        // The labels take the bottom line
+        let height = (bounds.height - OVERLAY_LINE_HEIGHT).max(0.0);
+        let width = bounds.width / used as f32;
+        for (bucket, &count) in buckets[..used].iter().enumerate() {
+            let length = height * count as f32 / max as f32;
+            frame.fill_rectangle(
+                iced::Point::new(bucket as f32 * width, height - length),
+                iced::Size::new((width - 1.0).max(1.0), length),
+                BLUE,
+            );
+        }
+
+        let label = |content: usize, x: f32, horizontal_alignment| canvas::Text {
+            content: content.to_string(),
+            position: iced::Point::new(x, bounds.height),
+            size: 14.0,
+            horizontal_alignment,
+            vertical_alignment: iced::alignment::Vertical::Bottom,
+            ..canvas::Text::default()
+        };
+        frame.fill_text(label(1, 0.0, iced::alignment::Horizontal::Left));
+        if used > 1 {
+            frame.fill_text(label(
+                array::Distances::shortest(used - 1),
+                bounds.width,
+                iced::alignment::Horizontal::Right,
+            ));
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
@@ -0,0 +1,18 @@
+//! The views of the window: the array, or the image in its place, and the panels around it.
+
+mod array;
+mod events;
+mod history;
+mod info;
+mod pixels;
+mod settings;
+
+pub use array::{array_view, budget_overlay, screenshot};
+pub use events::{EventLog, EventsSnapshot};
+pub use history::{CompletedRun, History};
+pub use info::{InfoPanel, Measured};
+pub use pixels::Pixels;
+pub use settings::SettingsPanel;
+
+#[cfg(test)]
+pub use history::ResultsChart;
@@ -0,0 +1,109 @@
+use crate::Message;
+use iced::canvas;
+use sorting_animations::view::RED;
+use sorting_animations::{array, pixels};
+use std::sync;
+
+/// A loaded image with one of its columns being sorted, drawn in place of the array.
+pub struct Pixels {
+    image: pixels::Image,
+    column: pixels::ColumnSort,
+    /// The image only changes in the sorted column, which is drawn over it.
+    cache: canvas::Cache,
+}
+
+impl Pixels {
+    /// Sorts the middle column of the image.
+    pub fn new(image: pixels::Image) -> Pixels {
+        let column = pixels::ColumnSort::new(&image, image.width() / 2);
+
+        Pixels {
+            image,
+            column,
+            cache: canvas::Cache::new(),
+        }
+    }
+
+    pub fn select_column(&mut self, column: usize) {
+        let column = column.min(self.image.width() - 1);
+        self.column = pixels::ColumnSort::new(&self.image, column);
+    }
+
+    pub fn column(&self) -> usize {
+        self.column.column()
+    }
+
+    pub fn width(&self) -> usize {
+        self.image.width()
+    }
+
+    /// The numbers standing for the pixels of the sorted column.
+    pub fn numbers(&self) -> Vec<usize> {
+        self.column.numbers().to_vec()
+    }
+
+    /// The canvas drawing the image with the column as sorted so far by the array.
+    pub fn view<'a>(&'a self, array: &array::ArrayState) -> iced::Element<'a, Message> {
+        iced::Canvas::new(PixelCanvas {
+            pixels: self,
+            numbers: array.shared_numbers(),
+            touched: array
+                .steps()
+                .iter()
+                .flat_map(array::Step::indices)
+                .collect(),
+        })
+        .width(iced::Length::Fill)
+        .height(iced::Length::Fill)
+        .into()
+    }
+}
+
+struct PixelCanvas<'a> {
+    pixels: &'a Pixels,
+    numbers: sync::Arc<Vec<usize>>,
+    touched: Vec<usize>,
+}
+
+impl canvas::Program<Message> for PixelCanvas<'_> {
+    fn draw(&self, bounds: iced::Rectangle, _: canvas::Cursor) -> Vec<canvas::Geometry> {
+        let image = &self.pixels.image;
+        let color = |[r, g, b]: [u8; 3]| iced::Color::from_rgb8(r, g, b);
+
+        // As large as fits, centered
+        let size = f32::min(
+            bounds.width / image.width() as f32,
+            bounds.height / image.height() as f32,
+        );
+        let origin = iced::Point::new(
+            (bounds.width - image.width() as f32 * size) / 2.0,
+            (bounds.height - image.height() as f32 * size) / 2.0,
+        );
+        let pixel = |x: usize, y: usize| origin + iced::Vector::new(x as f32, y as f32) * size;
+
+        let background = self.pixels.cache.draw(bounds.size(), |frame| {
+            for y in 0..image.height() {
+                for x in 0..image.width() {
+                    frame.fill_rectangle(
+                        pixel(x, y),
+                        iced::Size::new(size, size),
+                        color(image.pixel(x, y)),
+                    );
+                }
+            }
+        });
+
+        let mut frame = canvas::Frame::new(bounds.size());
+        let column = self.pixels.column();
+        for (y, &number) in self.numbers.iter().enumerate().take(image.height()) {
+            let color = match self.touched.contains(&y) {
+                true => RED,
+                false => color(self.pixels.column.color(number)),
+            };
+
+            frame.fill_rectangle(pixel(column, y), iced::Size::new(size, size), color);
+        }
+
+        vec![background, frame.into_geometry()]
+    }
+}
@@ -0,0 +1,270 @@
+use crate::gui::{localized, Localized, Ramp};
+use crate::Message;
+use iced::{button, pick_list, scrollable, text_input};
+use sorting_animations::i18n::Language;
+use sorting_animations::settings::{Indicators, Settings, Theme, Waveform};
+use sorting_animations::view::RED;
+use std::time;
+
+/// The width of the labels of the settings panel's fields.
+const SETTINGS_LABEL_WIDTH: u16 = 140;
+
+/// The settings in sections beside the array, edited as text and applied at once when all of
+/// them are valid.
+///
+/// Invalid values are reported next to their fields, and settings that can't work together below
+/// them.
+#[derive(Default)]
+pub struct SettingsPanel {
+    toggle: button::State,
+    open: bool,
+    /// The values as edited, by the keys of the settings.
+    draft: Vec<(&'static str, String)>,
+    fields: [text_input::State; SettingsPanel::FIELDS.len()],
+    ramp: pick_list::State<Localized<Ramp>>,
+    waveform: pick_list::State<Localized<Waveform>>,
+    theme: pick_list::State<Localized<Theme>>,
+    indicators: pick_list::State<Localized<Indicators>>,
+    language: pick_list::State<Language>,
+    scroll: scrollable::State,
+    apply: button::State,
+    reset: button::State,
+}
+
+impl SettingsPanel {
+    /// The keys of the settings edited in text fields, in the order of their sections.
+    ///
+    /// Their labels are the `settings.<key>` texts.
+    const FIELDS: [&'static str; 6] = [
+        "tick-interval",
+        "max-numbers",
+        "highlights",
+        "volume",
+        "min-frequency",
+        "max-frequency",
+    ];
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Opens the panel editing the current settings, or closes it dropping the edits.
+    pub fn toggle(&mut self, current: &Settings) {
+        self.open = !self.open;
+        self.draft = current.entries();
+    }
+
+    /// Edits the setting of the key.
+    pub fn set(&mut self, key: &str, value: String) {
+        if let Some((_, draft)) = self.draft.iter_mut().find(|(k, _)| *k == key) {
+            *draft = value;
+        }
+    }
+
+    /// Edits every setting back to its default.
+    pub fn reset(&mut self) {
+        self.draft = Settings::default().entries();
+    }
+
+    /// The settings as edited, or why they can't be applied.
+    pub fn settings(&self) -> Result<Settings, String> {
+        let mut settings = Settings::default();
+        for (key, value) in &self.draft {
+            settings.set(key, value)?;
+        }
+
+        match settings.problem() {
+            Some(problem) => Err(problem.to_string()),
+            None => Ok(settings),
+        }
+    }
+
+    /// The panel in the language of the applied settings, rather than the edited ones.
+    pub fn view(&mut self, current: &Settings) -> iced::Element<'_, Message> {
+        let (padding, language) = (current.padding, current.language);
+        let settings = self.settings();
+        // Values that don't parse are reported next to their fields already
+        let parsed = self
+            .draft
+            .iter()
+            .all(|(key, value)| Settings::default().set(key, value).is_ok());
+        let SettingsPanel {
+            toggle,
+            open,
+            draft,
+            fields,
+            ramp,
+            waveform,
+            theme,
+            indicators,
+            language: language_state,
+            scroll,
+            apply,
+            reset,
+        } = self;
+
+        let toggle_button = iced::Button::new(
+            toggle,
+            iced::Text::new(language.text(if *open {
+                "settings.close"
+            } else {
+                "settings.open"
+            })),
+        )
+        .on_press(Message::ToggleSettings);
+        if !*open {
+            return iced::Container::new(toggle_button).padding(padding).into();
+        }
+
+        let value = |key: &str| {
+            draft
+                .iter()
+                .find(|(k, _)| *k == key)
+                .map_or("", |(_, value)| value.as_str())
+        };
+        let label = |key: &str| {
+            iced::Text::new(language.text(&format!("settings.{key}")))
+                .width(iced::Length::Units(SETTINGS_LABEL_WIDTH))
+        };
+        let row = || {
+            iced::Row::new()
+                .spacing(5)
+                .align_items(iced::Alignment::Center)
+        };
+        let section = |key: &str| iced::Text::new(language.text(key)).size(20);
+
+        let mut fields = fields
+            .iter_mut()
+            .zip(SettingsPanel::FIELDS)
+            .map(|(state, key)| {
+                let text = value(key);
+                let field = row().push(label(key)).push(
+                    iced::TextInput::new(state, "", text, move |text| {
+                        Message::SettingChanged(key, text)
+                    })
+                    .width(iced::Length::Units(80))
+                    .on_submit(Message::ApplySettings),
+                );
+
+                match Settings::default().set(key, text) {
+                    Ok(()) => field,
+                    Err(_) => field.push(
+                        iced::Text::new(language.format("settings.invalid", &[&text])).color(RED),
+                    ),
+                }
+            });
+        let mut column = iced::Column::new().spacing(padding);
+
+        column = column.push(section("settings.timing"));
+        for field in fields.by_ref().take(1) {
+            column = column.push(field);
+        }
+        let selected_ramp = value("ramp").parse().ok().map(|seconds| {
+            let ramp = Some(time::Duration::from_secs(seconds)).filter(|ramp| !ramp.is_zero());
+            Localized(Ramp::from(ramp), language)
+        });
+        column = column
+            .push(row().push(label("ramp")).push(iced::PickList::new(
+                ramp,
+                localized(&Ramp::VALUES, language),
+                selected_ramp,
+                |Localized(ramp, _)| {
+                    let seconds = ramp.duration().map_or(0, |ramp| ramp.as_secs());
+                    Message::SettingChanged("ramp", seconds.to_string())
+                },
+            )))
+            .push(
+                iced::Toggler::new(
+                    value("auto-speed") == "true",
+                    language.text("settings.auto-speed"),
+                    |auto_speed| Message::SettingChanged("auto-speed", auto_speed.to_string()),
+                )
+                .width(iced::Length::Shrink),
+            );
+
+        column = column.push(section("settings.numbers"));
+        for field in fields.by_ref().take(2) {
+            column = column.push(field);
+        }
+
+        column = column.push(section("settings.audio"));
+        for field in fields.by_ref().take(3) {
+            column = column.push(field);
+        }
+        column = column.push(
+            row().push(label("waveform")).push(iced::PickList::new(
+                waveform,
+                localized(&Waveform::VALUES, language),
+                value("waveform")
+                    .parse()
+                    .ok()
+                    .map(|waveform| Localized(waveform, language)),
+                |Localized(waveform, _)| Message::SettingChanged("waveform", waveform.to_string()),
+            )),
+        );
+
+        column = column
+            .push(section("settings.appearance"))
+            .push(
+                row().push(label("theme")).push(iced::PickList::new(
+                    theme,
+                    localized(&Theme::VALUES, language),
+                    value("theme")
+                        .parse()
+                        .ok()
+                        .map(|theme| Localized(theme, language)),
+                    |Localized(theme, _)| Message::SettingChanged("theme", theme.to_string()),
+                )),
+            )
+            .push(
+                row().push(label("indicators")).push(iced::PickList::new(
+                    indicators,
+                    localized(&Indicators::VALUES, language),
+                    value("indicators")
+                        .parse()
+                        .ok()
+                        .map(|indicators| Localized(indicators, language)),
+                    |Localized(indicators, _)| {
+                        Message::SettingChanged("indicators", indicators.to_string())
+                    },
+                )),
+            )
+            .push(
+                iced::Toggler::new(
+                    value("line-fill") == "true",
+                    language.text("settings.line-fill"),
+                    |fill| Message::SettingChanged("line-fill", fill.to_string()),
+                )
+                .width(iced::Length::Shrink),
+            )
+            // Every language in its own name, so it can be found without understanding the current
+            .push(row().push(label("language")).push(iced::PickList::new(
+                language_state,
+                Language::values(),
+                value("language").parse().ok(),
+                |language| Message::SettingChanged("language", language.code().to_string()),
+            )));
+
+        if let (Err(problem), true) = (&settings, parsed) {
+            column = column.push(iced::Text::new(problem.as_str()).color(RED));
+        }
+        let mut apply_button =
+            iced::Button::new(apply, iced::Text::new(language.text("settings.apply")));
+        if settings.is_ok() {
+            apply_button = apply_button.on_press(Message::ApplySettings);
+        }
+        column = column.push(
+            row().push(apply_button).push(
+                iced::Button::new(reset, iced::Text::new(language.text("settings.reset")))
+                    .on_press(Message::ResetSettings),
+            ),
+        );
+
+        iced::Column::new()
+            .spacing(padding)
+            .padding(padding)
+            .push(toggle_button)
+            .push(iced::Scrollable::new(scroll).push(column))
+            .into()
+    }
+}
@@ -0,0 +1,85 @@
+use crate::Message;
+use iced::button;
+use sorting_animations::settings::Settings;
+use sorting_animations::view::{worker_color, RED};
+use std::time;
+
+/// The colors of the workers, for runs with more than one.
+pub fn worker_legend<'a>(workers: usize, settings: &Settings) -> iced::Element<'a, Message> {
+    let padding = settings.padding;
+
+    (0..workers)
+        .fold(
+            iced::Row::new().spacing(padding).padding([0, padding]),
+            |row, worker| {
+                let label = settings.language.format("worker", &[&(worker + 1)]);
+                row.push(iced::Text::new(format!("■ {label}")).color(worker_color(worker)))
+            },
+        )
+        .into()
+}
+
+/// A banner reporting an error, such as a panicked sort.
+pub fn error_banner<'a>(message: String, padding: u16) -> iced::Element<'a, Message> {
+    iced::Container::new(iced::Text::new(message).color(RED))
+        .padding(padding)
+        .width(iced::Length::Fill)
+        .into()
+}
+
+/// The progress of a run through its estimated operations, with the time it has left.
+pub fn progress<'a>(
+    progress: f32,
+    remaining: Option<time::Duration>,
+    settings: &Settings,
+) -> iced::Element<'a, Message> {
+    let padding = settings.padding;
+    let remaining = match remaining {
+        Some(remaining) => settings
+            .language
+            .format("progress.left", &[&(remaining.as_secs() + 1)]),
+        None => String::new(),
+    };
+
+    iced::Row::new()
+        .spacing(padding)
+        .padding([0, padding])
+        .align_items(iced::Alignment::Center)
+        .push(iced::ProgressBar::new(0.0..=1.0, progress).height(iced::Length::Units(10)))
+        .push(iced::Text::new(remaining).width(iced::Length::Units(80)))
+        .into()
+}
+
+/// A banner warning about a sort that stopped performing operations, offering to kill it.
+#[derive(Default)]
+pub struct WedgedBanner {
+    kill: button::State,
+}
+
+impl WedgedBanner {
+    pub fn view(
+        &mut self,
+        stalled: time::Duration,
+        settings: &Settings,
+    ) -> iced::Element<'_, Message> {
+        let language = settings.language;
+
+        iced::Row::new()
+            .spacing(settings.padding)
+            .padding(settings.padding)
+            .align_items(iced::Alignment::Center)
+            .push(
+                iced::Text::new(language.format("wedged.stalled", &[&stalled.as_secs()]))
+                    .color(RED)
+                    .width(iced::Length::Fill),
+            )
+            .push(
+                iced::Button::new(
+                    &mut self.kill,
+                    iced::Text::new(language.text("wedged.kill")),
+                )
+                .on_press(Message::Kill),
+            )
+            .into()
+    }
+}
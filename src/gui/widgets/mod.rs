@@ -0,0 +1,12 @@
+//! The smaller parts of the window, shared between the views or composed into them.
+
+mod banners;
+mod number_input;
+mod stats;
+
+pub use banners::{error_banner, progress, worker_legend, WedgedBanner};
+pub use number_input::NumberInput;
+pub use stats::{StatsPanel, StatsSnapshot, Toggles};
+
+#[cfg(test)]
+pub use number_input::InputError;
@@ -0,0 +1,135 @@
+use crate::gui::{thousands, Localize};
+use crate::Message;
+use iced::text_input;
+use sorting_animations::i18n::Language;
+use sorting_animations::view::RED;
+use std::ops;
+
+/// Why the text of a number field can't be applied.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputError {
+    NotANumber,
+    Fraction,
+    Negative,
+}
+
+impl Localize for InputError {
+    fn localize(&self, language: Language) -> String {
+        language.text(match self {
+            InputError::NotANumber => "input.not_a_number",
+            InputError::Fraction => "input.fraction",
+            InputError::Negative => "input.negative",
+        })
+    }
+}
+
+/// The text of a number field as typed, read leniently and clamped to the bounds when applied.
+#[derive(Clone, Debug)]
+pub struct NumberInput {
+    text: String,
+    bounds: ops::RangeInclusive<usize>,
+}
+
+impl NumberInput {
+    /// The separators of thousands ignored besides whitespace, like in "12,000" or "12'000".
+    const SEPARATORS: [char; 3] = [',', '_', '\''];
+
+    pub fn new(bounds: ops::RangeInclusive<usize>) -> NumberInput {
+        NumberInput {
+            text: String::new(),
+            bounds,
+        }
+    }
+
+    pub fn set_text(&mut self, text: String) {
+        self.text = text;
+    }
+
+    /// Changes the bounds the value is clamped to, from the next value on.
+    pub fn set_bounds(&mut self, bounds: ops::RangeInclusive<usize>) {
+        self.bounds = bounds;
+    }
+
+    /// Shows the value, or empties the field without one.
+    pub fn set_value(&mut self, value: Option<usize>) {
+        self.text = value.map_or(String::new(), |value| value.to_string());
+    }
+
+    /// The value clamped to the bounds, none if the field is empty.
+    ///
+    /// Scientific notation like "1e4" is accepted too, as long as the number is whole.
+    pub fn value(&self) -> Result<Option<usize>, InputError> {
+        let text: String = self
+            .text
+            .chars()
+            .filter(|c| !c.is_whitespace() && !Self::SEPARATORS.contains(c))
+            .collect();
+        if text.is_empty() {
+            return Ok(None);
+        }
+
+        // Too many digits for an integer still parse as a float
+        let value = match text.parse::<usize>() {
+            Ok(value) => value,
+            Err(_) => Self::scientific(&text)?,
+        };
+
+        Ok(Some(value.clamp(*self.bounds.start(), *self.bounds.end())))
+    }
+
+    fn scientific(text: &str) -> Result<usize, InputError> {
+        // Floats also parse words like "inf" or "NaN"
+        if !text
+            .chars()
+            .all(|c| c.is_ascii_digit() || "+-.eE".contains(c))
+        {
+            return Err(InputError::NotANumber);
+        }
+
+        let value = text.parse::<f64>().map_err(|_| InputError::NotANumber)?;
+        if value < 0.0 {
+            Err(InputError::Negative)
+        } else if value.is_finite() && value.fract() != 0.0 {
+            Err(InputError::Fraction)
+        } else {
+            // Saturating at the largest value
+            Ok(value as usize)
+        }
+    }
+
+    /// The value applied unless the text reads exactly like it, or why none can be applied.
+    pub fn feedback(&self) -> Option<Result<usize, InputError>> {
+        match self.value() {
+            Ok(Some(value)) if value.to_string() != self.text.trim() => Some(Ok(value)),
+            Ok(_) => None,
+            Err(error) => Some(Err(error)),
+        }
+    }
+
+    /// The field followed by its feedback, in red if it can't be applied.
+    pub fn view<'a>(
+        &self,
+        state: &'a mut text_input::State,
+        placeholder: &str,
+        width: u16,
+        on_change: fn(String) -> Message,
+        on_submit: Message,
+        language: Language,
+    ) -> iced::Element<'a, Message> {
+        let row = iced::Row::new()
+            .spacing(5)
+            .align_items(iced::Alignment::Center)
+            .push(
+                iced::TextInput::new(state, placeholder, &self.text, on_change)
+                    .width(iced::Length::Units(width))
+                    .on_submit(on_submit),
+            );
+
+        match self.feedback() {
+            Some(Ok(value)) => row.push(iced::Text::new(format!("→ {}", thousands(value as u64)))),
+            Some(Err(error)) => row.push(iced::Text::new(error.localize(language)).color(RED)),
+            None => row,
+        }
+        .into()
+    }
+}
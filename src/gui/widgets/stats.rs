@@ -0,0 +1,193 @@
+use crate::gui::{abbreviated, bytes, focusable, thousands, Localize};
+use crate::Message;
+use sorting_animations::array;
+use sorting_animations::settings::Settings;
+
+/// What the stats panel shows of the run, read at once so none of the stats is an operation
+/// ahead of the others.
+#[derive(Clone, Debug)]
+pub struct StatsSnapshot {
+    /// Whether the run is ready, plays or finished, or replays a trace.
+    pub status: String,
+    /// The phase of sorts reporting phases.
+    pub phase: Option<array::Phase>,
+    pub stats: array::Stats,
+    /// The element under the cursor, or else the pinned one.
+    pub inspected: Option<String>,
+    /// Whether the counts are abbreviated to fit a compact window.
+    pub compact: bool,
+    pub toggles: Toggles,
+}
+
+/// The toggles of how the runs play, beside the stats.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Toggles {
+    pub auto_speed: bool,
+    pub suggest_speed: bool,
+    pub deterministic: bool,
+    pub animate_arrangements: bool,
+    pub count_distances: bool,
+    pub tween_swaps: bool,
+    pub muted: bool,
+    /// Whether the tone can be heard, else the mute toggle stays on.
+    pub audio: bool,
+    /// Whether the keyboard focus is on the mute toggle.
+    pub mute_focused: bool,
+}
+
+/// The status and stats of the run in a row with the toggles, where the stats get what the
+/// toggles leave them, so they wrap instead of pushing them off.
+///
+/// It keeps no state of its own, everything it shows is in the snapshot.
+#[derive(Default)]
+pub struct StatsPanel;
+
+impl StatsPanel {
+    pub fn view<'a>(
+        &self,
+        snapshot: &StatsSnapshot,
+        settings: &Settings,
+    ) -> iced::Element<'a, Message> {
+        let (padding, language) = (settings.padding, settings.language);
+        let mut stats = iced::Row::new()
+            .spacing(padding * 3)
+            .width(iced::Length::Fill)
+            .push(iced::Text::new(snapshot.status.as_str()));
+
+        // Only sorts reporting phases show them
+        if let Some(phase) = snapshot.phase {
+            stats = stats.push(iced::Text::new(phase.localize(language)));
+        }
+        // Fixed widths keep the counts from shifting what follows them as they grow
+        for (key, value, width) in StatsPanel::counts(&snapshot.stats, snapshot.compact) {
+            stats = stats.push(
+                iced::Text::new(language.format(key, &[&value])).width(iced::Length::Units(width)),
+            );
+        }
+        if !snapshot.compact {
+            stats = stats.push(iced::Text::new(snapshot.stats.last_step.localize(language)));
+        }
+        if let Some(ref inspected) = snapshot.inspected {
+            stats = stats.push(iced::Text::new(inspected.as_str()));
+        }
+
+        let toggles = snapshot.toggles;
+        let toggler = |toggled: bool, key: &str, on_toggle: fn(bool) -> Message| {
+            iced::Toggler::new(toggled, language.text(key) + "  ", on_toggle)
+                .width(iced::Length::Shrink)
+        };
+
+        let mute: iced::Element<'a, Message> = if toggles.audio {
+            toggler(toggles.muted, "toggle.mute", Message::Mute).into()
+        } else {
+            // Toggling it only keeps it on
+            iced::Tooltip::new(
+                toggler(true, "toggle.mute", |_| Message::Mute(true)),
+                language.text("toggle.mute_unavailable"),
+                iced::tooltip::Position::Bottom,
+            )
+            .into()
+        };
+
+        iced::Row::new()
+            .padding(padding)
+            .spacing(padding * 3)
+            .push(stats)
+            .push(toggler(
+                toggles.auto_speed,
+                "toggle.auto_speed",
+                Message::AutoSpeed,
+            ))
+            .push(toggler(
+                toggles.suggest_speed,
+                "toggle.suggest_speed",
+                Message::SuggestSpeed,
+            ))
+            .push(toggler(
+                toggles.deterministic,
+                "toggle.deterministic",
+                Message::Deterministic,
+            ))
+            .push(toggler(
+                toggles.animate_arrangements,
+                "toggle.animate_shuffling",
+                Message::AnimateArrangements,
+            ))
+            .push(toggler(
+                toggles.count_distances,
+                "toggle.count_distances",
+                Message::CountDistances,
+            ))
+            .push(toggler(
+                toggles.tween_swaps,
+                "toggle.tween_swaps",
+                Message::TweenSwaps,
+            ))
+            .push(focusable(mute, toggles.mute_focused))
+            .into()
+    }
+
+    /// The keys of the counts with their values and widths, abbreviated in a compact window,
+    /// which leaves out the wasted comparisons and the depth.
+    fn counts(stats: &array::Stats, compact: bool) -> Vec<(&'static str, String, u16)> {
+        let placed = stats.placed_percent().to_string();
+        let aux_memory = bytes(stats.space.peak_aux_bytes);
+
+        if compact {
+            let [comparisons, reads, writes] =
+                [stats.comparisons, stats.reads, stats.writes].map(abbreviated);
+            vec![
+                ("stats.comparisons_short", comparisons, 95),
+                ("stats.reads_short", reads, 80),
+                ("stats.writes_short", writes, 80),
+                ("stats.placed_short", placed, 40),
+                ("stats.aux_memory_short", aux_memory, 80),
+            ]
+        } else {
+            let [comparisons, reads, writes] =
+                [stats.comparisons, stats.reads, stats.writes].map(thousands);
+            // Only a recorded run tells which numbers never moved again, once it completes
+            let wasted = stats
+                .wasted_comparisons
+                .map_or_else(|| String::from("-"), thousands);
+            vec![
+                ("stats.comparisons", comparisons, 220),
+                ("stats.wasted", wasted, 190),
+                ("stats.reads", reads, 180),
+                ("stats.writes", writes, 180),
+                ("stats.placed", placed, 110),
+                ("stats.aux_memory", aux_memory, 170),
+                ("stats.depth", stats.space.peak_depth.to_string(), 110),
+            ]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compact_counts_are_abbreviated() {
+        let mut stats = array::ArrayState::from_numbers(vec![2, 1]).stats_snapshot();
+        stats.comparisons = 1_234_567;
+        let count = |compact: bool, key: &str| {
+            StatsPanel::counts(&stats, compact)
+                .into_iter()
+                .find(|&(k, _, _)| k == key)
+                .map(|(_, value, _)| value)
+        };
+
+        assert_eq!(
+            count(true, "stats.comparisons_short").as_deref(),
+            Some("1.23 M")
+        );
+        assert_eq!(
+            count(false, "stats.comparisons").as_deref(),
+            Some("1,234,567")
+        );
+        assert_eq!(count(false, "stats.placed").as_deref(), Some("0"));
+        // Only a wide window has room for the depth
+        assert_eq!(count(true, "stats.depth"), None);
+    }
+}
@@ -0,0 +1,155 @@
+//! The visible strings in every language, looked up by their keys.
+//!
+//! Every language has a catalog file next to this one, with a `key = text` per line and `#`
+//! starting a comment line. Its `language` key names the language in itself, and texts take their
+//! arguments as `{0}`, `{1}` and so on. Adding a language means adding its catalog to `CATALOGS`.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// The catalogs by the codes of their languages, English first as the fallback for keys missing
+/// from the others.
+const CATALOGS: &[(&str, &str)] = &[
+    ("en", include_str!("en.catalog")),
+    ("de", include_str!("de.catalog")),
+];
+
+/// A language of the catalogs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Language(usize);
+
+impl Language {
+    pub const ENGLISH: Language = Language(0);
+
+    pub fn values() -> Vec<Language> {
+        (0..CATALOGS.len()).map(Language).collect()
+    }
+
+    /// The code the language is selected by in the settings, like "en".
+    pub fn code(self) -> &'static str {
+        CATALOGS[self.0].0
+    }
+
+    /// The text of the key, or else the English one, none if neither catalog has it.
+    pub fn lookup(self, key: &str) -> Option<&'static str> {
+        let catalogs = catalogs();
+
+        catalogs[self.0]
+            .get(key)
+            .or_else(|| catalogs[Language::ENGLISH.0].get(key))
+            .copied()
+    }
+
+    /// The text of the key, which is shown as it is if no catalog has it, so it gets noticed.
+    pub fn text(self, key: &str) -> String {
+        self.lookup(key).unwrap_or(key).to_string()
+    }
+
+    /// The text of the key with the arguments in place of `{0}`, `{1}` and so on.
+    pub fn format(self, key: &str, args: &[&dyn std::fmt::Display]) -> String {
+        args.iter()
+            .enumerate()
+            .fold(self.text(key), |text, (i, arg)| {
+                text.replace(&format!("{{{i}}}"), &arg.to_string())
+            })
+    }
+}
+
+impl std::fmt::Display for Language {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.lookup("language").unwrap_or(self.code()))
+    }
+}
+
+impl std::str::FromStr for Language {
+    type Err = ();
+
+    fn from_str(code: &str) -> Result<Language, ()> {
+        CATALOGS
+            .iter()
+            .position(|&(c, _)| c.eq_ignore_ascii_case(code))
+            .map(Language)
+            .ok_or(())
+    }
+}
+
+/// The catalogs parsed once, in the order of `CATALOGS`.
+fn catalogs() -> &'static [HashMap<&'static str, &'static str>] {
+    static PARSED: OnceLock<Vec<HashMap<&'static str, &'static str>>> = OnceLock::new();
+
+    PARSED.get_or_init(|| {
+        CATALOGS
+            .iter()
+            .map(|&(_, catalog)| parse(catalog))
+            .collect()
+    })
+}
+
+fn parse(catalog: &'static str) -> HashMap<&'static str, &'static str> {
+    catalog
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, text)| (key.trim(), text.trim()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The arguments a text takes, as their numbers.
+    fn arguments(text: &str) -> Vec<usize> {
+        let mut arguments: Vec<usize> = (0..10)
+            .filter(|i| text.contains(&format!("{{{i}}}")))
+            .collect();
+        arguments.sort();
+
+        arguments
+    }
+
+    #[test]
+    fn catalogs_translate_every_key() {
+        let catalogs = catalogs();
+        let english = &catalogs[Language::ENGLISH.0];
+
+        for (language, catalog) in Language::values().into_iter().zip(catalogs) {
+            assert!(catalog.contains_key("language"), "{}", language.code());
+
+            for (key, english_text) in english {
+                let text = catalog.get(key);
+                assert!(text.is_some(), "{} misses {key}", language.code());
+                assert_eq!(
+                    arguments(text.unwrap()),
+                    arguments(english_text),
+                    "{} takes other arguments for {key}",
+                    language.code()
+                );
+            }
+            for key in catalog.keys() {
+                assert!(
+                    english.contains_key(key),
+                    "{} has unknown {key}",
+                    language.code()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn texts_fall_back_to_english() {
+        let german: Language = "de".parse().unwrap();
+
+        assert_eq!(german.to_string(), "Deutsch");
+        assert_eq!(Language::ENGLISH.to_string(), "English");
+        assert_eq!(german.text("controls.play"), "Abspielen");
+        assert_eq!(german.text("no.such.key"), "no.such.key");
+        assert_eq!(german.lookup("no.such.key"), None);
+        assert_eq!(
+            Language::ENGLISH.format("timeline.operation", &[&3, &10]),
+            "Operation: 3 / 10"
+        );
+        assert_eq!("fr".parse::<Language>(), Err(()));
+    }
+}
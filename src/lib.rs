@@ -0,0 +1,27 @@
+// The engine only fails once a sort is killed or done, which carries no further information
+#![allow(clippy::result_unit_err)]
+
+use std::time;
+
+/// The instant of the platform, the same as `iced::time::Instant`, as `std::time::Instant`
+/// panics on the web.
+#[cfg(not(target_arch = "wasm32"))]
+pub use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+pub use wasm_timer::Instant;
+
+/// The tick interval unless the settings select another one.
+pub const DELAY_TIME: time::Duration = time::Duration::from_millis(10);
+const JOIN_TIMEOUT: time::Duration = time::Duration::from_secs(1);
+const WEDGED_TIMEOUT: time::Duration = time::Duration::from_secs(3);
+
+pub mod array;
+pub mod audio;
+pub mod control;
+pub mod i18n;
+pub mod metrics;
+pub mod pixels;
+pub mod settings;
+pub mod sorting;
+#[cfg(feature = "gui")]
+pub mod view;
@@ -1,21 +1,52 @@
-use std::time;
+use gui::Localize;
+use iced::time;
+use iced::Application;
+use sorting_animations::i18n::Language;
+use sorting_animations::view::Style;
+use sorting_animations::{array, audio, control, metrics, pixels, settings::Settings, sorting};
 
 const TITLE: &str = "Sorting Animations";
-const PADDING: u16 = 15;
 const INITIAL_NUMBERS: usize = 100;
-const MIN_NUMBERS: usize = 10;
-const DELAY_TIME: time::Duration = time::Duration::from_millis(10);
-const MAX_SPEED: u32 = 100;
-const TIME_OUT_CHECK: u64 = 10000;
+/// The largest value the numbers can be spread up to, bounding the buckets of counting sort.
+const MAX_VALUE: usize = 1_000_000;
+const TRACE_PATH: &str = "trace.satr";
+const IMAGE_PATH: &str = "image.ppm";
+const SCREENSHOTS_PATH: &str = "screenshots";
+const HISTORY_PATH: &str = "history.csv";
+const EVENT_LOG_PATH: &str = "events.txt";
+/// How long a run takes with auto speed, if its operations can be estimated.
+const AUTO_SPEED_DURATION: time::Duration = time::Duration::from_secs(20);
+/// Runs estimated to take longer at the current speed are only played after a warning.
+const INFEASIBLE_DURATION: time::Duration = time::Duration::from_secs(10 * 60);
+/// About as many operations as the sort thread performs a second at most, however much budget
+/// the speed grants.
+const MAX_OPERATIONS_PER_SECOND: f64 = 20_000_000.0;
+/// Below this window size the controls collapse, so the array keeps most of the window.
+const COMPACT_WIDTH: u32 = 1100;
+const COMPACT_HEIGHT: u32 = 700;
+#[cfg(feature = "scripting")]
+const SCRIPTS_DIR: &str = "scripts";
+/// The sizes the runs of the attract mode pick from, large enough to watch but quick to sort.
+const ATTRACT_SIZES: std::ops::RangeInclusive<usize> = 50..=500;
+/// The longest a run of the attract mode is estimated to take, so the display moves on to others
+/// instead of playing one of the slowest sorts for hours.
+const ATTRACT_DURATION: time::Duration = time::Duration::from_secs(2 * 60);
+/// The share of the volume the attract mode plays the tone at, so it doesn't drown out a hallway.
+const ATTRACT_VOLUME: f32 = 0.3;
 
-mod array;
 mod gui;
-mod sorting;
 
 pub fn main() -> iced::Result {
-    use iced::Application;
+    let settings = match Settings::load() {
+        Ok(loaded) => loaded,
+        Err(error) => {
+            eprintln!("{error}");
+            std::process::exit(2);
+        }
+    };
 
     SortingAnimations::run(iced::Settings {
+        flags: settings,
         antialiasing: true,
         window: iced::window::Settings {
             position: iced::window::Position::Centered,
@@ -23,6 +54,7 @@ pub fn main() -> iced::Result {
             ..iced::window::Settings::default()
         },
         default_text_size: 14,
+        exit_on_close_request: false,
 
         ..iced::Settings::default()
     })
@@ -32,57 +64,279 @@ pub fn main() -> iced::Result {
 pub enum Message {
     Play,
     Shuffle,
+    /// Shuffles the numbers the same way every time for the seed, without animating it.
+    ShuffleSeeded(u64),
     Reverse,
+    Order,
+    Equalize,
     Step,
     Mute(bool),
+    AutoSpeed(bool),
+    SuggestSpeed(bool),
+    AnimateArrangements(bool),
+    CountDistances(bool),
+    TweenSwaps(bool),
+    InterruptionSelected(gui::Interruption),
+    ConfirmPending,
+    CancelPending,
+    Deterministic(bool),
     Tick(time::Instant),
+    /// Input without a message of its own, like moving the mouse, which ends the attract mode.
+    Activity,
+    Kill,
+    Exit,
+    Resized(u32, u32),
+    ToggleControls,
+    Hover(Option<usize>),
+    Pin(usize),
+    ModifiersChanged(iced::keyboard::Modifiers),
+    ToggleBudgetOverlay,
+    ClearWatched,
+    ToggleSettings,
+    /// A key moving the focus through the controls or acting on the focused one.
+    Navigate(gui::Navigation),
+    /// A command of the control socket, answered once it is carried out.
+    Control(control::Request),
+    ControlFailed(String),
+    /// A setting edited in the settings panel, by its key.
+    SettingChanged(&'static str, String),
+    ApplySettings,
+    ResetSettings,
 
-    SortSelected(sorting::Sort),
+    SortSelected(sorting::Algorithm),
     ViewSelected(gui::View),
+    Structure(bool),
+    ScaleSelected(gui::Scale),
     SpeedSelected(u32),
+    RampSelected(gui::Ramp),
+    SlowMotionSelected(gui::SlowMotion),
+    GranularitySelected(sorting::Granularity),
+    HighlightsSelected(u32),
     NumbersInput(String),
+    MaxValueInput(String),
     NumbersSelected,
+    RangeStartInput(String),
+    RangeEndInput(String),
+    RangeSelected,
+    ArrangementSelected(array::Arrangement),
+
+    Record(bool),
+    TraceInput(String),
+    SaveTrace,
+    LoadTrace,
+    Scrub(u32),
+    SetReference,
+    ClearReference,
+    Screenshots(bool),
+    ScreenshotsInput(String),
+
+    ToggleHistory,
+    ToggleInfo,
+    Rerun(Box<gui::CompletedRun>),
+    ExportHistory,
+    ToggleEventLog,
+    ExportEventLog,
+    PauseOnAnnotation(bool),
+    AnnotationFilterInput(String),
+
+    ImageInput(String),
+    LoadImage,
+    CloseImage,
+    ColumnSelected(u32),
+}
+
+/// What the animations count about themselves, only reported in the metrics.
+#[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+struct Counters {
+    /// When the animations started, for the uptime.
+    launched: time::Instant,
+    runs_completed: u64,
+    /// How often the window was drawn, once for every view built.
+    frames_rendered: u64,
+}
+
+/// What the window shows besides the states of its widgets, read from the animations at once.
+struct Parts<'a> {
+    banners: Vec<iced::Element<'a, Message>>,
+    stats: gui::StatsSnapshot,
+    rows: Vec<iced::Element<'a, Message>>,
+    measured: Option<gui::Measured>,
+    distances: Option<array::Distances>,
+    settings_open: bool,
+    /// The position in the operations of a replayed or recorded run, and how many there are.
+    timeline: Option<(u32, u32)>,
+    events: gui::EventsSnapshot,
+    controls: gui::ControlsSnapshot,
+    trace: gui::TraceSnapshot,
+    compact: bool,
+    collapsed: bool,
+}
+
+/// The controls the user left the animations with, restored when the attract mode ends.
+#[derive(Clone, Debug)]
+struct AttractSnapshot {
+    sort: sorting::Algorithm,
+    view: gui::View,
+    speed: u32,
+    /// The numbers as they were, which also restores their size.
+    numbers: Vec<usize>,
+    seed: u64,
+}
+
+/// Where the current run is in its lifecycle, deciding which stats are displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Run {
+    /// Set up but not started, still showing the stats of the previous run.
+    Ready,
+    /// Started, the stats are live.
+    Live,
+    /// Done, the stats are frozen until the next run begins.
+    Finished,
+}
+
+impl Localize for Run {
+    fn localize(&self, language: Language) -> String {
+        language.text(&format!("run.{self:?}"))
+    }
+}
+
+/// The points of a run a screenshot is captured at, if enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Milestone {
+    Start,
+    Quarter,
+    Half,
+    ThreeQuarters,
+    Finish,
+}
+
+impl Milestone {
+    const VALUES: [Milestone; 5] = [
+        Milestone::Start,
+        Milestone::Quarter,
+        Milestone::Half,
+        Milestone::ThreeQuarters,
+        Milestone::Finish,
+    ];
+
+    /// The share of the estimated operations performed at the milestone.
+    fn progress(self) -> f32 {
+        match self {
+            Milestone::Start => 0.0,
+            Milestone::Quarter => 0.25,
+            Milestone::Half => 0.5,
+            Milestone::ThreeQuarters => 0.75,
+            Milestone::Finish => 1.0,
+        }
+    }
+}
+
+impl std::fmt::Display for Milestone {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Milestone::Start => f.write_str("start"),
+            Milestone::Finish => f.write_str("finish"),
+            _ => write!(f, "{}-percent", (self.progress() * 100.0) as u32),
+        }
+    }
 }
 
 struct SortingAnimations {
     controls: gui::Controls,
+    trace_controls: gui::TraceControls,
+    image_controls: gui::ImageControls,
+    wedged_banner: gui::WedgedBanner,
+    stats_panel: gui::StatsPanel,
+    timeline: gui::Timeline,
+    history: gui::History,
+    event_log: gui::EventLog,
+    info_panel: gui::InfoPanel,
+    settings_panel: gui::SettingsPanel,
     sorter: sorting::Sorter,
+    registry: sorting::Registry,
+    replay: Option<sorting::Replay>,
+    /// A recorded run the array is compared against, replayed to as many operations as the
+    /// current run performed.
+    reference: Option<sorting::Replay>,
     playing: bool,
+    run: Run,
+    view: gui::View,
+    /// Whether the structure a sort reports, like the tree of its heap or the pivot it partitions
+    /// around, is drawn over the bars.
+    structure: bool,
+    scale: gui::Scale,
+    /// The index under the cursor.
+    hovered: Option<usize>,
+    /// The value of the pinned element, which is followed wherever the sort moves it.
+    pinned: Option<usize>,
+    /// Whether shift is held, so clicking an element watches it instead of pinning it.
+    shift: bool,
     speed: u32,
-    changed_numbers: Option<usize>,
-    reset_stats: bool,
+    auto_speed: bool,
+    /// Whether switching sorts sets the speed to the one suggested for the new sort.
+    suggest_speed: bool,
+    /// Whether Play warned the run takes too long, so pressing it again plays it anyway.
+    play_warned: bool,
+    /// Whether shuffling and reversing animate their swaps before the sort can run.
+    animate_arrangements: bool,
+    /// Whether playing pauses on annotations containing the filter.
+    pause_on_annotation: bool,
+    annotation_filter: String,
+    /// The pause hits of the array as of the last pause, or of resuming.
+    pause_hits: u64,
+    interruption: gui::Interruption,
+    /// The action that would cut the playing run short, until confirmed or the run stops.
+    pending: Option<gui::Pending>,
+    /// The expected operations of the live run, if its sort can estimate them.
+    estimate: Option<u64>,
+    /// When the live run started, along with the numbers it started on.
+    started: Option<(time::Instant, std::sync::Arc<Vec<usize>>)>,
+    /// When the current pace was set along with the operations performed by then.
+    pace: Option<(time::Instant, u64)>,
+    numbers_input: gui::NumberInput,
+    /// The largest value of the numbers, where none spreads them up to their count.
+    max_value_input: gui::NumberInput,
+    /// The start of the range to sort, from the first number if none.
+    range_start_input: gui::NumberInput,
+    /// The end of the range to sort, to the last number if none.
+    range_end_input: gui::NumberInput,
     muted: bool,
-    sink: rodio::Sink,
-    _stream: rodio::OutputStream,
+    trace_path: String,
+    image_path: String,
+    screenshots: bool,
+    /// The directory the screenshots are saved into.
+    screenshots_path: String,
+    /// The last milestone of the live run a screenshot was captured at.
+    milestone: Option<Milestone>,
+    /// The image whose column is sorted instead of the numbers, if one is loaded.
+    pixels: Option<gui::Pixels>,
+    /// When the user gave the last input, for the attract mode.
+    last_input: time::Instant,
+    /// The controls the user left, while the attract mode plays runs of its own.
+    attract: Option<AttractSnapshot>,
+    error: Option<String>,
+    exit: bool,
+    counters: Counters,
+    #[cfg(feature = "metrics")]
+    metrics_server: Option<metrics::Server>,
+    settings: Settings,
+    /// The tick interval applied by the settings panel during a run, which takes over with the
+    /// next one.
+    next_tick_interval: Option<time::Duration>,
+    /// The config file the settings are saved to once applied, none without one.
+    config: Option<std::path::PathBuf>,
+    audio: Box<dyn audio::Sonifier>,
 }
 
 impl iced::Application for SortingAnimations {
     type Executor = iced::executor::Default;
     type Message = Message;
-    type Flags = ();
-
-    fn new(_: Self::Flags) -> (Self, iced::Command<Self::Message>) {
-        let (_stream, handle) = rodio::OutputStream::try_default().unwrap();
-        let sink = rodio::Sink::try_new(&handle).unwrap();
-        sink.set_volume(0.1);
-        sink.append(rodio::source::SineWave::new(440.0));
-        sink.pause();
+    type Flags = (Settings, std::path::PathBuf);
 
-        let mut animations = SortingAnimations {
-            controls: gui::Controls::default(),
-            sorter: sorting::Sorter::new(array::ArrayState::new(
-                INITIAL_NUMBERS,
-                gui::View::default(),
-            )),
-            playing: false,
-            speed: 1,
-            changed_numbers: Some(INITIAL_NUMBERS),
-            reset_stats: false,
-            muted: true,
-            sink,
-            _stream,
-        };
-        animations.initialize_sort(sorting::Sort::default());
+    fn new((settings, config): Self::Flags) -> (Self, iced::Command<Self::Message>) {
+        let audio = audio::speaker(&settings);
+        let mut animations = SortingAnimations::with_audio(settings, audio);
+        animations.config = Some(config);
 
         (animations, iced::Command::none())
     }
@@ -92,160 +346,3232 @@ impl iced::Application for SortingAnimations {
     }
 
     fn update(&mut self, message: Self::Message) -> iced::Command<Self::Message> {
-        match message {
-            Message::Play => {
-                if self.reset_stats {
-                    self.sorter.reset_stats();
-                    self.reset_stats = false;
-                }
+        if !matches!(
+            message,
+            Message::Tick(_) | Message::Resized(..) | Message::ControlFailed(_)
+        ) {
+            self.last_input = time::Instant::now();
 
-                self.playing = !self.playing;
-                if !self.playing {
-                    self.sink.pause();
+            // The input ending the attract mode is only carried out if it can't wait
+            if self.attract.is_some() {
+                self.leave_attract();
+                if !matches!(message, Message::Exit | Message::Control(_)) {
+                    return iced::Command::none();
                 }
             }
-            Message::Shuffle => {
-                self.initialize_sort(self.sorter.sort());
+        }
 
-                self.sorter.shuffle();
+        match message {
+            // The sort only starts once the numbers are rearranged
+            Message::Play | Message::Step if self.sorter.arranging() => {}
+            // A run taking too long only plays once Play is pressed again
+            Message::Play if !self.playing && !self.play_warned && self.infeasible().is_some() => {
+                self.play_warned = true;
             }
-            Message::Reverse => {
-                self.initialize_sort(self.sorter.sort());
+            Message::Play => {
+                self.begin_run();
 
-                self.sorter.reverse();
+                if self.playing {
+                    self.pause();
+                } else {
+                    self.play();
+                }
             }
-            Message::Step => {
-                if self.reset_stats {
-                    self.sorter.reset_stats();
-                    self.reset_stats = false;
+            Message::Shuffle => self.interrupt(gui::Interrupting::Shuffle),
+            // Like the other arrangements without a confirmation, only while stopped
+            Message::ShuffleSeeded(seed) => {
+                if !self.playing && !self.sorter.arranging() {
+                    self.sorter.set_seed(seed);
+                    self.initialize_sort(self.sorter.sort().clone());
+                    self.sorter.shuffle_seeded(seed);
                 }
+            }
+            Message::Reverse => self.interrupt(gui::Interrupting::Reverse),
+            Message::Order => self.arrange(array::Arrangement::Sorted),
+            Message::Equalize => self.arrange(array::Arrangement::AllEqual),
+            // The buttons arranging the numbers can't be pressed while playing, but the list can
+            Message::ArrangementSelected(arrangement) => {
+                if !self.playing {
+                    self.arrange(arrangement);
+                }
+            }
+            Message::Step => {
+                self.begin_run();
 
-                self.sorter.step();
+                match self.replay {
+                    Some(ref mut replay) => replay.step().unwrap_or_default(),
+                    None => self.sorter.step().unwrap_or_default(),
+                }
             }
-            Message::Tick(_instant) => {
-                self.sink.set_speed(match self.sorter.last_step() {
-                    array::Step::None => self.sink.speed(),
-                    _ => {
-                        0.5 + (self.sorter.last_step().values().iter().sum::<usize>() as f32
-                            / self.sorter.last_step().values().len() as f32)
-                            / self.sorter.size() as f32
-                    }
+            Message::Tick(instant) => {
+                // Only the first worker is heard, several at once would be a cacophony
+                let (step, max_value) = self.read_array(|array| {
+                    let step = array.steps().first().copied();
+                    (step.unwrap_or(array::Step::None), array.max_value())
                 });
-                if !self.sorter.alive() {
-                    self.playing = false;
-                    self.initialize_sort(self.sorter.sort());
+                self.audio.on_step(&step, max_value, &self.settings);
+                self.audio.release_idle(instant);
+                // The swapped numbers slide on by a frame, the same as the others drawn
+                if self.read_array(array::ArrayState::sliding) {
+                    self.sorter.advance_tweens(self.settings.tick_interval);
+                }
+                let speed = self.speed as f32 / self.settings.max_speed as f32;
+                let auto_operations = self.estimate.filter(|_| self.auto_speed).map(|estimate| {
+                    let ticks =
+                        AUTO_SPEED_DURATION.as_millis() / self.settings.tick_interval.as_millis();
+                    estimate / (ticks as u64).max(1)
+                });
+                let finished = if self.sorter.arranging() {
+                    // Rearranging the numbers goes at its own pace, before the sort can play
+                    self.sorter.tick_arrangement();
+                    false
+                } else if !self.alive() {
+                    true
+                } else if self.playing
+                    && self.read_array(array::ArrayState::pause_hits) != self.pause_hits
+                {
+                    // The sort ended its batch on the annotation, so it pauses right there
+                    self.pause();
+                    false
                 } else if self.playing {
-                    if self.sink.is_paused() && !self.muted {
-                        self.sink.play()
+                    if !self.muted {
+                        self.audio.set_enabled(true);
+                    }
+
+                    if self.pace.is_none() {
+                        self.pace = Some((time::Instant::now(), self.sorter.operations()));
                     }
 
-                    self.sorter.tick(self.speed as f32 / MAX_SPEED as f32);
+                    match (self.replay.as_mut(), auto_operations) {
+                        (Some(replay), _) => replay.tick(speed).is_err(),
+                        (None, Some(operations)) => {
+                            self.sorter.tick_operations(operations).is_err()
+                        }
+                        (None, None) => self.sorter.tick(speed).is_err(),
+                    }
+                } else {
+                    false
+                };
+
+                // Keep the finished (or panicked) array and its stats around until the user
+                // starts a new run
+                if finished && self.run != Run::Finished {
+                    self.playing = false;
+                    self.run = Run::Finished;
+                    self.audio.set_enabled(false);
+                    self.complete_run();
+                    self.stopped();
+                }
+                if self.pending.as_ref().is_some_and(gui::Pending::expired) {
+                    self.pending = None;
                 }
+                self.play_warned &= self.infeasible().is_some();
+                // Left without a valid value, the numbers field shows their count again
+                if !self.controls.numbers_focused()
+                    && !matches!(self.numbers_input.value(), Ok(Some(_)))
+                {
+                    let size = self.read_array(array::ArrayState::size);
+                    self.numbers_input.set_value(Some(size));
+                }
+
+                self.attract();
+                self.capture_milestones();
+                #[cfg(feature = "metrics")]
+                self.publish_metrics();
             }
             Message::SortSelected(sort) => {
-                self.initialize_sort(sort);
+                // Picking the sort again keeps its run
+                if sort != *self.sorter.sort() {
+                    self.interrupt(gui::Interrupting::Sort(sort));
+                }
             }
             Message::ViewSelected(view) => {
-                self.sorter.set_view(view);
+                self.view = view;
+            }
+            Message::Structure(structure) => {
+                self.structure = structure;
+            }
+            Message::ScaleSelected(scale) => {
+                self.scale = scale;
             }
             Message::SpeedSelected(speed) => {
+                // The budget the sort was already granted follows the speed right away
+                if self.playing && self.replay.is_none() && !self.auto_speed {
+                    let factor = speed as f32 / self.speed.max(1) as f32;
+                    self.sorter.rescale(factor).unwrap_or_default();
+                }
                 self.speed = speed;
+                self.pace = None;
+            }
+            Message::RampSelected(ramp) => {
+                self.sorter.set_ramp(ramp.duration());
             }
-            Message::NumbersInput(nums) => {
-                if nums.trim().is_empty() {
-                    self.changed_numbers = None;
-                } else if let Ok(number) = nums.trim().parse::<usize>() {
-                    self.changed_numbers = Some(number);
+            Message::SlowMotionSelected(slow_motion) => {
+                self.sorter.set_slow_motion(slow_motion.factor());
+            }
+            Message::GranularitySelected(granularity) => {
+                self.sorter.set_granularity(granularity);
+
+                // A sort waiting for its first operation was started with the old granularity
+                if self.run == Run::Ready && self.replay.is_none() {
+                    self.initialize_sort(self.sorter.sort().clone());
                 }
             }
-            Message::NumbersSelected => {
-                self.playing = false;
-                self.sorter.kill_sort();
+            Message::ToggleSettings => {
+                let current = self.current_settings();
+                self.settings_panel.toggle(&current);
+            }
+            Message::SettingChanged(key, value) => self.settings_panel.set(key, value),
+            Message::ApplySettings => {
+                if let Ok(settings) = self.settings_panel.settings() {
+                    self.apply_settings(settings);
+                }
+            }
+            Message::ResetSettings => self.settings_panel.reset(),
+            Message::HighlightsSelected(highlights) => {
+                self.sorter.set_highlights(highlights as usize);
+            }
+            Message::AutoSpeed(auto_speed) => {
+                self.auto_speed = auto_speed;
+                self.pace = None;
+            }
+            Message::SuggestSpeed(suggest_speed) => {
+                self.suggest_speed = suggest_speed;
+            }
+            Message::AnimateArrangements(animate) => {
+                self.animate_arrangements = animate;
+            }
+            Message::CountDistances(count) => {
+                self.sorter.set_count_distances(count);
+            }
+            Message::TweenSwaps(tween) => {
+                self.sorter.set_tweening(tween);
+            }
+            Message::InterruptionSelected(interruption) => {
+                self.interruption = interruption;
+                self.pending = None;
+            }
+            Message::ConfirmPending => {
+                if let Some(pending) = self.pending.take() {
+                    self.perform(pending.action);
+                }
+            }
+            Message::CancelPending => {
+                self.pending = None;
+            }
+            Message::Deterministic(deterministic) => {
+                self.sorter.set_deterministic(deterministic);
 
-                self.changed_numbers = self.changed_numbers.map_or(Some(INITIAL_NUMBERS), |n| {
-                    Some(std::cmp::max(MIN_NUMBERS, n))
-                });
+                if self.replay.is_none() {
+                    self.initialize_sort(self.sorter.sort().clone());
+                }
+            }
+            Message::NumbersInput(numbers) => self.numbers_input.set_text(numbers),
+            Message::MaxValueInput(value) => self.max_value_input.set_text(value),
+            // The fields show why they can't be applied
+            Message::NumbersSelected => {
+                if let Some((size, max_value)) = self.input_numbers() {
+                    let current = self.read_array(|array| (array.size(), array.max_value()));
 
-                self.sink.pause();
-                self.sorter.initialize(self.changed_numbers.unwrap());
-                self.sorter.start_sort();
+                    // As many numbers up to the same value keep their run, Shuffle deals anew
+                    if self.pixels.is_none() && current == (size, max_value.unwrap_or(size)) {
+                        self.numbers_input.set_value(Some(size));
+                        self.max_value_input.set_value(max_value);
+                    } else {
+                        self.interrupt(gui::Interrupting::Numbers);
+                    }
+                }
+            }
+            Message::RangeStartInput(start) => self.range_start_input.set_text(start),
+            Message::RangeEndInput(end) => self.range_end_input.set_text(end),
+            Message::RangeSelected => {
+                if self.range_start_input.value().is_ok() && self.range_end_input.value().is_ok() {
+                    self.interrupt(gui::Interrupting::Range);
+                }
             }
             Message::Mute(muted) => {
-                self.muted = muted;
+                // The audio device is only opened now, without one there is nothing to unmute
+                self.muted = muted || !self.audio.open();
                 if self.muted {
-                    self.sink.pause();
+                    self.audio.set_enabled(false);
+                }
+            }
+            Message::Record(recording) => {
+                self.sorter.set_recording(recording);
+
+                // A sort waiting for its first operation was started with the old setting, a
+                // running one records from the next run on
+                if self.run == Run::Ready && self.replay.is_none() {
+                    self.initialize_sort(self.sorter.sort().clone());
+                }
+            }
+            Message::TraceInput(path) => {
+                self.trace_path = path;
+            }
+            Message::SaveTrace => {
+                let language = self.settings.language;
+                self.error = match self.sorter.trace() {
+                    Some(trace) => std::fs::File::create(&self.trace_path)
+                        .and_then(|file| trace.write(std::io::BufWriter::new(file)))
+                        .err()
+                        .map(|error| language.format("error.save_trace", &[&error])),
+                    None => Some(language.text("error.no_trace")),
+                };
+            }
+            Message::LoadTrace => {
+                match std::fs::File::open(&self.trace_path)
+                    .and_then(|file| sorting::Trace::read(std::io::BufReader::new(file)))
+                {
+                    Ok(trace) => {
+                        self.pixels = None;
+                        self.initialize_sort(trace.sort.into());
+                        self.replay = Some(sorting::Replay::new(std::sync::Arc::new(trace)));
+                        self.error = None;
+                    }
+                    Err(error) => {
+                        self.error =
+                            Some(self.settings.language.format("error.load_trace", &[&error]));
+                    }
+                }
+            }
+            Message::Screenshots(screenshots) => {
+                self.screenshots = screenshots;
+            }
+            Message::ScreenshotsInput(path) => {
+                self.screenshots_path = path;
+            }
+            Message::SetReference => {
+                if let Some(trace) = self.sorter.trace().cloned() {
+                    // The next run starts on the reference's input, for whichever sort is picked
+                    self.initialize_sort(self.sorter.sort().clone());
+                    self.sorter.load_numbers(trace.numbers.clone());
+                    self.reference = Some(sorting::Replay::new(trace));
+                }
+            }
+            Message::ClearReference => {
+                self.reference = None;
+            }
+            Message::ToggleHistory => {
+                self.history.toggle_expanded();
+            }
+            Message::ToggleInfo => {
+                self.info_panel.toggle_expanded();
+            }
+            Message::Rerun(run) => {
+                self.pixels = None;
+                self.numbers_input.set_value(Some(run.numbers.len()));
+                self.sorter.set_seed(run.seed);
+                self.initialize_sort(run.sort);
+                self.sorter.load_numbers(run.numbers.to_vec());
+
+                self.begin_run();
+                self.playing = true;
+            }
+            Message::ExportHistory => {
+                self.error = std::fs::File::create(HISTORY_PATH)
+                    .and_then(|file| self.history.write_csv(std::io::BufWriter::new(file)))
+                    .err()
+                    .map(|error| {
+                        self.settings
+                            .language
+                            .format("error.export_history", &[&error])
+                    });
+            }
+            Message::ToggleEventLog => {
+                self.event_log.toggle_expanded();
+            }
+            Message::PauseOnAnnotation(pause) => {
+                self.pause_on_annotation = pause;
+                self.apply_annotation_filter();
+            }
+            Message::AnnotationFilterInput(filter) => {
+                self.annotation_filter = filter;
+                self.apply_annotation_filter();
+            }
+            Message::ExportEventLog => {
+                let annotations = self.read_array(|array| array.annotations().clone());
+                self.error = std::fs::File::create(EVENT_LOG_PATH)
+                    .and_then(|file| annotations.write_text(std::io::BufWriter::new(file)))
+                    .err()
+                    .map(|error| {
+                        self.settings
+                            .language
+                            .format("error.export_events", &[&error])
+                    });
+            }
+            Message::ImageInput(path) => {
+                self.image_path = path;
+            }
+            Message::LoadImage => {
+                match std::fs::File::open(&self.image_path)
+                    .and_then(|file| pixels::Image::read(std::io::BufReader::new(file)))
+                {
+                    Ok(image) => {
+                        self.pixels = Some(gui::Pixels::new(image.fit()));
+                        self.load_pixels();
+                        self.error = None;
+                    }
+                    Err(error) => {
+                        self.error =
+                            Some(self.settings.language.format("error.load_image", &[&error]));
+                    }
+                }
+            }
+            Message::CloseImage => {
+                self.pixels = None;
+                self.select_numbers();
+            }
+            Message::ColumnSelected(column) => {
+                if let Some(pixels) = self.pixels.as_mut().filter(|_| !self.playing) {
+                    pixels.select_column(column as usize);
+                    self.load_pixels();
+                }
+            }
+            Message::Scrub(position) => {
+                // A completed recorded run is scrubbed by replaying its trace, shared with the run
+                if self.replay.is_none() {
+                    if let Some(trace) = self.sorter.trace() {
+                        self.replay = Some(sorting::Replay::new(std::sync::Arc::clone(trace)));
+                    }
                 }
+
+                if let Some(ref mut replay) = self.replay {
+                    self.playing = false;
+                    self.audio.set_enabled(false);
+
+                    replay.seek(position as usize);
+                    self.run = if replay.finished() {
+                        Run::Finished
+                    } else {
+                        Run::Ready
+                    };
+                }
+            }
+            // Only counts as input
+            Message::Activity => {}
+            Message::Kill => {
+                self.playing = false;
+                self.audio.set_enabled(false);
+                self.sorter.kill_sort();
+                self.run = Run::Finished;
+            }
+            Message::Resized(width, height) => {
+                self.controls
+                    .set_compact(width < COMPACT_WIDTH || height < COMPACT_HEIGHT);
+            }
+            Message::ToggleControls => {
+                self.controls.toggle_expanded();
+            }
+            Message::Navigate(navigation) => return self.navigate(navigation),
+            Message::Control(request) => self.control(request),
+            Message::ControlFailed(error) => {
+                self.error = Some(
+                    self.settings
+                        .language
+                        .format("error.control_socket", &[&error]),
+                );
+            }
+            Message::Hover(index) => {
+                self.hovered = index;
+            }
+            Message::ModifiersChanged(modifiers) => {
+                self.shift = modifiers.shift();
+            }
+            Message::ToggleBudgetOverlay => {
+                self.sorter.set_probing(!self.sorter.probing());
+            }
+            Message::Pin(index) if self.shift => {
+                self.sorter.toggle_watched(index);
+            }
+            Message::ClearWatched => {
+                self.sorter.clear_watched();
+            }
+            Message::Pin(index) => {
+                let value = self.read_array(|array| array.numbers().get(index).copied());
+
+                // Pinning the pinned element again lets go of it
+                self.pinned = if value == self.pinned { None } else { value };
+            }
+            Message::Exit => {
+                self.playing = false;
+                self.audio.set_enabled(false);
+                self.sorter.kill_sort();
+                // Stops serving the metrics before the window closes
+                #[cfg(feature = "metrics")]
+                {
+                    self.metrics_server = None;
+                }
+                self.exit = true;
             }
         }
 
+        self.follow_reference();
+
         iced::Command::none()
     }
 
     fn subscription(&self) -> iced::Subscription<Self::Message> {
-        iced::time::every(DELAY_TIME).map(Message::Tick)
+        let control = match self.settings.control_port {
+            Some(port) => control_socket(port),
+            None => iced::Subscription::none(),
+        };
+
+        iced::Subscription::batch([
+            control,
+            iced::time::every(self.settings.tick_interval).map(Message::Tick),
+            iced_native::subscription::events_with(|event, status| match event {
+                iced_native::Event::Window(iced_native::window::Event::CloseRequested) => {
+                    Some(Message::Exit)
+                }
+                iced_native::Event::Window(iced_native::window::Event::Resized {
+                    width,
+                    height,
+                }) => Some(Message::Resized(width, height)),
+                iced_native::Event::Keyboard(iced_native::keyboard::Event::ModifiersChanged(
+                    modifiers,
+                )) => Some(Message::ModifiersChanged(modifiers)),
+                // Unless typed into a field
+                iced_native::Event::Keyboard(iced_native::keyboard::Event::KeyPressed {
+                    key_code: iced_native::keyboard::KeyCode::F3,
+                    ..
+                }) if status == iced_native::event::Status::Ignored => {
+                    Some(Message::ToggleBudgetOverlay)
+                }
+                iced_native::Event::Keyboard(iced_native::keyboard::Event::KeyPressed {
+                    key_code: iced_native::keyboard::KeyCode::S,
+                    modifiers,
+                }) if modifiers.is_empty() && status == iced_native::event::Status::Ignored => {
+                    Some(Message::ToggleSettings)
+                }
+                iced_native::Event::Keyboard(iced_native::keyboard::Event::KeyPressed {
+                    key_code,
+                    modifiers,
+                }) => gui::Navigation::of(key_code, modifiers, status)
+                    .map(Message::Navigate)
+                    .or(Some(Message::Activity)),
+                iced_native::Event::Mouse(_) | iced_native::Event::Touch(_) => {
+                    Some(Message::Activity)
+                }
+                _ => None,
+            }),
+        ])
+    }
+
+    fn should_exit(&self) -> bool {
+        self.exit
     }
 
     #[rustfmt::skip]
-    fn view(&mut self) -> iced::Element<Self::Message> {
-        let content = iced::Column::new()
-            .push(
-                iced::Row::new()
-                    .padding(PADDING)
-                    .push(iced::Text::new(format!(
-                        "Comparisons: {}",
-                        self.sorter.comparisons()
-                    )))
-                    .push(iced::Space::new(
-                        iced::Length::Units(100),
-                        iced::Length::Shrink,
-                    ))
-                    .push(iced::Text::new(format!(
-                        "Reads: {}",
-                        self.sorter.reads()
-                    )))
-                    .push(iced::Space::new(
-                        iced::Length::Units(50),
-                        iced::Length::Shrink,
-                    ))
-                    .push(iced::Text::new(format!(
-                        "Writes: {}",
-                        self.sorter.writes()
-                    )))
-                    .push(iced::Space::new(
-                        iced::Length::Fill,
-                        iced::Length::Shrink
-                    ))
-                    .push(
-                        iced::Toggler::new(self.muted, String::from("Mute  "), Message::Mute)
-                            .width(iced::Length::Shrink),
-                    ),
-            )
-            .push(self.sorter.array_view())
-            .push(
-                self.controls.view(
-                    self.sorter.sort(),
-                    self.playing,
-                    self.speed,
-                    MAX_SPEED,
-                    self.changed_numbers
-                        .map_or(String::new(), |x| x.to_string()),
-                    self.sorter.get_view(),
-                ),
-            );
-
-        iced::Container::new(content).into()
+    fn view(&mut self) -> iced::Element<'_, Self::Message> {
+        self.counters.frames_rendered += 1;
+        // Read at once, before the widgets borrow their parts of the state
+        let parts = self.parts();
+        let array = match self.pixels {
+            Some(ref pixels) => self.read_array(|array| pixels.view(array)),
+            None => self.array_view(),
+        };
+        let (settings, mut banners) = (&self.settings, parts.banners);
+        if let Some(stalled) = self.sorter.wedged().filter(|_| self.playing) {
+            banners.push(self.wedged_banner.view(stalled, settings));
+        }
+        let mut timeline = None;
+        if let Some((position, len)) = parts.timeline {
+            timeline = Some(self.timeline.view(position, len, settings));
+        }
+
+        gui::Layout {
+            banners,
+            stats: self.stats_panel.view(&parts.stats, settings),
+            rows: parts.rows,
+            array,
+            settings_panel: self.settings_panel.view(settings),
+            settings_open: parts.settings_open,
+            info_panel: self.info_panel
+                .view(self.sorter.sort(), parts.measured, parts.distances, settings),
+            timeline,
+            history: self.history.view(self.playing, settings),
+            event_log: self.event_log.view(&parts.events, settings),
+            controls: self.controls
+                .view(&self.sorter, self.registry.algorithms(), &parts.controls, settings),
+            trace_controls: self.trace_controls.view(&parts.trace, settings),
+            image_controls: self.image_controls
+                .view(self.playing, &self.image_path, self.pixels.as_ref(), settings),
+            compact: parts.compact,
+            collapsed: parts.collapsed,
+        }
+        .view(settings.theme)
     }
 }
 
 impl SortingAnimations {
-    fn initialize_sort(&mut self, sort: sorting::Sort) {
-        self.reset_stats = true;
+    fn with_audio(settings: Settings, audio: Box<dyn audio::Sonifier>) -> SortingAnimations {
+        let initial = INITIAL_NUMBERS.clamp(settings.min_numbers, settings.max_numbers);
+        let mut animations = SortingAnimations {
+            controls: gui::Controls::default(),
+            trace_controls: gui::TraceControls::default(),
+            image_controls: gui::ImageControls::default(),
+            wedged_banner: gui::WedgedBanner::default(),
+            stats_panel: gui::StatsPanel,
+            timeline: gui::Timeline::default(),
+            history: gui::History::default(),
+            event_log: gui::EventLog::default(),
+            info_panel: gui::InfoPanel::default(),
+            settings_panel: gui::SettingsPanel::default(),
+            sorter: sorting::Sorter::new(array::ArrayState::new(initial)),
+            registry: sorting::Registry::default(),
+            replay: None,
+            reference: None,
+            playing: false,
+            run: Run::Ready,
+            view: gui::View::default(),
+            structure: true,
+            scale: gui::Scale::default(),
+            hovered: None,
+            pinned: None,
+            shift: false,
+            speed: 1,
+            auto_speed: settings.auto_speed,
+            suggest_speed: true,
+            play_warned: false,
+            animate_arrangements: true,
+            pause_on_annotation: false,
+            annotation_filter: String::new(),
+            pause_hits: 0,
+            interruption: gui::Interruption::default(),
+            pending: None,
+            estimate: None,
+            started: None,
+            pace: None,
+            numbers_input: {
+                let mut input = gui::NumberInput::new(settings.min_numbers..=settings.max_numbers);
+                input.set_value(Some(initial));
+                input
+            },
+            max_value_input: gui::NumberInput::new(1..=MAX_VALUE),
+            range_start_input: gui::NumberInput::new(0..=usize::MAX),
+            range_end_input: gui::NumberInput::new(0..=usize::MAX),
+            muted: true,
+            trace_path: String::from(TRACE_PATH),
+            image_path: String::from(IMAGE_PATH),
+            screenshots: false,
+            screenshots_path: String::from(SCREENSHOTS_PATH),
+            milestone: None,
+            pixels: None,
+            last_input: time::Instant::now(),
+            attract: None,
+            error: None,
+            exit: false,
+            counters: Counters {
+                launched: time::Instant::now(),
+                runs_completed: 0,
+                frames_rendered: 0,
+            },
+            #[cfg(feature = "metrics")]
+            metrics_server: None,
+            settings,
+            next_tick_interval: None,
+            config: None,
+            audio,
+        };
+        animations
+            .sorter
+            .set_tick_interval(animations.settings.tick_interval);
+        animations.sorter.set_ramp(animations.settings.ramp);
+        animations
+            .sorter
+            .set_highlights(animations.settings.highlights);
+        animations.initialize_sort(sorting::Algorithm::default());
+        #[cfg(feature = "scripting")]
+        animations.load_scripts(std::path::Path::new(SCRIPTS_DIR));
+        #[cfg(feature = "metrics")]
+        if let Some(port) = animations.settings.metrics_port {
+            match metrics::Server::start(
+                animations.settings.metrics_address,
+                port,
+                animations.metrics(true),
+            ) {
+                Ok(server) => animations.metrics_server = Some(server),
+                Err(error) => {
+                    animations.error = Some(
+                        animations
+                            .settings
+                            .language
+                            .format("error.metrics", &[&error]),
+                    );
+                }
+            }
+        }
+
+        animations
+    }
+
+    fn initialize_sort(&mut self, sort: sorting::Algorithm) {
+        self.run = Run::Ready;
+        self.replay = None;
+        self.estimate = None;
+        self.started = None;
         self.playing = false;
-        self.sink.pause();
+        self.play_warned = false;
+        self.audio.set_enabled(false);
+
+        if let Some(tick_interval) = self.next_tick_interval.take() {
+            self.settings.tick_interval = tick_interval;
+            self.sorter.set_tick_interval(tick_interval);
+        }
 
         self.sorter.kill_sort();
         self.sorter.clear_step();
         self.sorter.set_sort(sort);
         self.sorter.start_sort();
     }
+
+    /// The settings as they are now, with those the controls change too and a tick interval
+    /// waiting for the next run.
+    fn current_settings(&self) -> Settings {
+        Settings {
+            tick_interval: self
+                .next_tick_interval
+                .unwrap_or(self.settings.tick_interval),
+            highlights: self.sorter.highlights(),
+            ramp: self.sorter.ramp(),
+            auto_speed: self.auto_speed,
+            ..self.settings.clone()
+        }
+    }
+
+    /// Applies the settings of the settings panel and saves them to the config file.
+    ///
+    /// Most apply at once, but a new tick interval waits for the next run, unless none started.
+    fn apply_settings(&mut self, settings: Settings) {
+        self.audio.apply(&settings);
+        self.sorter.set_highlights(settings.highlights);
+        self.sorter.set_ramp(settings.ramp);
+        if self.auto_speed != settings.auto_speed {
+            self.auto_speed = settings.auto_speed;
+            self.pace = None;
+        }
+        self.numbers_input
+            .set_bounds(settings.min_numbers..=settings.max_numbers);
+
+        let tick_interval = settings.tick_interval;
+        self.settings = Settings {
+            tick_interval: self.settings.tick_interval,
+            ..settings
+        };
+        self.next_tick_interval = Some(tick_interval)
+            .filter(|&tick_interval| tick_interval != self.settings.tick_interval);
+        if self.next_tick_interval.is_some() && self.run == Run::Ready && self.replay.is_none() {
+            self.initialize_sort(self.sorter.sort().clone());
+        }
+
+        if let Some(ref config) = self.config {
+            if let Err(error) = self.current_settings().save(config) {
+                self.error = Some(
+                    self.settings
+                        .language
+                        .format("error.save_settings", &[&error]),
+                );
+            }
+        }
+    }
+
+    /// Initializes the numbers as input, or else as many as there are, showing the values applied.
+    ///
+    /// The numbers stay as they are while either field can't be applied.
+    fn select_numbers(&mut self) {
+        let Some((size, max_value)) = self.input_numbers() else {
+            return;
+        };
+        self.numbers_input.set_value(Some(size));
+        self.max_value_input.set_value(max_value);
+
+        self.sorter.kill_sort();
+        self.sorter.initialize(size, max_value.unwrap_or(size));
+        self.initialize_sort(self.sorter.sort().clone());
+    }
+
+    /// The size and largest value of the numbers as input, none while either can't be applied.
+    fn input_numbers(&self) -> Option<(usize, Option<usize>)> {
+        let (Ok(size), Ok(max_value)) = (self.numbers_input.value(), self.max_value_input.value())
+        else {
+            return None;
+        };
+
+        Some((
+            size.unwrap_or_else(|| self.read_array(array::ArrayState::size)),
+            max_value,
+        ))
+    }
+
+    /// Sets up the sort to run on the pixels of the selected column of the loaded image.
+    fn load_pixels(&mut self) {
+        if let Some(numbers) = self.pixels.as_ref().map(gui::Pixels::numbers) {
+            self.initialize_sort(self.sorter.sort().clone());
+
+            self.sorter.load_numbers(numbers);
+            self.pinned = None;
+        }
+    }
+
+    /// Sorts the range as input from now on, clamped to the numbers, starting over on the numbers
+    /// as they are.
+    ///
+    /// Without either end, the sort sorts all the numbers again.
+    fn select_range(&mut self) {
+        let (Ok(start), Ok(end)) = (self.range_start_input.value(), self.range_end_input.value())
+        else {
+            return;
+        };
+        self.range_start_input.set_value(start);
+        self.range_end_input.set_value(end);
+
+        let range = match (start, end) {
+            (None, None) => None,
+            (start, end) => Some(start.unwrap_or(0)..end.unwrap_or(usize::MAX)),
+        };
+
+        self.sorter.set_range(range);
+        self.initialize_sort(self.sorter.sort().clone());
+    }
+
+    /// Performs the action, unless it would cut the playing run short. Then it has to be done
+    /// again to be confirmed or, if queued, waits for the run to stop.
+    fn interrupt(&mut self, action: gui::Interrupting) {
+        let confirmed = self.pending.take().is_some_and(|pending| {
+            !pending.queued && !pending.expired() && pending.action == action
+        });
+
+        if !self.playing || confirmed {
+            self.perform(action);
+        } else {
+            self.pending = Some(gui::Pending {
+                action,
+                since: time::Instant::now(),
+                queued: self.interruption == gui::Interruption::Queue,
+            });
+        }
+    }
+
+    fn perform(&mut self, action: gui::Interrupting) {
+        match action {
+            gui::Interrupting::Shuffle => self.arrange(array::Arrangement::Shuffled),
+            gui::Interrupting::Reverse => self.arrange(array::Arrangement::Reversed),
+            gui::Interrupting::Sort(sort) => {
+                if self.suggest_speed {
+                    let size = self.sorter.range().len() as u64;
+                    let max_speed = self.settings.max_speed;
+                    let speed = sort.suggested_speed(size) * max_speed as f32;
+                    self.speed = (speed.round() as u32).clamp(1, max_speed);
+                    self.pace = None;
+                }
+
+                self.initialize_sort(sort);
+            }
+            gui::Interrupting::Numbers => {
+                self.pixels = None;
+                self.select_numbers();
+            }
+            gui::Interrupting::Range => self.select_range(),
+        }
+    }
+
+    /// Moves the focus through the controls, or acts on the focused one with the message its mouse
+    /// interaction sends, if it is enabled.
+    fn navigate(&mut self, navigation: gui::Navigation) -> iced::Command<Message> {
+        use gui::{Focus, Navigation};
+
+        let focus = match (navigation, self.controls.focus()) {
+            (Navigation::Next, _) => {
+                self.controls.move_focus(true);
+                return iced::Command::none();
+            }
+            (Navigation::Previous, _) => {
+                self.controls.move_focus(false);
+                return iced::Command::none();
+            }
+            (Navigation::Leave, _) | (_, None) => {
+                self.controls.leave_focus();
+                return iced::Command::none();
+            }
+            (_, Some(focus)) => focus,
+        };
+        // Pick lists and the speed go one value further, wrapping around the pick lists
+        let step = |index: usize, len: usize| match navigation {
+            Navigation::Increase => (index + 1) % len,
+            _ => (index + len - 1) % len,
+        };
+        let idle = !self.playing && !self.sorter.arranging();
+
+        let message = match (navigation, focus) {
+            (Navigation::Activate, Focus::Play) if !self.sorter.arranging() => Message::Play,
+            (Navigation::Activate, Focus::Step) if idle => Message::Step,
+            (Navigation::Activate, Focus::More) => Message::ToggleControls,
+            (Navigation::Activate, Focus::Shuffle) if !self.sorter.arranging() => Message::Shuffle,
+            (Navigation::Activate, Focus::Reverse) if !self.sorter.arranging() => Message::Reverse,
+            (Navigation::Activate, Focus::Sorted) if idle => Message::Order,
+            (Navigation::Activate, Focus::AllEqual) if idle => Message::Equalize,
+            (Navigation::Activate, Focus::WorstCase) if idle => {
+                match self.sorter.sort().worst_case() {
+                    Some(arrangement) => Message::ArrangementSelected(arrangement),
+                    None => return iced::Command::none(),
+                }
+            }
+            (Navigation::Activate, Focus::Mute) => Message::Mute(!self.muted),
+            (Navigation::Increase | Navigation::Decrease, Focus::Sort) => {
+                let algorithms = self.registry.algorithms();
+                let index = algorithms
+                    .iter()
+                    .position(|algorithm| algorithm == self.sorter.sort())
+                    .unwrap_or(0);
+                Message::SortSelected(algorithms[step(index, algorithms.len())].clone())
+            }
+            (Navigation::Increase | Navigation::Decrease, Focus::View) => {
+                let views = gui::View::values();
+                let index = views.iter().position(|&view| view == self.view);
+                Message::ViewSelected(views[step(index.unwrap_or(0), views.len())])
+            }
+            (Navigation::Increase, Focus::Speed) => {
+                Message::SpeedSelected((self.speed + 1).min(self.settings.max_speed))
+            }
+            (Navigation::Decrease, Focus::Speed) => {
+                Message::SpeedSelected(self.speed.saturating_sub(1).max(1))
+            }
+            _ => return iced::Command::none(),
+        };
+
+        self.update(message)
+    }
+
+    /// Carries out a command of the control socket with the messages of the controls, answering
+    /// it once done.
+    fn control(&mut self, request: control::Request) {
+        use control::{Command, Response};
+
+        let paused = |animations: &SortingAnimations| {
+            if animations.playing {
+                Err(String::from("the run has to be paused first"))
+            } else {
+                Ok(())
+            }
+        };
+        let result = match request.command {
+            Command::SetSort(ref name) => match self.registry.get(name) {
+                Some(sort) => {
+                    self.update(Message::SortSelected(sort.clone()));
+                    Ok(())
+                }
+                None => Err(format!("no sort named `{name}`")),
+            },
+            Command::SetNumbers(count) => {
+                self.update(Message::NumbersInput(count.to_string()));
+                match self.input_numbers() {
+                    Some(_) => {
+                        self.update(Message::NumbersSelected);
+                        Ok(())
+                    }
+                    None => Err(String::from("the largest value typed in is invalid")),
+                }
+            }
+            Command::Shuffle(None) => {
+                self.update(Message::Shuffle);
+                Ok(())
+            }
+            Command::Shuffle(Some(seed)) => paused(self).map(|()| {
+                self.update(Message::ShuffleSeeded(seed));
+            }),
+            Command::Play if self.sorter.arranging() => {
+                Err(String::from("the numbers are still being rearranged"))
+            }
+            // Asked for explicitly, a run taking too long plays without a warning
+            Command::Play => {
+                if !self.playing {
+                    self.begin_run();
+                    self.play();
+                }
+                Ok(())
+            }
+            Command::Pause => {
+                if self.playing {
+                    self.update(Message::Play);
+                }
+                Ok(())
+            }
+            // Stepping on stops once the run is done
+            Command::Step(count) => paused(self).map(|()| {
+                for _ in 0..count {
+                    self.update(Message::Step);
+                    if !self.alive() {
+                        break;
+                    }
+                }
+            }),
+            Command::SetSpeed(speed) if (1..=self.settings.max_speed).contains(&speed) => {
+                self.update(Message::SpeedSelected(speed));
+                Ok(())
+            }
+            Command::SetSpeed(_) => Err(format!(
+                "the speed has to be from 1 to {}",
+                self.settings.max_speed
+            )),
+            Command::QueryStats => {
+                let stats = Box::new(self.read_array(array::ArrayState::stats_snapshot));
+                let playing = self.playing;
+                return request.respond(Response::Stats { stats, playing });
+            }
+        };
+
+        request.respond(match result {
+            Ok(()) => Response::Ok,
+            Err(message) => Response::Error(message),
+        });
+    }
+
+    fn play(&mut self) {
+        self.playing = true;
+        self.pace = None;
+        // Continues until the next annotation paused on
+        self.pause_hits = self.read_array(array::ArrayState::pause_hits);
+        self.sorter.resume().unwrap_or_default();
+    }
+
+    fn pause(&mut self) {
+        self.playing = false;
+        self.pace = None;
+        self.audio.set_enabled(false);
+        if self.replay.is_none() {
+            self.sorter.drain().unwrap_or_default();
+        }
+        self.stopped();
+    }
+
+    /// Pauses on the annotations containing the filter, if enabled.
+    fn apply_annotation_filter(&mut self) {
+        let filter = Some(self.annotation_filter.as_str()).filter(|_| self.pause_on_annotation);
+        self.sorter
+            .operate_array(|array| array.set_pause_on(filter));
+        // A sort held on an annotation the gui didn't see yet would never be paused
+        self.pause_hits = self.read_array(array::ArrayState::pause_hits);
+        self.sorter.resume().unwrap_or_default();
+    }
+
+    /// Performs the action queued while the run played, dropping one left unconfirmed.
+    fn stopped(&mut self) {
+        if let Some(pending) = self.pending.take().filter(|pending| pending.queued) {
+            self.perform(pending.action);
+        }
+    }
+
+    /// Rearranges the numbers and sets up the sort to run on them, once shuffling or reversing
+    /// them is animated if enabled.
+    fn arrange(&mut self, arrangement: array::Arrangement) {
+        self.initialize_sort(self.sorter.sort().clone());
+
+        if self.animate_arrangements {
+            self.sorter.arrange_animated(arrangement);
+        } else {
+            self.sorter.arrange(arrangement);
+        }
+    }
+
+    /// Starts a new run on its first operation, resetting the stats of the previous one.
+    ///
+    /// Replaying a finished run sorts a freshly shuffled array instead of the sorted one, or the
+    /// reference run's input if there is one.
+    ///
+    /// Replaying a finished trace starts it over instead.
+    fn begin_run(&mut self) {
+        if self.run == Run::Finished {
+            match self.replay {
+                Some(ref mut replay) => {
+                    replay.rewind();
+                    self.run = Run::Ready;
+                }
+                None => {
+                    self.initialize_sort(self.sorter.sort().clone());
+                    match self.reference {
+                        Some(ref reference) => {
+                            self.sorter.load_numbers(reference.trace().numbers.clone())
+                        }
+                        None => self.sorter.shuffle(),
+                    }
+                }
+            }
+        }
+
+        if self.run == Run::Ready {
+            if self.replay.is_none() {
+                self.sorter.reset_stats();
+                self.estimate = self.estimate_run();
+                self.started = Some((
+                    time::Instant::now(),
+                    self.read_array(array::ArrayState::shared_numbers),
+                ));
+            }
+            self.pace = None;
+            self.run = Run::Live;
+            self.milestone = None;
+        }
+
+        self.capture_milestones();
+    }
+
+    /// Replays the reference run to as many operations as the current run performed.
+    fn follow_reference(&mut self) {
+        let operations = match self.replay {
+            Some(ref replay) => replay.position(),
+            None => self.sorter.operations() as usize,
+        };
+
+        if let Some(ref mut reference) = self.reference {
+            if reference.position() != operations {
+                reference.seek(operations);
+            }
+        }
+    }
+
+    /// The numbers of the reference run at the current run's operations, if one is set.
+    fn reference_numbers(&self) -> Option<std::sync::Arc<Vec<usize>>> {
+        self.reference
+            .as_ref()
+            .map(|reference| reference.read_array(array::ArrayState::shared_numbers))
+    }
+
+    /// Adds the finished run to the history, unless it was a replay or its sort panicked.
+    fn complete_run(&mut self) {
+        let Some((started, numbers)) = self.started.take() else {
+            return;
+        };
+        if self.sorter.panic().is_some() {
+            return;
+        }
+
+        // The runs of the attract mode aren't the user's
+        if self.attract.is_some() {
+            return;
+        }
+
+        let stats = self.sorter.stats_snapshot();
+        self.history.push(gui::CompletedRun {
+            sort: self.sorter.sort().clone(),
+            numbers,
+            seed: self.sorter.seed(),
+            comparisons: stats.comparisons,
+            reads: stats.reads,
+            writes: stats.writes,
+            duration: started.elapsed(),
+            distances: stats.distances,
+            peak_aux_bytes: stats.space.peak_aux_bytes,
+            peak_depth: stats.space.peak_depth,
+        });
+        self.counters.runs_completed += 1;
+    }
+
+    /// Enters the attract mode once the animations were left stopped without input for long
+    /// enough, and plays another run in it once one stopped.
+    ///
+    /// A run paused halfway, or anything else the user left set up, is never cut short.
+    fn attract(&mut self) {
+        if self.attract.is_some() {
+            if !self.playing && !self.sorter.arranging() {
+                self.play_attract_run();
+            }
+            return;
+        }
+
+        let idle = self
+            .settings
+            .attract_after
+            .is_some_and(|after| self.last_input.elapsed() >= after);
+        let stopped = !self.playing
+            && self.run != Run::Live
+            && !self.sorter.arranging()
+            && self.pending.is_none()
+            && self.replay.is_none()
+            && self.reference.is_none()
+            && self.pixels.is_none();
+
+        if idle && stopped {
+            self.attract = Some(AttractSnapshot {
+                sort: self.sorter.sort().clone(),
+                view: self.view,
+                speed: self.speed,
+                numbers: self.read_array(|array| array.numbers().to_vec()),
+                seed: self.sorter.seed(),
+            });
+            self.audio.set_volume(self.settings.volume * ATTRACT_VOLUME);
+            self.play_attract_run();
+        }
+    }
+
+    /// Plays a random sort on a random number of shuffled numbers in a random view, at the speed
+    /// suggested for it.
+    fn play_attract_run(&mut self) {
+        use rand::{seq::SliceRandom, Rng};
+
+        let mut rng = rand::thread_rng();
+        let (sort, max_size) = *self
+            .attract_candidates()
+            .choose(&mut rng)
+            .unwrap_or(&(sorting::Sort::default(), *ATTRACT_SIZES.start()));
+        let size = rng.gen_range(*ATTRACT_SIZES.start()..=max_size);
+        self.view = *gui::View::values().choose(&mut rng).unwrap_or(&self.view);
+        self.speed = self.attract_speed(sort, size);
+
+        self.sorter.kill_sort();
+        self.sorter.initialize(size, size);
+        self.initialize_sort(sort.into());
+        self.sorter.shuffle();
+        self.begin_run();
+        self.play();
+    }
+
+    /// The sorts the attract mode picks from, each with the most numbers of `ATTRACT_SIZES` it is
+    /// estimated to sort within `ATTRACT_DURATION`, leaving out those too slow for even the fewest.
+    fn attract_candidates(&self) -> Vec<(sorting::Sort, usize)> {
+        sorting::Sort::VALUES
+            .iter()
+            .filter_map(|&sort| {
+                let max_size = ATTRACT_SIZES
+                    .rev()
+                    .find(|&size| self.attract_duration(sort, size) <= ATTRACT_DURATION)?;
+                Some((sort, max_size))
+            })
+            .collect()
+    }
+
+    /// The speed suggested for sorting the numbers, in steps of the slider.
+    fn attract_speed(&self, sort: sorting::Sort, size: usize) -> u32 {
+        let max_speed = self.settings.max_speed;
+        let speed = sort.suggested_speed(size as u64) * max_speed as f32;
+
+        (speed.round() as u32).clamp(1, max_speed)
+    }
+
+    /// How long the attract mode is estimated to take sorting the shuffled numbers.
+    fn attract_duration(&self, sort: sorting::Sort, size: usize) -> time::Duration {
+        let size = size as u64;
+
+        self.duration_at(
+            sort.estimated_operations(size, sorting::Distribution::Shuffled),
+            sort.calculate_max_ticks(size),
+            self.attract_speed(sort, size as usize),
+        )
+    }
+
+    /// Restores the controls the user left, with the numbers as they were.
+    fn leave_attract(&mut self) {
+        let Some(snapshot) = self.attract.take() else {
+            return;
+        };
+
+        self.audio.set_volume(self.settings.volume);
+        self.view = snapshot.view;
+        self.speed = snapshot.speed;
+        self.numbers_input.set_value(Some(snapshot.numbers.len()));
+        self.initialize_sort(snapshot.sort);
+        self.sorter.set_seed(snapshot.seed);
+        self.sorter.load_numbers(snapshot.numbers);
+        self.sorter.reset_stats();
+    }
+
+    /// Captures a screenshot at every milestone the run reached since the last one, if enabled.
+    ///
+    /// Only runs whose operations can be estimated have milestones in between their start and
+    /// finish.
+    fn capture_milestones(&mut self) {
+        if !self.screenshots || self.run == Run::Ready {
+            return;
+        }
+
+        let operations = self.sorter.operations();
+        for milestone in Milestone::VALUES {
+            let reached = match milestone {
+                Milestone::Start => true,
+                Milestone::Finish => self.run == Run::Finished,
+                _ => self.estimate.is_some_and(|estimate| {
+                    operations as f32 >= milestone.progress() * estimate as f32
+                }),
+            };
+            if !reached || Some(milestone) <= self.milestone {
+                continue;
+            }
+
+            self.milestone = Some(milestone);
+            if let Err(error) = self.save_screenshot(milestone) {
+                // Stop trying, the directory won't get any more writable by the next tick
+                self.screenshots = false;
+                self.error = Some(
+                    self.settings
+                        .language
+                        .format("error.save_screenshot", &[&error]),
+                );
+
+                return;
+            }
+        }
+    }
+
+    /// Saves the array as a PNG named after the sort, the number of elements, the seed and the
+    /// milestone.
+    fn save_screenshot(&self, milestone: Milestone) -> std::io::Result<()> {
+        let (size, png) = self.read_array(|array| {
+            (
+                array.size(),
+                gui::screenshot(array, self.view, Style::of(&self.settings)),
+            )
+        });
+        let sort: String = self
+            .sorter
+            .sort()
+            .name()
+            .chars()
+            .map(|c| match c.is_alphanumeric() {
+                true => c.to_ascii_lowercase(),
+                false => '-',
+            })
+            .collect();
+        let name = format!("{sort}-{size}-seed{}-{milestone}.png", self.sorter.seed());
+
+        let dir = std::path::Path::new(&self.screenshots_path);
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(dir.join(name), png)
+    }
+
+    /// The expected operations of sorting the range of the numbers as they are, if the sort can
+    /// estimate them.
+    fn estimate_run(&self) -> Option<u64> {
+        let range = self.sorter.range();
+        let distribution =
+            self.read_array(|array| sorting::Distribution::of(&array.numbers()[range.clone()]));
+
+        self.sorter
+            .sort()
+            .estimated_operations(range.len() as u64, distribution)
+    }
+
+    /// How long the rest of the run takes at the current speed, if that is too long to play it
+    /// without a warning.
+    ///
+    /// Auto speed paces every run it can estimate to take `AUTO_SPEED_DURATION`, and a finished
+    /// run plays again on shuffled numbers.
+    fn infeasible(&self) -> Option<time::Duration> {
+        if self.auto_speed || self.replay.is_some() {
+            return None;
+        }
+
+        let size = self.sorter.range().len() as u64;
+        let (estimate, performed) = match self.run {
+            Run::Ready => (self.estimate_run()?, 0),
+            Run::Live => (self.estimate?, self.sorter.operations()),
+            Run::Finished => (
+                self.sorter
+                    .sort()
+                    .estimated_operations(size, sorting::Distribution::Shuffled)?,
+                0,
+            ),
+        };
+        let operations = estimate.saturating_sub(performed);
+
+        Some(self.duration_at(operations, self.sorter.sort().max_ticks(size), self.speed))
+            .filter(|&duration| duration > INFEASIBLE_DURATION)
+    }
+
+    /// How long the operations take at the speed, in steps of the slider, for a sort granted the
+    /// maximum ticks at full speed.
+    fn duration_at(&self, operations: u64, max_ticks: u64, speed: u32) -> time::Duration {
+        let speed = speed as f32 / self.settings.max_speed as f32;
+        let per_tick = ((speed * max_ticks as f32) as u64).max(1);
+        let duration = f64::max(
+            operations.div_ceil(per_tick) as f64 * self.settings.tick_interval.as_secs_f64(),
+            operations as f64 / MAX_OPERATIONS_PER_SECOND,
+        );
+
+        time::Duration::from_secs_f64(duration)
+    }
+
+    /// The operations the run performed against its estimate, once it started.
+    fn measured(&self) -> Option<gui::Measured> {
+        if self.run == Run::Ready {
+            return None;
+        }
+
+        Some(gui::Measured {
+            operations: self.sorter.operations(),
+            estimate: self.estimate?,
+            size: self.sorter.range().len(),
+        })
+    }
+
+    fn parts<'a>(&self) -> Parts<'a> {
+        let padding = self.settings.padding;
+        let mut banners = Vec::new();
+        if let Some(panic) = self.sorter.panic() {
+            banners.push(gui::error_banner(panic.to_string(), padding));
+        }
+        if let Some(ref error) = self.error {
+            banners.push(gui::error_banner(error.clone(), padding));
+        }
+
+        let mut rows = Vec::new();
+        if let Some(progress) = self.progress() {
+            rows.push(gui::progress(progress, self.remaining(), &self.settings));
+        }
+        let workers = self.read_array(array::ArrayState::workers);
+        if workers > 1 {
+            rows.push(gui::worker_legend(workers, &self.settings));
+        }
+
+        let timeline = match self.replay {
+            Some(ref replay) => Some((replay.position(), replay.len())),
+            None => self
+                .sorter
+                .trace()
+                .map(|trace| (trace.operations.len(), trace.operations.len())),
+        };
+
+        Parts {
+            banners,
+            stats: self.stats_snapshot(),
+            rows,
+            measured: self.measured(),
+            distances: self.read_array(|array| array.distances().copied()),
+            settings_open: self.settings_panel.is_open(),
+            timeline: timeline.map(|(position, len)| (position as u32, len as u32)),
+            events: gui::EventsSnapshot {
+                annotations: self.read_array(|array| array.annotations().clone()),
+                pause_on: self.pause_on_annotation,
+                filter: self.annotation_filter.clone(),
+            },
+            controls: self.controls_snapshot(),
+            trace: gui::TraceSnapshot {
+                playing: self.playing,
+                recording: self.sorter.recording(),
+                recorded: self.sorter.trace().is_some(),
+                path: self.trace_path.clone(),
+                reference: self.reference.as_ref().map(sorting::Replay::sort),
+                screenshots: self.screenshots,
+                screenshots_path: self.screenshots_path.clone(),
+            },
+            compact: self.controls.compact(),
+            collapsed: self.controls.collapsed(),
+        }
+    }
+
+    /// The index of the pinned element wherever the sort moved it.
+    fn pinned_index(&self) -> Option<usize> {
+        let value = self.pinned?;
+
+        self.read_array(|array| array.numbers().iter().position(|&number| number == value))
+    }
+
+    /// The status and stats of the run, with the element under the cursor or else the pinned
+    /// one.
+    fn stats_snapshot(&self) -> gui::StatsSnapshot {
+        let language = self.settings.language;
+        let pinned = self.pinned_index();
+        let inspected = self.read_array(|array| match (self.hovered, pinned) {
+            (Some(index), _) => array
+                .numbers()
+                .get(index)
+                .map(|value| format!("[{index}] = {value}")),
+            (None, Some(index)) => {
+                Some(language.format("stats.pinned", &[&index, &array.numbers()[index]]))
+            }
+            (None, None) => None,
+        });
+
+        gui::StatsSnapshot {
+            status: match self.replay {
+                Some(_) => language.format("run.replay", &[&self.run.localize(language)]),
+                None => self.run.localize(language),
+            },
+            phase: self.read_array(array::ArrayState::phase),
+            stats: self.read_array(array::ArrayState::stats_snapshot),
+            inspected,
+            compact: self.controls.compact(),
+            toggles: gui::Toggles {
+                auto_speed: self.auto_speed,
+                suggest_speed: self.suggest_speed,
+                deterministic: self.sorter.deterministic(),
+                animate_arrangements: self.animate_arrangements,
+                count_distances: self.read_array(|array| array.distances().is_some()),
+                tween_swaps: self.read_array(array::ArrayState::tweening),
+                muted: self.muted,
+                audio: self.audio.available(),
+                mute_focused: self.controls.focus() == Some(gui::Focus::Mute),
+            },
+        }
+    }
+
+    /// Whether the sort has a structure to draw, a heap it builds or markers it reports.
+    fn structured(&self) -> bool {
+        self.sorter
+            .sort()
+            .builtin()
+            .is_some_and(|sort| sort.builds_heap() || sort.reports_markers())
+    }
+
+    /// The array in the selected view, with the budget overlay while probing.
+    fn array_view(&self) -> iced::Element<'static, Message> {
+        let overlay = if self.sorter.probing() {
+            gui::budget_overlay(
+                self.sorter.tick_stats(),
+                self.sorter.sort().max_ticks(self.sorter.size() as u64),
+                self.settings.language,
+            )
+        } else {
+            Vec::new()
+        };
+        let (reference, pinned) = (self.reference_numbers(), self.pinned_index());
+        let structure = self.structure && self.structured();
+
+        self.read_array(|array| {
+            gui::array_view(
+                array,
+                reference,
+                self.view,
+                self.scale,
+                self.hovered,
+                pinned,
+                overlay,
+                structure,
+                &self.settings,
+            )
+        })
+    }
+
+    fn controls_snapshot(&self) -> gui::ControlsSnapshot {
+        gui::ControlsSnapshot {
+            playing: self.playing,
+            speed: self.speed,
+            numbers: self.numbers_input.clone(),
+            max_value: self.max_value_input.clone(),
+            range_start: self.range_start_input.clone(),
+            range_end: self.range_end_input.clone(),
+            view: self.view,
+            structure: self.structured().then_some(self.structure),
+            scale: self.scale,
+            interruption: self.interruption,
+            pending: self.pending.clone(),
+            infeasible: self.infeasible().filter(|_| !self.playing).map(|duration| {
+                gui::Infeasible {
+                    duration,
+                    warned: self.play_warned,
+                }
+            }),
+        }
+    }
+
+    /// The share of the estimated operations the live run performed, full once it finished.
+    fn progress(&self) -> Option<f32> {
+        let estimate = self.estimate?;
+
+        Some(match self.run {
+            Run::Finished => 1.0,
+            _ => (self.sorter.operations() as f32 / estimate.max(1) as f32).min(1.0),
+        })
+    }
+
+    /// How long the live run takes to perform the rest of its estimated operations at the pace
+    /// since it was last set.
+    fn remaining(&self) -> Option<time::Duration> {
+        let estimate = self.estimate?;
+        let (since, start) = self
+            .pace
+            .filter(|_| self.playing && self.run == Run::Live)?;
+        let operations = self.sorter.operations();
+        if operations <= start {
+            return None;
+        }
+
+        let rate = (operations - start) as f64 / since.elapsed().as_secs_f64();
+        Some(time::Duration::from_secs_f64(
+            estimate.saturating_sub(operations) as f64 / rate,
+        ))
+    }
+
+    /// Registers the scripts in the directory, reporting those that failed to load.
+    #[cfg(feature = "scripting")]
+    fn load_scripts(&mut self, dir: &std::path::Path) {
+        let mut errors = Vec::new();
+
+        for script in sorting::Script::load_dir(dir) {
+            match script {
+                Ok(script) => self.registry.register(sorting::Algorithm::new(script)),
+                Err(error) => errors.push(error.to_string()),
+            }
+        }
+
+        if !errors.is_empty() {
+            self.error = Some(errors.join("\n"));
+        }
+    }
+
+    /// What the metrics report about the animations, given whether the sort still runs, which
+    /// takes checking on its thread.
+    #[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+    fn metrics(&self, sort_alive: bool) -> metrics::Metrics {
+        metrics::Metrics {
+            uptime: self.counters.launched.elapsed(),
+            runs_completed: self.counters.runs_completed,
+            algorithm: self.sorter.sort().name().to_string(),
+            stats: self.read_array(array::ArrayState::stats_snapshot),
+            frames_rendered: self.counters.frames_rendered,
+            sort_alive,
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    fn publish_metrics(&mut self) {
+        let sort_alive = self.alive();
+        if let Some(ref server) = self.metrics_server {
+            server.publish(self.metrics(sort_alive));
+        }
+    }
+
+    /// Whether the sort or the loaded trace has operations left.
+    fn alive(&mut self) -> bool {
+        match self.replay {
+            Some(ref replay) => !replay.finished(),
+            None => self.sorter.alive(),
+        }
+    }
+
+    /// Reads the array of the loaded trace, or the sort's otherwise.
+    fn read_array<T>(&self, f: impl FnOnce(&array::ArrayState) -> T) -> T {
+        match self.replay {
+            Some(ref replay) => replay.read_array(f),
+            None => self.sorter.read_array(f),
+        }
+    }
+}
+
+/// Listens for the commands of the control socket on the port, for the rest of the session.
+fn control_socket(port: u16) -> iced::Subscription<Message> {
+    use iced::futures::{channel::mpsc, StreamExt};
+
+    enum State {
+        Starting(u16),
+        Listening(mpsc::UnboundedReceiver<control::Request>),
+        Failed,
+    }
+
+    iced_native::subscription::unfold(
+        std::any::TypeId::of::<control::Request>(),
+        State::Starting(port),
+        |state| async move {
+            match state {
+                State::Starting(port) => {
+                    let (sender, receiver) = mpsc::unbounded();
+                    match control::listen(port, move |request| {
+                        sender.unbounded_send(request).is_ok()
+                    }) {
+                        Ok(_) => (None, State::Listening(receiver)),
+                        Err(error) => (
+                            Some(Message::ControlFailed(error.to_string())),
+                            State::Failed,
+                        ),
+                    }
+                }
+                State::Listening(mut receiver) => match receiver.next().await {
+                    Some(request) => (Some(Message::Control(request)), State::Listening(receiver)),
+                    None => (None, State::Failed),
+                },
+                State::Failed => iced::futures::future::pending().await,
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell, rc};
+
+    /// Hears the volume set by the animations, in place of a speaker.
+    #[derive(Default)]
+    struct Listener(rc::Rc<cell::Cell<Option<f32>>>);
+
+    impl audio::Sonifier for Listener {
+        fn set_frequency(&mut self, _frequency: f32) {}
+
+        fn set_enabled(&mut self, _enabled: bool) {}
+
+        fn set_volume(&mut self, volume: f32) {
+            self.0.set(Some(volume));
+        }
+
+        fn set_waveform(&mut self, _waveform: sorting_animations::settings::Waveform) {}
+
+        fn available(&self) -> bool {
+            true
+        }
+    }
+
+    fn animations() -> SortingAnimations {
+        let mut animations =
+            SortingAnimations::with_audio(Settings::default(), Box::new(Listener::default()));
+        animations.update(Message::SpeedSelected(Settings::default().max_speed));
+        animations.update(Message::AnimateArrangements(false));
+        animations.update(Message::SuggestSpeed(false));
+
+        animations
+    }
+
+    fn stats(animations: &SortingAnimations) -> (u64, u64, u64) {
+        let stats = animations.sorter.stats_snapshot();
+
+        (stats.comparisons, stats.reads, stats.writes)
+    }
+
+    fn finish(animations: &mut SortingAnimations) {
+        animations.update(Message::Play);
+        while animations.run != Run::Finished {
+            animations.update(Message::Tick(time::Instant::now()));
+        }
+    }
+
+    /// Tabs to the control, failing if it can't be reached.
+    fn focus_on(animations: &mut SortingAnimations, focus: gui::Focus) {
+        for _ in 0..gui::Focus::ORDER.len() {
+            if animations.controls.focus() == Some(focus) {
+                return;
+            }
+            animations.update(Message::Navigate(gui::Navigation::Next));
+        }
+        panic!("{focus:?} can't be focused");
+    }
+
+    /// What a scenario feeds the update loop.
+    enum Input {
+        Send(Message),
+        Ticks(usize),
+        /// Plays, ticking until the run finishes.
+        Finish,
+        /// Takes note of the stats, for the scenario to expect them kept.
+        Mark,
+    }
+
+    /// How the stats are expected to be.
+    #[derive(Clone, Copy, Debug)]
+    enum Counts {
+        Fresh,
+        Counted,
+        /// As they were at the last mark.
+        Kept,
+    }
+
+    /// What the animations look like after a scenario, leaving out what isn't given.
+    #[derive(Default)]
+    struct Expect {
+        playing: Option<bool>,
+        run: Option<Run>,
+        stats: Option<Counts>,
+        operations: Option<u64>,
+        size: Option<usize>,
+        sorted: Option<bool>,
+        numbers_input: Option<usize>,
+        muted: Option<bool>,
+        sort: Option<sorting::Algorithm>,
+    }
+
+    /// Messages fed to the update loop in order, with what they lead to.
+    struct Scenario {
+        name: &'static str,
+        inputs: Vec<Input>,
+        expect: Expect,
+    }
+
+    impl Scenario {
+        fn play(self) {
+            let Scenario {
+                name,
+                inputs,
+                expect,
+            } = self;
+            let mut animations = animations();
+            let mut marked = stats(&animations);
+
+            for input in inputs {
+                match input {
+                    Input::Send(message) => {
+                        animations.update(message);
+                    }
+                    Input::Ticks(ticks) => {
+                        for _ in 0..ticks {
+                            animations.update(Message::Tick(time::Instant::now()));
+                        }
+                    }
+                    Input::Finish => finish(&mut animations),
+                    Input::Mark => marked = stats(&animations),
+                }
+            }
+
+            if let Some(playing) = expect.playing {
+                assert_eq!(animations.playing, playing, "{name}: playing");
+            }
+            if let Some(run) = expect.run {
+                assert_eq!(animations.run, run, "{name}: run");
+            }
+            match expect.stats {
+                Some(Counts::Fresh) => assert_eq!(stats(&animations), (0, 0, 0), "{name}: stats"),
+                Some(Counts::Counted) => assert_ne!(stats(&animations), (0, 0, 0), "{name}: stats"),
+                Some(Counts::Kept) => assert_eq!(stats(&animations), marked, "{name}: stats"),
+                None => {}
+            }
+            if let Some(operations) = expect.operations {
+                assert_eq!(
+                    animations.sorter.operations(),
+                    operations,
+                    "{name}: operations"
+                );
+            }
+            let numbers = animations.read_array(|array| array.numbers().to_vec());
+            if let Some(size) = expect.size {
+                assert_eq!(numbers.len(), size, "{name}: size");
+            }
+            if let Some(sorted) = expect.sorted {
+                assert_eq!(numbers.is_sorted(), sorted, "{name}: sorted");
+            }
+            if let Some(value) = expect.numbers_input {
+                assert_eq!(
+                    animations.numbers_input.value(),
+                    Ok(Some(value)),
+                    "{name}: input"
+                );
+            }
+            if let Some(muted) = expect.muted {
+                assert_eq!(animations.muted, muted, "{name}: muted");
+            }
+            if let Some(sort) = expect.sort {
+                assert_eq!(animations.sorter.sort(), &sort, "{name}: sort");
+            }
+        }
+    }
+
+    #[test]
+    fn message_sequences_lead_to_their_states() {
+        use Input::{Finish, Mark, Send, Ticks};
+
+        let scenarios = [
+            Scenario {
+                name: "stepping begins a live run",
+                inputs: vec![
+                    Send(Message::Deterministic(true)),
+                    Send(Message::Shuffle),
+                    Send(Message::Step),
+                ],
+                expect: Expect {
+                    playing: Some(false),
+                    run: Some(Run::Live),
+                    operations: Some(1),
+                    ..Expect::default()
+                },
+            },
+            Scenario {
+                name: "deterministic steps are single operations",
+                inputs: vec![
+                    Send(Message::Deterministic(true)),
+                    Send(Message::Shuffle),
+                    Send(Message::Step),
+                    Send(Message::Step),
+                    Send(Message::Step),
+                ],
+                expect: Expect {
+                    operations: Some(3),
+                    stats: Some(Counts::Counted),
+                    ..Expect::default()
+                },
+            },
+            Scenario {
+                name: "switching the engine starts over",
+                inputs: vec![
+                    Send(Message::Deterministic(true)),
+                    Send(Message::Shuffle),
+                    Send(Message::Step),
+                    Send(Message::Deterministic(false)),
+                ],
+                expect: Expect {
+                    run: Some(Run::Ready),
+                    ..Expect::default()
+                },
+            },
+            Scenario {
+                name: "play pauses the second time",
+                inputs: vec![
+                    Send(Message::Shuffle),
+                    Send(Message::Play),
+                    Send(Message::Play),
+                ],
+                expect: Expect {
+                    playing: Some(false),
+                    run: Some(Run::Live),
+                    ..Expect::default()
+                },
+            },
+            Scenario {
+                name: "finished runs are sorted and stopped",
+                inputs: vec![Send(Message::Shuffle), Finish],
+                expect: Expect {
+                    playing: Some(false),
+                    run: Some(Run::Finished),
+                    stats: Some(Counts::Counted),
+                    sorted: Some(true),
+                    size: Some(INITIAL_NUMBERS),
+                    ..Expect::default()
+                },
+            },
+            Scenario {
+                name: "ticks leave the stats of a finished run",
+                inputs: vec![Send(Message::Shuffle), Finish, Mark, Ticks(10)],
+                expect: Expect {
+                    run: Some(Run::Finished),
+                    stats: Some(Counts::Kept),
+                    ..Expect::default()
+                },
+            },
+            Scenario {
+                name: "arranging keeps the stats until the run begins",
+                inputs: vec![
+                    Send(Message::Shuffle),
+                    Finish,
+                    Mark,
+                    Send(Message::Reverse),
+                    Send(Message::Order),
+                ],
+                expect: Expect {
+                    run: Some(Run::Ready),
+                    stats: Some(Counts::Kept),
+                    sorted: Some(true),
+                    ..Expect::default()
+                },
+            },
+            Scenario {
+                name: "playing a finished run starts over",
+                inputs: vec![Send(Message::Shuffle), Finish, Send(Message::Play)],
+                expect: Expect {
+                    playing: Some(true),
+                    run: Some(Run::Live),
+                    stats: Some(Counts::Fresh),
+                    ..Expect::default()
+                },
+            },
+            Scenario {
+                name: "killing a run finishes it",
+                inputs: vec![
+                    Send(Message::Shuffle),
+                    Send(Message::Play),
+                    Send(Message::Kill),
+                ],
+                expect: Expect {
+                    playing: Some(false),
+                    run: Some(Run::Finished),
+                    ..Expect::default()
+                },
+            },
+            Scenario {
+                name: "unchanged numbers keep the run",
+                inputs: vec![
+                    Send(Message::Deterministic(true)),
+                    Send(Message::Shuffle),
+                    Send(Message::Step),
+                    Send(Message::Step),
+                    Mark,
+                    Send(Message::NumbersInput(format!(" {INITIAL_NUMBERS} "))),
+                    Send(Message::NumbersSelected),
+                ],
+                expect: Expect {
+                    run: Some(Run::Live),
+                    stats: Some(Counts::Kept),
+                    size: Some(INITIAL_NUMBERS),
+                    ..Expect::default()
+                },
+            },
+            Scenario {
+                name: "submitted empty, the numbers keep their size",
+                inputs: vec![
+                    Send(Message::NumbersInput(String::from("5000"))),
+                    Send(Message::NumbersSelected),
+                    Send(Message::NumbersInput(String::new())),
+                    Send(Message::NumbersSelected),
+                ],
+                expect: Expect {
+                    size: Some(5000),
+                    numbers_input: Some(5000),
+                    ..Expect::default()
+                },
+            },
+            Scenario {
+                name: "an invalid largest value applies nothing",
+                inputs: vec![
+                    Send(Message::NumbersInput(String::from("2000"))),
+                    Send(Message::MaxValueInput(String::from("abc"))),
+                    Send(Message::NumbersSelected),
+                ],
+                expect: Expect {
+                    size: Some(INITIAL_NUMBERS),
+                    ..Expect::default()
+                },
+            },
+            Scenario {
+                name: "muting leaves the run playing",
+                inputs: vec![
+                    Send(Message::Mute(false)),
+                    Send(Message::Shuffle),
+                    Send(Message::Play),
+                    Send(Message::Mute(true)),
+                ],
+                expect: Expect {
+                    playing: Some(true),
+                    muted: Some(true),
+                    ..Expect::default()
+                },
+            },
+            Scenario {
+                name: "selecting a sort readies it",
+                inputs: vec![
+                    Send(Message::Shuffle),
+                    Finish,
+                    Send(Message::SortSelected(sorting::Sort::InsertionSort.into())),
+                ],
+                expect: Expect {
+                    run: Some(Run::Ready),
+                    playing: Some(false),
+                    sort: Some(sorting::Sort::InsertionSort.into()),
+                    ..Expect::default()
+                },
+            },
+        ];
+
+        for scenario in scenarios {
+            scenario.play();
+        }
+    }
+
+    #[test]
+    fn whole_session_works_from_the_keyboard() {
+        use gui::{Focus, Navigation};
+
+        let mut animations = animations();
+        animations.update(Message::Deterministic(true));
+        animations.update(Message::AnimateArrangements(false));
+        assert_eq!(animations.controls.focus(), None);
+
+        animations.update(Message::Navigate(Navigation::Next));
+        assert_eq!(animations.controls.focus(), Some(Focus::Sort));
+        let sort = animations.sorter.sort().clone();
+        animations.update(Message::Navigate(Navigation::Increase));
+        assert_ne!(animations.sorter.sort(), &sort);
+        animations.update(Message::Navigate(Navigation::Decrease));
+        assert_eq!(animations.sorter.sort(), &sort);
+
+        // The numbers field takes the typing once tabbed to
+        focus_on(&mut animations, Focus::Numbers);
+        assert!(animations.controls.numbers_focused());
+        animations.update(Message::NumbersInput(String::from("50")));
+        animations.update(Message::NumbersSelected);
+        assert_eq!(animations.read_array(array::ArrayState::size), 50);
+        animations.update(Message::Navigate(Navigation::Previous));
+        assert_eq!(animations.controls.focus(), Some(Focus::Speed));
+        assert!(!animations.controls.numbers_focused());
+
+        // Playing at the top speed, it only goes down and back up again
+        let speed = animations.speed;
+        animations.update(Message::Navigate(Navigation::Increase));
+        assert_eq!(animations.speed, speed);
+        animations.update(Message::Navigate(Navigation::Decrease));
+        assert_eq!(animations.speed, speed - 1);
+        animations.update(Message::Navigate(Navigation::Increase));
+        assert_eq!(animations.speed, speed);
+
+        focus_on(&mut animations, Focus::Shuffle);
+        let numbers = animations.read_array(|array| array.numbers().to_vec());
+        animations.update(Message::Navigate(Navigation::Activate));
+        assert_ne!(
+            animations.read_array(|array| array.numbers().to_vec()),
+            numbers
+        );
+
+        focus_on(&mut animations, Focus::Play);
+        animations.update(Message::Navigate(Navigation::Activate));
+        assert!(animations.playing);
+        animations.update(Message::Navigate(Navigation::Activate));
+        assert!(!animations.playing);
+
+        focus_on(&mut animations, Focus::Step);
+        let operations = stats(&animations);
+        animations.update(Message::Navigate(Navigation::Activate));
+        assert_ne!(stats(&animations), operations);
+
+        // Starting out muted, the toggle unmutes and mutes again
+        focus_on(&mut animations, Focus::Mute);
+        assert!(animations.muted);
+        animations.update(Message::Navigate(Navigation::Activate));
+        assert!(!animations.muted);
+        animations.update(Message::Navigate(Navigation::Activate));
+        assert!(animations.muted);
+
+        animations.update(Message::Navigate(Navigation::Leave));
+        assert_eq!(animations.controls.focus(), None);
+    }
+
+    #[test]
+    fn animations_run_without_an_audio_device() {
+        let no_device = audio::Lazy::new(|| None::<Listener>, &Settings::default());
+        let mut animations =
+            SortingAnimations::with_audio(Settings::default(), Box::new(no_device));
+        assert!(animations.stats_snapshot().toggles.audio);
+
+        // There is nothing to unmute, but the sort plays all the same
+        animations.update(Message::Mute(false));
+        assert!(animations.muted);
+        assert!(!animations.stats_snapshot().toggles.audio);
+        animations.update(Message::SpeedSelected(Settings::default().max_speed));
+        animations.update(Message::AnimateArrangements(false));
+        animations.update(Message::Shuffle);
+        finish(&mut animations);
+        assert!(animations.sorter.operations() > 0);
+
+        // Turned off, the audio device isn't even looked for
+        let no_audio = Settings {
+            audio: false,
+            ..Settings::default()
+        };
+        assert!(!audio::speaker(&no_audio).available());
+    }
+
+    #[test]
+    fn swapped_bars_slide_over_the_ticks() {
+        let mut animations = animations();
+        animations.update(Message::TweenSwaps(true));
+        assert!(animations.stats_snapshot().toggles.tween_swaps);
+
+        animations.sorter.operate_array(|array| array.swap(0, 1));
+        assert!(animations.read_array(array::ArrayState::sliding));
+        let tick_interval = Settings::default().tick_interval;
+        for _ in 0..=array::TWEEN_DURATION.as_millis() / tick_interval.as_millis() {
+            animations.update(Message::Tick(time::Instant::now()));
+        }
+        assert!(!animations.read_array(array::ArrayState::sliding));
+
+        animations.update(Message::TweenSwaps(false));
+        assert!(!animations.stats_snapshot().toggles.tween_swaps);
+    }
+
+    #[test]
+    fn focus_skips_collapsed_controls() {
+        use gui::{Focus, Navigation};
+
+        let mut animations = animations();
+        animations.update(Message::Resized(COMPACT_WIDTH - 1, COMPACT_HEIGHT));
+
+        let mut focused = Vec::new();
+        for _ in Focus::ORDER {
+            animations.update(Message::Navigate(Navigation::Next));
+            focused.extend(animations.controls.focus());
+        }
+        assert!(!focused.contains(&Focus::Shuffle));
+
+        // Expanded from the keyboard, the collapsed controls can be focused again
+        focus_on(&mut animations, Focus::More);
+        animations.update(Message::Navigate(Navigation::Activate));
+        focus_on(&mut animations, Focus::Shuffle);
+    }
+
+    #[test]
+    fn control_socket_drives_the_animations() {
+        let mut animations = animations();
+        animations.update(Message::Deterministic(true));
+        let (requests, received) = std::sync::mpsc::channel();
+        let address = control::listen(0, move |request| requests.send(request).is_ok()).unwrap();
+
+        let commands = [
+            r#"{"command":"set_sort","sort":"BubbleSort"}"#,
+            r#"{"command":"set_numbers","count":40}"#,
+            r#"{"command":"shuffle","seed":7}"#,
+            r#"{"command":"step","count":10}"#,
+            r#"{"command":"set_speed","speed":0}"#,
+            r#"{"command":"play"}"#,
+            r#"{"command":"step"}"#,
+            r#"{"command":"pause"}"#,
+            r#"{"command":"step","count":18446744073709551615}"#,
+            r#"{"command":"step","count":10000}"#,
+            r#"{"command":"query_stats"}"#,
+        ];
+        let client = std::thread::spawn(move || {
+            use std::io::{BufRead, Write};
+
+            let stream = std::net::TcpStream::connect(address).unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            let mut lines = std::io::BufReader::new(stream).lines();
+            commands.map(|command| {
+                writeln!(writer, "{command}").unwrap();
+                lines.next().unwrap().unwrap()
+            })
+        });
+        // The step count too large is refused without arriving
+        for request in received.iter().take(commands.len() - 1) {
+            animations.update(Message::Control(request));
+        }
+        let responses = client.join().unwrap();
+
+        let ok = r#"{"ok":true}"#;
+        assert_eq!(responses[..4], [ok; 4]);
+        assert!(responses[4].contains("the speed has to be from 1"));
+        assert_eq!(responses[5], ok);
+        assert!(responses[6].contains("paused first"));
+        assert_eq!(responses[7], ok);
+        assert!(responses[8].contains("the count can be at most"));
+        assert_eq!(responses[9], ok);
+        assert!(responses[10].contains(r#""playing":false,"size":40,"placed":40"#));
+
+        assert_eq!(animations.sorter.sort().name(), "BubbleSort");
+        assert_eq!(animations.sorter.seed(), 7);
+        assert_ne!(stats(&animations), (0, 0, 0));
+    }
+
+    #[test]
+    fn metrics_follow_the_session() {
+        let mut animations = animations();
+        animations.update(Message::Deterministic(true));
+        animations.view();
+        let metrics = animations.metrics(true);
+        assert_eq!(metrics.runs_completed, 0);
+        assert_eq!(metrics.frames_rendered, 1);
+        assert_eq!(metrics.algorithm, animations.sorter.sort().name());
+
+        finish(&mut animations);
+        let metrics = animations.metrics(false);
+        assert_eq!(metrics.runs_completed, 1);
+        assert_eq!(
+            (
+                metrics.stats.comparisons,
+                metrics.stats.reads,
+                metrics.stats.writes
+            ),
+            stats(&animations)
+        );
+        assert!(metrics.uptime <= animations.counters.launched.elapsed());
+    }
+
+    #[test]
+    fn small_windows_collapse_controls() {
+        let mut animations = animations();
+        assert!(!animations.controls.compact());
+
+        animations.update(Message::Resized(640, 480));
+        assert!(animations.controls.collapsed());
+        animations.update(Message::ToggleControls);
+        assert!(animations.controls.compact() && !animations.controls.collapsed());
+
+        animations.update(Message::Resized(1600, 500));
+        assert!(animations.controls.compact());
+        animations.update(Message::Resized(3840, 2160));
+        assert!(!animations.controls.compact() && !animations.controls.collapsed());
+    }
+
+    #[test]
+    fn pinning_follows_element() {
+        let mut animations = animations();
+        animations.update(Message::Reverse);
+        let value = animations.read_array(|array| array.numbers()[3]);
+
+        animations.update(Message::Pin(3));
+        assert_eq!(animations.pinned, Some(value));
+
+        finish(&mut animations);
+        // Sorted, the value is found where it belongs
+        let index = animations.read_array(|array| array.numbers().iter().position(|&n| n == value));
+        assert_eq!(index, Some(value - 1));
+
+        animations.update(Message::Pin(value - 1));
+        assert_eq!(animations.pinned, None);
+    }
+
+    #[test]
+    fn milestones_are_captured() {
+        let dir = std::env::temp_dir().join(format!("screenshots-{}", std::process::id()));
+
+        let mut animations = animations();
+        animations.update(Message::ScreenshotsInput(dir.display().to_string()));
+        animations.update(Message::Screenshots(true));
+        animations.update(Message::Shuffle);
+        animations.update(Message::AutoSpeed(true));
+        finish(&mut animations);
+
+        let mut names: Vec<_> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let prefix = format!(
+            "{}-{INITIAL_NUMBERS}-seed{}",
+            animations
+                .sorter
+                .sort()
+                .name()
+                .to_lowercase()
+                .replace(' ', "-"),
+            animations.sorter.seed()
+        );
+        let expected: Vec<_> = ["25-percent", "50-percent", "75-percent", "finish", "start"]
+            .map(|milestone| format!("{prefix}-{milestone}.png"))
+            .into();
+        assert_eq!(names, expected);
+        assert!(animations.error.is_none());
+    }
+
+    #[test]
+    fn completed_runs_can_be_rerun() {
+        let mut animations = animations();
+        animations.update(Message::Shuffle);
+        let numbers = animations.read_array(|array| array.numbers().to_vec());
+        finish(&mut animations);
+
+        assert_eq!(animations.history.runs().len(), 1);
+        let run = animations.history.runs()[0].clone();
+        assert_eq!(*run.numbers, numbers);
+        assert_eq!(run.distribution(), sorting::Distribution::Shuffled);
+        assert_eq!(stats(&animations), (run.comparisons, run.reads, run.writes));
+
+        animations.update(Message::Shuffle);
+        animations.update(Message::Rerun(Box::new(run.clone())));
+        assert!(animations.playing);
+        assert_eq!(
+            animations.read_array(|array| array.numbers().to_vec()),
+            numbers
+        );
+        while animations.run != Run::Finished {
+            animations.update(Message::Tick(time::Instant::now()));
+        }
+
+        let runs = animations.history.runs();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(
+            (runs[1].comparisons, runs[1].reads, runs[1].writes),
+            (run.comparisons, run.reads, run.writes)
+        );
+
+        let mut csv = Vec::new();
+        animations.history.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].contains(",writes,peak_aux_bytes,peak_depth,duration_ms,distance_1,"));
+        assert!(lines[0].ends_with(",distance_524288"));
+        let distances = run
+            .distances
+            .unwrap()
+            .buckets()
+            .map(|count| count.to_string());
+        assert!(lines[1].ends_with(&distances.join(",")));
+        assert!(lines[1].starts_with(&format!(
+            "{},{INITIAL_NUMBERS},Shuffled,{},{},",
+            run.sort.name(),
+            run.seed,
+            run.comparisons
+        )));
+    }
+
+    #[test]
+    fn interruptions_are_confirmed_or_queued() {
+        let mut animations = animations();
+        animations.update(Message::Deterministic(true));
+        animations.update(Message::SpeedSelected(1));
+        animations.update(Message::Play);
+        let heap_sort: sorting::Algorithm = sorting::Sort::HeapSort.into();
+
+        // The first press only asks for confirmation
+        animations.update(Message::SortSelected(heap_sort.clone()));
+        assert!(animations.playing);
+        assert_ne!(animations.sorter.sort(), &heap_sort);
+        animations.update(Message::SortSelected(heap_sort.clone()));
+        assert!(!animations.playing);
+        assert_eq!(animations.sorter.sort(), &heap_sort);
+        assert!(animations.pending.is_none());
+
+        // Paused, nothing prompts
+        animations.update(Message::Step);
+        animations.update(Message::Reverse);
+        assert!(animations.read_array(|array| array.numbers().iter().rev().is_sorted()));
+
+        animations.update(Message::Play);
+        animations.update(Message::Shuffle);
+        animations.update(Message::CancelPending);
+        animations.update(Message::Shuffle);
+        assert!(animations.playing);
+
+        // Queued, the action waits for the run to stop
+        animations.update(Message::InterruptionSelected(gui::Interruption::Queue));
+        animations.update(Message::SortSelected(sorting::Sort::BubbleSort.into()));
+        animations.update(Message::SortSelected(sorting::Sort::BubbleSort.into()));
+        assert!(animations.playing);
+        assert_eq!(animations.sorter.sort(), &heap_sort);
+        animations.update(Message::Play);
+        assert_eq!(animations.sorter.sort(), &sorting::Sort::BubbleSort.into());
+        assert!(animations.pending.is_none());
+    }
+
+    #[test]
+    fn event_log_follows_the_run() {
+        let mut animations = animations();
+        animations.update(Message::SortSelected(sorting::Sort::ShellSort.into()));
+        animations.update(Message::Shuffle);
+        finish(&mut animations);
+
+        let log = animations.read_array(|array| array.annotations().clone());
+        assert_eq!(
+            log.entries().next().map(|a| a.text.as_str()),
+            Some("Gap reduced to 50")
+        );
+        let events = gui::EventsSnapshot {
+            annotations: log,
+            pause_on: false,
+            filter: String::new(),
+        };
+        animations.event_log.view(&events, &animations.settings);
+
+        // A new run starts the log over
+        animations.update(Message::SortSelected(sorting::Sort::InsertionSort.into()));
+        assert!(animations.read_array(|array| array.annotations().is_empty()));
+    }
+
+    #[test]
+    fn playing_pauses_on_annotations() {
+        for deterministic in [false, true] {
+            let mut animations = animations();
+            animations.update(Message::Deterministic(deterministic));
+            animations.update(Message::SortSelected(sorting::Sort::ShellSort.into()));
+            animations.update(Message::Reverse);
+            animations.update(Message::AnnotationFilterInput(String::from("gap reduced")));
+            animations.update(Message::PauseOnAnnotation(true));
+
+            // Every gap is discussed, then the run goes on to the end
+            let mut gaps = Vec::new();
+            animations.update(Message::Play);
+            while animations.run != Run::Finished {
+                animations.update(Message::Tick(time::Instant::now()));
+                if !animations.playing && animations.run != Run::Finished {
+                    let log = animations.read_array(|array| array.annotations().clone());
+                    let last = log.entries().last().unwrap();
+                    assert!(last.matched);
+                    gaps.push(last.text.clone());
+                    let events = gui::EventsSnapshot {
+                        annotations: log.clone(),
+                        pause_on: true,
+                        filter: String::from("gap reduced"),
+                    };
+                    animations.event_log.view(&events, &animations.settings);
+
+                    animations.update(Message::Play);
+                }
+            }
+            assert_eq!(gaps.len(), 6, "{gaps:?}");
+            assert_eq!(gaps[0], "Gap reduced to 50");
+        }
+
+        // Disabled, the run plays through
+        let mut animations = animations();
+        animations.update(Message::SortSelected(sorting::Sort::ShellSort.into()));
+        animations.update(Message::AnnotationFilterInput(String::from("gap reduced")));
+        finish(&mut animations);
+        assert_eq!(animations.read_array(array::ArrayState::pause_hits), 0);
+    }
+
+    #[test]
+    fn shuffling_animates_before_sort() {
+        let mut animations = animations();
+        animations.update(Message::AnimateArrangements(true));
+        let sort = animations.sorter.sort().clone();
+
+        animations.update(Message::Shuffle);
+        assert!(animations.sorter.arranging());
+        assert_eq!(animations.sorter.sort(), &sort);
+        // Playing waits for the shuffle
+        animations.update(Message::Play);
+        assert!(!animations.playing);
+
+        let mut ticks = 0;
+        while animations.sorter.arranging() {
+            animations.update(Message::Tick(time::Instant::now()));
+            ticks += 1;
+        }
+        // About a second of ticks, however many numbers there are
+        assert!(ticks > 50, "{ticks}");
+
+        let mut numbers = animations.read_array(|array| array.numbers().to_vec());
+        assert_ne!(numbers, (1..=INITIAL_NUMBERS).collect::<Vec<_>>());
+        numbers.sort();
+        assert_eq!(numbers, (1..=INITIAL_NUMBERS).collect::<Vec<_>>());
+        assert_eq!(stats(&animations), (0, 0, 0));
+        assert_eq!(animations.sorter.sort(), &sort);
+        assert_eq!(animations.run, Run::Ready);
+
+        finish(&mut animations);
+        assert_eq!(animations.history.runs().len(), 1);
+        assert_eq!(animations.history.runs()[0].sort, sort);
+    }
+
+    #[test]
+    fn switching_sorts_suggests_speed() {
+        let mut animations = animations();
+        animations.update(Message::SuggestSpeed(true));
+
+        animations.update(Message::SortSelected(sorting::Sort::QuickSort.into()));
+        let quick_sort = animations.speed;
+        animations.update(Message::SortSelected(sorting::Sort::BubbleSort.into()));
+        let bubble_sort = animations.speed;
+        assert_eq!(
+            quick_sort,
+            (sorting::Sort::QuickSort.suggested_speed(INITIAL_NUMBERS as u64)
+                * Settings::default().max_speed as f32)
+                .round() as u32
+        );
+        // Bubble sort is granted more of its operations a tick, so it slows down further
+        assert!(bubble_sort < quick_sort, "{bubble_sort} {quick_sort}");
+
+        // A speed set by hand is kept while switching
+        animations.update(Message::SuggestSpeed(false));
+        animations.update(Message::SpeedSelected(42));
+        animations.update(Message::SortSelected(sorting::Sort::QuickSort.into()));
+        assert_eq!(animations.speed, 42);
+    }
+
+    #[test]
+    fn infeasible_runs_warn_before_playing() {
+        let mut animations = animations();
+        animations.update(Message::NumbersInput(String::from("20000")));
+        animations.update(Message::NumbersSelected);
+        animations.update(Message::Shuffle);
+        animations.update(Message::SortSelected(sorting::Sort::StoogeSort.into()));
+
+        // However much budget full speed grants, the operations take their time
+        let duration = animations.infeasible().unwrap();
+        assert!(duration > INFEASIBLE_DURATION);
+        assert!(gui::approximate(duration, Language::ENGLISH).ends_with(" hours"));
+        animations.update(Message::SortSelected(sorting::Sort::SlowSort.into()));
+        assert!(
+            gui::approximate(animations.infeasible().unwrap(), Language::ENGLISH)
+                .ends_with(" years")
+        );
+        assert_eq!(
+            gui::approximate(
+                time::Duration::from_secs(2 * 60 * 60 + 59),
+                Language::ENGLISH
+            ),
+            "≈ 2 hours"
+        );
+        assert_eq!(
+            gui::approximate(time::Duration::from_secs(61), Language::ENGLISH),
+            "≈ 1 minute"
+        );
+
+        // The first press only warns
+        animations.update(Message::Play);
+        assert!(!animations.playing && animations.play_warned);
+        animations.update(Message::Play);
+        assert!(animations.playing);
+        animations.update(Message::Play);
+
+        // A feasible sort plays right away
+        animations.update(Message::SortSelected(sorting::Sort::QuickSort.into()));
+        assert_eq!(animations.infeasible(), None);
+        animations.update(Message::Play);
+        assert!(animations.playing);
+    }
+
+    #[test]
+    fn counts_are_formatted() {
+        assert_eq!(gui::thousands(0), "0");
+        assert_eq!(gui::thousands(999), "999");
+        assert_eq!(gui::thousands(1000), "1,000");
+        assert_eq!(gui::thousands(1_273_945_882), "1,273,945,882");
+        assert_eq!(gui::thousands(u64::MAX), "18,446,744,073,709,551,615");
+
+        assert_eq!(gui::abbreviated(0), "0");
+        assert_eq!(gui::abbreviated(999), "999");
+        assert_eq!(gui::abbreviated(1000), "1.00 k");
+        assert_eq!(gui::abbreviated(1_273_945_882), "1.27 G");
+        assert_eq!(gui::abbreviated(12_345), "12.3 k");
+        assert_eq!(gui::abbreviated(123_456), "123 k");
+        // Rounding carries into the next prefix instead of showing "1000 k"
+        assert_eq!(gui::abbreviated(9_999), "10.0 k");
+        assert_eq!(gui::abbreviated(999_999), "1.00 M");
+        assert_eq!(gui::abbreviated(u64::MAX), "18.4 E");
+
+        assert_eq!(gui::bytes(0), "0 B");
+        assert_eq!(gui::bytes(800), "800 B");
+        assert_eq!(gui::bytes(8_000), "8.00 kB");
+        assert_eq!(gui::bytes(1_273_945_882), "1.27 GB");
+    }
+
+    #[test]
+    fn settings_panel_applies_valid_settings() {
+        let mut animations = animations();
+        let volume = rc::Rc::default();
+        animations.audio = Box::new(Listener(rc::Rc::clone(&volume)));
+        animations.update(Message::ToggleSettings);
+        assert!(animations.settings_panel.is_open());
+
+        // Invalid values are rejected, leaving the settings as they were
+        animations.update(Message::SettingChanged("volume", String::from("loud")));
+        animations.update(Message::ApplySettings);
+        assert_eq!(volume.get(), None);
+        animations.update(Message::SettingChanged("volume", String::from("0.5")));
+        animations.update(Message::SettingChanged(
+            "min-frequency",
+            String::from("1000"),
+        ));
+        assert!(animations.settings_panel.settings().is_err());
+        animations.update(Message::SettingChanged(
+            "min-frequency",
+            String::from("110"),
+        ));
+
+        // The tick interval waits for the next run, the rest applies at once
+        animations.update(Message::SettingChanged("tick-interval", String::from("20")));
+        animations.update(Message::SettingChanged("highlights", String::from("5")));
+        animations.update(Message::SettingChanged("max-numbers", String::from("500")));
+        animations.update(Message::Play);
+        animations.update(Message::Tick(time::Instant::now()));
+        animations.update(Message::ApplySettings);
+        assert_eq!(volume.get(), Some(0.5));
+        assert_eq!(animations.settings.min_frequency, 110.0);
+        assert_eq!(animations.sorter.highlights(), 5);
+        animations.update(Message::NumbersInput(String::from("1e6")));
+        assert_eq!(animations.numbers_input.value(), Ok(Some(500)));
+        assert_eq!(
+            animations.sorter.tick_interval(),
+            Settings::default().tick_interval
+        );
+
+        animations.update(Message::Play);
+        animations.update(Message::SortSelected(sorting::Sort::InsertionSort.into()));
+        assert_eq!(
+            animations.sorter.tick_interval(),
+            time::Duration::from_millis(20)
+        );
+        assert_eq!(
+            animations.current_settings().tick_interval,
+            time::Duration::from_millis(20)
+        );
+
+        // Resetting only edits the panel, until applied
+        animations.update(Message::ResetSettings);
+        assert_eq!(animations.sorter.highlights(), 5);
+        animations.update(Message::ApplySettings);
+        assert_eq!(animations.current_settings(), Settings::default());
+        assert_eq!(
+            animations.sorter.tick_interval(),
+            Settings::default().tick_interval
+        );
+    }
+
+    #[test]
+    fn number_input_is_lenient() {
+        use gui::InputError::*;
+
+        let Settings {
+            min_numbers,
+            max_numbers,
+            ..
+        } = Settings::default();
+
+        let cases = [
+            ("", Ok(None)),
+            ("   ", Ok(None)),
+            ("500", Ok(Some(500))),
+            (" 500 ", Ok(Some(500))),
+            ("12,000", Ok(Some(12_000))),
+            ("12 000", Ok(Some(12_000))),
+            ("12'000", Ok(Some(12_000))),
+            ("1_000", Ok(Some(1000))),
+            ("1e4", Ok(Some(10_000))),
+            ("1.5E3", Ok(Some(1500))),
+            ("+200", Ok(Some(200))),
+            // Clamped to the bounds
+            ("3", Ok(Some(min_numbers))),
+            ("0", Ok(Some(min_numbers))),
+            ("1e9", Ok(Some(max_numbers))),
+            ("1e400", Ok(Some(max_numbers))),
+            ("123456789012345678901234567890", Ok(Some(max_numbers))),
+            ("abc", Err(NotANumber)),
+            ("12abc", Err(NotANumber)),
+            ("inf", Err(NotANumber)),
+            ("NaN", Err(NotANumber)),
+            ("1e", Err(NotANumber)),
+            ("0x10", Err(NotANumber)),
+            ("12.5", Err(Fraction)),
+            ("1.5e0", Err(Fraction)),
+            ("-5", Err(Negative)),
+        ];
+
+        let mut input = gui::NumberInput::new(min_numbers..=max_numbers);
+        for (text, value) in cases {
+            input.set_text(String::from(text));
+            assert_eq!(input.value(), value, "{text:?}");
+        }
+
+        // Only values applied differently than typed are shown
+        let cases = [
+            ("", None),
+            ("500", None),
+            (" 500 ", None),
+            ("1e4", Some(Ok(10_000))),
+            ("12,000", Some(Ok(12_000))),
+            ("3", Some(Ok(min_numbers))),
+            ("abc", Some(Err(NotANumber))),
+        ];
+        for (text, feedback) in cases {
+            input.set_text(String::from(text));
+            assert_eq!(input.feedback(), feedback, "{text:?}");
+        }
+        assert_eq!(NotANumber.localize(Language::ENGLISH), "Not a number");
+    }
+
+    #[test]
+    fn halves_sort_one_after_the_other() {
+        let mut animations = animations();
+        animations.update(Message::Reverse);
+        let half = INITIAL_NUMBERS / 2;
+
+        animations.update(Message::RangeEndInput(half.to_string()));
+        animations.update(Message::RangeSelected);
+        assert_eq!(animations.sorter.range(), 0..half);
+        finish(&mut animations);
+        let numbers = animations.read_array(|array| array.numbers().to_vec());
+        assert!(numbers[..half].is_sorted() && numbers[half..].iter().rev().is_sorted());
+        assert_eq!(numbers[0], half + 1);
+
+        // Left empty, the range goes to the last number
+        animations.update(Message::RangeStartInput(half.to_string()));
+        animations.update(Message::RangeEndInput(String::new()));
+        animations.update(Message::RangeSelected);
+        assert_eq!(animations.sorter.range(), half..INITIAL_NUMBERS);
+        finish(&mut animations);
+        let numbers = animations.read_array(|array| array.numbers().to_vec());
+        assert!(numbers[..half].is_sorted() && numbers[half..].is_sorted());
+        assert_eq!(numbers[half], 1);
+
+        animations.update(Message::RangeStartInput(String::new()));
+        animations.update(Message::RangeSelected);
+        assert_eq!(animations.sorter.range(), 0..INITIAL_NUMBERS);
+    }
+
+    #[test]
+    fn results_rank_sorts_on_same_input() {
+        use iced::canvas::Program;
+
+        let mut animations = animations();
+        animations.update(Message::Shuffle);
+        finish(&mut animations);
+        let run = animations.history.runs()[0].clone();
+        assert_ne!(run.sort, sorting::Sort::HeapSort.into());
+
+        animations.update(Message::Rerun(Box::new(gui::CompletedRun {
+            sort: sorting::Sort::HeapSort.into(),
+            ..run.clone()
+        })));
+        while animations.run != Run::Finished {
+            animations.update(Message::Tick(time::Instant::now()));
+        }
+
+        let results = animations.history.results();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].sort, sorting::Sort::HeapSort.into());
+        assert_eq!(results[1].sort, run.sort);
+
+        // Clicking the top bar selects the sort with the fewest comparisons
+        let mut chart = gui::ResultsChart::new(&results, &Settings::default());
+        let bounds = iced::Rectangle::new(iced::Point::ORIGIN, iced::Size::new(800.0, 100.0));
+        let click = iced::canvas::Event::Mouse(iced::mouse::Event::ButtonPressed(
+            iced::mouse::Button::Left,
+        ));
+        let cursor = iced::canvas::Cursor::Available(iced::Point::new(300.0, 5.0));
+        assert!(matches!(
+            chart.update(click, bounds, cursor).1,
+            Some(Message::SortSelected(sort)) if sort == sorting::Sort::HeapSort.into()
+        ));
+
+        // Another input leaves the chart to the new run
+        animations.update(Message::Shuffle);
+        finish(&mut animations);
+        assert_eq!(animations.history.results().len(), 1);
+    }
+
+    #[test]
+    fn runs_compare_against_reference() {
+        let mut animations = animations();
+        // Exact about the operations a step performed, which a sort thread only is eventually
+        animations.update(Message::Deterministic(true));
+        animations.update(Message::Record(true));
+        animations.update(Message::Shuffle);
+        let input = animations.read_array(|array| array.numbers().to_vec());
+        finish(&mut animations);
+
+        animations.update(Message::SetReference);
+        animations.update(Message::SortSelected(sorting::Sort::HeapSort.into()));
+        assert_eq!(
+            animations.read_array(|array| array.numbers().to_vec()),
+            input
+        );
+
+        let differing = |animations: &SortingAnimations| {
+            let reference = animations.reference_numbers().unwrap();
+            animations.read_array(|array| {
+                array
+                    .numbers()
+                    .iter()
+                    .zip(reference.iter())
+                    .filter(|(number, reference)| number != reference)
+                    .count()
+            })
+        };
+        for _ in 0..200 {
+            animations.update(Message::Step);
+        }
+        assert_eq!(animations.reference.as_ref().unwrap().position(), 200);
+        assert!(differing(&animations) > 0);
+
+        // Heap sort is done long before bubble sort, which is compared as far as it got by then
+        finish(&mut animations);
+        let reference = animations.reference.as_ref().unwrap();
+        assert!(reference.position() < reference.len());
+        assert_eq!(reference.position() as u64, animations.sorter.operations());
+        assert!(differing(&animations) > 0);
+
+        // Runs go on starting from the reference's input
+        animations.update(Message::Play);
+        assert_eq!(
+            animations.read_array(|array| array.numbers().to_vec()),
+            input
+        );
+
+        animations.update(Message::ClearReference);
+        assert!(animations.reference_numbers().is_none());
+    }
+
+    #[test]
+    fn shift_clicks_watch_elements() {
+        let mut animations = animations();
+        let shift = iced::keyboard::Modifiers::SHIFT;
+
+        animations.update(Message::ModifiersChanged(shift));
+        animations.update(Message::Pin(3));
+        animations.update(Message::Pin(5));
+        animations.update(Message::Pin(3));
+        animations.update(Message::ModifiersChanged(
+            iced::keyboard::Modifiers::default(),
+        ));
+        animations.update(Message::Pin(7));
+
+        assert_eq!(animations.read_array(|array| array.watched().to_vec()), [5]);
+        assert!(animations.pinned.is_some());
+
+        animations.update(Message::ClearWatched);
+        assert!(animations.read_array(|array| array.watched().is_empty()));
+    }
+
+    #[test]
+    fn unchanged_selections_keep_the_run() {
+        let mut animations = animations();
+        // Stepping right away, instead of on the sort thread
+        animations.update(Message::Deterministic(true));
+        animations.update(Message::Shuffle);
+        for _ in 0..10 {
+            animations.update(Message::Step);
+        }
+        let numbers = animations.read_array(|array| array.numbers().to_vec());
+        let operations = stats(&animations);
+        assert_ne!(operations, (0, 0, 0));
+
+        animations.update(Message::NumbersInput(format!(" {INITIAL_NUMBERS} ")));
+        animations.update(Message::NumbersSelected);
+        animations.update(Message::SortSelected(animations.sorter.sort().clone()));
+        assert_eq!(
+            animations.read_array(|array| array.numbers().to_vec()),
+            numbers
+        );
+        assert_eq!(stats(&animations), operations);
+        assert_eq!(animations.run, Run::Live);
+        assert_eq!(animations.numbers_input.feedback(), None);
+
+        // A different largest value deals the numbers anew
+        animations.update(Message::MaxValueInput(String::from("50")));
+        animations.update(Message::NumbersSelected);
+        assert_eq!(animations.read_array(array::ArrayState::max_value), 50);
+        assert_eq!(animations.run, Run::Ready);
+    }
+
+    #[test]
+    fn cleared_numbers_keep_the_size() {
+        let mut animations = animations();
+        animations.update(Message::NumbersInput(String::from("5000")));
+        animations.update(Message::NumbersSelected);
+
+        // Submitted empty, the numbers are initialized again at their size
+        animations.update(Message::NumbersInput(String::new()));
+        animations.update(Message::NumbersSelected);
+        assert_eq!(animations.read_array(array::ArrayState::size), 5000);
+        assert_eq!(animations.numbers_input.value(), Ok(Some(5000)));
+
+        // Left empty or invalid, the field shows the size again
+        for text in ["", "abc"] {
+            animations.controls.focus_numbers(true);
+            animations.update(Message::NumbersInput(String::from(text)));
+            animations.update(Message::Tick(time::Instant::now()));
+            assert_ne!(animations.numbers_input.value(), Ok(Some(5000)));
+
+            animations.controls.focus_numbers(false);
+            animations.update(Message::Tick(time::Instant::now()));
+            assert_eq!(animations.numbers_input.value(), Ok(Some(5000)));
+            assert_eq!(animations.numbers_input.feedback(), None);
+        }
+        assert_eq!(animations.read_array(array::ArrayState::size), 5000);
+    }
+
+    #[test]
+    fn max_value_is_validated() {
+        let mut animations = animations();
+        animations.update(Message::NumbersInput(String::from("2000")));
+        animations.update(Message::MaxValueInput(String::from("abc")));
+        assert_eq!(
+            animations.max_value_input.feedback(),
+            Some(Err(gui::InputError::NotANumber))
+        );
+
+        // Nothing is applied while a field is invalid
+        animations.update(Message::NumbersSelected);
+        assert_eq!(
+            animations.read_array(array::ArrayState::size),
+            INITIAL_NUMBERS
+        );
+
+        animations.update(Message::MaxValueInput(String::from("256")));
+        animations.update(Message::NumbersSelected);
+        let numbers = animations.read_array(|array| array.numbers().to_vec());
+        assert_eq!(numbers.len(), 2000);
+        assert_eq!((numbers[0], numbers[1999]), (1, 256));
+
+        // Applied clamped, the field shows the value
+        animations.update(Message::MaxValueInput(String::from("0")));
+        assert_eq!(animations.max_value_input.feedback(), Some(Ok(1)));
+        animations.update(Message::NumbersSelected);
+        assert_eq!(animations.max_value_input.value(), Ok(Some(1)));
+        assert_eq!(animations.max_value_input.feedback(), None);
+
+        // Left empty, the values are spread up to the size again
+        animations.update(Message::MaxValueInput(String::new()));
+        animations.update(Message::NumbersSelected);
+        assert_eq!(animations.read_array(array::ArrayState::max_value), 2000);
+    }
+
+    #[test]
+    fn stats_freeze_at_completion() {
+        let mut animations = animations();
+        animations.update(Message::Shuffle);
+        finish(&mut animations);
+
+        let finished = stats(&animations);
+        assert_ne!(finished, (0, 0, 0));
+        assert!(!animations.playing);
+
+        for _ in 0..10 {
+            animations.update(Message::Tick(time::Instant::now()));
+        }
+        assert_eq!(stats(&animations), finished);
+        assert_eq!(animations.run, Run::Finished);
+    }
+
+    #[test]
+    fn stats_reset_when_run_begins() {
+        let mut animations = animations();
+        animations.update(Message::Shuffle);
+        finish(&mut animations);
+        let finished = stats(&animations);
+
+        for message in [
+            Message::Shuffle,
+            Message::Shuffle,
+            Message::Reverse,
+            Message::Order,
+            Message::Equalize,
+            Message::ArrangementSelected(array::Arrangement::Interleaved),
+        ] {
+            animations.update(message);
+            assert_eq!(animations.run, Run::Ready);
+            assert_eq!(stats(&animations), finished);
+        }
+
+        animations.update(Message::Play);
+        assert_eq!(animations.run, Run::Live);
+        assert_eq!(stats(&animations), (0, 0, 0));
+    }
+
+    #[test]
+    fn attract_runs_finish_in_time() {
+        let animations = animations();
+        let candidates = animations.attract_candidates();
+
+        for &(sort, max_size) in &candidates {
+            for size in *ATTRACT_SIZES.start()..=max_size {
+                let duration = animations.attract_duration(sort, size);
+                assert!(duration <= ATTRACT_DURATION, "{sort:?} on {size} numbers");
+            }
+        }
+        // The slowest sorts only play on fewer numbers, if at all
+        assert!(candidates.contains(&(sorting::Sort::QuickSort, *ATTRACT_SIZES.end())));
+        assert!(candidates
+            .iter()
+            .all(|&(sort, max_size)| sort != sorting::Sort::SlowSort
+                || max_size < *ATTRACT_SIZES.end()));
+    }
+
+    #[test]
+    fn attract_mode_plays_while_idle() {
+        let mut animations = animations();
+        animations.settings.attract_after = Some(time::Duration::from_secs(60));
+        let idle = || {
+            time::Instant::now()
+                .checked_sub(time::Duration::from_secs(61))
+                .unwrap()
+        };
+        animations.update(Message::SortSelected(sorting::Sort::InsertionSort.into()));
+        animations.update(Message::Shuffle);
+        let numbers = animations.read_array(|array| array.numbers().to_vec());
+        let speed = animations.speed;
+
+        animations.update(Message::Tick(time::Instant::now()));
+        assert!(animations.attract.is_none());
+        animations.last_input = idle();
+        animations.update(Message::Tick(time::Instant::now()));
+        assert!(animations.attract.is_some() && animations.playing);
+        assert_eq!(animations.run, Run::Live);
+        assert!(ATTRACT_SIZES.contains(&animations.read_array(array::ArrayState::size)));
+
+        // Input restores the controls, instead of acting on them
+        animations.update(Message::Play);
+        assert!(animations.attract.is_none() && !animations.playing);
+        assert_eq!(animations.run, Run::Ready);
+        assert_eq!(
+            animations.sorter.sort(),
+            &sorting::Sort::InsertionSort.into()
+        );
+        assert_eq!(
+            animations.read_array(|array| array.numbers().to_vec()),
+            numbers
+        );
+        assert_eq!(animations.speed, speed);
+
+        // A run paused halfway is left alone
+        animations.update(Message::Deterministic(true));
+        animations.update(Message::Step);
+        animations.last_input = idle();
+        animations.update(Message::Tick(time::Instant::now()));
+        assert!(animations.attract.is_none());
+        assert_eq!(animations.sorter.operations(), 1);
+    }
+
+    #[test]
+    fn stats_reset_on_step() {
+        let mut animations = animations();
+        animations.update(Message::Reverse);
+        finish(&mut animations);
+
+        animations.update(Message::Order);
+        assert_eq!(animations.run, Run::Ready);
+        assert_ne!(stats(&animations), (0, 0, 0));
+
+        animations.update(Message::Step);
+        assert_eq!(animations.run, Run::Live);
+        assert!(stats(&animations).0 <= 1);
+    }
+
+    #[test]
+    fn deterministic_steps_are_exact() {
+        let mut animations = animations();
+        animations.update(Message::Deterministic(true));
+        animations.update(Message::Shuffle);
+        assert!(animations.sorter.deterministic());
+
+        for operations in 1..=3 {
+            animations.update(Message::Step);
+            assert_eq!(animations.sorter.operations(), operations);
+        }
+
+        animations.update(Message::Deterministic(false));
+        assert!(!animations.sorter.deterministic());
+        assert_eq!(animations.run, Run::Ready);
+    }
+
+    #[test]
+    fn replay_after_completion() {
+        let mut animations = animations();
+        animations.update(Message::Shuffle);
+        finish(&mut animations);
+
+        animations.update(Message::Play);
+        assert!(animations.playing);
+        assert_eq!(animations.run, Run::Live);
+        assert_eq!(stats(&animations), (0, 0, 0));
+    }
+
+    #[test]
+    fn progress_through_estimate() {
+        let mut animations = animations();
+        animations.update(Message::AutoSpeed(true));
+        animations.update(Message::Shuffle);
+        assert_eq!(animations.progress(), None);
+
+        animations.update(Message::Play);
+        assert_eq!(
+            animations.estimate,
+            Some(
+                sorting::Sort::default()
+                    .estimated_operations(INITIAL_NUMBERS as u64, sorting::Distribution::Shuffled)
+            )
+        );
+
+        let mut last = 0.0;
+        while animations.run != Run::Finished {
+            animations.update(Message::Tick(time::Instant::now()));
+
+            let progress = animations.progress().unwrap();
+            assert!(progress >= last);
+            last = progress;
+        }
+        assert_eq!(animations.progress(), Some(1.0));
+        assert_eq!(animations.remaining(), None);
+    }
+
+    #[test]
+    fn info_panel_measures_live_run() {
+        let mut animations = animations();
+        animations.update(Message::Shuffle);
+        assert_eq!(animations.measured(), None);
+        assert!(animations.sorter.sort().info().is_some());
+
+        animations.update(Message::Play);
+        animations.update(Message::Tick(time::Instant::now()));
+        let measured = animations.measured().unwrap();
+        assert_eq!(measured.estimate, animations.estimate.unwrap());
+        assert_eq!(measured.operations, animations.sorter.operations());
+        assert_eq!(measured.size, INITIAL_NUMBERS);
+
+        while animations.run != Run::Finished {
+            animations.update(Message::Tick(time::Instant::now()));
+        }
+        let measured = animations.measured().unwrap();
+        assert!(measured.operations > 0);
+        assert_eq!(measured.operations, animations.sorter.operations());
+    }
+
+    #[test]
+    fn sort_pixels_of_image() {
+        let path = std::env::temp_dir().join(format!("image-{}.ppm", std::process::id()));
+        // Two columns of four pixels, the first getting darker downwards
+        let mut ppm = b"P6 2 4 255\n".to_vec();
+        for value in [200, 150, 100, 50] {
+            ppm.extend([value, value, value, 0, 0, value]);
+        }
+        std::fs::write(&path, ppm).unwrap();
+
+        let mut animations = animations();
+        animations.update(Message::ImageInput(path.display().to_string()));
+        animations.update(Message::LoadImage);
+        std::fs::remove_file(&path).unwrap();
+        assert!(animations.error.is_none());
+        assert_eq!(animations.pixels.as_ref().map(gui::Pixels::column), Some(1));
+
+        animations.update(Message::ColumnSelected(0));
+        assert_eq!(
+            animations.read_array(|array| array.numbers().to_vec()),
+            [4, 3, 2, 1]
+        );
+        finish(&mut animations);
+        assert_eq!(
+            animations.read_array(|array| array.numbers().to_vec()),
+            [1, 2, 3, 4]
+        );
+
+        animations.update(Message::CloseImage);
+        assert!(animations.pixels.is_none());
+        assert_eq!(
+            animations.read_array(array::ArrayState::size),
+            INITIAL_NUMBERS
+        );
+
+        animations.update(Message::LoadImage);
+        assert!(animations.error.is_some());
+    }
+
+    #[test]
+    fn save_and_load_trace() {
+        let path = std::env::temp_dir().join(format!("trace-{}.satr", std::process::id()));
+
+        let mut animations = animations();
+        animations.update(Message::Record(true));
+        animations.update(Message::TraceInput(path.display().to_string()));
+        animations.update(Message::Shuffle);
+        finish(&mut animations);
+        animations.update(Message::SaveTrace);
+        assert!(animations.error.is_none());
+
+        let numbers = animations.read_array(|array| array.numbers().to_vec());
+        let finished = stats(&animations);
+
+        animations.update(Message::Shuffle);
+        animations.update(Message::LoadTrace);
+        std::fs::remove_file(&path).unwrap();
+        assert!(animations.error.is_none());
+        assert!(animations.replay.is_some());
+
+        finish(&mut animations);
+        assert_eq!(
+            animations.read_array(|array| array.numbers().to_vec()),
+            numbers
+        );
+        assert_eq!(
+            animations.read_array(|array| (array.comparisons(), array.reads(), array.writes())),
+            finished
+        );
+
+        animations.update(Message::TraceInput(String::from("/nonexistent/trace.satr")));
+        animations.update(Message::LoadTrace);
+        assert!(animations.error.is_some());
+    }
+
+    #[test]
+    fn scrub_recorded_run() {
+        let mut animations = animations();
+        animations.update(Message::Record(true));
+        animations.update(Message::Shuffle);
+        finish(&mut animations);
+        let sorted = animations.read_array(|array| array.numbers().to_vec());
+        let len = animations.sorter.trace().unwrap().operations.len();
+
+        animations.update(Message::Scrub(0));
+        assert!(animations.replay.is_some());
+        assert_eq!(animations.run, Run::Ready);
+        assert_eq!(
+            animations.read_array(|array| (array.comparisons(), array.reads(), array.writes())),
+            (0, 0, 0)
+        );
+
+        animations.update(Message::Scrub(len as u32 / 2));
+        assert_eq!(animations.replay.as_ref().unwrap().position(), len / 2);
+        assert_ne!(
+            animations.read_array(|array| array.numbers().to_vec()),
+            sorted
+        );
+
+        finish(&mut animations);
+        assert_eq!(
+            animations.read_array(|array| array.numbers().to_vec()),
+            sorted
+        );
+        assert_eq!(animations.replay.as_ref().unwrap().position(), len);
+    }
 }
@@ -0,0 +1,256 @@
+//! The metrics of a running frontend in the Prometheus text format, served over HTTP with the
+//! `metrics` feature so unattended installations can be monitored.
+
+use crate::array;
+use std::time;
+
+/// What a frontend reports about itself, taken as a whole whenever it is published.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Metrics {
+    pub uptime: time::Duration,
+    pub runs_completed: u64,
+    /// The name of the selected sort.
+    pub algorithm: String,
+    /// The stats of the current run.
+    pub stats: array::Stats,
+    pub frames_rendered: u64,
+    /// Whether the sort still runs, or waits to.
+    pub sort_alive: bool,
+}
+
+/// Written in the Prometheus text exposition format.
+impl std::fmt::Display for Metrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut metric = |name: &str, kind: &str, help: &str, samples: &[(&str, String)]| {
+            writeln!(f, "# HELP sorting_animations_{name} {help}")?;
+            writeln!(f, "# TYPE sorting_animations_{name} {kind}")?;
+            samples.iter().try_for_each(|(labels, value)| {
+                writeln!(f, "sorting_animations_{name}{labels} {value}")
+            })
+        };
+
+        metric(
+            "uptime_seconds",
+            "gauge",
+            "How long the animations have been running.",
+            &[("", self.uptime.as_secs_f64().to_string())],
+        )?;
+        metric(
+            "runs_completed_total",
+            "counter",
+            "The runs that sorted all their numbers.",
+            &[("", self.runs_completed.to_string())],
+        )?;
+        metric(
+            "algorithm_info",
+            "gauge",
+            "The selected sort.",
+            &[(
+                &format!("{{algorithm={:?}}}", self.algorithm),
+                1.to_string(),
+            )],
+        )?;
+        // The stats start over with every run, so they aren't counters
+        metric(
+            "comparisons",
+            "gauge",
+            "The comparisons of the current run.",
+            &[("", self.stats.comparisons.to_string())],
+        )?;
+        metric(
+            "accesses",
+            "gauge",
+            "The array accesses of the current run.",
+            &[
+                ("{kind=\"read\"}", self.stats.reads.to_string()),
+                ("{kind=\"write\"}", self.stats.writes.to_string()),
+            ],
+        )?;
+        metric(
+            "frames_rendered_total",
+            "counter",
+            "The frames drawn since the start.",
+            &[("", self.frames_rendered.to_string())],
+        )?;
+        metric(
+            "sort_alive",
+            "gauge",
+            "Whether the sort still runs.",
+            &[("", u8::from(self.sort_alive).to_string())],
+        )
+    }
+}
+
+#[cfg(feature = "metrics")]
+pub use server::Server;
+
+#[cfg(feature = "metrics")]
+mod server {
+    use super::Metrics;
+    use std::io::{self, BufRead, Write};
+    use std::sync::{self, atomic};
+    use std::{net, thread, time};
+
+    /// How long a scrape may take to send its request, so it can't hold up the shutdown.
+    const READ_TIMEOUT: time::Duration = time::Duration::from_secs(1);
+
+    /// Serves the last published metrics at `/metrics` on its own thread, until dropped.
+    pub struct Server {
+        metrics: sync::Arc<sync::Mutex<Metrics>>,
+        address: net::SocketAddr,
+        shutdown: sync::Arc<atomic::AtomicBool>,
+        thread: Option<thread::JoinHandle<()>>,
+    }
+
+    impl Server {
+        /// Listens on the port of the address, serving the metrics until others are published.
+        ///
+        /// Nothing guards the endpoint, so only an address other than localhost lets the display
+        /// be monitored from elsewhere. Port 0 picks a free port, which the address tells.
+        pub fn start(address: net::IpAddr, port: u16, metrics: Metrics) -> io::Result<Server> {
+            let listener = net::TcpListener::bind((address, port))?;
+            let address = listener.local_addr()?;
+            let metrics = sync::Arc::new(sync::Mutex::new(metrics));
+            let shutdown = sync::Arc::<atomic::AtomicBool>::default();
+
+            let thread = thread::spawn({
+                let (metrics, shutdown) = (metrics.clone(), shutdown.clone());
+                move || {
+                    for stream in listener.incoming() {
+                        if shutdown.load(atomic::Ordering::Relaxed) {
+                            break;
+                        }
+                        // A failed scrape is retried by the scraper
+                        if let Ok(stream) = stream {
+                            respond(stream, &metrics).ok();
+                        }
+                    }
+                }
+            });
+
+            Ok(Server {
+                metrics,
+                address,
+                shutdown,
+                thread: Some(thread),
+            })
+        }
+
+        pub fn address(&self) -> net::SocketAddr {
+            self.address
+        }
+
+        pub fn publish(&self, metrics: Metrics) {
+            *self
+                .metrics
+                .lock()
+                .unwrap_or_else(sync::PoisonError::into_inner) = metrics;
+        }
+    }
+
+    /// Stops the thread, waking it up from waiting for the next scrape.
+    impl Drop for Server {
+        fn drop(&mut self) {
+            self.shutdown.store(true, atomic::Ordering::Relaxed);
+            // Woken by a connection of its own, through localhost if it listens on every interface
+            let mut local = self.address;
+            if local.ip().is_unspecified() {
+                local.set_ip(match local {
+                    net::SocketAddr::V4(_) => net::Ipv4Addr::LOCALHOST.into(),
+                    net::SocketAddr::V6(_) => net::Ipv6Addr::LOCALHOST.into(),
+                });
+            }
+            if net::TcpStream::connect(local).is_ok() {
+                if let Some(thread) = self.thread.take() {
+                    thread.join().ok();
+                }
+            }
+        }
+    }
+
+    fn respond(stream: net::TcpStream, metrics: &sync::Mutex<Metrics>) -> io::Result<()> {
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        let mut writer = stream.try_clone()?;
+        let mut request = String::new();
+        io::BufReader::new(stream).read_line(&mut request)?;
+
+        let (status, body) = match request.split_whitespace().take(2).collect::<Vec<_>>()[..] {
+            ["GET", "/metrics"] => (
+                "200 OK",
+                metrics
+                    .lock()
+                    .unwrap_or_else(sync::PoisonError::into_inner)
+                    .to_string(),
+            ),
+            _ => ("404 Not Found", String::from("Not Found\n")),
+        };
+        write!(
+            writer,
+            "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics() -> Metrics {
+        Metrics {
+            uptime: time::Duration::from_millis(1500),
+            runs_completed: 2,
+            algorithm: String::from("QuickSort"),
+            stats: array::ArrayState::from_numbers(vec![2, 1]).stats_snapshot(),
+            frames_rendered: 40,
+            sort_alive: true,
+        }
+    }
+
+    #[test]
+    fn metrics_are_exposed() {
+        let text = metrics().to_string();
+
+        for line in [
+            "sorting_animations_uptime_seconds 1.5",
+            "sorting_animations_runs_completed_total 2",
+            "sorting_animations_algorithm_info{algorithm=\"QuickSort\"} 1",
+            "sorting_animations_accesses{kind=\"write\"} 0",
+            "sorting_animations_frames_rendered_total 40",
+            "sorting_animations_sort_alive 1",
+            "# TYPE sorting_animations_comparisons gauge",
+        ] {
+            assert!(text.lines().any(|l| l == line), "{line}");
+        }
+        // Every sample has its help and type
+        assert_eq!(text.lines().filter(|l| l.starts_with("# TYPE")).count(), 7);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn server_serves_published_metrics() {
+        use std::io::{Read, Write};
+
+        let server = Server::start(std::net::Ipv4Addr::LOCALHOST.into(), 0, metrics()).unwrap();
+        server.publish(Metrics {
+            runs_completed: 3,
+            ..metrics()
+        });
+        let get = |path: &str| {
+            let mut stream = std::net::TcpStream::connect(server.address()).unwrap();
+            write!(stream, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            response
+        };
+
+        let response = get("/metrics");
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("sorting_animations_runs_completed_total 3\n"));
+        assert!(get("/").starts_with("HTTP/1.1 404"));
+
+        // Shutting down waits for the thread
+        drop(server);
+    }
+}
@@ -0,0 +1,276 @@
+//! Pixel sorting, animating a sort of the pixels of a column of an image by their luminance.
+
+use std::io;
+
+/// The largest width and height of an image, larger ones are scaled down to it.
+pub const MAX_SIZE: usize = 512;
+
+/// An RGB image.
+///
+/// Images are read as binary portable pixmaps or graymaps, which most image editors export and
+/// need no decoder.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Image {
+    pub fn read(mut reader: impl io::Read) -> io::Result<Image> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        let mut header = Header {
+            bytes: &bytes,
+            position: 0,
+        };
+        let channels = match header.token()? {
+            b"P6" => 3,
+            b"P5" => 1,
+            _ => return Err(invalid_data("not a binary portable pixmap or graymap")),
+        };
+        let width = header.number()?;
+        let height = header.number()?;
+        if width == 0 || height == 0 {
+            return Err(invalid_data("empty image"));
+        }
+        let max_value = header.number()?;
+        if !(1..=255).contains(&max_value) {
+            return Err(invalid_data(format!("unsupported max value {max_value}")));
+        }
+
+        // A single whitespace separates the header from the pixels
+        let data = bytes.get(header.position + 1..).unwrap_or_default();
+        let len = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(channels))
+            .filter(|&len| len <= data.len())
+            .ok_or_else(|| invalid_data("truncated pixels"))?;
+
+        let scale = |value: u8| (value as usize * 255 / max_value) as u8;
+        let pixels = data[..len]
+            .chunks_exact(channels)
+            .map(|pixel| match *pixel {
+                [r, g, b] => [scale(r), scale(g), scale(b)],
+                _ => [scale(pixel[0]); 3],
+            })
+            .collect();
+
+        Ok(Image {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// Scales the image down to at most `MAX_SIZE` pixels in either direction, keeping its aspect
+    /// ratio by picking the nearest pixels.
+    pub fn fit(self) -> Image {
+        let (width, height) = (self.width, self.height);
+        if width <= MAX_SIZE && height <= MAX_SIZE {
+            return self;
+        }
+
+        let scale = f64::max(width as f64, height as f64) / MAX_SIZE as f64;
+        let fitted = |len: usize| ((len as f64 / scale) as usize).max(1);
+        let (fitted_width, fitted_height) = (fitted(width), fitted(height));
+        let source = |index: usize, len: usize, fitted: usize| index * len / fitted;
+
+        Image {
+            width: fitted_width,
+            height: fitted_height,
+            pixels: (0..fitted_height)
+                .flat_map(|y| (0..fitted_width).map(move |x| (x, y)))
+                .map(|(x, y)| {
+                    self.pixel(
+                        source(x, width, fitted_width),
+                        source(y, height, fitted_height),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 3] {
+        self.pixels[y * self.width + x]
+    }
+}
+
+/// A column of an image being sorted by luminance.
+///
+/// The pixels are sorted as the numbers ranking them by their luminance, ties broken by their
+/// position, so the numbers are those of an ordinary array and every number stands for a pixel.
+#[derive(Clone, Debug)]
+pub struct ColumnSort {
+    column: usize,
+    /// The pixels of the column by their rank.
+    colors: Vec<[u8; 3]>,
+    numbers: Vec<usize>,
+}
+
+impl ColumnSort {
+    pub fn new(image: &Image, column: usize) -> ColumnSort {
+        let pixels: Vec<_> = (0..image.height())
+            .map(|y| image.pixel(column, y))
+            .collect();
+
+        let mut ranked: Vec<usize> = (0..pixels.len()).collect();
+        ranked.sort_by_key(|&y| luminance(pixels[y]));
+
+        let mut numbers = vec![0; pixels.len()];
+        for (rank, &y) in ranked.iter().enumerate() {
+            numbers[y] = rank + 1;
+        }
+
+        ColumnSort {
+            column,
+            colors: ranked.iter().map(|&y| pixels[y]).collect(),
+            numbers,
+        }
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// The numbers standing for the pixels of the column from top to bottom.
+    pub fn numbers(&self) -> &[usize] {
+        &self.numbers
+    }
+
+    /// The color of the pixel the number stands for.
+    pub fn color(&self, number: usize) -> [u8; 3] {
+        self.colors[number.clamp(1, self.colors.len()) - 1]
+    }
+}
+
+/// The relative luminance of the pixel, as weighted by Rec. 709.
+pub fn luminance([r, g, b]: [u8; 3]) -> u32 {
+    2126 * r as u32 + 7152 * g as u32 + 722 * b as u32
+}
+
+/// The whitespace separated header of a portable pixmap, where comments run to the end of a line.
+struct Header<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Header<'a> {
+    fn token(&mut self) -> io::Result<&'a [u8]> {
+        loop {
+            match self.bytes.get(self.position) {
+                Some(byte) if byte.is_ascii_whitespace() => self.position += 1,
+                Some(b'#') => {
+                    while self
+                        .bytes
+                        .get(self.position)
+                        .is_some_and(|&byte| byte != b'\n')
+                    {
+                        self.position += 1;
+                    }
+                }
+                Some(_) => break,
+                None => return Err(io::ErrorKind::UnexpectedEof.into()),
+            }
+        }
+
+        let start = self.position;
+        while self
+            .bytes
+            .get(self.position)
+            .is_some_and(|byte| !byte.is_ascii_whitespace())
+        {
+            self.position += 1;
+        }
+
+        Ok(&self.bytes[start..self.position])
+    }
+
+    fn number(&mut self) -> io::Result<usize> {
+        let token = self.token()?;
+
+        std::str::from_utf8(token)
+            .ok()
+            .and_then(|token| token.parse().ok())
+            .ok_or_else(|| invalid_data("invalid header"))
+    }
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_pixmaps() {
+        let mut ppm = b"P6\n# made by hand\n2 2\n255\n".to_vec();
+        ppm.extend([255, 0, 0, 0, 255, 0, 0, 0, 255, 10, 20, 30]);
+
+        let image = Image::read(ppm.as_slice()).unwrap();
+        assert_eq!((image.width(), image.height()), (2, 2));
+        assert_eq!(image.pixel(1, 0), [0, 255, 0]);
+        assert_eq!(image.pixel(1, 1), [10, 20, 30]);
+
+        let pgm = b"P5 1 2 15\n\x0f\x05";
+        let image = Image::read(pgm.as_slice()).unwrap();
+        assert_eq!(image.pixel(0, 0), [255; 3]);
+        assert_eq!(image.pixel(0, 1), [85; 3]);
+
+        let truncated = b"P6 2 2 255\n\x00\x00\x00";
+        assert!(Image::read(truncated.as_slice()).is_err());
+        assert!(Image::read(b"P6 2 2 255".as_slice()).is_err());
+        assert!(Image::read(b"P3 1 1 255\n0 0 0".as_slice()).is_err());
+    }
+
+    #[test]
+    fn large_images_fit() {
+        let image = Image {
+            width: MAX_SIZE * 2,
+            height: 10,
+            pixels: (0..MAX_SIZE * 2 * 10)
+                .map(|i| [(i % (MAX_SIZE * 2) / 4) as u8; 3])
+                .collect(),
+        }
+        .fit();
+
+        assert_eq!((image.width(), image.height()), (MAX_SIZE, 5));
+        assert_eq!(image.pixel(3, 4), [1; 3]);
+    }
+
+    #[test]
+    fn columns_rank_pixels_by_luminance() {
+        let image = Image {
+            width: 2,
+            height: 4,
+            pixels: vec![
+                [200; 3],
+                [0; 3],
+                [0, 0, 255],
+                [0; 3],
+                [50; 3],
+                [0; 3],
+                [0, 0, 255],
+                [0; 3],
+            ],
+        };
+        let column = ColumnSort::new(&image, 0);
+
+        // Equal pixels keep their order
+        assert_eq!(column.numbers(), [4, 1, 3, 2]);
+        assert_eq!(column.color(1), [0, 0, 255]);
+        assert_eq!(column.color(3), [50; 3]);
+        assert_eq!(column.color(4), [200; 3]);
+    }
+}
@@ -0,0 +1,594 @@
+//! The settings a frontend starts with, read from the defaults, then the config file, then the
+//! command line, each overriding the ones before.
+//!
+//! The config file has a `key = value` per line, with `#` starting a comment, and the command line
+//! takes the same keys as `--key value` or `--key=value`, along with flags like `--no-audio`.
+
+use crate::i18n::Language;
+use std::{fs, io, net, path, time};
+
+/// The config file read from the working directory, unless another one is passed as `--config`.
+pub const CONFIG_PATH: &str = "sorting_animations.conf";
+
+/// The settings that stay the same for a whole session.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    /// How long a tick lasts, granting the sort its budget and redrawing the array.
+    pub tick_interval: time::Duration,
+    /// The space around and between the controls, in logical pixels.
+    pub padding: u16,
+    /// The fewest numbers that can be selected.
+    pub min_numbers: usize,
+    /// The most numbers that can be selected, already far more than there are pixels to draw them
+    /// on by default.
+    pub max_numbers: usize,
+    /// The steps of the speed slider, the last of which is full speed.
+    pub max_speed: u32,
+    /// Whether the tone is played at all, or the audio device is left alone.
+    pub audio: bool,
+    /// How long the audio device stays open while the tone is paused, for the whole session
+    /// without.
+    pub audio_release: Option<time::Duration>,
+    /// The volume of the tone following the values, from silent at 0 to full at 1.
+    pub volume: f32,
+    /// How many dots the circle view draws for every eighth of the circle, sampling the numbers.
+    pub circle_dots: u32,
+    /// Whether the line view fills the area below the line down to the baseline.
+    pub line_fill: bool,
+    /// The shape of the tone.
+    pub waveform: Waveform,
+    /// The pitch of the tone for the smallest values, in Hz.
+    pub min_frequency: f32,
+    /// The pitch of the tone for the largest values, in Hz.
+    pub max_frequency: f32,
+    pub theme: Theme,
+    /// How the steps are told apart in the array.
+    pub indicators: Indicators,
+    /// How many of the last steps are highlighted.
+    pub highlights: usize,
+    /// How long the speed of a run eases in, if at all.
+    pub ramp: Option<time::Duration>,
+    /// Whether runs are sped up to take about the same time.
+    pub auto_speed: bool,
+    /// How long the window has to be left stopped without input before it plays runs of its
+    /// own, never without.
+    pub attract_after: Option<time::Duration>,
+    /// The language of the visible strings, selected by its code.
+    pub language: Language,
+    /// The port of localhost the control socket listens on, if it is enabled at all.
+    pub control_port: Option<u16>,
+    /// The port the metrics are served on with the `metrics` feature, if they are at all.
+    pub metrics_port: Option<u16>,
+    /// The address the metrics are served on, localhost unless they are scraped from elsewhere.
+    pub metrics_address: net::IpAddr,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            tick_interval: crate::DELAY_TIME,
+            padding: 15,
+            min_numbers: 10,
+            max_numbers: 1_000_000,
+            max_speed: 100,
+            audio: true,
+            audio_release: Some(time::Duration::from_secs(60)),
+            volume: 0.1,
+            circle_dots: 750,
+            line_fill: true,
+            waveform: Waveform::Sine,
+            min_frequency: 220.0,
+            max_frequency: 660.0,
+            theme: Theme::Light,
+            indicators: Indicators::Color,
+            highlights: 1,
+            ramp: Some(time::Duration::from_secs(3)),
+            auto_speed: false,
+            attract_after: None,
+            language: Language::ENGLISH,
+            control_port: None,
+            metrics_port: None,
+            metrics_address: net::Ipv4Addr::LOCALHOST.into(),
+        }
+    }
+}
+
+/// The shapes the tone can take.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Waveform {
+    Sine,
+    Square,
+    Triangle,
+    Sawtooth,
+}
+
+impl Waveform {
+    pub const VALUES: [Waveform; 4] = [
+        Waveform::Sine,
+        Waveform::Square,
+        Waveform::Triangle,
+        Waveform::Sawtooth,
+    ];
+}
+
+impl std::fmt::Display for Waveform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::str::FromStr for Waveform {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Waveform, ()> {
+        Waveform::VALUES
+            .into_iter()
+            .find(|waveform| waveform.to_string().eq_ignore_ascii_case(name))
+            .ok_or(())
+    }
+}
+
+/// The colors of the window around the array.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Theme {
+    Light,
+    Dark,
+    /// Black and white around the array, with the steps in it as far apart in luminance as they
+    /// can be.
+    HighContrast,
+}
+
+impl Theme {
+    pub const VALUES: [Theme; 3] = [Theme::Light, Theme::Dark, Theme::HighContrast];
+}
+
+impl std::fmt::Display for Theme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::str::FromStr for Theme {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Theme, ()> {
+        Theme::VALUES
+            .into_iter()
+            .find(|theme| theme.to_string().eq_ignore_ascii_case(name))
+            .ok_or(())
+    }
+}
+
+/// How the steps are told apart in the array, for those who can't tell the colors apart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Indicators {
+    /// Comparisons and accesses are colored differently.
+    Color,
+    /// Comparisons get a triangle and accesses a square, both outlined.
+    Shape,
+    Both,
+}
+
+impl Indicators {
+    pub const VALUES: [Indicators; 3] = [Indicators::Color, Indicators::Shape, Indicators::Both];
+
+    pub fn colors(self) -> bool {
+        self != Indicators::Shape
+    }
+
+    pub fn shapes(self) -> bool {
+        self != Indicators::Color
+    }
+}
+
+impl std::fmt::Display for Indicators {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::str::FromStr for Indicators {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Indicators, ()> {
+        Indicators::VALUES
+            .into_iter()
+            .find(|indicators| indicators.to_string().eq_ignore_ascii_case(name))
+            .ok_or(())
+    }
+}
+
+/// Why the settings can't be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SettingsError {
+    /// Where the setting was read, the config file and its line or the command line.
+    pub source: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.source, self.message)
+    }
+}
+
+impl Settings {
+    /// Reads the settings of the process, from the config file and its arguments, along with the
+    /// config file they are saved to.
+    ///
+    /// A missing config file leaves the defaults, unless it was passed explicitly.
+    pub fn load() -> Result<(Settings, path::PathBuf), SettingsError> {
+        let args: Vec<String> = std::env::args().skip(1).collect();
+        let config = config_path(&args)?;
+
+        let mut settings = Settings::default();
+        match fs::read_to_string(&config) {
+            Ok(text) => settings.apply_config(&config.display().to_string(), &text)?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound && !passed_config(&args) => {}
+            Err(error) => {
+                return Err(SettingsError {
+                    source: config.display().to_string(),
+                    message: error.to_string(),
+                })
+            }
+        }
+        settings.apply_args(args)?;
+
+        Ok((settings, config))
+    }
+
+    /// Writes every setting to the config file, so the next session starts with them.
+    pub fn save(&self, path: &path::Path) -> io::Result<()> {
+        let config: String = self
+            .entries()
+            .into_iter()
+            .map(|(key, value)| format!("{key} = {value}\n"))
+            .collect();
+
+        fs::write(path, config)
+    }
+
+    /// Every setting by its key, with its value as it is written in the config file.
+    pub fn entries(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("tick-interval", self.tick_interval.as_millis().to_string()),
+            ("padding", self.padding.to_string()),
+            ("min-numbers", self.min_numbers.to_string()),
+            ("max-numbers", self.max_numbers.to_string()),
+            ("max-speed", self.max_speed.to_string()),
+            ("audio", self.audio.to_string()),
+            (
+                "audio-release",
+                self.audio_release
+                    .map_or(0, |release| release.as_secs())
+                    .to_string(),
+            ),
+            ("volume", self.volume.to_string()),
+            ("circle-dots", self.circle_dots.to_string()),
+            ("line-fill", self.line_fill.to_string()),
+            ("waveform", self.waveform.to_string()),
+            ("min-frequency", self.min_frequency.to_string()),
+            ("max-frequency", self.max_frequency.to_string()),
+            ("theme", self.theme.to_string()),
+            ("indicators", self.indicators.to_string()),
+            ("highlights", self.highlights.to_string()),
+            (
+                "ramp",
+                self.ramp.map_or(0, |ramp| ramp.as_secs()).to_string(),
+            ),
+            ("auto-speed", self.auto_speed.to_string()),
+            (
+                "attract-after",
+                self.attract_after
+                    .map_or(0, |after| after.as_secs() / 60)
+                    .to_string(),
+            ),
+            ("language", self.language.code().to_string()),
+            ("control-port", self.control_port.unwrap_or(0).to_string()),
+            ("metrics-port", self.metrics_port.unwrap_or(0).to_string()),
+            ("metrics-address", self.metrics_address.to_string()),
+        ]
+    }
+
+    /// Applies the lines of a config file, named by the source in errors.
+    pub fn apply_config(&mut self, source: &str, text: &str) -> Result<(), SettingsError> {
+        for (number, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let error = |message: String| SettingsError {
+                source: format!("{source}:{}", number + 1),
+                message,
+            };
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| error(format!("expected `key = value`, found `{line}`")))?;
+            self.set(key.trim(), value.trim()).map_err(error)?;
+        }
+
+        self.validate(source)
+    }
+
+    /// Applies the arguments, `--key value` or `--key=value` each, or a flag, skipping the config
+    /// file which was read before.
+    pub fn apply_args(
+        &mut self,
+        args: impl IntoIterator<Item = String>,
+    ) -> Result<(), SettingsError> {
+        let error = |message: String| SettingsError {
+            source: String::from("command line"),
+            message,
+        };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let option = arg
+                .strip_prefix("--")
+                .ok_or_else(|| error(format!("unexpected argument `{arg}`")))?;
+            let (key, value) = match option.split_once('=') {
+                Some((key, value)) => (key.to_string(), value.to_string()),
+                // Flags take no value, turning a setting off
+                None if option == "no-audio" => (String::from("audio"), String::from("false")),
+                None => (
+                    option.to_string(),
+                    args.next()
+                        .ok_or_else(|| error(format!("missing value for `--{option}`")))?,
+                ),
+            };
+
+            if key != "config" {
+                self.set(&key, &value).map_err(error)?;
+            }
+        }
+
+        self.validate("command line")
+    }
+
+    /// Sets the setting of the key, failing if there is none or the value doesn't parse.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        fn parse<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, String> {
+            value
+                .parse()
+                .map_err(|_| format!("invalid value `{value}` for `{key}`"))
+        }
+
+        match key {
+            "tick-interval" => {
+                self.tick_interval = time::Duration::from_millis(parse(key, value)?);
+            }
+            "padding" => self.padding = parse(key, value)?,
+            "min-numbers" => self.min_numbers = parse(key, value)?,
+            "max-numbers" => self.max_numbers = parse(key, value)?,
+            "max-speed" => self.max_speed = parse(key, value)?,
+            "audio" => self.audio = parse(key, value)?,
+            // In seconds, with the device kept open at 0
+            "audio-release" => {
+                self.audio_release = Some(time::Duration::from_secs(parse(key, value)?))
+                    .filter(|release| !release.is_zero());
+            }
+            "volume" => self.volume = parse(key, value)?,
+            "circle-dots" => self.circle_dots = parse(key, value)?,
+            "line-fill" => self.line_fill = parse(key, value)?,
+            "waveform" => self.waveform = parse(key, value)?,
+            "min-frequency" => self.min_frequency = parse(key, value)?,
+            "max-frequency" => self.max_frequency = parse(key, value)?,
+            "theme" => self.theme = parse(key, value)?,
+            "indicators" => self.indicators = parse(key, value)?,
+            "highlights" => self.highlights = parse(key, value)?,
+            // In seconds, with none easing in at all
+            "ramp" => {
+                self.ramp = Some(time::Duration::from_secs(parse(key, value)?))
+                    .filter(|ramp| !ramp.is_zero());
+            }
+            "auto-speed" => self.auto_speed = parse(key, value)?,
+            // In minutes, with no attract mode at 0
+            "attract-after" => {
+                let minutes: u64 = parse(key, value)?;
+                self.attract_after = Some(time::Duration::from_secs(minutes.saturating_mul(60)))
+                    .filter(|after| !after.is_zero());
+            }
+            "language" => self.language = parse(key, value)?,
+            // With none listening at 0
+            "control-port" => {
+                self.control_port = Some(parse(key, value)?).filter(|&port| port != 0);
+            }
+            "metrics-port" => {
+                self.metrics_port = Some(parse(key, value)?).filter(|&port| port != 0);
+            }
+            "metrics-address" => self.metrics_address = parse(key, value)?,
+            _ => return Err(format!("unknown setting `{key}`")),
+        }
+
+        Ok(())
+    }
+
+    /// Fails on settings that can't work together, naming the source that set them.
+    fn validate(&self, source: &str) -> Result<(), SettingsError> {
+        match self.problem() {
+            Some(message) => Err(SettingsError {
+                source: source.to_string(),
+                message: message.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Why the settings can't work together, if they can't.
+    pub fn problem(&self) -> Option<&'static str> {
+        Some(if self.tick_interval.is_zero() {
+            "the tick interval has to be at least a millisecond"
+        } else if self.min_numbers == 0 || self.min_numbers > self.max_numbers {
+            "the numbers have to go from at least one up to at least the fewest"
+        } else if self.max_speed == 0 {
+            "the speed needs at least one step"
+        } else if !(0.0..=1.0).contains(&self.volume) {
+            "the volume has to be from 0 to 1"
+        } else if self.circle_dots == 0 {
+            "the circle needs at least one dot"
+        } else if !(self.min_frequency > 0.0 && self.min_frequency <= self.max_frequency) {
+            "the frequencies have to go from above 0 Hz up to at least the lowest"
+        } else if !(1..=crate::array::MAX_HIGHLIGHTS).contains(&self.highlights) {
+            "there have to be at least one highlight and at most as many as are kept"
+        } else {
+            return None;
+        })
+    }
+}
+
+/// Whether the config file was passed on the command line.
+fn passed_config(args: &[String]) -> bool {
+    args.iter()
+        .any(|arg| arg == "--config" || arg.starts_with("--config="))
+}
+
+/// The config file passed on the command line, or else `CONFIG_PATH`.
+fn config_path(args: &[String]) -> Result<path::PathBuf, SettingsError> {
+    let mut config = path::PathBuf::from(CONFIG_PATH);
+
+    for (i, arg) in args.iter().enumerate() {
+        if let Some(path) = arg.strip_prefix("--config=") {
+            config = path::PathBuf::from(path);
+        } else if arg == "--config" {
+            config = args
+                .get(i + 1)
+                .map(path::PathBuf::from)
+                .ok_or_else(|| SettingsError {
+                    source: String::from("command line"),
+                    message: String::from("missing value for `--config`"),
+                })?;
+        }
+    }
+
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn later_sources_override_earlier_ones() {
+        let mut settings = Settings::default();
+
+        settings
+            .apply_config(
+                "test.conf",
+                "# Slower ticks\ntick-interval = 20\n\nvolume=0.5  # Louder\nmax-speed = 50\n",
+            )
+            .unwrap();
+        settings
+            .apply_args(args(&["--max-speed", "200", "--circle-dots=10"]))
+            .unwrap();
+
+        assert_eq!(
+            settings,
+            Settings {
+                tick_interval: time::Duration::from_millis(20),
+                volume: 0.5,
+                max_speed: 200,
+                circle_dots: 10,
+                ..Settings::default()
+            }
+        );
+    }
+
+    #[test]
+    fn saved_settings_read_back() {
+        let settings = Settings {
+            tick_interval: time::Duration::from_millis(25),
+            volume: 0.35,
+            waveform: Waveform::Triangle,
+            min_frequency: 110.5,
+            theme: Theme::HighContrast,
+            indicators: Indicators::Both,
+            highlights: 8,
+            ramp: None,
+            audio_release: None,
+            auto_speed: true,
+            line_fill: false,
+            attract_after: Some(time::Duration::from_secs(300)),
+            language: "de".parse().unwrap(),
+            control_port: Some(7878),
+            metrics_port: Some(9184),
+            metrics_address: net::Ipv4Addr::UNSPECIFIED.into(),
+            ..Settings::default()
+        };
+        let path = std::env::temp_dir().join(format!("{}-{CONFIG_PATH}", std::process::id()));
+        settings.save(&path).unwrap();
+
+        let mut read = Settings::default();
+        read.apply_config("saved", &fs::read_to_string(&path).unwrap())
+            .unwrap();
+        fs::remove_file(path).unwrap();
+        assert_eq!(read, settings);
+
+        let mut read = Settings::default();
+        read.apply_args(args(&[
+            "--waveform",
+            "SQUARE",
+            "--ramp=5",
+            "--indicators=shape",
+            "--control-port=0",
+            "--no-audio",
+            "--audio-release",
+            "90",
+        ]))
+        .unwrap();
+        assert_eq!(read.waveform, Waveform::Square);
+        assert_eq!(read.indicators, Indicators::Shape);
+        assert_eq!(read.ramp, Some(time::Duration::from_secs(5)));
+        assert_eq!(read.control_port, None);
+        assert!(!read.audio);
+        assert_eq!(read.audio_release, Some(time::Duration::from_secs(90)));
+        assert!(read.set("theme", "sepia").is_err());
+        assert!(read.set("language", "xx").is_err());
+    }
+
+    #[test]
+    fn invalid_settings_name_their_source() {
+        let mut settings = Settings::default();
+
+        let error = settings
+            .apply_config("test.conf", "padding = 5\nspeed = 3\n")
+            .unwrap_err();
+        assert_eq!(error.to_string(), "test.conf:2: unknown setting `speed`");
+        let error = settings.apply_config("test.conf", "volume 3").unwrap_err();
+        assert_eq!(error.source, "test.conf:1");
+
+        for invalid in [
+            &["--max-numbers", "lots"][..],
+            &["--volume", "2"],
+            &["--min-numbers=0"],
+            &["--tick-interval"],
+            &["--min-frequency", "1000"],
+            &["--highlights", "100"],
+            &["fast"],
+        ] {
+            assert!(
+                Settings::default().apply_args(args(invalid)).is_err(),
+                "{invalid:?}"
+            );
+        }
+
+        // The config file was read before the arguments
+        let mut settings = Settings::default();
+        settings
+            .apply_args(args(&["--config", "other.conf", "--padding", "3"]))
+            .unwrap();
+        assert_eq!(settings.padding, 3);
+        assert_eq!(
+            config_path(&args(&["--config", "other.conf"])),
+            Ok(path::PathBuf::from("other.conf"))
+        );
+        assert_eq!(
+            config_path(&args(&[])),
+            Ok(path::PathBuf::from(CONFIG_PATH))
+        );
+    }
+}
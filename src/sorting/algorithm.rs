@@ -0,0 +1,298 @@
+use std::{future, pin, sync};
+
+use super::ops::{self, ArrayOps, AsyncOps};
+use super::sort::{Distribution, Info, Sort};
+
+/// How long sorting shuffled numbers takes at the suggested speed.
+const SUGGESTED_DURATION: std::time::Duration = std::time::Duration::from_secs(10);
+/// About how many ticks a sort without an estimate takes at full speed, which the operations per
+/// tick of the built in sorts are chosen for.
+const FULL_SPEED_TICKS: u64 = 200;
+
+/// Why a sort ended before sorting the array.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SortError {
+    /// The operations failed, because the sort was killed.
+    Stopped,
+    /// The sort itself failed, like a script running into an error.
+    Failed(String),
+}
+
+impl From<()> for SortError {
+    fn from(_: ()) -> SortError {
+        SortError::Stopped
+    }
+}
+
+/// A run of a sort, which is suspended between its operations until it is polled again.
+pub type SortFuture<'a> = pin::Pin<Box<dyn future::Future<Output = Result<(), SortError>> + 'a>>;
+
+/// A sort that can be animated, built in or registered by a downstream crate.
+///
+/// A sort only reaches the array through the operations, awaiting each of them and passing on
+/// their failure with `?` so it stops once killed. Failing with `SortError::Failed` reports the
+/// message like a panic.
+pub trait AnimatedSort: Send + Sync {
+    /// The name the sort is listed by, which identifies it within a registry.
+    fn name(&self) -> &str;
+
+    fn run<'a>(&'a self, ops: &'a mut AsyncOps<'_>) -> SortFuture<'a>;
+
+    /// The operations per tick at full speed for an array of the size.
+    fn max_ticks(&self, size: u64) -> u64;
+
+    /// The expected operations sorting an array of the size and arrangement takes, if known.
+    fn estimated_operations(&self, _size: u64, _distribution: Distribution) -> Option<u64> {
+        None
+    }
+}
+
+/// A shared handle to a sort the sorter can run.
+///
+/// Handles compare equal by the name of their sort.
+#[derive(Clone)]
+pub struct Algorithm {
+    sort: sync::Arc<dyn AnimatedSort>,
+    builtin: Option<Sort>,
+}
+
+impl Algorithm {
+    pub fn new(sort: impl AnimatedSort + 'static) -> Algorithm {
+        Algorithm {
+            sort: sync::Arc::new(sort),
+            builtin: None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        self.sort.name()
+    }
+
+    /// The built in sort this is, if it is one.
+    pub fn builtin(&self) -> Option<Sort> {
+        self.builtin
+    }
+
+    /// Runs the sort to the end on the calling thread.
+    pub fn run(&self, ops: &mut dyn ArrayOps) -> Result<(), SortError> {
+        ops::block_on(self.sort.run(&mut AsyncOps::new(ops)))
+    }
+
+    /// Runs the sort as a future, for whoever suspends it between the operations.
+    pub fn run_async<'a>(&'a self, ops: &'a mut AsyncOps<'_>) -> SortFuture<'a> {
+        self.sort.run(ops)
+    }
+
+    /// The arrangement the sort takes the most operations on, known for the built in sorts.
+    pub fn worst_case(&self) -> Option<crate::array::Arrangement> {
+        self.builtin.map(|sort| sort.worst_case())
+    }
+
+    /// The complexity and properties of the sort, known for the built in sorts.
+    pub fn info(&self) -> Option<Info> {
+        self.builtin.map(|sort| sort.info())
+    }
+
+    pub fn max_ticks(&self, size: u64) -> u64 {
+        self.sort.max_ticks(size)
+    }
+
+    pub fn estimated_operations(&self, size: u64, distribution: Distribution) -> Option<u64> {
+        self.sort.estimated_operations(size, distribution)
+    }
+
+    /// The fraction of full speed sorting shuffled numbers of the size takes about
+    /// `SUGGESTED_DURATION` at.
+    pub fn suggested_speed(&self, size: u64) -> f32 {
+        suggested_speed(
+            self.estimated_operations(size, Distribution::Shuffled),
+            self.max_ticks(size),
+        )
+    }
+}
+
+/// The fraction of full speed the expected operations take `SUGGESTED_DURATION` at, or else the
+/// fraction `FULL_SPEED_TICKS` stretch to it at.
+pub(super) fn suggested_speed(estimate: Option<u64>, max_ticks: u64) -> f32 {
+    let full_speed_ticks = match estimate {
+        Some(estimate) => estimate as f32 / max_ticks.max(1) as f32,
+        None => FULL_SPEED_TICKS as f32,
+    };
+    let ticks = SUGGESTED_DURATION.as_secs_f32() / crate::DELAY_TIME.as_secs_f32();
+
+    (full_speed_ticks / ticks).clamp(f32::EPSILON, 1.0)
+}
+
+impl Default for Algorithm {
+    fn default() -> Algorithm {
+        Algorithm::from(Sort::default())
+    }
+}
+
+impl From<Sort> for Algorithm {
+    fn from(sort: Sort) -> Algorithm {
+        Algorithm {
+            sort: sync::Arc::new(sort),
+            builtin: Some(sort),
+        }
+    }
+}
+
+impl PartialEq for Algorithm {
+    fn eq(&self, other: &Algorithm) -> bool {
+        self.name() == other.name()
+    }
+}
+
+impl Eq for Algorithm {}
+
+impl std::fmt::Debug for Algorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Algorithm").field(&self.name()).finish()
+    }
+}
+
+impl std::fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The sorts to choose from, in the order they are listed.
+#[derive(Clone, Debug)]
+pub struct Registry {
+    algorithms: Vec<Algorithm>,
+}
+
+impl Registry {
+    /// A registry without any sorts, not even the built in ones.
+    pub fn empty() -> Registry {
+        Registry {
+            algorithms: Vec::new(),
+        }
+    }
+
+    /// Adds the sort to the end, replacing a sort of the same name in its place instead.
+    pub fn register(&mut self, algorithm: impl Into<Algorithm>) {
+        let algorithm = algorithm.into();
+
+        match self
+            .algorithms
+            .iter_mut()
+            .find(|registered| **registered == algorithm)
+        {
+            Some(registered) => *registered = algorithm,
+            None => self.algorithms.push(algorithm),
+        }
+    }
+
+    pub fn algorithms(&self) -> &[Algorithm] {
+        &self.algorithms
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Algorithm> {
+        self.algorithms
+            .iter()
+            .find(|algorithm| algorithm.name() == name)
+    }
+}
+
+/// The built in sorts.
+impl Default for Registry {
+    fn default() -> Registry {
+        let mut registry = Registry::empty();
+        for &sort in Sort::VALUES {
+            registry.register(sort);
+        }
+
+        registry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array::ArrayState;
+    use crate::sorting::Sorter;
+
+    /// Sorts by swapping every out of order neighbors, like a downstream crate might add.
+    struct NeighborSort;
+
+    impl AnimatedSort for NeighborSort {
+        fn name(&self) -> &str {
+            "NeighborSort"
+        }
+
+        fn run<'a>(&'a self, ops: &'a mut AsyncOps<'_>) -> SortFuture<'a> {
+            Box::pin(async move {
+                let mut sorted = false;
+                while !sorted {
+                    sorted = true;
+                    for i in 1..ops.size() {
+                        if ops.cmp_two(i - 1, i).await?.is_gt() {
+                            ops.swap(i - 1, i).await?;
+                            sorted = false;
+                        }
+                    }
+                }
+
+                Ok(())
+            })
+        }
+
+        fn max_ticks(&self, size: u64) -> u64 {
+            std::cmp::max(1, size.pow(2) / 100)
+        }
+    }
+
+    #[test]
+    fn registered_sorts_animate() {
+        let mut registry = Registry::default();
+        registry.register(Algorithm::new(NeighborSort));
+        assert_eq!(registry.algorithms().len(), Sort::VALUES.len() + 1);
+        // Only the built in sorts tell about themselves
+        assert_eq!(registry.get("NeighborSort").unwrap().info(), None);
+        assert_eq!(
+            registry.get("HeapSort").unwrap().info(),
+            Some(Sort::HeapSort.info())
+        );
+
+        let mut sorter = Sorter::new(ArrayState::new(50));
+        sorter.set_sort(registry.get("NeighborSort").unwrap().clone());
+        sorter.reverse();
+        sorter.start_sort();
+        while sorter.tick(1.0).is_ok() {}
+
+        assert_eq!(
+            sorter.read_array(|array| array.numbers().to_vec()),
+            (1..=50).collect::<Vec<_>>()
+        );
+        assert_eq!(sorter.comparisons(), 50 * 49);
+        assert_eq!(sorter.writes(), 50 * 49);
+
+        sorter.reverse();
+        sorter.start_sort();
+        sorter.tick(0.0).unwrap();
+        sorter.kill_sort();
+        assert!(!sorter.alive());
+        assert!(sorter.panic().is_none());
+    }
+
+    #[test]
+    fn register_replaces_by_name() {
+        let mut registry = Registry::empty();
+        registry.register(Sort::BubbleSort);
+        registry.register(Sort::HeapSort);
+        registry.register(Sort::BubbleSort);
+
+        assert_eq!(
+            registry.algorithms(),
+            [Sort::BubbleSort.into(), Sort::HeapSort.into()]
+        );
+        assert_eq!(
+            registry.get("HeapSort").unwrap().builtin(),
+            Some(Sort::HeapSort)
+        );
+        assert!(registry.get("Script: HeapSort").is_none());
+    }
+}
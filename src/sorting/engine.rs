@@ -0,0 +1,930 @@
+use std::{ops, sync, time};
+
+use super::algorithm::Algorithm;
+use super::ops::ArrayResult;
+use super::utility::Utility;
+use super::wrapping::{wrap_sorter_array_ops, Granularity, SortPanic, TickStats};
+use super::{stepping, trace, wrapping};
+use crate::array;
+
+/// The fraction of the budget the first tick of a ramp grants.
+const RAMP_START: f32 = 0.05;
+/// How long the budget stays slowed down after an operation on a watched index, before easing
+/// back in over as long again.
+const SLOW_MOTION: time::Duration = time::Duration::from_secs(1);
+/// The most operations a single tick grants, so pausing or killing the sort never waits long on
+/// the budget granted before.
+pub(super) const MAX_TICK_OPERATIONS: u64 = 10_000_000;
+/// The steps between no and full speed the budget of a tick is granted in.
+const SPEED_STEPS: u64 = 1 << 20;
+
+/// Runs sorts either on a thread of their own or deterministically on the calling thread.
+///
+/// The threaded engine is faster, but the deterministic one performs exactly the operations every
+/// tick grants, however the threads are scheduled, which makes it a reference for the threaded
+/// one. Targets without threads like the web only have the deterministic engine, which is also
+/// the default with the `single-threaded` feature.
+pub struct Sorter {
+    engine: Engine,
+    /// How long a tick lasts, which the ramp and slow motion count their durations in.
+    tick_interval: time::Duration,
+    ramp: Option<time::Duration>,
+    /// The fraction of the budget granted in slow motion.
+    slow_motion: f32,
+    /// The ticks since the sort started.
+    ticks: u32,
+    /// The tick the last operation on a watched index was noticed at.
+    slowed_at: Option<u32>,
+    watch_hits: u64,
+    /// The fraction of an operation left over by the slowed down budgets.
+    carry: f32,
+    /// The sort, its granularity and range to set up again once the numbers are rearranged.
+    arranging: Option<(Algorithm, Granularity, Option<ops::Range<usize>>)>,
+}
+
+// There is only the one engine of the sorter, so its size hardly matters
+#[allow(clippy::large_enum_variant)]
+enum Engine {
+    Threaded(wrapping::Sorter),
+    Deterministic(stepping::Sorter),
+}
+
+macro_rules! delegate_to_engine {
+    ($(fn $name:ident(&self $(, $arg:ident: $typ:ty)*) -> $ret:ty;)+) => {
+        $(pub fn $name(&self, $($arg: $typ),*) -> $ret {
+            match self.engine {
+                Engine::Threaded(ref sorter) => sorter.$name($($arg),*),
+                Engine::Deterministic(ref sorter) => sorter.$name($($arg),*),
+            }
+        })+
+    };
+    ($(fn $name:ident(&mut self $(, $arg:ident: $typ:ty)*) -> $ret:ty;)+) => {
+        $(pub fn $name(&mut self, $($arg: $typ),*) -> $ret {
+            match self.engine {
+                Engine::Threaded(ref mut sorter) => sorter.$name($($arg),*),
+                Engine::Deterministic(ref mut sorter) => sorter.$name($($arg),*),
+            }
+        })+
+    };
+}
+
+impl Sorter {
+    pub fn new(array_state: array::ArrayState) -> Sorter {
+        let engine = if cfg!(any(target_arch = "wasm32", feature = "single-threaded")) {
+            Engine::Deterministic(stepping::Sorter::new(array_state))
+        } else {
+            Engine::Threaded(wrapping::Sorter::new(array_state))
+        };
+
+        Sorter {
+            engine,
+            tick_interval: crate::DELAY_TIME,
+            ramp: None,
+            slow_motion: 0.01,
+            ticks: 0,
+            slowed_at: None,
+            watch_hits: 0,
+            carry: 0.0,
+            arranging: None,
+        }
+    }
+
+    pub fn deterministic(&self) -> bool {
+        matches!(self.engine, Engine::Deterministic(_))
+    }
+
+    /// Switches the engine, killing the sort and keeping the array and the settings.
+    ///
+    /// The panic and trace of the last run are dropped. Without threads, the engine stays
+    /// deterministic.
+    pub fn set_deterministic(&mut self, deterministic: bool) {
+        if deterministic == self.deterministic() || cfg!(target_arch = "wasm32") {
+            return;
+        }
+
+        self.kill_sort();
+        let array_state = self.read_array(Clone::clone);
+        let mut sorter = Sorter {
+            engine: if deterministic {
+                Engine::Deterministic(stepping::Sorter::new(array_state))
+            } else {
+                Engine::Threaded(wrapping::Sorter::new(array_state))
+            },
+            tick_interval: self.tick_interval,
+            ramp: self.ramp,
+            slow_motion: self.slow_motion,
+            ticks: 0,
+            slowed_at: None,
+            watch_hits: 0,
+            carry: 0.0,
+            arranging: None,
+        };
+        sorter.set_sort(self.sort().clone());
+        sorter.set_seed(self.seed());
+        sorter.set_granularity(self.granularity());
+        sorter.set_highlights(self.highlights());
+        sorter.set_recording(self.recording());
+        sorter.set_probing(self.probing());
+        sorter.set_tick_interval(self.tick_interval);
+
+        *self = sorter;
+    }
+
+    /// Sets how long a tick lasts, which is `DELAY_TIME` unless the settings select another one.
+    pub fn set_tick_interval(&mut self, tick_interval: time::Duration) {
+        self.tick_interval = tick_interval;
+
+        if let Engine::Threaded(ref mut sorter) = self.engine {
+            sorter.set_tick_interval(tick_interval);
+        }
+    }
+
+    pub fn tick_interval(&self) -> time::Duration {
+        self.tick_interval
+    }
+
+    /// Sets how long the budget of a run eases in, starting at `RAMP_START` of the granted one,
+    /// where none grants it right away.
+    ///
+    /// The duration is counted in ticks of the tick interval, so pausing holds the ramp and the
+    /// deterministic engine stays deterministic.
+    pub fn set_ramp(&mut self, ramp: Option<time::Duration>) {
+        self.ramp = ramp;
+    }
+
+    pub fn ramp(&self) -> Option<time::Duration> {
+        self.ramp
+    }
+
+    /// Sets the fraction of the budget granted for `SLOW_MOTION` after an operation on a watched
+    /// index, which the budget then eases back in from.
+    pub fn set_slow_motion(&mut self, slow_motion: f32) {
+        self.slow_motion = slow_motion.clamp(0.0, 1.0);
+    }
+
+    pub fn slow_motion(&self) -> f32 {
+        self.slow_motion
+    }
+
+    /// Watches the index, or stops watching it if it already is.
+    pub fn toggle_watched(&mut self, index: usize) {
+        self.operate_array(|array| array.toggle_watched(index));
+    }
+
+    /// Stops watching any index, going back to full speed right away.
+    pub fn clear_watched(&mut self) {
+        self.operate_array(array::ArrayState::clear_watched);
+        self.slowed_at = None;
+    }
+
+    /// Rearranges the numbers as an animation of every swap, which `tick_arrangement` plays at a
+    /// quick fixed pace. Once done, the sort is set up again to run on them.
+    ///
+    /// Arrangements that aren't animated apply right away, setting up the sort the same way.
+    pub fn arrange_animated(&mut self, arrangement: array::Arrangement) {
+        self.kill_sort();
+
+        match Utility::of(arrangement) {
+            // All the numbers are rearranged, whatever range the sort sorts
+            Some(utility) => {
+                let range = self.read_array(|array| array.selected_range());
+                self.arranging = Some((self.sort().clone(), self.granularity(), range));
+                self.set_engine_sort(Algorithm::new(utility), Granularity::All);
+                self.operate_array(|array| array.set_range(None));
+                self.start_sort();
+            }
+            None => {
+                self.arrange(arrangement);
+                self.start_sort();
+            }
+        }
+    }
+
+    /// Whether the numbers are being rearranged, before the sort can start.
+    pub fn arranging(&self) -> bool {
+        self.arranging.is_some()
+    }
+
+    /// Grants the rearranging its operations of a tick, setting the sort up once it is done.
+    ///
+    /// The rearranging isn't slowed down, and its operations aren't counted in the stats.
+    pub fn tick_arrangement(&mut self) {
+        if !self.arranging() {
+            return;
+        }
+
+        let size = self.size() as u64;
+        let done = match self.engine {
+            Engine::Threaded(ref mut sorter) => {
+                sorter.tick_operations(sorter.sort().max_ticks(size))
+            }
+            Engine::Deterministic(ref mut sorter) => {
+                sorter.tick_operations(sorter.sort().max_ticks(size))
+            }
+        };
+
+        if done.is_err() {
+            self.kill_sort();
+            self.reset_stats();
+            self.clear_step();
+            self.start_sort();
+        }
+    }
+
+    /// Kills the sort, or stops rearranging the numbers where they are and sets the sort up again.
+    pub fn kill_sort(&mut self) {
+        match self.engine {
+            Engine::Threaded(ref mut sorter) => sorter.kill_sort(),
+            Engine::Deterministic(ref mut sorter) => sorter.kill_sort(),
+        }
+
+        if let Some((sort, granularity, range)) = self.arranging.take() {
+            self.set_engine_sort(sort, granularity);
+            self.operate_array(|array| array.set_range(range));
+        }
+    }
+
+    /// The sort, also while the numbers are rearranged before it runs.
+    pub fn sort(&self) -> &Algorithm {
+        match (&self.arranging, &self.engine) {
+            (Some((sort, ..)), _) => sort,
+            (None, Engine::Threaded(sorter)) => sorter.sort(),
+            (None, Engine::Deterministic(sorter)) => sorter.sort(),
+        }
+    }
+
+    pub fn set_sort(&mut self, sort: impl Into<Algorithm>) {
+        match self.arranging {
+            Some((ref mut arranged, ..)) => *arranged = sort.into(),
+            None => self.set_engine_sort(sort.into(), self.granularity()),
+        }
+    }
+
+    pub fn granularity(&self) -> Granularity {
+        match (&self.arranging, &self.engine) {
+            (Some((_, granularity, _)), _) => *granularity,
+            (None, Engine::Threaded(sorter)) => sorter.granularity(),
+            (None, Engine::Deterministic(sorter)) => sorter.granularity(),
+        }
+    }
+
+    pub fn set_granularity(&mut self, granularity: Granularity) {
+        match self.arranging {
+            Some((_, ref mut arranged, _)) => *arranged = granularity,
+            None => self.set_engine_sort(self.sort().clone(), granularity),
+        }
+    }
+
+    /// The range the sort sorts, also while the numbers are rearranged before it runs.
+    pub fn range(&self) -> ops::Range<usize> {
+        match self.arranging {
+            Some((_, _, ref range)) => array::clamp_range(range.clone(), self.size()),
+            None => self.read_array(array::ArrayState::range),
+        }
+    }
+
+    /// Selects the range the next sort sorts, or all the numbers.
+    pub fn set_range(&mut self, range: Option<ops::Range<usize>>) {
+        match self.arranging {
+            Some((_, _, ref mut arranged)) => *arranged = range,
+            None => self.operate_array(|array| array.set_range(range)),
+        }
+    }
+
+    /// Sets up the engine to run the sort, bypassing the one waiting for the rearranging.
+    fn set_engine_sort(&mut self, sort: Algorithm, granularity: Granularity) {
+        match self.engine {
+            Engine::Threaded(ref mut sorter) => {
+                sorter.set_sort(sort);
+                sorter.set_granularity(granularity);
+            }
+            Engine::Deterministic(ref mut sorter) => {
+                sorter.set_sort(sort);
+                sorter.set_granularity(granularity);
+            }
+        }
+    }
+
+    pub fn start_sort(&mut self) {
+        self.ticks = 0;
+        self.slowed_at = None;
+        self.watch_hits = self.read_array(array::ArrayState::watch_hits);
+        self.carry = 0.0;
+
+        match self.engine {
+            Engine::Threaded(ref mut sorter) => sorter.start_sort(),
+            Engine::Deterministic(ref mut sorter) => sorter.start_sort(),
+        }
+    }
+
+    /// Grants the sort the fraction of its operations per tick at full speed.
+    pub fn tick(&mut self, speed: f32) -> ArrayResult<()> {
+        self.tick_operations(budget(speed, self.sort().max_ticks(self.size() as u64)))
+    }
+
+    /// Grants the sort a number of operations, at least one and at most `MAX_TICK_OPERATIONS`,
+    /// eased in while ramping up.
+    ///
+    /// While slowed down, the fractions of operations are carried over until they add up to one,
+    /// so the sort can crawl slower than an operation per tick.
+    pub fn tick_operations(&mut self, operations: u64) -> ArrayResult<()> {
+        let watch_hits = self.read_array(array::ArrayState::watch_hits);
+        if watch_hits != self.watch_hits {
+            self.watch_hits = watch_hits;
+            self.slowed_at = Some(self.ticks);
+        }
+
+        let operations = operations.min(MAX_TICK_OPERATIONS);
+        let factor = self.ramp_factor() * self.slow_motion_factor();
+        self.ticks = self.ticks.saturating_add(1);
+
+        let operations = if factor < 1.0 {
+            let budget = operations as f32 * factor + self.carry;
+            self.carry = budget.fract();
+            if budget < 1.0 {
+                return if self.alive() { Ok(()) } else { Err(()) };
+            }
+
+            budget as u64
+        } else {
+            self.carry = 0.0;
+            operations
+        };
+
+        match self.engine {
+            Engine::Threaded(ref mut sorter) => sorter.tick_operations(operations),
+            Engine::Deterministic(ref mut sorter) => sorter.tick_operations(operations),
+        }
+    }
+
+    /// The fraction of the budget the next tick grants, easing in quadratically.
+    fn ramp_factor(&self) -> f32 {
+        let progress = match self.ramp {
+            Some(ramp) if !ramp.is_zero() => {
+                (self.tick_interval * self.ticks).as_secs_f32() / ramp.as_secs_f32()
+            }
+            _ => return 1.0,
+        };
+
+        RAMP_START + (1.0 - RAMP_START) * progress.min(1.0).powi(2)
+    }
+
+    /// The fraction of the budget the next tick grants after an operation on a watched index.
+    fn slow_motion_factor(&self) -> f32 {
+        let Some(slowed_at) = self.slowed_at else {
+            return 1.0;
+        };
+        let elapsed = self.tick_interval * (self.ticks - slowed_at);
+        let easing = elapsed.saturating_sub(SLOW_MOTION).as_secs_f32() / SLOW_MOTION.as_secs_f32();
+
+        self.slow_motion + (1.0 - self.slow_motion) * easing.min(1.0).powi(2)
+    }
+
+    delegate_to_engine! {
+        fn panic(&self) -> Option<&SortPanic>;
+        fn seed(&self) -> u64;
+        fn highlights(&self) -> usize;
+        fn recording(&self) -> bool;
+        fn trace(&self) -> Option<&sync::Arc<trace::Trace>>;
+        fn operations(&self) -> u64;
+        fn probing(&self) -> bool;
+        fn tick_stats(&self) -> Option<TickStats>;
+    }
+
+    delegate_to_engine! {
+        fn set_seed(&mut self, seed: u64) -> ();
+        fn set_highlights(&mut self, highlights: usize) -> ();
+        fn set_recording(&mut self, recording: bool) -> ();
+        fn set_probing(&mut self, probing: bool) -> ();
+        fn wedged(&mut self) -> Option<time::Duration>;
+        fn alive(&mut self) -> bool;
+        fn step(&mut self) -> ArrayResult<()>;
+        fn drain(&mut self) -> ArrayResult<()>;
+        fn resume(&mut self) -> ArrayResult<()>;
+        fn rescale(&mut self, factor: f32) -> ArrayResult<()>;
+    }
+}
+
+impl Sorter {
+    pub fn operate_array<T>(&self, f: impl FnOnce(&mut array::ArrayState) -> T) -> T {
+        match self.engine {
+            Engine::Threaded(ref sorter) => sorter.operate_array(f),
+            Engine::Deterministic(ref sorter) => sorter.operate_array(f),
+        }
+    }
+
+    pub fn read_array<T>(&self, f: impl FnOnce(&array::ArrayState) -> T) -> T {
+        match self.engine {
+            Engine::Threaded(ref sorter) => sorter.read_array(f),
+            Engine::Deterministic(ref sorter) => sorter.read_array(f),
+        }
+    }
+
+    wrap_sorter_array_ops! {
+        operate_array:
+        fn clear_step() -> ();
+        fn shuffle() -> ();
+        fn shuffle_seeded(seed: u64) -> ();
+        fn reverse() -> ();
+        fn order() -> ();
+        fn equalize() -> ();
+        fn arrange(arrangement: array::Arrangement) -> ();
+        fn initialize(size: usize, max_value: usize) -> ();
+        fn load_numbers(numbers: Vec<usize>) -> ();
+        fn reset_stats() -> ();
+        fn set_count_distances(count: bool) -> ();
+        fn set_tweening(tweening: bool) -> ();
+        fn advance_tweens(elapsed: time::Duration) -> ();
+        fn take_dirty() -> Option<ops::Range<usize>>;
+    }
+
+    wrap_sorter_array_ops! {
+        read_array:
+        fn size() -> usize;
+        fn last_step() -> array::Step;
+        fn comparisons() -> u64;
+        fn wasted_comparisons() -> Option<u64>;
+        fn reads() -> u64;
+        fn writes() -> u64;
+        fn stats_snapshot() -> array::Stats;
+        fn generation() -> u64;
+    }
+}
+
+/// The operations a tick at the speed, the fraction of full speed, grants out of the most per
+/// tick, capped at `MAX_TICK_OPERATIONS` but never none at any speed.
+pub(super) fn budget(speed: f32, max_ticks: u64) -> u64 {
+    if speed.is_nan() || speed <= 0.0 {
+        return 0;
+    }
+
+    let steps = (speed.min(1.0) * SPEED_STEPS as f32).round() as u64;
+    // Saturating only where the budget is far above the cap anyway
+    (max_ticks.saturating_mul(steps) / SPEED_STEPS).clamp(1, MAX_TICK_OPERATIONS)
+}
+
+/// The operations left of a budget once the speed changed by the factor, in the same steps as
+/// the budget and capped at `MAX_TICK_OPERATIONS`.
+pub(super) fn scaled(operations: u64, factor: f32) -> u64 {
+    if factor.is_nan() || factor <= 0.0 {
+        return 0;
+    }
+
+    // Converting saturates for factors beyond any budget
+    let steps = (factor * SPEED_STEPS as f32).round() as u64;
+    (operations.saturating_mul(steps) / SPEED_STEPS).min(MAX_TICK_OPERATIONS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array::ArrayState;
+    use crate::sorting::Sort;
+
+    #[test]
+    fn budget_stays_within_bounds() {
+        assert_eq!(budget(0.0, 1000), 0);
+        assert_eq!(budget(-1.0, 1000), 0);
+        assert_eq!(budget(f32::NAN, 1000), 0);
+        assert_eq!(budget(1.0, 1000), 1000);
+        assert_eq!(budget(0.3, 100), 30);
+        assert_eq!(budget(2.0, 1000), 1000);
+
+        let sizes = [0, 1, 2, 100, 1_000_000, u32::MAX as u64, u64::MAX];
+        let speeds = [f32::MIN_POSITIVE, 1e-9, 0.01, 0.5, 1.0, f32::INFINITY];
+        for &sort in Sort::VALUES {
+            for size in sizes {
+                let max_ticks = Algorithm::from(sort).max_ticks(size);
+                for speed in speeds {
+                    let operations = budget(speed, max_ticks);
+                    assert!(
+                        (1..=MAX_TICK_OPERATIONS).contains(&operations),
+                        "{sort:?} {size} {speed} {operations}"
+                    );
+                    assert!(operations <= max_ticks.max(1), "{sort:?} {size} {speed}");
+                }
+            }
+        }
+        // Quadratic sorts of millions are granted the cap, even though their max ticks saturate
+        let max_ticks = Algorithm::from(Sort::StoogeSort).max_ticks(u64::MAX);
+        assert_eq!(max_ticks, u64::MAX / 1000);
+        assert_eq!(budget(1.0, max_ticks), MAX_TICK_OPERATIONS);
+    }
+
+    #[test]
+    fn scaled_saturates() {
+        assert_eq!(scaled(990, 0.1), 99);
+        assert_eq!(scaled(990, 2.0), 1980);
+        assert_eq!(scaled(990, f32::NAN), 0);
+        assert_eq!(scaled(u64::MAX, 1.0), MAX_TICK_OPERATIONS);
+        assert_eq!(scaled(u64::MAX, f32::MIN_POSITIVE), 0);
+        assert_eq!(scaled(1, f32::MAX), MAX_TICK_OPERATIONS);
+    }
+
+    #[test]
+    fn switching_engines_keeps_settings() {
+        let mut sorter = Sorter::new(ArrayState::new(20));
+        sorter.set_deterministic(false);
+        sorter.set_sort(Sort::HeapSort);
+        sorter.set_seed(7);
+        sorter.set_highlights(3);
+        sorter.reverse();
+        sorter.start_sort();
+
+        sorter.set_deterministic(true);
+        assert!(sorter.deterministic());
+        assert!(!sorter.alive());
+        assert_eq!(sorter.sort(), &Sort::HeapSort.into());
+        assert_eq!((sorter.seed(), sorter.highlights()), (7, 3));
+        assert_eq!(sorter.read_array(|array| array.numbers()[0]), 20);
+
+        // Exactly the granted operations run
+        sorter.start_sort();
+        for operations in 1..=5 {
+            sorter.tick_operations(operations).unwrap();
+            assert_eq!(sorter.operations(), operations * (operations + 1) / 2);
+        }
+    }
+
+    #[test]
+    fn generation_follows_operations() {
+        let mut sorter = Sorter::new(ArrayState::new(10));
+        sorter.set_deterministic(true);
+        sorter.set_sort(Sort::BubbleSort);
+        sorter.reverse();
+        assert_eq!(sorter.take_dirty(), Some(0..10));
+        let generation = sorter.generation();
+
+        // Bubble sort compares the first two numbers, then swaps them
+        sorter.start_sort();
+        sorter.tick_operations(1).unwrap();
+        assert_eq!(sorter.generation(), generation);
+        assert_eq!(sorter.take_dirty(), None);
+        sorter.tick_operations(1).unwrap();
+        assert_eq!(sorter.generation(), generation + 1);
+        assert_eq!(sorter.take_dirty(), Some(0..2));
+        assert_eq!(sorter.stats_snapshot().generation, generation + 1);
+    }
+
+    #[test]
+    fn ramp_eases_budget_in() {
+        let mut sorter = Sorter::new(ArrayState::new(1000));
+        sorter.set_deterministic(true);
+        sorter.set_ramp(Some(crate::DELAY_TIME * 10));
+        sorter.set_sort(Sort::BubbleSort);
+        sorter.reverse();
+        sorter.start_sort();
+
+        let mut budgets = Vec::new();
+        for _ in 0..12 {
+            let operations = sorter.operations();
+            sorter.tick_operations(100).unwrap();
+            budgets.push(sorter.operations() - operations);
+        }
+        // The fractions carry over
+        assert_eq!(budgets[..3], [5, 5, 9]);
+        assert!(budgets.is_sorted());
+        assert_eq!(budgets[10..], [100, 100]);
+
+        // Restarting ramps up again
+        sorter.kill_sort();
+        sorter.start_sort();
+        sorter.tick_operations(100).unwrap();
+        assert_eq!(sorter.operations(), 5);
+    }
+
+    #[test]
+    fn watched_indices_slow_down() {
+        let watched_sorter = |deterministic| {
+            let mut sorter = Sorter::new(ArrayState::new(100));
+            sorter.set_deterministic(deterministic);
+            sorter.set_sort(Sort::BubbleSort);
+            sorter.set_slow_motion(0.1);
+            sorter.reverse();
+            sorter.toggle_watched(50);
+            sorter.start_sort();
+
+            // The batch ends on the first operation touching the watched index
+            let hit = |sorter: &Sorter| sorter.read_array(|array| array.last_step().contains(50));
+            while !hit(&sorter) {
+                assert!(sorter.operations() < 200, "{deterministic}");
+                sorter.tick_operations(1000).unwrap();
+                sorter.drain().unwrap();
+            }
+            assert_eq!(sorter.read_array(array::ArrayState::watch_hits), 1);
+
+            sorter
+        };
+        watched_sorter(false).kill_sort();
+
+        // Only the deterministic engine grants exactly the budgets
+        let mut sorter = watched_sorter(true);
+        let operations = sorter.operations();
+        let ticks = (SLOW_MOTION.as_millis() / crate::DELAY_TIME.as_millis()) as u64;
+
+        // A tenth for as long as the slow motion lasts
+        sorter.operate_array(array::ArrayState::clear_watched);
+        for _ in 0..ticks {
+            sorter.tick_operations(100).unwrap();
+        }
+        assert_eq!(sorter.operations() - operations, ticks * 10);
+
+        sorter.toggle_watched(50);
+        sorter.clear_watched();
+        sorter.tick_operations(100).unwrap();
+        assert_eq!(sorter.operations() - operations, ticks * 10 + 100);
+    }
+
+    #[test]
+    fn arrangements_animate_as_utility_sorts() {
+        let mut sorter = Sorter::new(ArrayState::new(1000));
+        sorter.set_deterministic(true);
+        sorter.set_sort(Sort::HeapSort);
+        sorter.set_granularity(Granularity::Comparisons);
+
+        sorter.arrange_animated(array::Arrangement::Reversed);
+        assert!(sorter.arranging());
+        assert_eq!(sorter.sort(), &Sort::HeapSort.into());
+        assert_eq!(sorter.granularity(), Granularity::Comparisons);
+
+        // Every swap is paced, spread over the ticks of a second
+        sorter.tick_arrangement();
+        assert_eq!(sorter.operations(), 5);
+
+        // Killing it leaves the numbers half way
+        sorter.kill_sort();
+        assert!(!sorter.arranging());
+        assert_eq!(
+            sorter.read_array(|array| array.numbers()[..2].to_vec()),
+            [1000, 999]
+        );
+        assert_eq!(sorter.read_array(|array| array.numbers()[5]), 6);
+
+        sorter.order();
+        sorter.arrange_animated(array::Arrangement::Reversed);
+        while sorter.arranging() {
+            sorter.tick_arrangement();
+        }
+        assert!(sorter.read_array(|array| array.numbers().iter().rev().is_sorted()));
+        assert_eq!(
+            (sorter.comparisons(), sorter.reads(), sorter.writes()),
+            (0, 0, 0)
+        );
+        assert_eq!(sorter.granularity(), Granularity::Comparisons);
+
+        // The sort is set up to run on them
+        assert!(sorter.alive());
+        sorter.tick_operations(10).unwrap();
+        assert!(sorter.comparisons() > 0);
+
+        // Other arrangements apply right away
+        sorter.arrange_animated(array::Arrangement::Sorted);
+        assert!(!sorter.arranging() && sorter.alive());
+        assert!(sorter.read_array(|array| array.numbers().is_sorted()));
+    }
+
+    #[test]
+    fn sorts_only_sort_the_range() {
+        for deterministic in [false, true] {
+            for sort in [Sort::QuickSort, Sort::ParallelMergeSort] {
+                let mut sorter = Sorter::new(ArrayState::new(100));
+                sorter.set_deterministic(deterministic);
+                sorter.set_sort(sort);
+                sorter.reverse();
+                sorter.set_range(Some(20..150));
+                assert_eq!(sorter.range(), 20..100);
+
+                sorter.start_sort();
+                while sorter.tick(1.0).is_ok() {}
+
+                sorter.read_array(|array| {
+                    let numbers = array.numbers();
+                    assert_eq!(numbers[..20], (81..=100).rev().collect::<Vec<_>>()[..]);
+                    assert_eq!(numbers[20..], (1..=80).collect::<Vec<_>>()[..], "{sort}");
+                });
+            }
+        }
+
+        // Rearranging rearranges all the numbers, then the sort sorts the range again
+        let mut sorter = Sorter::new(ArrayState::new(10));
+        sorter.set_deterministic(true);
+        sorter.set_range(Some(0..5));
+        sorter.arrange_animated(array::Arrangement::Reversed);
+        assert_eq!(sorter.range(), 0..5);
+        while sorter.arranging() {
+            sorter.tick_arrangement();
+        }
+        while sorter.tick(1.0).is_ok() {}
+        assert_eq!(
+            sorter.read_array(|array| array.numbers().to_vec()),
+            [6, 7, 8, 9, 10, 5, 4, 3, 2, 1]
+        );
+    }
+
+    #[test]
+    fn phases_are_reported_until_restarted() {
+        for deterministic in [false, true] {
+            let mut sorter = Sorter::new(ArrayState::new(100));
+            sorter.set_deterministic(deterministic);
+            sorter.set_sort(Sort::ShellSort);
+            sorter.reverse();
+            sorter.start_sort();
+
+            // The phase is published along with the operations after it
+            sorter.tick_operations(10).unwrap();
+            while sorter.operations() < 10 {
+                std::thread::yield_now();
+            }
+            sorter.drain().unwrap();
+            let gap = |sorter: &Sorter| sorter.read_array(array::ArrayState::phase);
+            assert_eq!(
+                gap(&sorter),
+                Some(array::Phase {
+                    label: "Gap",
+                    value: 50
+                })
+            );
+
+            while sorter.tick(1.0).is_ok() {}
+            assert_eq!(gap(&sorter).map(|phase| phase.value), Some(1));
+
+            sorter.set_sort(Sort::InsertionSort);
+            sorter.start_sort();
+            while sorter.tick(1.0).is_ok() {}
+            assert_eq!(gap(&sorter), None);
+        }
+    }
+
+    #[test]
+    fn peak_space_is_published_by_both_engines() {
+        for deterministic in [false, true] {
+            for sort in [Sort::MergeSort, Sort::ParallelMergeSort] {
+                let mut sorter = Sorter::new(ArrayState::new(64));
+                sorter.set_deterministic(deterministic);
+                sorter.set_sort(sort);
+                sorter.reverse();
+                sorter.start_sort();
+                while sorter.tick(1.0).is_ok() {}
+
+                let space = sorter.read_array(ArrayState::space);
+                // The last merge of both halves needs the most, whether they merged at once or not
+                assert_eq!(
+                    space.peak_aux_bytes,
+                    64 * std::mem::size_of::<usize>(),
+                    "{sort}"
+                );
+                assert_eq!(space.peak_depth, 6, "{sort}");
+            }
+        }
+
+        let mut sorter = Sorter::new(ArrayState::new(64));
+        sorter.set_sort(Sort::HeapSort);
+        sorter.start_sort();
+        sorter.reset_stats();
+        while sorter.tick(1.0).is_ok() {}
+        assert_eq!(
+            sorter.read_array(ArrayState::space),
+            array::Space::default()
+        );
+    }
+
+    #[test]
+    fn annotations_are_logged_until_restarted() {
+        for deterministic in [false, true] {
+            let mut sorter = Sorter::new(ArrayState::new(100));
+            sorter.set_deterministic(deterministic);
+            sorter.set_sort(Sort::ShellSort);
+            sorter.reverse();
+            sorter.start_sort();
+            while sorter.tick(1.0).is_ok() {}
+
+            let log = sorter.read_array(|array| array.annotations().clone());
+            let texts: Vec<_> = log.entries().map(|a| a.text.as_str()).collect();
+            assert_eq!(texts.len(), 6);
+            assert_eq!(texts[0], "Gap reduced to 50");
+            // Logged along with the operation after them, the first one right away
+            let operations: Vec<_> = log.entries().map(|a| a.operation).collect();
+            assert_eq!(operations[0], 0);
+            assert!(operations.is_sorted() && operations[1] > 0);
+
+            sorter.set_sort(Sort::InsertionSort);
+            sorter.start_sort();
+            assert!(sorter.read_array(|array| array.annotations().is_empty()));
+        }
+    }
+
+    #[test]
+    fn markers_are_reported_within_the_range() {
+        for deterministic in [false, true] {
+            let mut sorter = Sorter::new(ArrayState::new(40));
+            sorter.set_deterministic(deterministic);
+            sorter.set_sort(Sort::MergeSort);
+            sorter.reverse();
+            sorter.set_range(Some(10..30));
+            sorter.start_sort();
+
+            let merges =
+                |sorter: &Sorter| sorter.read_array(|array| array.markers().collect::<Vec<_>>());
+            let mut seen = Vec::new();
+            let mut performed = 0;
+            while sorter.alive() {
+                performed += 1;
+                sorter.tick_operations(1).unwrap_or_default();
+                while sorter.operations() < performed && sorter.alive() {
+                    std::thread::yield_now();
+                }
+                sorter.drain().unwrap_or_default();
+                seen.extend(merges(&sorter));
+            }
+
+            assert!(seen.iter().all(|&merge| matches!(
+                merge,
+                array::Marker::Merge { start, end, .. } if start >= 10 && end <= 30
+            )));
+            assert!(seen.contains(&array::Marker::Merge {
+                start: 10,
+                middle: 20,
+                end: 30
+            }));
+
+            sorter.set_sort(Sort::InsertionSort);
+            sorter.start_sort();
+            assert_eq!(merges(&sorter), Vec::new());
+        }
+    }
+
+    #[test]
+    fn marks_follow_the_buckets_within_the_range() {
+        for deterministic in [false, true] {
+            let mut sorter = Sorter::new(ArrayState::new(40));
+            sorter.set_deterministic(deterministic);
+            sorter.set_sort(Sort::RadixSort10);
+            sorter.reverse();
+            sorter.set_range(Some(10..30));
+            sorter.start_sort();
+
+            let marks = |sorter: &Sorter| sorter.read_array(|array| array.marks().to_vec());
+            let mut seen = Vec::new();
+            let mut performed = 0;
+            while sorter.alive() {
+                performed += 1;
+                sorter.tick_operations(1).unwrap_or_default();
+                while sorter.operations() < performed && sorter.alive() {
+                    std::thread::yield_now();
+                }
+                sorter.drain().unwrap_or_default();
+                seen.push(marks(&sorter));
+            }
+
+            // A cursor for each of the ten digits, which every one of the twenty numbers ends in
+            // twice, ending up at the starts of their buckets. Reading the numbers marks nothing.
+            assert!(seen.iter().flatten().all(|index| (10..=30).contains(index)));
+            assert!(seen.contains(&vec![10, 12, 14, 16, 18, 20, 22, 24, 26, 28]));
+            let marked = seen.iter().position(|marks| !marks.is_empty()).unwrap();
+            assert!(seen[marked..].contains(&Vec::new()));
+
+            sorter.set_sort(Sort::InsertionSort);
+            sorter.start_sort();
+            assert_eq!(marks(&sorter), Vec::new());
+        }
+    }
+
+    #[test]
+    fn probing_measures_ticks() {
+        for deterministic in [false, true] {
+            let mut sorter = Sorter::new(ArrayState::new(100));
+            sorter.set_deterministic(deterministic);
+            sorter.set_sort(Sort::BubbleSort);
+            sorter.reverse();
+            sorter.start_sort();
+
+            // The sort thread has to be done with a budget before the next tick replaces it
+            let tick = |sorter: &mut Sorter, operations| {
+                let total = sorter.operations() + operations;
+                sorter.tick_operations(operations).unwrap();
+                while sorter.operations() < total {
+                    std::thread::yield_now();
+                }
+            };
+            tick(&mut sorter, 10);
+            assert_eq!(sorter.tick_stats(), None);
+
+            sorter.set_probing(true);
+            tick(&mut sorter, 10);
+            tick(&mut sorter, 5);
+
+            let stats = sorter.tick_stats().unwrap();
+            let expected = if deterministic { (5, 5) } else { (10, 10) };
+            assert_eq!((stats.granted, stats.performed), expected);
+            assert_eq!(stats.remaining, 0);
+            if deterministic {
+                assert!(stats.waited.is_zero());
+            }
+
+            // Switching engines keeps probing
+            sorter.set_deterministic(!deterministic);
+            assert!(sorter.probing());
+            sorter.kill_sort();
+        }
+    }
+}
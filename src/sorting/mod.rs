@@ -1,5 +1,21 @@
+mod algorithm;
+mod engine;
+mod ops;
+#[cfg(feature = "scripting")]
+mod script;
 mod sort;
+mod stepping;
+mod trace;
+mod utility;
 mod wrapping;
 
-pub use sort::Sort;
-pub use wrapping::Sorter;
+pub use algorithm::{Algorithm, AnimatedSort, Registry, SortError, SortFuture};
+pub use engine::Sorter;
+pub use ops::{ArrayOps, ArrayResult, AsyncOps, Checked, Counting, Operation, Recording, Subrange};
+#[cfg(feature = "scripting")]
+pub use script::{Script, ScriptError};
+pub use sort::{Distribution, Info, Sort};
+pub use stepping::Sorter as SteppingSorter;
+pub use trace::{Replay, Trace};
+pub use wrapping::Sorter as ThreadedSorter;
+pub use wrapping::{run, Granularity, SortPanic, TickStats};
@@ -0,0 +1,723 @@
+use rand::SeedableRng;
+use std::{cmp, future, pin, sync, task, thread};
+
+use crate::array::{ArrayState, Marker, Phase, SpaceReport};
+
+pub type ArrayResult<T> = Result<T, ()>;
+
+/// The operations a sort performs on the array it sorts.
+///
+/// Every operation fails once the sort should stop, which the sort passes on with `?`.
+pub trait ArrayOps {
+    fn cmp_two(&mut self, a: usize, b: usize) -> ArrayResult<cmp::Ordering>;
+    fn cmp(&mut self, index: usize, value: usize) -> ArrayResult<cmp::Ordering>;
+    fn swap(&mut self, a: usize, b: usize) -> ArrayResult<()>;
+    fn get(&mut self, index: usize) -> ArrayResult<usize>;
+    fn set(&mut self, index: usize, value: usize) -> ArrayResult<()>;
+    fn size(&self) -> usize;
+
+    /// Random numbers for the sort, seeded by whoever runs it.
+    fn rng(&mut self) -> &mut rand::rngs::StdRng;
+
+    /// Reports how far the sort got, like its current gap, which is shown along the stats.
+    ///
+    /// Reporting never waits for the array, the phase is published along with the operations.
+    fn report_phase(&mut self, _label: &'static str, _value: usize) {}
+
+    /// Reports what the sort is working on, like the runs it merges, or that it is done with it.
+    ///
+    /// Like a phase, the marker is published along with the operations after it.
+    fn report_marker(&mut self, _marker: Option<Marker>) {}
+
+    /// Marks indices of the whole array to stay highlighted for as long as a pass takes, like
+    /// its cursors, replacing the indices marked before.
+    ///
+    /// Like a phase, the marks are published along with the operations after them.
+    fn mark_indices(&mut self, _indices: &[usize]) {}
+
+    fn clear_marks(&mut self) {
+        self.mark_indices(&[]);
+    }
+
+    /// Reports the space the sort needs besides the array, of which the most it needed at once is
+    /// shown along the stats.
+    ///
+    /// Like a phase, the space is published along with the operations after it.
+    fn report_space(&mut self, _report: SpaceReport) {}
+
+    /// Reports a buffer of `bytes` the sort allocated, until it reports freeing it.
+    fn report_aux_alloc(&mut self, bytes: usize) {
+        self.report_space(SpaceReport::AuxAlloc(bytes));
+    }
+
+    fn report_aux_free(&mut self, bytes: usize) {
+        self.report_space(SpaceReport::AuxFree(bytes));
+    }
+
+    /// Reports the depth of the recursion the sort entered, 1 for its outermost call.
+    fn report_depth(&mut self, depth: usize) {
+        self.report_space(SpaceReport::Depth(depth));
+    }
+
+    /// Announces a milestone in the log of the sort, like the pivot it chose, which drops some
+    /// if there are too many.
+    ///
+    /// Like a phase, the annotation is published along with the operation after it.
+    fn annotate(&mut self, _text: String) {}
+
+    /// Fails once the sort should stop, without performing an operation.
+    fn check(&mut self) -> ArrayResult<()> {
+        Ok(())
+    }
+
+    /// Whether the next operation can be performed right away, which a sort awaits before every
+    /// operation through `AsyncOps`.
+    ///
+    /// Operations paced on the calling thread are pending once the budget of the tick is used up,
+    /// suspending the sort until the next tick polls it again, while those of a sort thread just
+    /// block within the operation.
+    fn poll_ready(&mut self, _cx: &mut task::Context<'_>) -> task::Poll<()> {
+        task::Poll::Ready(())
+    }
+
+    /// Operations for worker threads sorting parts of the array at the same time, sharing the
+    /// budget of these operations, which must not be used until the workers are dropped.
+    ///
+    /// Operations that can't be shared hand out none, so the sort does the work itself instead.
+    fn fork(&mut self, _workers: usize) -> Option<Vec<Box<dyn ArrayOps + Send + '_>>> {
+        None
+    }
+}
+
+/// The operations as a sort awaits them, so it can be suspended before any of them, like when
+/// it runs a tick at a time on the calling thread.
+///
+/// Everything besides the operations is reached through the inner operations it derefs to.
+pub struct AsyncOps<'a> {
+    ops: &'a mut (dyn ArrayOps + 'a),
+}
+
+macro_rules! wrap_async_op {
+    ($name:ident, ($($arg:ident : $argtype:ty),*) -> $ret:ty) => {
+        pub async fn $name(&mut self, $($arg:$argtype),*) -> ArrayResult<$ret> {
+            future::poll_fn(|cx| self.ops.poll_ready(cx)).await;
+            self.ops.$name($($arg),*)
+        }
+    }
+}
+
+impl<'a> AsyncOps<'a> {
+    pub fn new(ops: &'a mut (dyn ArrayOps + 'a)) -> AsyncOps<'a> {
+        AsyncOps { ops }
+    }
+
+    wrap_async_op!(cmp_two, (a:usize, b:usize) -> cmp::Ordering);
+    wrap_async_op!(swap, (a:usize, b:usize) -> ());
+    wrap_async_op!(cmp, (index:usize, value:usize) -> cmp::Ordering);
+    wrap_async_op!(get, (index:usize) -> usize);
+    wrap_async_op!(set, (index:usize, value:usize) -> ());
+}
+
+impl<'a> std::ops::Deref for AsyncOps<'a> {
+    type Target = dyn ArrayOps + 'a;
+
+    fn deref(&self) -> &(dyn ArrayOps + 'a) {
+        self.ops
+    }
+}
+
+impl<'a> std::ops::DerefMut for AsyncOps<'a> {
+    fn deref_mut(&mut self) -> &mut (dyn ArrayOps + 'a) {
+        self.ops
+    }
+}
+
+/// Drives a sort on the calling thread until it is done.
+///
+/// While the sort waits for something other than the operations, like a script computing, the
+/// thread parks until woken, polling the sort at least every `DELAY_TIME` so it notices being
+/// stopped.
+pub(super) fn block_on<F: future::Future>(future: F) -> F::Output {
+    let mut future = pin::pin!(future);
+    let waker = thread_waker();
+    let mut cx = task::Context::from_waker(&waker);
+
+    loop {
+        if let task::Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+
+        thread::park_timeout(crate::DELAY_TIME);
+    }
+}
+
+/// A waker unparking the calling thread.
+pub(super) fn thread_waker() -> task::Waker {
+    struct Unpark(thread::Thread);
+
+    impl task::Wake for Unpark {
+        fn wake(self: sync::Arc<Unpark>) {
+            self.0.unpark();
+        }
+    }
+
+    task::Waker::from(sync::Arc::new(Unpark(thread::current())))
+}
+
+/// Runs every operation right away on an owned array, which counts the statistics.
+pub struct Counting<'a> {
+    array: ArrayState,
+    rng: rand::rngs::StdRng,
+    observer: Option<&'a mut dyn FnMut(&ArrayState)>,
+}
+
+impl<'a> Counting<'a> {
+    pub fn new(array: ArrayState, seed: u64) -> Counting<'a> {
+        Counting {
+            array,
+            rng: rand::rngs::StdRng::seed_from_u64(seed),
+            observer: None,
+        }
+    }
+
+    /// Calls the observer after every operation.
+    pub fn with_observer(mut self, observer: &'a mut dyn FnMut(&ArrayState)) -> Counting<'a> {
+        self.observer = Some(observer);
+        self
+    }
+
+    pub fn array(&self) -> &ArrayState {
+        &self.array
+    }
+
+    pub fn into_array(self) -> ArrayState {
+        self.array
+    }
+
+    fn perform_step<T>(&mut self, step: impl FnOnce(&mut ArrayState) -> T) -> ArrayResult<T> {
+        let result = step(&mut self.array);
+
+        if let Some(observer) = self.observer.as_mut() {
+            observer(&self.array);
+        }
+
+        Ok(result)
+    }
+}
+
+macro_rules! wrap_array_op {
+    ($name:ident, ($($arg:ident : $argtype:ty),*) -> $ret:ty) => {
+        fn $name(&mut self, $($arg:$argtype),*) -> ArrayResult<$ret> {
+            self.perform_step(|array_state_argument| {
+                array_state_argument.$name($($arg),*)
+            })
+        }
+    }
+}
+
+impl ArrayOps for Counting<'_> {
+    wrap_array_op!(cmp_two, (a:usize, b:usize) -> cmp::Ordering);
+    wrap_array_op!(swap, (a:usize, b:usize) -> ());
+    wrap_array_op!(cmp, (index:usize, value:usize) -> cmp::Ordering);
+    wrap_array_op!(get, (index:usize) -> usize);
+    wrap_array_op!(set, (index:usize, value:usize) -> ());
+
+    fn size(&self) -> usize {
+        self.array.size()
+    }
+
+    fn rng(&mut self) -> &mut rand::rngs::StdRng {
+        &mut self.rng
+    }
+
+    fn report_phase(&mut self, label: &'static str, value: usize) {
+        self.array.set_phase(Some(Phase { label, value }));
+    }
+
+    fn report_marker(&mut self, marker: Option<Marker>) {
+        self.array.set_marker(marker);
+    }
+
+    fn mark_indices(&mut self, indices: &[usize]) {
+        self.array.set_marks(indices);
+    }
+
+    fn report_space(&mut self, report: SpaceReport) {
+        self.array.report_space(report);
+    }
+
+    fn annotate(&mut self, text: String) {
+        self.array.annotate(text);
+    }
+}
+
+/// A single operation performed by a sort.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operation {
+    CmpTwo(usize, usize),
+    Cmp(usize, usize),
+    Swap(usize, usize),
+    Get(usize),
+    Set(usize, usize),
+}
+
+impl Operation {
+    /// The indices of the array the operation touches.
+    pub fn indices(&self) -> Vec<usize> {
+        match *self {
+            Operation::CmpTwo(a, b) | Operation::Swap(a, b) => vec![a, b],
+            Operation::Cmp(index, _) | Operation::Get(index) | Operation::Set(index, _) => {
+                vec![index]
+            }
+        }
+    }
+}
+
+/// Records the trace of every successful operation passed on to the inner operations.
+///
+/// The workers it forks record traces of their own, which join its trace in the order of the
+/// workers once it goes on, so the trace doesn't depend on how the workers interleaved.
+pub struct Recording<O> {
+    ops: O,
+    recorded: sync::Arc<sync::Mutex<Recorded>>,
+}
+
+/// The operations recorded so far, and the workers forked since, whose operations come next.
+#[derive(Default)]
+struct Recorded {
+    operations: Vec<Operation>,
+    forked: Vec<sync::Arc<sync::Mutex<Recorded>>>,
+}
+
+impl Recorded {
+    fn lock(recorded: &sync::Mutex<Recorded>) -> sync::MutexGuard<'_, Recorded> {
+        recorded
+            .lock()
+            .unwrap_or_else(sync::PoisonError::into_inner)
+    }
+
+    /// Appends the operations of the forked workers, including the ones they forked in turn.
+    fn join(&mut self) {
+        for worker in self.forked.drain(..) {
+            let mut worker = Recorded::lock(&worker);
+            worker.join();
+            self.operations.append(&mut worker.operations);
+        }
+    }
+}
+
+impl<O: ArrayOps> Recording<O> {
+    pub fn new(ops: O) -> Recording<O> {
+        Recording {
+            ops,
+            recorded: sync::Arc::default(),
+        }
+    }
+
+    pub fn trace(&self) -> Vec<Operation> {
+        let mut recorded = Recorded::lock(&self.recorded);
+        recorded.join();
+
+        recorded.operations.clone()
+    }
+
+    pub fn into_parts(self) -> (O, Vec<Operation>) {
+        let mut recorded = Recorded::lock(&self.recorded);
+        recorded.join();
+        let trace = std::mem::take(&mut recorded.operations);
+        drop(recorded);
+
+        (self.ops, trace)
+    }
+
+    fn record<T>(&mut self, operation: Operation, result: ArrayResult<T>) -> ArrayResult<T> {
+        if result.is_ok() {
+            let mut recorded = Recorded::lock(&self.recorded);
+            recorded.join();
+            recorded.operations.push(operation);
+        }
+
+        result
+    }
+}
+
+impl<O: ArrayOps> ArrayOps for Recording<O> {
+    fn cmp_two(&mut self, a: usize, b: usize) -> ArrayResult<cmp::Ordering> {
+        let result = self.ops.cmp_two(a, b);
+        self.record(Operation::CmpTwo(a, b), result)
+    }
+
+    fn cmp(&mut self, index: usize, value: usize) -> ArrayResult<cmp::Ordering> {
+        let result = self.ops.cmp(index, value);
+        self.record(Operation::Cmp(index, value), result)
+    }
+
+    fn swap(&mut self, a: usize, b: usize) -> ArrayResult<()> {
+        let result = self.ops.swap(a, b);
+        self.record(Operation::Swap(a, b), result)
+    }
+
+    fn get(&mut self, index: usize) -> ArrayResult<usize> {
+        let result = self.ops.get(index);
+        self.record(Operation::Get(index), result)
+    }
+
+    fn set(&mut self, index: usize, value: usize) -> ArrayResult<()> {
+        let result = self.ops.set(index, value);
+        self.record(Operation::Set(index, value), result)
+    }
+
+    fn size(&self) -> usize {
+        self.ops.size()
+    }
+
+    fn rng(&mut self) -> &mut rand::rngs::StdRng {
+        self.ops.rng()
+    }
+
+    fn report_phase(&mut self, label: &'static str, value: usize) {
+        self.ops.report_phase(label, value)
+    }
+
+    fn report_marker(&mut self, marker: Option<Marker>) {
+        self.ops.report_marker(marker)
+    }
+
+    fn mark_indices(&mut self, indices: &[usize]) {
+        self.ops.mark_indices(indices)
+    }
+
+    fn report_space(&mut self, report: SpaceReport) {
+        self.ops.report_space(report)
+    }
+
+    fn annotate(&mut self, text: String) {
+        self.ops.annotate(text)
+    }
+
+    fn check(&mut self) -> ArrayResult<()> {
+        self.ops.check()
+    }
+
+    fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> task::Poll<()> {
+        self.ops.poll_ready(cx)
+    }
+
+    fn fork(&mut self, workers: usize) -> Option<Vec<Box<dyn ArrayOps + Send + '_>>> {
+        let mut recorded = Recorded::lock(&self.recorded);
+        let workers = self.ops.fork(workers)?;
+
+        Some(
+            workers
+                .into_iter()
+                .map(|ops| {
+                    let worker = Recording::new(ops);
+                    recorded.forked.push(worker.recorded.clone());
+                    Box::new(worker) as Box<dyn ArrayOps + Send + '_>
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Passes every operation on to the inner operations, panicking if it is out of bounds.
+pub struct Checked<O> {
+    ops: O,
+}
+
+impl<O: ArrayOps> Checked<O> {
+    pub fn new(ops: O) -> Checked<O> {
+        Checked { ops }
+    }
+
+    pub fn into_inner(self) -> O {
+        self.ops
+    }
+
+    fn check_bounds(&self, operation: Operation) {
+        let size = self.ops.size();
+
+        for index in operation.indices() {
+            assert!(index < size, "{operation:?} out of bounds for size {size}");
+        }
+    }
+}
+
+macro_rules! wrap_checked_op {
+    ($name:ident, $operation:ident, ($($arg:ident : $argtype:ty),*) -> $ret:ty) => {
+        fn $name(&mut self, $($arg:$argtype),*) -> ArrayResult<$ret> {
+            self.check_bounds(Operation::$operation($($arg),*));
+            self.ops.$name($($arg),*)
+        }
+    }
+}
+
+impl<O: ArrayOps> ArrayOps for Checked<O> {
+    wrap_checked_op!(cmp_two, CmpTwo, (a:usize, b:usize) -> cmp::Ordering);
+    wrap_checked_op!(swap, Swap, (a:usize, b:usize) -> ());
+    wrap_checked_op!(cmp, Cmp, (index:usize, value:usize) -> cmp::Ordering);
+    wrap_checked_op!(get, Get, (index:usize) -> usize);
+    wrap_checked_op!(set, Set, (index:usize, value:usize) -> ());
+
+    fn size(&self) -> usize {
+        self.ops.size()
+    }
+
+    fn rng(&mut self) -> &mut rand::rngs::StdRng {
+        self.ops.rng()
+    }
+
+    fn report_phase(&mut self, label: &'static str, value: usize) {
+        self.ops.report_phase(label, value)
+    }
+
+    fn report_marker(&mut self, marker: Option<Marker>) {
+        self.ops.report_marker(marker)
+    }
+
+    fn mark_indices(&mut self, indices: &[usize]) {
+        self.ops.mark_indices(indices)
+    }
+
+    fn report_space(&mut self, report: SpaceReport) {
+        self.ops.report_space(report)
+    }
+
+    fn annotate(&mut self, text: String) {
+        self.ops.annotate(text)
+    }
+
+    fn check(&mut self) -> ArrayResult<()> {
+        self.ops.check()
+    }
+
+    fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> task::Poll<()> {
+        self.ops.poll_ready(cx)
+    }
+
+    fn fork(&mut self, workers: usize) -> Option<Vec<Box<dyn ArrayOps + Send + '_>>> {
+        let workers = self.ops.fork(workers)?;
+
+        Some(
+            workers
+                .into_iter()
+                .map(|ops| Box::new(Checked { ops }) as Box<dyn ArrayOps + Send + '_>)
+                .collect(),
+        )
+    }
+}
+
+/// Passes every operation on to a range of the inner operations, so the sort only sees that
+/// part of the array and leaves the rest untouched.
+pub struct Subrange<O> {
+    ops: O,
+    start: usize,
+    size: usize,
+}
+
+impl<O: ArrayOps> Subrange<O> {
+    /// Clamps the range to the inner operations.
+    pub fn new(ops: O, range: std::ops::Range<usize>) -> Subrange<O> {
+        let range = crate::array::clamp_range(Some(range), ops.size());
+
+        Subrange {
+            ops,
+            start: range.start,
+            size: range.len(),
+        }
+    }
+
+    pub fn into_inner(self) -> O {
+        self.ops
+    }
+}
+
+impl<O: ArrayOps> ArrayOps for Subrange<O> {
+    fn cmp_two(&mut self, a: usize, b: usize) -> ArrayResult<cmp::Ordering> {
+        self.ops.cmp_two(self.start + a, self.start + b)
+    }
+
+    fn cmp(&mut self, index: usize, value: usize) -> ArrayResult<cmp::Ordering> {
+        self.ops.cmp(self.start + index, value)
+    }
+
+    fn swap(&mut self, a: usize, b: usize) -> ArrayResult<()> {
+        self.ops.swap(self.start + a, self.start + b)
+    }
+
+    fn get(&mut self, index: usize) -> ArrayResult<usize> {
+        self.ops.get(self.start + index)
+    }
+
+    fn set(&mut self, index: usize, value: usize) -> ArrayResult<()> {
+        self.ops.set(self.start + index, value)
+    }
+
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn rng(&mut self) -> &mut rand::rngs::StdRng {
+        self.ops.rng()
+    }
+
+    fn report_phase(&mut self, label: &'static str, value: usize) {
+        self.ops.report_phase(label, value)
+    }
+
+    fn report_marker(&mut self, marker: Option<Marker>) {
+        let start = self.start;
+        self.ops
+            .report_marker(marker.map(|marker| marker.offset(start)))
+    }
+
+    fn mark_indices(&mut self, indices: &[usize]) {
+        let indices: Vec<usize> = indices.iter().map(|index| self.start + index).collect();
+        self.ops.mark_indices(&indices)
+    }
+
+    fn report_space(&mut self, report: SpaceReport) {
+        self.ops.report_space(report)
+    }
+
+    fn annotate(&mut self, text: String) {
+        self.ops.annotate(text)
+    }
+
+    fn check(&mut self) -> ArrayResult<()> {
+        self.ops.check()
+    }
+
+    fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> task::Poll<()> {
+        self.ops.poll_ready(cx)
+    }
+
+    fn fork(&mut self, workers: usize) -> Option<Vec<Box<dyn ArrayOps + Send + '_>>> {
+        let (start, size) = (self.start, self.size);
+        let workers = self.ops.fork(workers)?;
+
+        Some(
+            workers
+                .into_iter()
+                .map(|ops| Box::new(Subrange { ops, start, size }) as Box<dyn ArrayOps + Send + '_>)
+                .collect(),
+        )
+    }
+}
+
+impl<O: ArrayOps + ?Sized> ArrayOps for Box<O> {
+    fn cmp_two(&mut self, a: usize, b: usize) -> ArrayResult<cmp::Ordering> {
+        (**self).cmp_two(a, b)
+    }
+
+    fn cmp(&mut self, index: usize, value: usize) -> ArrayResult<cmp::Ordering> {
+        (**self).cmp(index, value)
+    }
+
+    fn swap(&mut self, a: usize, b: usize) -> ArrayResult<()> {
+        (**self).swap(a, b)
+    }
+
+    fn get(&mut self, index: usize) -> ArrayResult<usize> {
+        (**self).get(index)
+    }
+
+    fn set(&mut self, index: usize, value: usize) -> ArrayResult<()> {
+        (**self).set(index, value)
+    }
+
+    fn size(&self) -> usize {
+        (**self).size()
+    }
+
+    fn rng(&mut self) -> &mut rand::rngs::StdRng {
+        (**self).rng()
+    }
+
+    fn report_phase(&mut self, label: &'static str, value: usize) {
+        (**self).report_phase(label, value)
+    }
+
+    fn report_marker(&mut self, marker: Option<Marker>) {
+        (**self).report_marker(marker)
+    }
+
+    fn mark_indices(&mut self, indices: &[usize]) {
+        (**self).mark_indices(indices)
+    }
+
+    fn report_space(&mut self, report: SpaceReport) {
+        (**self).report_space(report)
+    }
+
+    fn annotate(&mut self, text: String) {
+        (**self).annotate(text)
+    }
+
+    fn check(&mut self) -> ArrayResult<()> {
+        (**self).check()
+    }
+
+    fn poll_ready(&mut self, cx: &mut task::Context<'_>) -> task::Poll<()> {
+        (**self).poll_ready(cx)
+    }
+
+    fn fork(&mut self, workers: usize) -> Option<Vec<Box<dyn ArrayOps + Send + '_>>> {
+        (**self).fork(workers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sorting::{Sort, Sorter};
+
+    #[test]
+    fn recording_matches_counting() {
+        for &sort in Sort::VALUES {
+            let mut array = ArrayState::new(100);
+            array.reverse();
+
+            let mut recording = Recording::new(Counting::new(array, 0));
+            sort.sort(&mut recording, 100).unwrap();
+            let (counting, trace) = recording.into_parts();
+            let array = counting.into_array();
+
+            assert_eq!(array.numbers(), (1..=100).collect::<Vec<_>>(), "{sort}");
+            let reads = trace
+                .iter()
+                .map(|operation| match operation {
+                    Operation::CmpTwo(..) | Operation::Swap(..) => 2,
+                    Operation::Cmp(..) | Operation::Get(..) => 1,
+                    Operation::Set(..) => 0,
+                })
+                .sum::<u64>();
+            assert_eq!(reads, array.reads(), "{sort}");
+        }
+    }
+
+    #[test]
+    fn counting_matches_animated() {
+        for &sort in Sort::VALUES {
+            let mut array = ArrayState::new(100);
+            array.reverse();
+
+            let mut counting = Counting::new(array.clone(), 0);
+            sort.sort(&mut counting, 100).unwrap();
+
+            let mut sorter = Sorter::new(array);
+            sorter.set_sort(sort);
+            sorter.set_seed(0);
+            sorter.start_sort();
+            while sorter.tick(1.0).is_ok() {}
+
+            let counted = counting.array();
+            sorter.read_array(|animated| {
+                assert_eq!(animated.numbers(), counted.numbers(), "{sort}");
+                assert_eq!(animated.comparisons(), counted.comparisons(), "{sort}");
+                assert_eq!(animated.reads(), counted.reads(), "{sort}");
+                assert_eq!(animated.writes(), counted.writes(), "{sort}");
+            });
+        }
+    }
+}
@@ -0,0 +1,482 @@
+use std::sync::{self, atomic, mpsc};
+use std::{cmp, fs, future, panic, path, task, thread};
+
+use super::algorithm::{AnimatedSort, SortError, SortFuture};
+use super::ops::{ArrayResult, AsyncOps, Operation};
+
+/// The extension of script files loaded from a directory.
+pub const EXTENSION: &str = "rhai";
+
+/// A sort written as a Rhai script, loaded at runtime.
+///
+/// The script is the body of the sort. It reaches the array through the functions `size()`,
+/// `cmp_two(a, b)`, `cmp(index, value)`, `swap(a, b)`, `get(index)` and `set(index, value)`,
+/// where comparisons return `-1`, `0` or `1`, and announces its milestones with `annotate(text)`.
+/// It is listed as `Script: <name>`.
+#[derive(Debug)]
+pub struct Script {
+    name: String,
+    ast: rhai::AST,
+}
+
+/// A script that failed to load.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptError {
+    pub name: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Script {} failed to load: {}", self.name, self.message)
+    }
+}
+
+impl Script {
+    pub fn compile(name: impl Into<String>, source: &str) -> Result<Script, ScriptError> {
+        let name = name.into();
+
+        match rhai::Engine::new().compile(source) {
+            Ok(ast) => Ok(Script {
+                name: format!("Script: {name}"),
+                ast,
+            }),
+            Err(error) => Err(ScriptError {
+                name,
+                message: error.to_string(),
+            }),
+        }
+    }
+
+    /// Loads a script, named after its file.
+    pub fn load(path: &path::Path) -> Result<Script, ScriptError> {
+        let name = path
+            .file_stem()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+
+        match fs::read_to_string(path) {
+            Ok(source) => Script::compile(name, &source),
+            Err(error) => Err(ScriptError {
+                name,
+                message: error.to_string(),
+            }),
+        }
+    }
+
+    /// Loads every script in the directory ordered by name, where a missing directory has none.
+    pub fn load_dir(dir: &path::Path) -> Vec<Result<Script, ScriptError>> {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+            Err(error) => {
+                return vec![Err(ScriptError {
+                    name: dir.display().to_string(),
+                    message: error.to_string(),
+                })]
+            }
+        };
+
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == EXTENSION)
+            })
+            .collect();
+        paths.sort();
+
+        paths.iter().map(|path| Script::load(path)).collect()
+    }
+
+    fn evaluate(
+        ast: &rhai::AST,
+        bridge: sync::Arc<Bridge>,
+        stop: sync::Arc<atomic::AtomicBool>,
+    ) -> Result<(), Box<rhai::EvalAltResult>> {
+        let mut engine = rhai::Engine::new();
+
+        engine.on_progress(move |_| {
+            stop.load(atomic::Ordering::Relaxed)
+                .then_some(rhai::Dynamic::UNIT)
+        });
+
+        let size = bridge.size as rhai::INT;
+        engine.register_fn("size", move || size);
+
+        let array = bridge.clone();
+        engine.register_fn("cmp_two", move |a: rhai::INT, b: rhai::INT| {
+            array.call(Operation::CmpTwo(array.index(a)?, array.index(b)?))
+        });
+        let array = bridge.clone();
+        engine.register_fn("cmp", move |index: rhai::INT, value: rhai::INT| {
+            array.call(Operation::Cmp(array.index(index)?, number(value)?))
+        });
+        let array = bridge.clone();
+        engine.register_fn("swap", move |a: rhai::INT, b: rhai::INT| {
+            array
+                .call(Operation::Swap(array.index(a)?, array.index(b)?))
+                .map(drop)
+        });
+        let array = bridge.clone();
+        engine.register_fn("get", move |index: rhai::INT| {
+            array.call(Operation::Get(array.index(index)?))
+        });
+        let array = bridge.clone();
+        engine.register_fn("set", move |index: rhai::INT, value: rhai::INT| {
+            array
+                .call(Operation::Set(array.index(index)?, number(value)?))
+                .map(drop)
+        });
+        let array = bridge;
+        engine.register_fn("annotate", move |text: &str| array.annotate(text));
+
+        engine.run_ast(ast)
+    }
+}
+
+impl AnimatedSort for Script {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Runs the script on the operations, failing with its error message if it errors.
+    ///
+    /// The script is evaluated on a helper thread which hands every operation to the sort, so the
+    /// operations are still performed by whoever drives it. While the script computes without
+    /// operating on the array the sort is pending, checking the operations for being stopped
+    /// whenever it is polled, until the next request of the script wakes it up.
+    fn run<'a>(&'a self, ops: &'a mut AsyncOps<'_>) -> SortFuture<'a> {
+        Box::pin(async move {
+            let (requests, request_receiver) = mpsc::channel();
+            let (reply_sender, replies) = mpsc::channel();
+            let waker = sync::Arc::new(sync::Mutex::new(None));
+            let bridge = sync::Arc::new(Bridge {
+                requests,
+                replies: sync::Mutex::new(replies),
+                waker: waker.clone(),
+                size: ops.size(),
+            });
+            let evaluation = Evaluation::spawn(self.ast.clone(), bridge);
+
+            let stopped = serve(ops, &request_receiver, &waker, &reply_sender)
+                .await
+                .is_err();
+            drop(reply_sender);
+
+            match evaluation.join() {
+                _ if stopped => Err(SortError::Stopped),
+                Ok(()) => Ok(()),
+                Err(error) => Err(SortError::Failed(error.to_string())),
+            }
+        })
+    }
+
+    /// Scripts are assumed to be quadratic.
+    fn max_ticks(&self, size: u64) -> u64 {
+        cmp::max(1, size.saturating_pow(2) / 100)
+    }
+}
+
+/// What the script asks of the thread performing its operations.
+enum Request {
+    Operation(Operation),
+    /// Annotations aren't replied to.
+    Annotate(String),
+}
+
+/// The waker of the sort waiting for the next request, if it waits.
+type Waker = sync::Mutex<Option<task::Waker>>;
+
+/// Performs the operations requested by the script until it finishes, failing once stopped.
+async fn serve(
+    ops: &mut AsyncOps<'_>,
+    requests: &mpsc::Receiver<Request>,
+    waker: &Waker,
+    replies: &mpsc::Sender<rhai::INT>,
+) -> ArrayResult<()> {
+    loop {
+        let request = future::poll_fn(|cx| {
+            let mut received = requests.try_recv();
+            if let Err(mpsc::TryRecvError::Empty) = received {
+                if ops.check().is_err() {
+                    return task::Poll::Ready(Err(()));
+                }
+
+                *waker.lock().unwrap() = Some(cx.waker().clone());
+                // The request may have been sent before the waker was in place
+                received = requests.try_recv();
+            }
+
+            match received {
+                Ok(request) => task::Poll::Ready(Ok(Some(request))),
+                Err(mpsc::TryRecvError::Empty) => task::Poll::Pending,
+                // The script dropped its bridge, so it is done
+                Err(mpsc::TryRecvError::Disconnected) => task::Poll::Ready(Ok(None)),
+            }
+        });
+
+        match request.await? {
+            Some(Request::Operation(operation)) => {
+                let reply = perform(ops, operation).await?;
+                replies.send(reply).map_err(drop)?;
+            }
+            Some(Request::Annotate(text)) => ops.annotate(text),
+            None => return Ok(()),
+        }
+    }
+}
+
+async fn perform(ops: &mut AsyncOps<'_>, operation: Operation) -> ArrayResult<rhai::INT> {
+    let ordering = |ordering: cmp::Ordering| ordering as rhai::INT;
+
+    Ok(match operation {
+        Operation::CmpTwo(a, b) => ordering(ops.cmp_two(a, b).await?),
+        Operation::Cmp(index, value) => ordering(ops.cmp(index, value).await?),
+        Operation::Swap(a, b) => ops.swap(a, b).await.map(|_| 0)?,
+        Operation::Get(index) => ops.get(index).await? as rhai::INT,
+        Operation::Set(index, value) => ops.set(index, value).await.map(|_| 0)?,
+    })
+}
+
+/// A script evaluated on its helper thread, which is stopped wherever it is once dropped.
+struct Evaluation {
+    thread: Option<thread::JoinHandle<Result<(), Box<rhai::EvalAltResult>>>>,
+    stop: sync::Arc<atomic::AtomicBool>,
+}
+
+impl Evaluation {
+    fn spawn(ast: rhai::AST, bridge: sync::Arc<Bridge>) -> Evaluation {
+        let stop = sync::Arc::new(atomic::AtomicBool::new(false));
+
+        Evaluation {
+            thread: Some(thread::spawn({
+                let stop = stop.clone();
+                move || Script::evaluate(&ast, bridge, stop)
+            })),
+            stop,
+        }
+    }
+
+    /// Stops the script and waits for its result, which it ends with right away if it was done.
+    fn join(mut self) -> Result<(), Box<rhai::EvalAltResult>> {
+        self.stop();
+
+        self.thread
+            .take()
+            .unwrap()
+            .join()
+            .unwrap_or_else(|payload| panic::resume_unwind(payload))
+    }
+
+    /// Wakes the script up wherever it is, it may be waiting for a reply or computing.
+    fn stop(&self) {
+        self.stop.store(true, atomic::Ordering::Relaxed);
+    }
+}
+
+/// Detaches the script when the sort is dropped halfway, which ends once it notices.
+impl Drop for Evaluation {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// The script's end of the channels to the thread performing its operations.
+struct Bridge {
+    requests: mpsc::Sender<Request>,
+    replies: sync::Mutex<mpsc::Receiver<rhai::INT>>,
+    waker: sync::Arc<Waker>,
+    size: usize,
+}
+
+impl Bridge {
+    fn call(&self, operation: Operation) -> Result<rhai::INT, Box<rhai::EvalAltResult>> {
+        let replies = self.replies.lock().unwrap();
+
+        self.send(Request::Operation(operation))?;
+        Ok(replies.recv().map_err(|_| "sort stopped")?)
+    }
+
+    fn annotate(&self, text: &str) -> Result<(), Box<rhai::EvalAltResult>> {
+        self.send(Request::Annotate(text.to_string()))
+    }
+
+    /// Sends the request, waking the sort up if it waits for it.
+    fn send(&self, request: Request) -> Result<(), Box<rhai::EvalAltResult>> {
+        self.requests.send(request).map_err(|_| "sort stopped")?;
+        if let Some(waker) = self.waker.lock().unwrap().take() {
+            waker.wake();
+        }
+
+        Ok(())
+    }
+
+    fn index(&self, index: rhai::INT) -> Result<usize, Box<rhai::EvalAltResult>> {
+        match usize::try_from(index) {
+            Ok(index) if index < self.size => Ok(index),
+            _ => Err(format!("index {index} out of bounds for size {}", self.size).into()),
+        }
+    }
+}
+
+fn number(value: rhai::INT) -> Result<usize, Box<rhai::EvalAltResult>> {
+    usize::try_from(value).map_err(|_| format!("invalid value {value}").into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array::ArrayState;
+    use crate::sorting::{ops, Algorithm, Counting, Sorter};
+
+    fn scripts() -> Vec<Script> {
+        let dir = path::Path::new(env!("CARGO_MANIFEST_DIR")).join("scripts");
+
+        Script::load_dir(&dir)
+            .into_iter()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn example_scripts_sort() {
+        let scripts = scripts();
+        assert_eq!(
+            scripts.iter().map(Script::name).collect::<Vec<_>>(),
+            ["Script: cocktail_sort", "Script: gnome_sort"]
+        );
+
+        for script in scripts {
+            for size in [0, 1, 2, 3, 50] {
+                let mut array = ArrayState::new(size);
+                array.reverse();
+
+                let mut counting = Counting::new(array, 0);
+                ops::block_on(script.run(&mut AsyncOps::new(&mut counting))).unwrap();
+
+                let array = counting.into_array();
+                assert_eq!(
+                    array.numbers(),
+                    (1..=size).collect::<Vec<_>>(),
+                    "{}",
+                    script.name()
+                );
+                assert_eq!(array.comparisons() > 0, size > 1, "{}", script.name());
+            }
+        }
+    }
+
+    #[test]
+    fn scripts_animate_like_sorts() {
+        let mut sorter = Sorter::new(ArrayState::new(50));
+
+        for script in scripts() {
+            sorter.set_sort(Algorithm::new(script));
+            sorter.shuffle();
+            sorter.start_sort();
+            while sorter.tick(1.0).is_ok() {}
+
+            assert!(sorter.panic().is_none());
+            assert_eq!(
+                sorter.read_array(|array| array.numbers().to_vec()),
+                (1..=50).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn runtime_errors_are_reported() {
+        let script = Script::compile("broken", "let x = get(0); swap(x, size());").unwrap();
+        let mut sorter = Sorter::new(ArrayState::new(10));
+        sorter.set_sort(Algorithm::new(script));
+        sorter.start_sort();
+        while sorter.tick(1.0).is_ok() {}
+
+        let panic = sorter.panic().unwrap();
+        assert!(panic.message.contains("out of bounds"), "{panic}");
+        assert_eq!(sorter.reads(), 1);
+    }
+
+    #[test]
+    fn killing_stops_script() {
+        let scripts = [
+            "loop { cmp_two(0, 1); }",
+            // Never touches the array, so only the progress check can stop it
+            "let x = 0; loop { x += 1; }",
+        ];
+
+        for deterministic in [false, true] {
+            for source in scripts {
+                let script = Script::compile("endless", source).unwrap();
+                let mut sorter = Sorter::new(ArrayState::new(10));
+                sorter.set_deterministic(deterministic);
+                sorter.set_sort(Algorithm::new(script));
+                sorter.start_sort();
+                for _ in 0..3 {
+                    sorter.tick(1.0).unwrap();
+                    thread::sleep(crate::DELAY_TIME);
+                }
+
+                sorter.kill_sort();
+                assert!(!sorter.alive());
+                assert!(sorter.panic().is_none());
+            }
+        }
+    }
+
+    #[test]
+    fn batches_end_on_annotations_paused_on() {
+        // Bubble sort announcing its passes
+        let source = r#"
+            for pass in 0..size() {
+                annotate(`Pass ${pass} started`);
+                for i in 0..size() - 1 - pass {
+                    if cmp_two(i, i + 1) > 0 { swap(i, i + 1); }
+                }
+            }
+        "#;
+
+        for deterministic in [false, true] {
+            let mut sorter = Sorter::new(ArrayState::new(20));
+            sorter.set_deterministic(deterministic);
+            sorter.set_sort(Algorithm::new(Script::compile("passes", source).unwrap()));
+            sorter.reverse();
+            sorter.operate_array(|array| array.set_pause_on(Some("pass 3 ")));
+            sorter.start_sort();
+
+            while sorter.read_array(ArrayState::pause_hits) == 0 {
+                sorter.tick(1.0).unwrap();
+                sorter.drain().unwrap();
+            }
+            sorter.read_array(|array| {
+                let last = array.annotations().entries().last().unwrap();
+                assert_eq!((last.text.as_str(), last.matched), ("Pass 3 started", true));
+                // Only the operation it was published with ran after it
+                assert_eq!(array.operations(), last.operation + 1);
+            });
+            let operations = sorter.read_array(ArrayState::operations);
+
+            // The sort thread ignores ticks until resumed, as they may have been sent before the
+            // hit was seen, while a sort on the calling thread only stops on it
+            if !deterministic {
+                sorter.tick(1.0).unwrap();
+                sorter.drain().unwrap();
+                assert_eq!(sorter.read_array(ArrayState::operations), operations);
+            }
+            // Going on continues until the end, as nothing else matches
+            sorter.resume().unwrap();
+            while sorter.tick(1.0).is_ok() {}
+            sorter.read_array(|array| {
+                assert_eq!(array.pause_hits(), 1);
+                assert_eq!(array.annotations().entries().len(), 20);
+                assert!(array.numbers().is_sorted());
+            });
+        }
+    }
+
+    #[test]
+    fn syntax_errors_fail_to_load() {
+        let error = Script::compile("broken", "let = ;").unwrap_err();
+        assert_eq!(error.name, "broken");
+    }
+}
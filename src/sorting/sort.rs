@@ -1,116 +1,591 @@
-use crate::sorting::wrapping;
-use std::cmp;
-
-use std::pin::Pin;
+use crate::array::{Arrangement, Marker};
+use crate::sorting::algorithm::{self, AnimatedSort, SortFuture};
+use crate::sorting::ops::{self, ArrayOps, AsyncOps};
+use rand::Rng;
+use std::{cmp, mem, panic, thread};
 
 type SortResult = Result<(), ()>;
 
-type Lock = Pin<Box<wrapping::ArrayLock>>;
+type Lock<'a> = AsyncOps<'a>;
+
+/// The most buckets of a counting sort whose cursors are marked, more would cover the numbers.
+const MAX_MARKED_BUCKETS: usize = 16;
+
+/// The bytes a number or a count takes in the buffers of a sort.
+const NUMBER_BYTES: usize = mem::size_of::<usize>();
 
+/// Declares the `Sort` enum and its `AnimatedSort` implementation. Entries after the braces are
+/// hidden from `Sort::VALUES`.
 macro_rules! declare_sorts {
     (|$lock:ident, $size:ident| {
         $($sort:ident: $func:expr => O($speed:expr))+
-    }) => {
+    }
+    $(#[$hidden_attr:meta] $hidden:ident: $hidden_func:expr => O($hidden_speed:expr))*) => {
         #[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
         #[allow(clippy::enum_variant_names)]
         pub enum Sort {
             #[default]
-            $($sort),+
+            $($sort,)+
+            $(#[$hidden_attr] $hidden,)*
         }
 
         impl Sort {
             pub const VALUES: &'static[Sort] = &[$(Sort::$sort),+];
 
-            pub fn sort(&self, mut $lock: Lock, $size: usize) -> SortResult {
-                let $lock = &mut $lock;
+            /// Sorts the numbers through the operations on the calling thread.
+            pub fn sort(&self, ops: &mut dyn ArrayOps, size: usize) -> SortResult {
+                ops::block_on(self.sort_async(&mut AsyncOps::new(ops), size))
+            }
+
+            /// Sorts the numbers awaiting every operation, so the sort can be suspended in between.
+            pub async fn sort_async(&self, $lock: &mut Lock<'_>, $size: usize) -> SortResult {
+                // Every array this small is already sorted
+                if $size < 2 {
+                    return Ok(());
+                }
+
                 match self {
-                    $(Sort::$sort => {$func}),+
+                    $(Sort::$sort => {$func.await}),+
+                    $(#[$hidden_attr] Sort::$hidden => {$hidden_func.await}),*
                 }
             }
 
             pub fn calculate_max_ticks(&self, $size: u64) -> u64 {
+                if $size < 2 {
+                    return 1;
+                }
+
                 match self {
                     $(Sort::$sort => {$speed}),+
+                    $(#[$hidden_attr] Sort::$hidden => {$hidden_speed}),*
                 }
             }
         }
+
+        impl AnimatedSort for Sort {
+            fn name(&self) -> &str {
+                match self {
+                    $(Sort::$sort => {stringify!($sort)}),+
+                    $(#[$hidden_attr] Sort::$hidden => {stringify!($hidden)}),*
+                }
+            }
+
+            fn run<'a>(&'a self, ops: &'a mut AsyncOps<'_>) -> SortFuture<'a> {
+                Box::pin(async move {
+                    let size = ops.size();
+
+                    Ok(self.sort_async(ops, size).await?)
+                })
+            }
+
+            fn max_ticks(&self, size: u64) -> u64 {
+                self.calculate_max_ticks(size)
+            }
+
+            fn estimated_operations(&self, size: u64, distribution: Distribution) -> Option<u64> {
+                Some(Sort::estimated_operations(self, size, distribution))
+            }
+        }
     };
 }
 
 declare_sorts! {
     |lock, size| {
         BubbleSort:
-            Sort::bubble_sort(lock, size) => O(size.pow(2) / 100)
+            Sort::bubble_sort(lock, size) => O(size.saturating_pow(2) / 100)
+        BubbleSortOptimized:
+            Sort::bubble_sort_optimized(lock, size) => O(size.saturating_pow(2) / 100)
         ShakerSort:
-            Sort::shaker_sort(lock, size) => O(size.pow(2) / 100)
+            Sort::shaker_sort(lock, size) => O(size.saturating_pow(2) / 100)
         ExchangeSort:
-            Sort::exchange_sort(lock, size) => O(size.pow(2) / 100)
+            Sort::exchange_sort(lock, size) => O(size.saturating_pow(2) / 100)
         CycleSort:
-            Sort::cycle_sort(lock, size) => O(size.pow(2) / 100)
+            Sort::cycle_sort(lock, size) => O(size.saturating_pow(2) / 100)
         CombSort:
-            Sort::comb_sort(lock, size) => O(size.pow(2) / 10000)
+            Sort::comb_sort(lock, size) => O(size.saturating_pow(2) / 10000)
         OddEvenSort:
-            Sort::odd_even_sort(lock, size) => O(size.pow(2) / 100)
+            Sort::odd_even_sort(lock, size) => O(size.saturating_pow(2) / 100)
         InsertionSort:
-            Sort::insertion_sort(lock, size) => O(size.pow(2) / 100)
+            Sort::insertion_sort(lock, size) => O(size.saturating_pow(2) / 100)
+        InsertionSortSwaps:
+            Sort::insertion_sort_swaps(lock, size) => O(size.saturating_pow(2) / 100)
         ShellSort:
-            Sort::shell_sort(lock, size) => O(size.pow(2) / 10000)
+            Sort::shell_sort(lock, size) => O(size.saturating_pow(2) / 10000)
         SelectionSort:
-            Sort::selection_sort(lock, size) => O(size.pow(2) / 100)
+            Sort::selection_sort(lock, size) => O(size.saturating_pow(2) / 100)
+        StableSelectionSort:
+            Sort::stable_selection_sort(lock, size) => O(size.saturating_pow(2) / 100)
         DoubleSelectionSort:
-            Sort::double_selection_sort(lock, size) => O(size.pow(2) / 100)
+            Sort::double_selection_sort(lock, size) => O(size.saturating_pow(2) / 100)
         StrandSort:
-            Sort::strand_sort(lock, size) => O(size.pow(2) / 1000)
+            Sort::strand_sort(lock, size) => O(size.saturating_pow(2) / 1000)
         StoogeSort:
-            Sort::stooge_sort(lock, 0, size - 1) => O(size.pow(3) / 1000)
+            Sort::stooge_sort(lock, 0, size - 1, 1) => O(size.saturating_pow(3) / 1000)
         SlowSort:
-            Sort::slow_sort(lock, 0, size - 1)  => O(size.pow(3) / 1000)
+            Sort::slow_sort(lock, 0, size - 1, 1) => O(size.saturating_pow(3) / 1000)
         QuickSort:
-            Sort::quick_sort(lock, 0, size - 1) => O(size * size.ilog2() as u64 / 100)
+            Sort::quick_sort(lock, 0, size - 1, Pivot::Last, 1)
+                => O(size.saturating_mul(size.ilog2() as u64) / 100)
+        QuickSortRandom:
+            Sort::quick_sort(lock, 0, size - 1, Pivot::Random, 1)
+                => O(size.saturating_mul(size.ilog2() as u64) / 100)
+        QuickSortMedian:
+            Sort::quick_sort(lock, 0, size - 1, Pivot::MedianOfThree, 1)
+                => O(size.saturating_mul(size.ilog2() as u64) / 100)
         MergeSort:
-            Sort::merge_sort(lock, 0, size - 1) => O(size * size.ilog2() as u64 / 100)
+            Sort::merge_sort(lock, 0, size - 1, 1)
+                => O(size.saturating_mul(size.ilog2() as u64) / 100)
+        ParallelMergeSort:
+            Sort::parallel_merge_sort(lock, size)
+                => O(size.saturating_mul(size.ilog2() as u64) / 100)
         HeapSort:
-            Sort::heap_sort(lock, size - 1) => O(size * size.ilog2() as u64 / 100)
+            Sort::heap_sort(lock, size - 1) => O(size.saturating_mul(size.ilog2() as u64) / 100)
         CountingSort:
-            Sort::counting_sort(lock, size, size, |x| x) => O(size / 50)
+            Sort::counting_sort(lock, size) => O(size / 50)
         RadixSort10:
             Sort::radix_sort(lock, size, 10) => O(size / 50)
         RadixSort2:
             Sort::radix_sort(lock, size, 2) => O(size / 50)
     }
+
+    #[cfg(test)]
+    BrokenSort: Sort::broken_sort(lock) => O(size)
+}
+
+/// How quick sort chooses its pivot element.
+#[derive(Clone, Copy)]
+enum Pivot {
+    Last,
+    Random,
+    MedianOfThree,
+}
+
+/// How the numbers are arranged before they are sorted.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Distribution {
+    /// In random order.
+    #[default]
+    Shuffled,
+    Sorted,
+    Reversed,
+}
+
+impl Distribution {
+    /// The arrangement of the numbers, where anything neither sorted nor reversed is shuffled.
+    pub fn of(numbers: &[usize]) -> Distribution {
+        if numbers.windows(2).all(|pair| pair[0] <= pair[1]) {
+            Distribution::Sorted
+        } else if numbers.windows(2).all(|pair| pair[0] >= pair[1]) {
+            Distribution::Reversed
+        } else {
+            Distribution::Shuffled
+        }
+    }
+}
+
+/// What the info panel tells about a sort, besides its name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Info {
+    /// The time complexity in the best, average and worst case, in terms of the size `n`.
+    pub time: [&'static str; 3],
+    /// The memory used besides the numbers, including the stack.
+    pub space: &'static str,
+    /// Whether equal numbers keep their order.
+    pub stable: bool,
+    /// Whether the numbers are only ever moved within the array.
+    pub in_place: bool,
 }
 
 impl std::fmt::Display for Sort {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{self:?}")
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for Sort {
+    type Err = ();
+
+    fn from_str(name: &str) -> Result<Sort, ()> {
+        Sort::VALUES
+            .iter()
+            .copied()
+            .find(|sort| sort.to_string() == name)
+            .ok_or(())
     }
 }
 
 impl Sort {
-    fn bubble_sort(lock: &mut Lock, size: usize) -> SortResult {
+    /// The label of the phase a sort keeping a heap reports its size as.
+    pub const HEAP_PHASE: &'static str = "Heap";
+
+    /// The arrangement of the numbers the sort takes the most operations on, as far as the
+    /// arrangements go.
+    pub fn worst_case(&self) -> Arrangement {
+        match self {
+            // Every number is an inversion, and the last one is the worst pivot
+            Sort::BubbleSort
+            | Sort::BubbleSortOptimized
+            | Sort::ShakerSort
+            | Sort::ExchangeSort
+            | Sort::OddEvenSort
+            | Sort::InsertionSort
+            | Sort::InsertionSortSwaps
+            | Sort::StableSelectionSort
+            | Sort::StrandSort
+            | Sort::StoogeSort
+            | Sort::SlowSort => Arrangement::Reversed,
+            Sort::QuickSort => Arrangement::Sorted,
+            Sort::CycleSort
+            | Sort::CombSort
+            | Sort::ShellSort
+            | Sort::SelectionSort
+            | Sort::DoubleSelectionSort
+            | Sort::QuickSortRandom
+            | Sort::QuickSortMedian
+            | Sort::MergeSort
+            | Sort::ParallelMergeSort
+            | Sort::HeapSort
+            | Sort::CountingSort
+            | Sort::RadixSort10
+            | Sort::RadixSort2 => Arrangement::Shuffled,
+            #[cfg(test)]
+            Sort::BrokenSort => Arrangement::Shuffled,
+        }
+    }
+
+    /// The complexity and properties of the sort.
+    ///
+    /// How it works is described by the `sort.<name>` texts of the language catalogs.
+    pub fn info(&self) -> Info {
+        match self {
+            Sort::BubbleSort => Info {
+                time: ["n²", "n²", "n²"],
+                space: "1",
+                stable: true,
+                in_place: true,
+            },
+            Sort::BubbleSortOptimized => Info {
+                time: ["n", "n²", "n²"],
+                space: "1",
+                stable: true,
+                in_place: true,
+            },
+            Sort::ShakerSort => Info {
+                time: ["n", "n²", "n²"],
+                space: "1",
+                stable: true,
+                in_place: true,
+            },
+            Sort::ExchangeSort => Info {
+                time: ["n²", "n²", "n²"],
+                space: "1",
+                stable: false,
+                in_place: true,
+            },
+            Sort::CycleSort => Info {
+                time: ["n²", "n²", "n²"],
+                space: "1",
+                stable: false,
+                in_place: true,
+            },
+            Sort::CombSort => Info {
+                time: ["n log n", "n²/2ᵖ", "n²"],
+                space: "1",
+                stable: false,
+                in_place: true,
+            },
+            Sort::OddEvenSort => Info {
+                time: ["n", "n²", "n²"],
+                space: "1",
+                stable: true,
+                in_place: true,
+            },
+            Sort::InsertionSort => Info {
+                time: ["n", "n²", "n²"],
+                space: "1",
+                stable: true,
+                in_place: true,
+            },
+            Sort::InsertionSortSwaps => Info {
+                time: ["n", "n²", "n²"],
+                space: "1",
+                stable: true,
+                in_place: true,
+            },
+            Sort::ShellSort => Info {
+                time: ["n log n", "n^1.5", "n²"],
+                space: "1",
+                stable: false,
+                in_place: true,
+            },
+            Sort::SelectionSort => Info {
+                time: ["n²", "n²", "n²"],
+                space: "1",
+                stable: false,
+                in_place: true,
+            },
+            Sort::StableSelectionSort => Info {
+                time: ["n²", "n²", "n²"],
+                space: "1",
+                stable: true,
+                in_place: true,
+            },
+            Sort::DoubleSelectionSort => Info {
+                time: ["n²", "n²", "n²"],
+                space: "1",
+                stable: false,
+                in_place: true,
+            },
+            Sort::StrandSort => Info {
+                time: ["n", "n²", "n²"],
+                space: "n",
+                stable: false,
+                in_place: false,
+            },
+            Sort::StoogeSort => Info {
+                time: ["n^2.71", "n^2.71", "n^2.71"],
+                space: "log n",
+                stable: false,
+                in_place: true,
+            },
+            Sort::SlowSort => Info {
+                time: ["n^(log n / 2)", "n^(log n / 2)", "n^(log n / 2)"],
+                space: "n",
+                stable: false,
+                in_place: true,
+            },
+            Sort::QuickSort => Info {
+                time: ["n log n", "n log n", "n²"],
+                space: "log n",
+                stable: false,
+                in_place: true,
+            },
+            Sort::QuickSortRandom => Info {
+                time: ["n log n", "n log n", "n²"],
+                space: "log n",
+                stable: false,
+                in_place: true,
+            },
+            Sort::QuickSortMedian => Info {
+                time: ["n log n", "n log n", "n²"],
+                space: "log n",
+                stable: false,
+                in_place: true,
+            },
+            Sort::MergeSort => Info {
+                time: ["n log n", "n log n", "n log n"],
+                space: "n",
+                stable: true,
+                in_place: false,
+            },
+            Sort::ParallelMergeSort => Info {
+                time: ["n log n", "n log n", "n log n"],
+                space: "n",
+                stable: true,
+                in_place: false,
+            },
+            Sort::HeapSort => Info {
+                time: ["n log n", "n log n", "n log n"],
+                space: "1",
+                stable: false,
+                in_place: true,
+            },
+            Sort::CountingSort => Info {
+                time: ["n + k", "n + k", "n + k"],
+                space: "n + k",
+                stable: true,
+                in_place: false,
+            },
+            Sort::RadixSort10 => Info {
+                time: ["d · n", "d · n", "d · n"],
+                space: "n",
+                stable: true,
+                in_place: false,
+            },
+            Sort::RadixSort2 => Info {
+                time: ["d · n", "d · n", "d · n"],
+                space: "n",
+                stable: true,
+                in_place: false,
+            },
+            #[cfg(test)]
+            Sort::BrokenSort => Info {
+                time: ["1", "1", "1"],
+                space: "1",
+                stable: false,
+                in_place: true,
+            },
+        }
+    }
+
+    /// Whether the sort keeps a heap at the front of the numbers, reporting its size as the
+    /// `HEAP_PHASE`.
+    pub fn builds_heap(&self) -> bool {
+        matches!(self, Sort::HeapSort)
+    }
+
+    /// Whether the sort reports markers of what it is working on, like the runs it merges or the
+    /// partition it scans, or marks the cursors of its buckets.
+    pub fn reports_markers(&self) -> bool {
+        matches!(
+            self,
+            Sort::MergeSort
+                | Sort::ParallelMergeSort
+                | Sort::QuickSort
+                | Sort::QuickSortRandom
+                | Sort::QuickSortMedian
+                | Sort::CountingSort
+                | Sort::RadixSort10
+                | Sort::RadixSort2
+        )
+    }
+
+    /// The fraction of full speed sorting shuffled numbers of the size takes about as long at as
+    /// any other sort at its suggested speed.
+    pub fn suggested_speed(&self, size: u64) -> f32 {
+        algorithm::suggested_speed(
+            Some(self.estimated_operations(size, Distribution::Shuffled)),
+            self.calculate_max_ticks(size),
+        )
+    }
+
+    /// The expected number of operations sorting a permutation of `1..=size` arranged by the
+    /// distribution takes, counting every comparison and access as one.
+    ///
+    /// Where the work only depends on the inversions the estimate is exact up to rounding, the
+    /// sorts without a closed form use constants fitted to measured runs instead.
+    pub fn estimated_operations(&self, size: u64, distribution: Distribution) -> u64 {
+        if size < 2 {
+            return 0;
+        }
+
+        let n = size;
+        let pairs = n * (n - 1) / 2;
+        let n_log_n = n * n.ilog2() as u64;
+        let n_pow = |exponent: f64| (n as f64).powf(exponent) as u64;
+        let inversions = match distribution {
+            Distribution::Shuffled => pairs / 2,
+            Distribution::Sorted => 0,
+            Distribution::Reversed => pairs,
+        };
+
+        match (self, distribution) {
+            // Every pass compares every neighbouring pair, however many are in place
+            (Sort::BubbleSort, _) => (n - 1).pow(2) + inversions,
+            (
+                Sort::BubbleSortOptimized | Sort::ShakerSort | Sort::OddEvenSort,
+                Distribution::Sorted,
+            ) => n - 1,
+            // Shaker sort's passes shrink from both ends, saving a quarter of the comparisons
+            (Sort::ShakerSort, Distribution::Shuffled) => pairs * 3 / 4 + inversions,
+            (
+                Sort::BubbleSortOptimized
+                | Sort::ShakerSort
+                | Sort::ExchangeSort
+                | Sort::OddEvenSort,
+                _,
+            ) => pairs + inversions,
+            (Sort::CycleSort, Distribution::Sorted) => pairs + n - 1,
+            (Sort::CycleSort, Distribution::Shuffled) => pairs * 3,
+            (Sort::CycleSort, Distribution::Reversed) => pairs * 7 / 4,
+            (Sort::CombSort, Distribution::Shuffled) => n_log_n * 5 / 2,
+            (Sort::CombSort, _) => n_log_n * 2,
+            (Sort::InsertionSort, _) => 3 * (n - 1) + 3 * inversions,
+            // Reversed, every element is swapped to the front without a comparison ending it
+            (Sort::InsertionSortSwaps, Distribution::Reversed) => 2 * inversions,
+            (Sort::InsertionSortSwaps, _) => (n - 1) + 2 * inversions,
+            (Sort::ShellSort, Distribution::Shuffled) => 9 * n_pow(1.3),
+            (Sort::ShellSort, Distribution::Sorted) => 3 * (n_log_n - n),
+            (Sort::ShellSort, Distribution::Reversed) => 4 * n_log_n,
+            (Sort::SelectionSort | Sort::DoubleSelectionSort, _) => {
+                pairs
+                    + match distribution {
+                        Distribution::Shuffled => n - 1,
+                        Distribution::Sorted => 0,
+                        Distribution::Reversed => n / 2,
+                    }
+            }
+            // Every shift takes a read and a write, and so does moving the minimum
+            (Sort::StableSelectionSort, Distribution::Sorted) => pairs,
+            (Sort::StableSelectionSort, _) => pairs + 2 * inversions + 2 * (n - 1),
+            (Sort::StrandSort, Distribution::Shuffled) => 4 * n_pow(1.5),
+            (Sort::StrandSort, Distribution::Sorted) => 6 * n,
+            (Sort::StrandSort, Distribution::Reversed) => pairs * 3,
+            (Sort::StoogeSort, _) => stooge_comparisons(n).saturating_add(inversions),
+            (Sort::SlowSort, _) => slow_comparisons(n).saturating_add(inversions),
+            (Sort::QuickSort, Distribution::Shuffled) => n_log_n * 8 / 5,
+            // The last element is the worst pivot for both sorted and reversed numbers
+            (Sort::QuickSort, _) => pairs + 2 * n,
+            (Sort::QuickSortRandom, Distribution::Shuffled) => n_log_n * 8 / 5,
+            (Sort::QuickSortRandom, _) => n_log_n * 7 / 5,
+            (Sort::QuickSortMedian, Distribution::Shuffled) => n_log_n * 7 / 4,
+            (Sort::QuickSortMedian, _) => n_log_n * 5 / 4,
+            (Sort::MergeSort | Sort::ParallelMergeSort, Distribution::Shuffled) => n_log_n * 11 / 4,
+            (Sort::MergeSort | Sort::ParallelMergeSort, _) => n_log_n * 9 / 4,
+            (Sort::HeapSort, _) => n_log_n * 5 / 2,
+            // Reading the range, then reading and writing every number once
+            (Sort::CountingSort, _) => 3 * n,
+            // Reading the range, then reading and writing every number once per digit
+            (Sort::RadixSort10, _) => (2 * (n.ilog10() as u64 + 1) + 1) * n,
+            (Sort::RadixSort2, _) => (2 * (n.ilog2() as u64 + 1) + 1) * n,
+            #[cfg(test)]
+            (Sort::BrokenSort, _) => 1,
+        }
+    }
+
+    /// Panics after its first operation.
+    #[cfg(test)]
+    async fn broken_sort(lock: &mut Lock<'_>) -> SortResult {
+        lock.get(0).await?;
+        panic!("deliberately broken sort")
+    }
+
+    /// Passes over all the numbers as many times as it takes at most, without ever stopping early.
+    async fn bubble_sort(lock: &mut Lock<'_>, size: usize) -> SortResult {
         for i in 1..size {
-            let mut abort = true;
-            for j in 0..size - i {
-                if lock.cmp_two(j, j + 1)?.is_gt() {
-                    lock.swap(j, j + 1)?;
-                    abort = false;
+            lock.report_phase("Pass", i);
+            for j in 0..size - 1 {
+                if lock.cmp_two(j, j + 1).await?.is_gt() {
+                    lock.swap(j, j + 1).await?;
                 }
             }
+        }
 
-            if abort {
-                break;
+        Ok(())
+    }
+
+    /// Passes only up to the last swap of the previous pass, as the numbers after it are in place,
+    /// and stops after a pass without any.
+    async fn bubble_sort_optimized(lock: &mut Lock<'_>, size: usize) -> SortResult {
+        let mut end = size - 1;
+        let mut pass = 1;
+
+        while end > 0 {
+            lock.report_phase("Pass", pass);
+            let mut last_swap = 0;
+            for j in 0..end {
+                if lock.cmp_two(j, j + 1).await?.is_gt() {
+                    lock.swap(j, j + 1).await?;
+                    last_swap = j;
+                }
             }
+
+            end = last_swap;
+            pass += 1;
         }
 
         Ok(())
     }
 
-    fn shaker_sort(lock: &mut Lock, size: usize) -> SortResult {
+    async fn shaker_sort(lock: &mut Lock<'_>, size: usize) -> SortResult {
+        // After pass i the first and last i elements are in place, so for odd sizes the middle
+        // element is the only one left after size / 2 passes
         for i in 1..size / 2 + 1 {
+            lock.report_phase("Pass", i);
             let mut abort = true;
             for j in i - 1..size - i {
-                if lock.cmp_two(j, j + 1)?.is_gt() {
-                    lock.swap(j, j + 1)?;
+                if lock.cmp_two(j, j + 1).await?.is_gt() {
+                    lock.swap(j, j + 1).await?;
                     abort = false;
                 }
             }
@@ -121,8 +596,8 @@ impl Sort {
 
             abort = true;
             for j in (i..size - i).rev() {
-                if lock.cmp_two(j, j - 1)?.is_lt() {
-                    lock.swap(j, j - 1)?;
+                if lock.cmp_two(j, j - 1).await?.is_lt() {
+                    lock.swap(j, j - 1).await?;
                     abort = false;
                 }
             }
@@ -135,11 +610,11 @@ impl Sort {
         Ok(())
     }
 
-    fn exchange_sort(lock: &mut Lock, size: usize) -> SortResult {
+    async fn exchange_sort(lock: &mut Lock<'_>, size: usize) -> SortResult {
         for i in 0..size - 1 {
             for j in i + 1..size {
-                if lock.cmp_two(i, j)?.is_gt() {
-                    lock.swap(i, j)?;
+                if lock.cmp_two(i, j).await?.is_gt() {
+                    lock.swap(i, j).await?;
                 }
             }
         }
@@ -147,40 +622,52 @@ impl Sort {
         Ok(())
     }
 
-    fn cycle_sort(lock: &mut Lock, size: usize) -> SortResult {
-        let mut buf = vec![false; size];
-        for i in 0..size - 1 {
-            if buf[i] {
+    async fn cycle_sort(lock: &mut Lock<'_>, size: usize) -> SortResult {
+        for start in 0..size - 1 {
+            let mut current = lock.get(start).await?;
+            let mut index = Sort::cycle_position(lock, start, size, current).await?;
+            if index == start {
                 continue;
             }
 
-            let mut current = lock.get(i)?;
             loop {
-                let mut index = 0;
-                for j in 0..size {
-                    if j != i && lock.cmp(j, current)?.is_lt() {
-                        index += 1;
-                    }
+                let next = lock.get(index).await?;
+                lock.set(index, current).await?;
+                if index == start {
+                    break;
                 }
 
-                if index != i {
-                    buf[index] = true;
+                current = next;
+                index = Sort::cycle_position(lock, start, size, current).await?;
+            }
+        }
 
-                    let new = lock.get(index)?;
-                    wrapping::ArrayLock::set(lock, index, current)?;
-                    current = new;
-                } else {
-                    wrapping::ArrayLock::set(lock, i, current)?;
+        Ok(())
+    }
 
-                    break;
-                }
+    /// The position of the value within the unsorted part starting at `start`, after all smaller
+    /// values and the equal values already placed.
+    async fn cycle_position(
+        lock: &mut Lock<'_>,
+        start: usize,
+        size: usize,
+        value: usize,
+    ) -> Result<usize, ()> {
+        let mut index = start;
+        for j in start + 1..size {
+            if lock.cmp(j, value).await?.is_lt() {
+                index += 1;
             }
         }
 
-        Ok(())
+        while index != start && lock.cmp(index, value).await?.is_eq() {
+            index += 1;
+        }
+
+        Ok(index)
     }
 
-    fn comb_sort(lock: &mut Lock, size: usize) -> SortResult {
+    async fn comb_sort(lock: &mut Lock<'_>, size: usize) -> SortResult {
         let mut gap = size;
         const SHRINK: f32 = 1.3;
         let mut sorted = false;
@@ -191,10 +678,12 @@ impl Sort {
                 gap = 1;
                 sorted = true;
             }
+            lock.report_phase("Gap", gap);
+            lock.annotate(format!("Gap reduced to {gap}"));
 
             for i in 0..size - gap {
-                if lock.cmp_two(i, i + gap)?.is_gt() {
-                    lock.swap(i, i + gap)?;
+                if lock.cmp_two(i, i + gap).await?.is_gt() {
+                    lock.swap(i, i + gap).await?;
                     sorted = false;
                 }
             }
@@ -203,7 +692,7 @@ impl Sort {
         Ok(())
     }
 
-    fn odd_even_sort(lock: &mut Lock, size: usize) -> SortResult {
+    async fn odd_even_sort(lock: &mut Lock<'_>, size: usize) -> SortResult {
         let mut sorted = false;
 
         while !sorted {
@@ -211,8 +700,8 @@ impl Sort {
 
             for start in 0..=1 {
                 for i in (start..size - 1).step_by(2) {
-                    if lock.cmp_two(i, i + 1)?.is_gt() {
-                        lock.swap(i, i + 1)?;
+                    if lock.cmp_two(i, i + 1).await?.is_gt() {
+                        lock.swap(i, i + 1).await?;
                         sorted = false;
                     }
                 }
@@ -222,267 +711,463 @@ impl Sort {
         Ok(())
     }
 
-    fn insertion_sort(lock: &mut Lock, size: usize) -> SortResult {
+    async fn insertion_sort(lock: &mut Lock<'_>, size: usize) -> SortResult {
         for i in 1..size {
-            let current = lock.get(i)?;
+            let current = lock.get(i).await?;
 
             let mut j = i;
-            while j > 0 && lock.cmp(j - 1, current)?.is_gt() {
-                let x = lock.get(j - 1)?;
-                wrapping::ArrayLock::set(lock, j, x)?;
+            while j > 0 && lock.cmp(j - 1, current).await?.is_gt() {
+                let x = lock.get(j - 1).await?;
+                lock.set(j, x).await?;
                 j -= 1;
             }
 
-            wrapping::ArrayLock::set(lock, j, current)?;
+            lock.set(j, current).await?;
         }
 
         Ok(())
     }
 
-    fn shell_sort(lock: &mut Lock, size: usize) -> SortResult {
+    /// Insertion sort swapping the element leftwards until it is in place, which takes twice the
+    /// writes of shifting but moves the element as a whole.
+    async fn insertion_sort_swaps(lock: &mut Lock<'_>, size: usize) -> SortResult {
+        for i in 1..size {
+            let mut j = i;
+            while j > 0 && lock.cmp_two(j - 1, j).await?.is_gt() {
+                lock.swap(j - 1, j).await?;
+                j -= 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn shell_sort(lock: &mut Lock<'_>, size: usize) -> SortResult {
         let mut gap = size;
 
         while gap > 1 {
             gap = cmp::max(1, gap / 2);
+            lock.report_phase("Gap", gap);
+            lock.annotate(format!("Gap reduced to {gap}"));
 
             for i in gap..size {
-                let tmp = lock.get(i)?;
+                let tmp = lock.get(i).await?;
 
                 let mut j = i;
 
-                while j >= gap && lock.cmp(j - gap, tmp)?.is_gt() {
-                    let x = lock.get(j - gap)?;
-                    wrapping::ArrayLock::set(lock, j, x)?;
+                while j >= gap && lock.cmp(j - gap, tmp).await?.is_gt() {
+                    let x = lock.get(j - gap).await?;
+                    lock.set(j, x).await?;
                     j -= gap;
                 }
 
-                wrapping::ArrayLock::set(lock, j, tmp)?;
+                lock.set(j, tmp).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn selection_sort(lock: &mut Lock<'_>, size: usize) -> SortResult {
+        for i in 0..size - 1 {
+            let mut min = i;
+            for j in i + 1..size {
+                if lock.cmp_two(min, j).await?.is_gt() {
+                    min = j;
+                }
+            }
+            if min != i {
+                lock.swap(min, i).await?;
             }
         }
 
         Ok(())
     }
 
-    fn selection_sort(lock: &mut Lock, size: usize) -> SortResult {
+    /// Selection sort taking the first minimum out and shifting the numbers before it over, so
+    /// unlike swapping it in equal numbers keep their order.
+    async fn stable_selection_sort(lock: &mut Lock<'_>, size: usize) -> SortResult {
         for i in 0..size - 1 {
             let mut min = i;
             for j in i + 1..size {
-                if lock.cmp_two(min, j)?.is_gt() {
+                if lock.cmp_two(min, j).await?.is_gt() {
                     min = j;
                 }
             }
+
             if min != i {
-                lock.swap(min, i)?;
+                let current = lock.get(min).await?;
+                for j in (i + 1..=min).rev() {
+                    let x = lock.get(j - 1).await?;
+                    lock.set(j, x).await?;
+                }
+                lock.set(i, current).await?;
             }
         }
 
         Ok(())
     }
 
-    fn double_selection_sort(lock: &mut Lock, size: usize) -> SortResult {
+    async fn double_selection_sort(lock: &mut Lock<'_>, size: usize) -> SortResult {
         for i in 0..size / 2 {
             let mut min = i;
             let mut max = size - i - 1;
 
             for j in i + 1..size - i {
-                if lock.cmp_two(min, j)?.is_gt() {
+                if lock.cmp_two(min, j).await?.is_gt() {
                     min = j;
                 }
             }
             if min != i {
-                lock.swap(min, i)?;
+                lock.swap(min, i).await?;
             }
 
+            // Index i now holds the minimum, so it can't be the maximum
             for j in (i + 1..size - i - 1).rev() {
-                if lock.cmp_two(max, j)?.is_lt() {
+                if lock.cmp_two(max, j).await?.is_lt() {
                     max = j;
                 }
             }
             if max != size - i - 1 {
-                lock.swap(max, size - i - 1)?;
+                lock.swap(max, size - i - 1).await?;
             }
         }
 
         Ok(())
     }
 
-    fn strand_sort(lock: &mut Lock, size: usize) -> SortResult {
+    async fn strand_sort(lock: &mut Lock<'_>, size: usize) -> SortResult {
         let mut index = 0;
         while index < size {
             let mut len = 1;
 
             for j in index + 1..size {
-                if lock.cmp_two(index + len - 1, j)?.is_lt() && j != index + len {
-                    lock.swap(j, index + len)?;
+                if lock.cmp_two(index + len - 1, j).await?.is_lt() && j != index + len {
+                    lock.swap(j, index + len).await?;
                     len += 1;
                 }
             }
 
+            lock.annotate(format!("Strand of length {len} detected"));
             let old_index = index;
             index += len;
 
             let mut tmp = Vec::with_capacity(index);
+            lock.report_aux_alloc(index * NUMBER_BYTES);
 
             let mut x = 0;
             let mut y = 0;
             for _ in 0..index {
-                if x >= old_index || y < len && lock.cmp_two(old_index + y, x)?.is_lt() {
-                    tmp.push(lock.get(old_index + y)?);
+                if x >= old_index || y < len && lock.cmp_two(old_index + y, x).await?.is_lt() {
+                    tmp.push(lock.get(old_index + y).await?);
                     y += 1;
                 } else {
-                    tmp.push(lock.get(x)?);
+                    tmp.push(lock.get(x).await?);
                     x += 1;
                 }
             }
 
             for (i, v) in tmp.iter().enumerate() {
-                wrapping::ArrayLock::set(lock, i, *v)?;
+                lock.set(i, *v).await?;
             }
+            lock.report_aux_free(index * NUMBER_BYTES);
         }
 
         Ok(())
     }
 
-    fn stooge_sort(lock: &mut Lock, start: usize, end: usize) -> SortResult {
-        if end == start + 1 && lock.cmp_two(start, end)?.is_gt() {
-            lock.swap(start, end)?;
+    async fn stooge_sort(
+        lock: &mut Lock<'_>,
+        start: usize,
+        end: usize,
+        depth: usize,
+    ) -> SortResult {
+        lock.report_phase("Depth", depth);
+        lock.report_depth(depth);
+
+        if end == start + 1 && lock.cmp_two(start, end).await?.is_gt() {
+            lock.swap(start, end).await?;
         }
 
         if end > start + 1 {
             let third = (end - start + 1) / 3;
-            Sort::stooge_sort(lock, start, end - third)?;
-            Sort::stooge_sort(lock, start + third, end)?;
-            Sort::stooge_sort(lock, start, end - third)?;
+            Box::pin(Sort::stooge_sort(lock, start, end - third, depth + 1)).await?;
+            Box::pin(Sort::stooge_sort(lock, start + third, end, depth + 1)).await?;
+            Box::pin(Sort::stooge_sort(lock, start, end - third, depth + 1)).await?;
         }
 
         Ok(())
     }
 
-    fn slow_sort(lock: &mut Lock, start: usize, end: usize) -> SortResult {
+    async fn slow_sort(lock: &mut Lock<'_>, start: usize, end: usize, depth: usize) -> SortResult {
         if start < end {
+            lock.report_phase("Depth", depth);
+            lock.report_depth(depth);
+
             let m = (start + end) / 2;
-            Sort::slow_sort(lock, start, m)?;
-            Sort::slow_sort(lock, m + 1, end)?;
+            Box::pin(Sort::slow_sort(lock, start, m, depth + 1)).await?;
+            Box::pin(Sort::slow_sort(lock, m + 1, end, depth + 1)).await?;
 
-            if lock.cmp_two(m, end)?.is_gt() {
-                lock.swap(m, end)?;
+            if lock.cmp_two(m, end).await?.is_gt() {
+                lock.swap(m, end).await?;
             }
 
-            Sort::slow_sort(lock, start, end - 1)?;
+            Box::pin(Sort::slow_sort(lock, start, end - 1, depth + 1)).await?;
         }
 
         Ok(())
     }
 
-    fn quick_sort(lock: &mut Lock, start: usize, end: usize) -> SortResult {
+    async fn quick_sort(
+        lock: &mut Lock<'_>,
+        start: usize,
+        end: usize,
+        pivot: Pivot,
+        depth: usize,
+    ) -> SortResult {
         if end <= start {
             return Ok(());
         }
+        lock.report_phase("Depth", depth);
+        lock.report_depth(depth);
+
+        let pivot_index = match pivot {
+            Pivot::Last => end,
+            Pivot::Random => lock.rng().gen_range(start..=end),
+            Pivot::MedianOfThree => {
+                Sort::median_of_three(lock, start, (start + end) / 2, end).await?
+            }
+        };
+        lock.annotate(format!("Pivot chosen: index {pivot_index}"));
+        if pivot_index != end {
+            lock.swap(pivot_index, end).await?;
+        }
 
         let mut l = start;
         let mut r = end - 1;
+        let mark = |lock: &mut Lock<'_>, left, right| {
+            lock.report_marker(Some(Marker::Partition {
+                pivot: end,
+                left,
+                right,
+            }))
+        };
 
         while l < r {
-            while l < end && lock.cmp_two(l, end)?.is_lt() {
+            mark(lock, l, r);
+            while l < end && lock.cmp_two(l, end).await?.is_lt() {
                 l += 1;
+                mark(lock, l, r);
             }
 
-            while r > start && lock.cmp_two(r, end)?.is_gt() {
+            while r > start && lock.cmp_two(r, end).await?.is_gt() {
                 r -= 1;
+                mark(lock, l, r);
             }
 
             if l < r {
-                lock.swap(l, r)?;
+                lock.swap(l, r).await?;
+                // Both may equal the pivot, which would stop the scans at them again
+                l += 1;
             }
         }
 
-        if lock.cmp_two(l, end)?.is_gt() {
-            lock.swap(l, end)?;
+        mark(lock, l, r);
+        if lock.cmp_two(l, end).await?.is_gt() {
+            lock.swap(l, end).await?;
         }
+        lock.report_marker(None);
 
         if l > start {
-            Sort::quick_sort(lock, start, l - 1)?;
+            Box::pin(Sort::quick_sort(lock, start, l - 1, pivot, depth + 1)).await?;
         }
         if l < end {
-            Sort::quick_sort(lock, l + 1, end)?;
+            Box::pin(Sort::quick_sort(lock, l + 1, end, pivot, depth + 1)).await?;
         }
 
         Ok(())
     }
 
-    fn merge_sort(lock: &mut Lock, start: usize, end: usize) -> SortResult {
-        if end == start + 1 && lock.cmp_two(start, end)?.is_gt() {
-            lock.swap(start, end)?;
+    /// Orders the elements at `a <= b <= c`, leaving their median at `b`.
+    async fn median_of_three(
+        lock: &mut Lock<'_>,
+        a: usize,
+        b: usize,
+        c: usize,
+    ) -> Result<usize, ()> {
+        for (x, y) in [(a, b), (b, c), (a, b)] {
+            if lock.cmp_two(x, y).await?.is_gt() {
+                lock.swap(x, y).await?;
+            }
+        }
+
+        Ok(b)
+    }
+
+    async fn merge_sort(lock: &mut Lock<'_>, start: usize, end: usize, depth: usize) -> SortResult {
+        lock.report_phase("Depth", depth);
+        lock.report_depth(depth);
+
+        if end == start + 1 && lock.cmp_two(start, end).await?.is_gt() {
+            lock.swap(start, end).await?;
         } else if end > start + 1 {
             let m = (start + end) / 2;
-            Sort::merge_sort(lock, start, m)?;
-            Sort::merge_sort(lock, m + 1, end)?;
-
-            let mut tmp = Vec::with_capacity(end - start + 1);
-            let mut l = start;
-            let mut r = m + 1;
-            while tmp.len() < tmp.capacity() {
-                if r > end || l <= m && lock.cmp_two(l, r)?.is_lt() {
-                    tmp.push(lock.get(l)?);
-                    l += 1;
-                } else {
-                    tmp.push(lock.get(r)?);
-                    r += 1;
-                }
-            }
+            Box::pin(Sort::merge_sort(lock, start, m, depth + 1)).await?;
+            Box::pin(Sort::merge_sort(lock, m + 1, end, depth + 1)).await?;
+
+            // Merging happens back up at the depth of the halves
+            lock.report_phase("Depth", depth);
+            lock.report_depth(depth);
+            Sort::merge(lock, start, m, end).await?;
+        }
 
-            for (index, val) in tmp.iter().enumerate() {
-                wrapping::ArrayLock::set(lock, start + index, *val)?;
+        Ok(())
+    }
+
+    /// Merges the sorted ranges `start..=m` and `m + 1..=end`.
+    async fn merge(lock: &mut Lock<'_>, start: usize, m: usize, end: usize) -> SortResult {
+        lock.report_marker(Some(Marker::Merge {
+            start,
+            middle: m + 1,
+            end: end + 1,
+        }));
+
+        let mut tmp = Vec::with_capacity(end - start + 1);
+        lock.report_aux_alloc(tmp.capacity() * NUMBER_BYTES);
+        let mut l = start;
+        let mut r = m + 1;
+        while tmp.len() < tmp.capacity() {
+            // Taking the left one of equal numbers keeps them in order
+            if r > end || l <= m && lock.cmp_two(l, r).await?.is_le() {
+                tmp.push(lock.get(l).await?);
+                l += 1;
+            } else {
+                tmp.push(lock.get(r).await?);
+                r += 1;
             }
         }
 
+        for (index, val) in tmp.iter().enumerate() {
+            lock.set(start + index, *val).await?;
+        }
+        lock.report_aux_free(tmp.capacity() * NUMBER_BYTES);
+
         Ok(())
     }
 
-    fn heap_sort(lock: &mut Lock, max: usize) -> SortResult {
+    /// Merge sorts both halves on worker threads of their own, before merging them.
+    async fn parallel_merge_sort(lock: &mut Lock<'_>, size: usize) -> SortResult {
+        let m = (size - 1) / 2;
+        lock.report_depth(1);
+
+        // The workers borrow the operations until the match ends, so falling back comes after
+        let forked = match lock.fork(2) {
+            Some(mut workers) => {
+                let [left, right] = workers.as_mut_slice() else {
+                    unreachable!("forked two workers")
+                };
+
+                // Each worker drives its half to the end on a thread of its own
+                thread::scope(|scope| {
+                    let left = scope.spawn(|| {
+                        ops::block_on(Sort::merge_sort(&mut AsyncOps::new(&mut **left), 0, m, 2))
+                    });
+                    let right = scope.spawn(|| {
+                        let ops = &mut AsyncOps::new(&mut **right);
+                        ops::block_on(Sort::merge_sort(ops, m + 1, size - 1, 2))
+                    });
+
+                    // A killed worker stops the other one as well, so joining both never hangs
+                    let left = left.join().unwrap_or_else(|p| panic::resume_unwind(p));
+                    let right = right.join().unwrap_or_else(|p| panic::resume_unwind(p));
+                    left.and(right)
+                })?;
+
+                true
+            }
+            None => false,
+        };
+
+        if !forked {
+            Sort::merge_sort(lock, 0, m, 2).await?;
+            Sort::merge_sort(lock, m + 1, size - 1, 2).await?;
+        }
+
+        lock.report_phase("Depth", 1);
+        lock.report_depth(1);
+        Sort::merge(lock, 0, m, size - 1).await
+    }
+
+    async fn heap_sort(lock: &mut Lock<'_>, max: usize) -> SortResult {
+        lock.report_phase(Sort::HEAP_PHASE, max + 1);
         for i in (0..=max / 2).rev() {
-            Sort::heapify_down(lock, i, max)?;
+            Sort::heapify_down(lock, i, max).await?;
         }
         for i in (1..=max).rev() {
-            lock.swap(0, i)?;
+            lock.report_phase(Sort::HEAP_PHASE, i);
+            lock.swap(0, i).await?;
 
-            Sort::heapify_down(lock, 0, i - 1)?;
+            Sort::heapify_down(lock, 0, i - 1).await?;
         }
 
         Ok(())
     }
 
-    fn heapify_down(lock: &mut Lock, index: usize, max: usize) -> SortResult {
+    async fn heapify_down(lock: &mut Lock<'_>, index: usize, max: usize) -> SortResult {
         if 2 * index < max {
-            let tmp_max =
-                if 2 * index + 2 <= max && lock.cmp_two(2 * index + 1, 2 * index + 2)?.is_lt() {
-                    2 * index + 2
-                } else {
-                    2 * index + 1
-                };
-
-            if lock.cmp_two(index, tmp_max)?.is_lt() {
-                lock.swap(index, tmp_max)?;
-
-                Sort::heapify_down(lock, tmp_max, max)?;
+            let tmp_max = if 2 * index + 2 <= max
+                && lock.cmp_two(2 * index + 1, 2 * index + 2).await?.is_lt()
+            {
+                2 * index + 2
+            } else {
+                2 * index + 1
+            };
+
+            if lock.cmp_two(index, tmp_max).await?.is_lt() {
+                lock.swap(index, tmp_max).await?;
+
+                Box::pin(Sort::heapify_down(lock, tmp_max, max)).await?;
             }
         }
 
         Ok(())
     }
 
-    fn counting_sort(
-        lock: &mut Lock,
+    /// Reads the whole array once to find its smallest and largest value.
+    async fn value_range(lock: &mut Lock<'_>, size: usize) -> Result<(usize, usize), ()> {
+        let mut min = usize::MAX;
+        let mut max = usize::MIN;
+
+        for i in 0..size {
+            let value = lock.get(i).await?;
+            min = cmp::min(min, value);
+            max = cmp::max(max, value);
+        }
+
+        Ok((min, max))
+    }
+
+    async fn counting_sort(lock: &mut Lock<'_>, size: usize) -> SortResult {
+        let (min, max) = Sort::value_range(lock, size).await?;
+
+        Sort::counting_sort_by_key(lock, size, max - min + 1, |x| x - min).await
+    }
+
+    /// Stable counting sort by `key`, failing if a key doesn't fit into the buckets.
+    async fn counting_sort_by_key(
+        lock: &mut Lock<'_>,
         size: usize,
         buckets: usize,
-        transform: impl Fn(usize) -> usize,
+        key: impl Fn(usize) -> usize,
     ) -> SortResult {
         let mut keys = vec![0; buckets];
         let mut vals = Vec::with_capacity(size);
+        let aux_bytes = (buckets + size) * NUMBER_BYTES;
+        lock.report_aux_alloc(aux_bytes);
 
         for i in 0..size {
-            vals.push(lock.get(i)?);
-            keys[transform(*vals.last().unwrap() - 1)] += 1;
+            vals.push(lock.get(i).await?);
+            *keys.get_mut(key(*vals.last().unwrap())).ok_or(())? += 1;
         }
 
         vals.reverse();
@@ -491,23 +1176,608 @@ impl Sort {
             keys[i] += keys[i - 1];
         }
 
+        // The cursors start at the ends of the buckets, the boundaries between them, and move down
+        // as the buckets fill, so few enough of them are marked for the writes to follow them
+        let marked = buckets <= MAX_MARKED_BUCKETS;
         for v in vals {
-            let key = transform(v - 1);
+            let key = key(v);
             keys[key] -= 1;
-            wrapping::ArrayLock::set(lock, keys[key], v)?;
+            if marked {
+                lock.mark_indices(&keys);
+            }
+            lock.set(keys[key], v).await?;
         }
+        lock.clear_marks();
+        lock.report_aux_free(aux_bytes);
 
         Ok(())
     }
 
-    fn radix_sort(lock: &mut Lock, size: usize, base: usize) -> SortResult {
+    async fn radix_sort(lock: &mut Lock<'_>, size: usize, base: usize) -> SortResult {
+        let (_, max) = Sort::value_range(lock, size).await?;
         let mut i = 1;
 
-        while size / i > 0 {
-            Sort::counting_sort(lock, size, base, |x| (x / i) % base)?;
-            i *= base;
+        for digit in 1.. {
+            if max / i == 0 {
+                break;
+            }
+
+            lock.report_phase("Digit", digit);
+            Sort::counting_sort_by_key(lock, size, base, |x| (x / i) % base).await?;
+
+            let Some(next) = i.checked_mul(base) else {
+                break;
+            };
+            i = next;
         }
 
         Ok(())
     }
 }
+
+/// The comparisons stooge sort makes on `size` numbers, the same for every arrangement.
+fn stooge_comparisons(size: u64) -> u64 {
+    // Every call on more than two numbers recurses three times on two thirds of them
+    let mut size = size;
+    let mut comparisons = 1u64;
+    while size > 2 {
+        size -= size / 3;
+        comparisons = comparisons.saturating_mul(3);
+    }
+
+    comparisons
+}
+
+/// The comparisons slow sort makes on `size` numbers, the same for every arrangement.
+fn slow_comparisons(size: u64) -> u64 {
+    // Every call sorts both halves, compares once and sorts all but the last number again
+    let mut comparisons = vec![0u64; size as usize + 1];
+    for len in 2..=size as usize {
+        comparisons[len] = comparisons[len.div_ceil(2)]
+            .saturating_add(comparisons[len / 2])
+            .saturating_add(1)
+            .saturating_add(comparisons[len - 1]);
+    }
+
+    comparisons[size as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::array::{ArrayState, Space};
+    use crate::sorting::{Checked, Counting};
+    use proptest::prelude::{prop_assert_eq, prop_oneof, proptest, Just, ProptestConfig, Strategy};
+    use rand::{seq::SliceRandom, SeedableRng};
+
+    #[derive(Clone, Copy, Debug)]
+    enum Input {
+        Permutation,
+        Duplicates,
+        AllEqual,
+        Sorted,
+        Reversed,
+        OrganPipe,
+    }
+
+    impl Input {
+        fn numbers(&self, size: usize, seed: u64) -> Vec<usize> {
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+
+            match self {
+                Input::Permutation => {
+                    let mut numbers: Vec<usize> = (1..=size).collect();
+                    numbers.shuffle(&mut rng);
+                    numbers
+                }
+                Input::Duplicates => (0..size).map(|_| rng.gen_range(1..=size / 4 + 1)).collect(),
+                Input::AllEqual => vec![size / 2 + 1; size],
+                Input::Sorted => (1..=size).collect(),
+                Input::Reversed => (1..=size).rev().collect(),
+                Input::OrganPipe => (0..size).map(|i| cmp::min(i, size - 1 - i) + 1).collect(),
+            }
+        }
+    }
+
+    fn input() -> impl Strategy<Value = Input> {
+        prop_oneof![
+            Just(Input::Permutation),
+            Just(Input::Duplicates),
+            Just(Input::AllEqual),
+            Just(Input::Sorted),
+            Just(Input::Reversed),
+            Just(Input::OrganPipe),
+        ]
+    }
+
+    /// The largest array the sort finishes quickly enough on to run many times.
+    fn max_size(sort: Sort) -> usize {
+        match sort {
+            Sort::StoogeSort | Sort::SlowSort => 60,
+            _ => 500,
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn sorts_permute_into_order(
+            size in 0..500usize,
+            input in input(),
+            seed: u64,
+        ) {
+            for &sort in Sort::VALUES {
+                let size = cmp::min(size, max_size(sort));
+                let numbers = input.numbers(size, seed);
+
+                // Checking panics as soon as an operation is out of bounds
+                let array = ArrayState::from_numbers(numbers.clone());
+                let mut ops = Checked::new(Counting::new(array, seed));
+                prop_assert_eq!(sort.sort(&mut ops, size), Ok(()), "{}", sort);
+                let array = ops.into_inner().into_array();
+
+                // Equal to the sorted input, so both sorted and a permutation of the input
+                let mut expected = numbers;
+                expected.sort_unstable();
+                prop_assert_eq!(array.numbers(), expected.as_slice(), "{}", sort);
+                prop_assert_eq!(array.placed(), size, "{}", sort);
+            }
+        }
+    }
+
+    /// Operations on numbers remembering the index every one started at, where setting a number
+    /// moves the one read first with that value which wasn't set yet.
+    struct Origins {
+        numbers: Vec<(usize, usize)>,
+        read: Vec<(usize, usize)>,
+        rng: rand::rngs::StdRng,
+    }
+
+    impl Origins {
+        fn new(numbers: &[usize]) -> Origins {
+            Origins {
+                numbers: numbers.iter().copied().zip(0..).collect(),
+                read: Vec::new(),
+                rng: rand::rngs::StdRng::seed_from_u64(0),
+            }
+        }
+
+        /// Whether equal numbers kept their order.
+        fn stable(&self) -> bool {
+            self.first_violation().is_none()
+        }
+
+        /// The first index whose number is equal to the next one but started after it.
+        fn first_violation(&self) -> Option<usize> {
+            self.numbers
+                .windows(2)
+                .position(|pair| pair[0].0 == pair[1].0 && pair[0].1 > pair[1].1)
+        }
+    }
+
+    impl ArrayOps for Origins {
+        fn cmp_two(&mut self, a: usize, b: usize) -> Result<cmp::Ordering, ()> {
+            Ok(self.numbers[a].0.cmp(&self.numbers[b].0))
+        }
+
+        fn cmp(&mut self, index: usize, value: usize) -> Result<cmp::Ordering, ()> {
+            Ok(self.numbers[index].0.cmp(&value))
+        }
+
+        fn swap(&mut self, a: usize, b: usize) -> Result<(), ()> {
+            self.numbers.swap(a, b);
+            Ok(())
+        }
+
+        fn get(&mut self, index: usize) -> Result<usize, ()> {
+            self.read.push(self.numbers[index]);
+            Ok(self.numbers[index].0)
+        }
+
+        fn set(&mut self, index: usize, value: usize) -> Result<(), ()> {
+            let read = self.read.iter().position(|&(number, _)| number == value);
+            self.numbers[index] = self.read.remove(read.expect("setting an unread number"));
+            Ok(())
+        }
+
+        fn size(&self) -> usize {
+            self.numbers.len()
+        }
+
+        fn rng(&mut self) -> &mut rand::rngs::StdRng {
+            &mut self.rng
+        }
+    }
+
+    #[test]
+    fn stable_selection_sort_keeps_equal_numbers_in_order() {
+        let unstable = (0..10).any(|seed| {
+            let numbers = Input::Duplicates.numbers(100, seed);
+
+            let mut ops = Origins::new(&numbers);
+            Sort::StableSelectionSort
+                .sort(&mut ops, numbers.len())
+                .unwrap();
+            assert!(ops.stable(), "{seed}");
+            assert!(ops.numbers.is_sorted_by_key(|&(number, _)| number));
+
+            let mut ops = Origins::new(&numbers);
+            Sort::SelectionSort.sort(&mut ops, numbers.len()).unwrap();
+            !ops.stable()
+        });
+
+        // Swapping the minimum in takes the number it swaps past its equals
+        assert!(unstable);
+    }
+
+    #[test]
+    fn stable_sorts_keep_duplicates_in_order() {
+        let first_violation = |sort: Sort, seed| {
+            let numbers = Input::Duplicates.numbers(100, seed);
+            let mut ops = Origins::new(&numbers);
+            sort.sort(&mut ops, numbers.len()).unwrap();
+            assert!(
+                ops.numbers.is_sorted_by_key(|&(number, _)| number),
+                "{sort}"
+            );
+
+            ops.first_violation()
+        };
+
+        // Every sort claiming to be stable is, for the info panel to be right. The counting sorts
+        // write equal numbers back from the last one read, which the origins take for a swap.
+        let counting = [Sort::CountingSort, Sort::RadixSort10, Sort::RadixSort2];
+        for &sort in Sort::VALUES
+            .iter()
+            .filter(|sort| sort.info().stable && !counting.contains(sort))
+        {
+            for seed in 0..10 {
+                assert_eq!(first_violation(sort, seed), None, "{sort} {seed}");
+            }
+        }
+        for sort in [Sort::SelectionSort, Sort::HeapSort, Sort::QuickSort] {
+            assert!(!sort.info().stable, "{sort}");
+            assert!(
+                (0..10).any(|seed| first_violation(sort, seed).is_some()),
+                "{sort}"
+            );
+        }
+    }
+
+    /// The operations sorting the numbers takes.
+    fn measure(sort: Sort, numbers: Vec<usize>, seed: u64) -> u64 {
+        let mut operations = 0;
+        crate::sorting::run(sort, numbers, seed, |_| operations += 1);
+
+        operations
+    }
+
+    #[test]
+    fn distribution_of_numbers() {
+        assert_eq!(Distribution::of(&[]), Distribution::Sorted);
+        assert_eq!(Distribution::of(&[1, 2, 2, 3]), Distribution::Sorted);
+        assert_eq!(Distribution::of(&[3, 2, 1]), Distribution::Reversed);
+        assert_eq!(Distribution::of(&[2, 3, 1]), Distribution::Shuffled);
+    }
+
+    #[test]
+    fn worst_cases_take_the_most_operations() {
+        let distribution = |arrangement| match arrangement {
+            Arrangement::Sorted => Distribution::Sorted,
+            Arrangement::Reversed => Distribution::Reversed,
+            _ => Distribution::Shuffled,
+        };
+        let distributions = [
+            Distribution::Shuffled,
+            Distribution::Sorted,
+            Distribution::Reversed,
+        ];
+
+        for sort in Sort::VALUES {
+            let estimate = |distribution| sort.estimated_operations(1000, distribution);
+            let worst = distributions.into_iter().map(estimate).max();
+            assert_eq!(
+                Some(estimate(distribution(sort.worst_case()))),
+                worst,
+                "{sort}"
+            );
+        }
+    }
+
+    #[test]
+    fn optimized_bubble_sort_compares_less_on_nearly_sorted_numbers() {
+        for seed in 0..10 {
+            // A few neighbours swapped in sorted numbers
+            let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+            let mut numbers: Vec<usize> = (1..=200).collect();
+            for _ in 0..5 {
+                let index = rng.gen_range(0..numbers.len() - 1);
+                numbers.swap(index, index + 1);
+            }
+
+            let run = |sort| crate::sorting::run(sort, numbers.clone(), 0, |_| ());
+            let (naive, optimized) = (run(Sort::BubbleSort), run(Sort::BubbleSortOptimized));
+            for array in [&naive, &optimized] {
+                assert_eq!(array.numbers(), (1..=200).collect::<Vec<_>>());
+            }
+            assert!(optimized.comparisons() < naive.comparisons(), "{seed}");
+            assert_eq!(optimized.writes(), naive.writes());
+        }
+    }
+
+    #[test]
+    fn insertion_variants_differ_in_writes() {
+        let numbers = Input::Permutation.numbers(200, 3);
+        let run = |sort| crate::sorting::run(sort, numbers.clone(), 0, |_| ());
+        let (shifting, swapping) = (run(Sort::InsertionSort), run(Sort::InsertionSortSwaps));
+
+        // Both compare the same pairs, only swapping writes every shift twice
+        assert_eq!(shifting.numbers(), swapping.numbers());
+        assert_eq!(shifting.comparisons(), swapping.comparisons());
+        let inversions = swapping.writes() / 2;
+        assert_eq!(shifting.writes(), inversions + 199);
+    }
+
+    #[test]
+    fn closed_form_estimates_are_exact() {
+        let size = 200;
+        let sorted: Vec<usize> = (1..=size).collect();
+        let reversed: Vec<usize> = (1..=size).rev().collect();
+        let cases = [
+            (Sort::BubbleSort, &sorted),
+            (Sort::BubbleSort, &reversed),
+            (Sort::BubbleSortOptimized, &sorted),
+            (Sort::BubbleSortOptimized, &reversed),
+            (Sort::ExchangeSort, &sorted),
+            (Sort::ExchangeSort, &reversed),
+            (Sort::SelectionSort, &sorted),
+            (Sort::StableSelectionSort, &sorted),
+            (Sort::StableSelectionSort, &reversed),
+            (Sort::InsertionSort, &sorted),
+            (Sort::InsertionSortSwaps, &sorted),
+            (Sort::InsertionSortSwaps, &reversed),
+            (Sort::CycleSort, &sorted),
+            (Sort::CountingSort, &reversed),
+            (Sort::RadixSort10, &reversed),
+            (Sort::RadixSort2, &reversed),
+        ];
+
+        for (sort, numbers) in cases {
+            let distribution = Distribution::of(numbers);
+            assert_eq!(
+                sort.estimated_operations(size as u64, distribution),
+                measure(sort, numbers.clone(), 0),
+                "{sort} on {distribution:?}"
+            );
+        }
+
+        // Selection sort compares every pair exactly once
+        assert_eq!(
+            Sort::SelectionSort.estimated_operations(size as u64, Distribution::Sorted),
+            (size * (size - 1) / 2) as u64
+        );
+
+        for sort in [Sort::StoogeSort, Sort::SlowSort] {
+            let sorted: Vec<usize> = (1..=50).collect();
+            assert_eq!(
+                sort.estimated_operations(50, Distribution::Sorted),
+                measure(sort, sorted, 0),
+                "{sort}"
+            );
+        }
+    }
+
+    #[test]
+    fn structured_sorts_report_phases() {
+        let phases = |sort: Sort| {
+            let mut phases = Vec::new();
+            let mut observer = |array: &ArrayState| {
+                if let Some(phase) = array.phase().filter(|&phase| phases.last() != Some(&phase)) {
+                    phases.push(phase);
+                }
+            };
+            let mut array = ArrayState::new(100);
+            array.reverse();
+            let mut ops = Counting::new(array, 0).with_observer(&mut observer);
+            sort.sort(&mut ops, 100).unwrap();
+
+            phases
+        };
+        let values = |sort, label| {
+            phases(sort)
+                .into_iter()
+                .inspect(|phase| assert_eq!(phase.label, label, "{sort}"))
+                .map(|phase| phase.value)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(values(Sort::ShellSort, "Gap"), [50, 25, 12, 6, 3, 1]);
+        assert_eq!(values(Sort::RadixSort10, "Digit"), [1, 2, 3]);
+        assert_eq!(values(Sort::HeapSort, "Heap").first(), Some(&100));
+        assert_eq!(values(Sort::BubbleSort, "Pass").last(), Some(&99));
+        let depths = values(Sort::MergeSort, "Depth");
+        assert_eq!(depths.iter().max(), Some(&7));
+        assert_eq!(depths.last(), Some(&1));
+        assert!(phases(Sort::InsertionSort).is_empty());
+    }
+
+    #[test]
+    fn sorts_report_their_space() {
+        let space = |sort: Sort| {
+            let mut array = ArrayState::new(100);
+            array.reverse();
+            let mut ops = Counting::new(array, 0);
+            sort.sort(&mut ops, 100).unwrap();
+
+            ops.array().space()
+        };
+
+        // Merging the halves last needs a buffer for all the numbers
+        let merge = space(Sort::MergeSort);
+        assert_eq!(merge.peak_aux_bytes, 100 * NUMBER_BYTES);
+        assert_eq!(merge.peak_depth, 7);
+        assert_eq!(merge.aux_bytes, 0);
+        assert_eq!(space(Sort::CountingSort).peak_aux_bytes, 200 * NUMBER_BYTES);
+        assert_eq!(space(Sort::RadixSort10).peak_aux_bytes, 110 * NUMBER_BYTES);
+        // Always picking the last number as the pivot of reversed numbers recurses for each
+        assert!(space(Sort::QuickSort).peak_depth > 30);
+        // Sorts in place
+        assert_eq!(space(Sort::HeapSort), Space::default());
+        assert_eq!(space(Sort::InsertionSort), Space::default());
+    }
+
+    #[test]
+    fn sorts_annotate_their_milestones() {
+        let annotations = |sort: Sort, size| {
+            let mut array = ArrayState::new(size);
+            array.reverse();
+            let mut ops = Counting::new(array, 0);
+            sort.sort(&mut ops, size).unwrap();
+
+            let texts = ops.array().annotations().entries();
+            texts
+                .map(|annotation| annotation.text.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            annotations(Sort::ShellSort, 20),
+            [
+                "Gap reduced to 10",
+                "Gap reduced to 5",
+                "Gap reduced to 2",
+                "Gap reduced to 1"
+            ]
+        );
+        assert_eq!(annotations(Sort::CombSort, 20)[0], "Gap reduced to 15");
+        assert_eq!(
+            annotations(Sort::QuickSort, 20)[0],
+            "Pivot chosen: index 19"
+        );
+        assert_eq!(
+            annotations(Sort::StrandSort, 3)[0],
+            "Strand of length 1 detected"
+        );
+        assert!(annotations(Sort::InsertionSort, 20).is_empty());
+    }
+
+    #[test]
+    fn merge_sorts_report_their_runs() {
+        for sort in [Sort::MergeSort, Sort::ParallelMergeSort] {
+            let mut merges = Vec::new();
+            let mut observer = |array: &ArrayState| {
+                if let Some(merge) = array
+                    .markers()
+                    .last()
+                    .filter(|&m| merges.last() != Some(&m))
+                {
+                    merges.push(merge);
+                }
+            };
+            let mut array = ArrayState::new(100);
+            array.reverse();
+            let mut ops = Counting::new(array, 0).with_observer(&mut observer);
+            sort.sort(&mut ops, 100).unwrap();
+
+            // Pairs are swapped rather than merged, the last merge joins both halves
+            assert_eq!(merges.len(), 63, "{sort}");
+            assert!(merges.iter().all(|&merge| matches!(
+                merge,
+                Marker::Merge { start, middle, end } if start < middle && middle < end
+            )));
+            assert_eq!(
+                merges.last(),
+                Some(&Marker::Merge {
+                    start: 0,
+                    middle: 50,
+                    end: 100
+                })
+            );
+        }
+        assert!(!Sort::HeapSort.reports_markers());
+    }
+
+    #[test]
+    fn quick_sorts_mark_their_partitions() {
+        for sort in [
+            Sort::QuickSort,
+            Sort::QuickSortRandom,
+            Sort::QuickSortMedian,
+        ] {
+            let mut partitions = Vec::new();
+            let mut observer = |array: &ArrayState| partitions.extend(array.markers());
+            let mut array = ArrayState::new(100);
+            array.shuffle();
+            let mut ops = Counting::new(array, 0).with_observer(&mut observer);
+            sort.sort(&mut ops, 100).unwrap();
+
+            // The scans stay left of the pivot, and the markers are gone once the sort is done
+            assert!(ops.array().markers().next().is_none(), "{sort}");
+            drop(ops);
+            assert!(partitions.iter().all(|&partition| matches!(
+                partition,
+                Marker::Partition { pivot, left, right } if left <= pivot && right < pivot
+            )));
+            assert!(partitions.contains(&Marker::Partition {
+                pivot: 99,
+                left: 0,
+                right: 98
+            }));
+        }
+    }
+
+    #[test]
+    fn suggested_speeds_take_as_long() {
+        for size in [100, 1000] {
+            // The ticks a sort takes, granted like `Sorter::tick` grants them
+            let ticks = |sort: Sort| {
+                let speed = sort.suggested_speed(size);
+                let per_tick = (speed * sort.calculate_max_ticks(size) as f32) as u64;
+                let estimate = sort.estimated_operations(size, Distribution::Shuffled);
+
+                (estimate.div_ceil(per_tick.max(1)), per_tick)
+            };
+            let (expected, _) = ticks(Sort::QuickSort);
+
+            for &sort in Sort::VALUES {
+                let (ticks, per_tick) = ticks(sort);
+
+                // Unless too slow even at full speed, or too fast even at an operation a tick
+                let max_ticks = sort.calculate_max_ticks(size);
+                assert!(
+                    ticks <= expected * 2 || per_tick == max_ticks,
+                    "{sort} at {size}"
+                );
+                assert!(ticks >= expected / 2 || per_tick <= 1, "{sort} at {size}");
+            }
+        }
+    }
+
+    /// Runs every sort on shuffled arrays of a few sizes, checking the estimate is within a
+    /// factor of two of the average measured operations.
+    #[cfg(feature = "calibration")]
+    #[test]
+    fn estimates_match_measured_runs() {
+        const RUNS: u64 = 5;
+
+        for &sort in Sort::VALUES {
+            for size in [16, 64, 256, 1024] {
+                if matches!(sort, Sort::StoogeSort | Sort::SlowSort) && size > 64 {
+                    continue;
+                }
+
+                let measured = (0..RUNS)
+                    .map(|seed| measure(sort, Input::Permutation.numbers(size, seed), seed))
+                    .sum::<u64>()
+                    / RUNS;
+                let estimated = sort.estimated_operations(size as u64, Distribution::Shuffled);
+
+                assert!(
+                    estimated <= 2 * measured && measured <= 2 * estimated,
+                    "{sort} on {size} numbers estimated {estimated} but measured {measured}"
+                );
+            }
+        }
+    }
+}
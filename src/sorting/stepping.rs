@@ -0,0 +1,641 @@
+use rand::SeedableRng;
+use std::future::Future;
+use std::{cell, cmp, mem, panic, pin, rc, sync, task, thread, time};
+
+use super::algorithm::{Algorithm, SortError};
+use super::ops::{self, ArrayOps, ArrayResult, AsyncOps, Operation, Subrange};
+use super::wrapping::{panic_message, wrap_sorter_array_ops, Granularity, SortPanic, TickStats};
+use super::{engine, trace};
+use crate::array::{self, ArrayState};
+
+/// A started sort, suspended between its operations until the next tick polls it.
+type Run = pin::Pin<Box<dyn Future<Output = Result<Option<trace::Trace>, SortError>>>>;
+
+/// Runs sorts on the calling thread, for targets without threads like the web.
+///
+/// A sort is a future awaiting every operation, which every tick polls until it used up the
+/// budget granted, so the sort performs exactly that many operations and is suspended before the
+/// next one until the following tick.
+pub struct Sorter {
+    sort: Algorithm,
+    array_state: rc::Rc<cell::RefCell<ArrayState>>,
+    pace: rc::Rc<cell::RefCell<Pace>>,
+    run: Option<Run>,
+    panic: Option<SortPanic>,
+    seed: u64,
+    granularity: Granularity,
+    highlights: usize,
+    recording: bool,
+    trace: Option<sync::Arc<trace::Trace>>,
+    probing: bool,
+    tick_stats: Option<TickStats>,
+}
+
+impl Sorter {
+    pub fn new(array_state: array::ArrayState) -> Sorter {
+        Sorter {
+            sort: Algorithm::default(),
+            array_state: rc::Rc::new(cell::RefCell::new(array_state)),
+            pace: rc::Rc::default(),
+            run: None,
+            panic: None,
+            seed: rand::random(),
+            granularity: Granularity::default(),
+            highlights: 1,
+            recording: false,
+            trace: None,
+            probing: false,
+            tick_stats: None,
+        }
+    }
+
+    pub fn start_sort(&mut self) {
+        assert!(!self.alive(), "Sort already running");
+
+        self.panic = None;
+        self.trace = None;
+        self.tick_stats = None;
+        self.pace = rc::Rc::default();
+        self.operate_array(|array| {
+            array.set_phase(None);
+            array.clear_markers();
+            array.clear_space();
+            array.clear_annotations();
+        });
+
+        let sort = self.sort.clone();
+        let (range, size) = self.read_array(|array| (array.range(), array.size()));
+        // Traces name the sort they replay, so only built in sorts of all the numbers are recorded
+        let recording = sort
+            .builtin()
+            .filter(|_| self.recording && range == (0..size));
+        let stepped = Stepped {
+            array_state: self.array_state.clone(),
+            pace: self.pace.clone(),
+            rng: rand::rngs::StdRng::seed_from_u64(self.seed),
+            size,
+            initial: None,
+            phase: None,
+            marker: None,
+            marks: None,
+            space: Vec::new(),
+            annotations: Vec::new(),
+        };
+
+        self.run = Some(Box::pin(async move {
+            match recording {
+                Some(recorded) => {
+                    let mut recording = ops::Recording::new(stepped);
+                    sort.run_async(&mut AsyncOps::new(&mut recording)).await?;
+                    let (stepped, operations) = recording.into_parts();
+
+                    Ok(Some(trace::Trace {
+                        sort: recorded,
+                        numbers: stepped.initial_numbers(),
+                        operations,
+                    }))
+                }
+                None => {
+                    let mut ops = Subrange::new(stepped, range);
+                    sort.run_async(&mut AsyncOps::new(&mut ops)).await?;
+
+                    Ok(None)
+                }
+            }
+        }));
+    }
+
+    pub fn kill_sort(&mut self) {
+        self.run = None;
+    }
+
+    /// The failure of the last sort, if it panicked or failed.
+    pub fn panic(&self) -> Option<&SortPanic> {
+        self.panic.as_ref()
+    }
+
+    pub fn set_sort(&mut self, sort: impl Into<Algorithm>) {
+        assert!(!self.alive(), "Sort still running, cannot change");
+
+        self.sort = sort.into();
+    }
+
+    pub fn sort(&self) -> &Algorithm {
+        &self.sort
+    }
+
+    /// Sets the seed for the random choices sorts make, so runs can be reproduced.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Sets which operations use up the budget of the following ticks.
+    pub fn set_granularity(&mut self, granularity: Granularity) {
+        self.granularity = granularity;
+    }
+
+    pub fn granularity(&self) -> Granularity {
+        self.granularity
+    }
+
+    /// Sets how many steps of each tick are highlighted, spread evenly over its operations.
+    pub fn set_highlights(&mut self, highlights: usize) {
+        self.highlights = highlights.clamp(1, array::MAX_HIGHLIGHTS);
+    }
+
+    pub fn highlights(&self) -> usize {
+        self.highlights
+    }
+
+    /// Records the trace of the following runs of built in sorts, kept once a run completes.
+    pub fn set_recording(&mut self, recording: bool) {
+        self.recording = recording;
+    }
+
+    pub fn recording(&self) -> bool {
+        self.recording
+    }
+
+    /// The trace of the last run, if it was recorded and completed.
+    pub fn trace(&self) -> Option<&sync::Arc<trace::Trace>> {
+        self.trace.as_ref()
+    }
+
+    /// Never wedged, as the sort only runs while a tick polls it.
+    pub fn wedged(&mut self) -> Option<time::Duration> {
+        None
+    }
+
+    /// The operations the current or last sort performed so far.
+    pub fn operations(&self) -> u64 {
+        self.pace.borrow().operations
+    }
+
+    pub fn alive(&mut self) -> bool {
+        self.run.is_some()
+    }
+
+    /// Sets whether the ticks are measured.
+    pub fn set_probing(&mut self, probing: bool) {
+        self.probing = probing;
+        self.tick_stats = None;
+    }
+
+    pub fn probing(&self) -> bool {
+        self.probing
+    }
+
+    /// How the budget of the last tick turned out, while probing.
+    ///
+    /// The sort runs within the tick, so nothing ever waits for the array.
+    pub fn tick_stats(&self) -> Option<TickStats> {
+        self.tick_stats
+    }
+
+    /// Grants the sort the fraction of its operations per tick at full speed.
+    pub fn tick(&mut self, speed: f32) -> ArrayResult<()> {
+        let operations = engine::budget(speed, self.sort.max_ticks(self.size() as u64));
+
+        self.tick_operations(operations)
+    }
+
+    /// Grants the sort a number of operations, at least one.
+    pub fn tick_operations(&mut self, operations: u64) -> ArrayResult<()> {
+        let granted = cmp::max(1, operations);
+        let before = self.operations();
+        let remaining = self.advance(granted, self.highlights)?;
+
+        if self.probing {
+            self.tick_stats = Some(TickStats {
+                granted,
+                performed: self.operations() - before,
+                remaining,
+                waited: time::Duration::ZERO,
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn step(&mut self) -> ArrayResult<()> {
+        self.advance(1, 1).map(drop)
+    }
+
+    /// Does nothing, as no budget outlasts its tick, but fails once the sort finished.
+    pub fn drain(&mut self) -> ArrayResult<()> {
+        match self.run {
+            Some(_) => Ok(()),
+            None => Err(()),
+        }
+    }
+
+    /// Does nothing like draining, as the sort isn't held on an annotation paused on but only
+    /// ends its batch there.
+    pub fn resume(&mut self) -> ArrayResult<()> {
+        self.drain()
+    }
+
+    /// Does nothing like draining, as there is no budget left to scale.
+    pub fn rescale(&mut self, _factor: f32) -> ArrayResult<()> {
+        self.drain()
+    }
+
+    /// Polls the sort with the budget until it is used up, the sort ended the batch on a watched
+    /// index or an annotation paused on, or it finished.
+    ///
+    /// Returns the budget left over. A sort pending with budget left waits for something else,
+    /// like a script computing, which is waited for until the tick is over.
+    fn advance(&mut self, budget: u64, highlights: usize) -> ArrayResult<u64> {
+        let run = self.run.as_mut().ok_or(())?;
+
+        {
+            let mut pace = self.pace.borrow_mut();
+            pace.budget = budget;
+            pace.ended = false;
+            pace.granularity = self.granularity;
+            pace.highlights = highlights;
+            pace.interval = budget.div_ceil(highlights as u64);
+            pace.unhighlighted = 0;
+        }
+        self.array_state.borrow_mut().clear_highlights();
+
+        let waker = ops::thread_waker();
+        let mut cx = task::Context::from_waker(&waker);
+        let mut deadline = None;
+        let result = loop {
+            match panic::catch_unwind(panic::AssertUnwindSafe(|| run.as_mut().poll(&mut cx))) {
+                Ok(task::Poll::Ready(result)) => break Some(result),
+                Ok(task::Poll::Pending) if self.pace.borrow().ready() => {
+                    let deadline =
+                        *deadline.get_or_insert_with(|| crate::Instant::now() + crate::DELAY_TIME);
+                    let now = crate::Instant::now();
+                    if now >= deadline {
+                        break None;
+                    }
+
+                    thread::park_timeout(deadline - now);
+                }
+                Ok(task::Poll::Pending) => break None,
+                Err(payload) => break Some(Err(SortError::Failed(panic_message(payload)))),
+            }
+        };
+        let remaining = mem::take(&mut self.pace.borrow_mut().budget);
+
+        if let Some(result) = result {
+            self.run = None;
+            self.finish(result);
+        }
+
+        Ok(remaining)
+    }
+
+    /// Keeps the trace of the finished sort or its failure, like a joined sort thread.
+    fn finish(&mut self, result: Result<Option<trace::Trace>, SortError>) {
+        match result {
+            Ok(trace) => {
+                if let Some(ref trace) = trace {
+                    let wasted = trace.wasted_comparisons();
+                    self.operate_array(|array| array.set_wasted_comparisons(wasted));
+                }
+                self.trace = trace.map(sync::Arc::new);
+            }
+            Err(SortError::Stopped) => {}
+            Err(SortError::Failed(message)) => {
+                self.panic = Some(SortPanic {
+                    sort: self.sort.clone(),
+                    message,
+                });
+            }
+        }
+    }
+}
+
+impl Sorter {
+    /// Mutates the array.
+    pub fn operate_array<T>(&self, f: impl FnOnce(&mut array::ArrayState) -> T) -> T {
+        f(&mut self.array_state.borrow_mut())
+    }
+
+    /// Reads the array as the operations performed so far left it.
+    pub fn read_array<T>(&self, f: impl FnOnce(&array::ArrayState) -> T) -> T {
+        f(&self.array_state.borrow())
+    }
+
+    wrap_sorter_array_ops! {
+        operate_array:
+        fn clear_step() -> ();
+        fn shuffle() -> ();
+        fn shuffle_seeded(seed: u64) -> ();
+        fn reverse() -> ();
+        fn order() -> ();
+        fn equalize() -> ();
+        fn arrange(arrangement: array::Arrangement) -> ();
+        fn initialize(size: usize, max_value: usize) -> ();
+        fn load_numbers(numbers: Vec<usize>) -> ();
+        fn reset_stats() -> ();
+        fn set_count_distances(count: bool) -> ();
+        fn set_tweening(tweening: bool) -> ();
+        fn advance_tweens(elapsed: time::Duration) -> ();
+        fn take_dirty() -> Option<std::ops::Range<usize>>;
+    }
+
+    wrap_sorter_array_ops! {
+        read_array:
+        fn size() -> usize;
+        fn last_step() -> array::Step;
+        fn comparisons() -> u64;
+        fn wasted_comparisons() -> Option<u64>;
+        fn reads() -> u64;
+        fn writes() -> u64;
+        fn stats_snapshot() -> array::Stats;
+        fn generation() -> u64;
+    }
+}
+
+/// The budget of the current tick, shared between the sorter and the operations of its sort.
+#[derive(Default)]
+struct Pace {
+    budget: u64,
+    /// Whether the batch ended on a watched index or an annotation paused on.
+    ended: bool,
+    granularity: Granularity,
+    /// How many operations of a budget are highlighted.
+    highlights: usize,
+    /// Every how many operations one is highlighted, spreading the highlights over the budget.
+    interval: u64,
+    unhighlighted: u64,
+    /// The operations the sort performed so far.
+    operations: u64,
+}
+
+impl Pace {
+    /// Whether the sort may perform its next operation within the tick.
+    fn ready(&self) -> bool {
+        self.budget > 0 && !self.ended
+    }
+
+    /// Takes the operation from the budget, unless it runs for free, returning whether its step
+    /// is highlighted.
+    fn spend(&mut self, operation: Operation) -> bool {
+        self.operations += 1;
+        if self.granularity.paces(operation) {
+            self.budget = self.budget.saturating_sub(1);
+        }
+
+        if self.highlights <= 1 {
+            return false;
+        }
+
+        self.unhighlighted += 1;
+        if self.unhighlighted < self.interval {
+            return false;
+        }
+
+        self.unhighlighted = 0;
+        true
+    }
+}
+
+/// Operations on the array performed right away, as long as the tick has budget left.
+///
+/// Like the operations of a sort thread, what the sort reports is published along with the
+/// operation after it.
+struct Stepped {
+    array_state: rc::Rc<cell::RefCell<ArrayState>>,
+    pace: rc::Rc<cell::RefCell<Pace>>,
+    rng: rand::rngs::StdRng,
+    size: usize,
+    /// The numbers as the sort found them on its first operation.
+    initial: Option<Vec<usize>>,
+    phase: Option<array::Phase>,
+    marker: Option<Option<array::Marker>>,
+    marks: Option<Vec<usize>>,
+    space: Vec<array::SpaceReport>,
+    annotations: Vec<String>,
+}
+
+impl Stepped {
+    fn initial_numbers(&self) -> Vec<usize> {
+        match self.initial {
+            Some(ref numbers) => numbers.clone(),
+            None => self.array_state.borrow().numbers().to_vec(),
+        }
+    }
+
+    fn perform_step<T>(
+        &mut self,
+        operation: Operation,
+        step: impl FnOnce(&mut ArrayState) -> T,
+    ) -> ArrayResult<T> {
+        let mut array_state = self.array_state.borrow_mut();
+        self.initial
+            .get_or_insert_with(|| array_state.numbers().to_vec());
+
+        if let Some(phase) = self.phase.take() {
+            array_state.set_phase(Some(phase));
+        }
+        if let Some(marker) = self.marker.take() {
+            array_state.set_marker(marker);
+        }
+        if let Some(marks) = self.marks.take() {
+            array_state.set_marks(&marks);
+        }
+        for report in self.space.drain(..) {
+            array_state.report_space(report);
+        }
+        let hits = (array_state.watch_hits(), array_state.pause_hits());
+        for text in self.annotations.drain(..) {
+            array_state.annotate(text);
+        }
+        let result = step(&mut array_state);
+
+        let mut pace = self.pace.borrow_mut();
+        if pace.spend(operation) {
+            array_state.highlight_last_step();
+        }
+        // Like a sort thread, the batch ends on a watched index or an annotation paused on
+        if (array_state.watch_hits(), array_state.pause_hits()) != hits {
+            pace.ended = true;
+        }
+
+        Ok(result)
+    }
+}
+
+/// Publishes what the sort reported after its last operation along with the end of it.
+impl Drop for Stepped {
+    fn drop(&mut self) {
+        let mut array_state = self.array_state.borrow_mut();
+        for report in self.space.drain(..) {
+            array_state.report_space(report);
+        }
+        for text in self.annotations.drain(..) {
+            array_state.annotate(text);
+        }
+    }
+}
+
+macro_rules! wrap_stepped_op {
+    ($name:ident, $operation:ident, ($($arg:ident : $argtype:ty),*) -> $ret:ty) => {
+        fn $name(&mut self, $($arg:$argtype),*) -> ArrayResult<$ret> {
+            self.perform_step(Operation::$operation($($arg),*), |array_state| {
+                array_state.$name($($arg),*)
+            })
+        }
+    }
+}
+
+impl ArrayOps for Stepped {
+    wrap_stepped_op!(cmp_two, CmpTwo, (a:usize, b:usize) -> cmp::Ordering);
+    wrap_stepped_op!(swap, Swap, (a:usize, b:usize) -> ());
+    wrap_stepped_op!(cmp, Cmp, (index:usize, value:usize) -> cmp::Ordering);
+    wrap_stepped_op!(get, Get, (index:usize) -> usize);
+    wrap_stepped_op!(set, Set, (index:usize, value:usize) -> ());
+
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn rng(&mut self) -> &mut rand::rngs::StdRng {
+        &mut self.rng
+    }
+
+    fn report_phase(&mut self, label: &'static str, value: usize) {
+        self.phase = Some(array::Phase { label, value });
+    }
+
+    fn report_marker(&mut self, marker: Option<array::Marker>) {
+        self.marker = Some(marker);
+    }
+
+    fn mark_indices(&mut self, indices: &[usize]) {
+        self.marks = Some(indices.to_vec());
+    }
+
+    fn report_space(&mut self, report: array::SpaceReport) {
+        self.space.push(report);
+    }
+
+    fn annotate(&mut self, text: String) {
+        self.annotations.push(text);
+    }
+
+    /// Pending once the budget of the tick is used up, suspending the sort until the next one.
+    fn poll_ready(&mut self, _cx: &mut task::Context<'_>) -> task::Poll<()> {
+        match self.pace.borrow().ready() {
+            true => task::Poll::Ready(()),
+            false => task::Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sorting::{sort, ThreadedSorter};
+
+    #[test]
+    fn operates_like_sort_thread() {
+        use rand::seq::SliceRandom;
+
+        for &sort in sort::Sort::VALUES {
+            for seed in 0..3 {
+                // Few enough numbers for the slowest sorts to finish in a few ticks
+                let mut numbers: Vec<usize> = (1..=20).collect();
+                numbers.shuffle(&mut rand::rngs::StdRng::seed_from_u64(seed));
+
+                let mut sorter = Sorter::new(ArrayState::from_numbers(numbers.clone()));
+                sorter.set_sort(sort);
+                sorter.set_seed(seed);
+                sorter.set_recording(true);
+                sorter.start_sort();
+                while sorter.tick_operations(100_000).is_ok() {}
+
+                let mut threaded = ThreadedSorter::new(ArrayState::from_numbers(numbers));
+                threaded.set_sort(sort);
+                threaded.set_seed(seed);
+                threaded.set_recording(true);
+                threaded.start_sort();
+                while threaded.tick_operations(100_000).is_ok() {}
+
+                assert!(sorter.trace().is_some(), "{sort}");
+                assert_eq!(sorter.trace(), threaded.trace(), "{sort}");
+                assert_eq!(
+                    sorter.read_array(|array| array.numbers().to_vec()),
+                    (1..=20).collect::<Vec<_>>()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn ticks_advance_exactly_their_budget() {
+        let mut sorter = Sorter::new(ArrayState::new(100));
+        sorter.set_sort(sort::Sort::BubbleSort);
+        sorter.reverse();
+        sorter.start_sort();
+
+        // The sort is suspended right where the budget of every tick ran out
+        let mut ticks = 0;
+        while sorter.tick_operations(7).is_ok() {
+            ticks += 1;
+            let operations = sorter.operations();
+            assert!(operations == ticks * 7 || !sorter.alive(), "{operations}");
+            assert_eq!(sorter.read_array(ArrayState::operations), operations);
+        }
+
+        assert_eq!(sorter.operations().div_ceil(7), ticks);
+        assert!(sorter.read_array(|array| array.numbers().is_sorted()));
+    }
+
+    #[test]
+    fn ticks_pace_sort() {
+        let mut sorter = Sorter::new(ArrayState::new(10));
+        sorter.set_sort(sort::Sort::BubbleSort);
+        sorter.reverse();
+        sorter.set_highlights(3);
+        sorter.start_sort();
+
+        // The shuffle after starting is what the sort finds on its first tick
+        sorter.shuffle();
+        let numbers = sorter.read_array(|array| array.numbers().to_vec());
+        sorter.tick_operations(6).unwrap();
+        assert_eq!(sorter.operations(), 6);
+        assert_eq!(sorter.read_array(|array| array.highlights().len()), 3);
+
+        sorter.set_granularity(Granularity::Comparisons);
+        let comparisons = sorter.comparisons();
+        sorter.step().unwrap();
+        assert_eq!(sorter.comparisons(), comparisons + 1);
+        assert!(sorter.read_array(|array| array.highlights().is_empty()));
+
+        sorter.set_recording(true);
+        sorter.kill_sort();
+        sorter.operate_array(|array| array.load_numbers(numbers.clone()));
+        sorter.start_sort();
+        while sorter.step().is_ok() {}
+        assert!(sorter.drain().is_err());
+
+        let trace = sorter.trace().unwrap();
+        assert_eq!(trace.numbers, numbers);
+        assert_eq!(trace.operations.len() as u64, sorter.operations());
+    }
+
+    #[test]
+    fn failures_are_reported() {
+        let mut sorter = Sorter::new(ArrayState::new(10));
+        sorter.set_sort(sort::Sort::BrokenSort);
+        sorter.start_sort();
+        assert!(sorter.alive());
+
+        // The sort panics after its only operation
+        sorter.step().unwrap();
+        assert!(!sorter.alive());
+        assert_eq!(sorter.panic().unwrap().message, "deliberately broken sort");
+        assert_eq!(sorter.reads(), 1);
+    }
+}
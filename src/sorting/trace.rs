@@ -0,0 +1,574 @@
+use std::{io, sync};
+
+use super::ops::{ArrayResult, Operation};
+use super::{engine, sort};
+use crate::array::{self, ArrayState};
+
+const MAGIC: &[u8; 4] = b"SATR";
+const VERSION: u16 = 1;
+/// The number of operations between the snapshots seeking starts replaying from.
+const SNAPSHOT_INTERVAL: usize = 10000;
+/// The longest name of a sort a trace may claim, far longer than any of them.
+const MAX_NAME_LENGTH: usize = 256;
+
+/// A recorded run of a sort: the array it started from and every operation it performed.
+///
+/// Traces are stored as the magic bytes `SATR`, a little endian `u16` version, the name of the
+/// sort, the initial numbers and the operations, where every number is a little endian `u64` and
+/// every operation a tag byte followed by its operands.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trace {
+    pub sort: sort::Sort,
+    pub numbers: Vec<usize>,
+    pub operations: Vec<Operation>,
+}
+
+impl Trace {
+    /// How many comparisons compared two numbers which were both in their final places already,
+    /// never moving again until the run completed.
+    ///
+    /// Equal numbers are told apart by the places they were compared at, so even swapping two of
+    /// them moves both, and every write moves the number written.
+    pub fn wasted_comparisons(&self) -> u64 {
+        let mut moves_later = vec![false; self.numbers.len()];
+        let mut wasted = 0;
+
+        for &operation in self.operations.iter().rev() {
+            match operation {
+                Operation::CmpTwo(a, b) => wasted += u64::from(!moves_later[a] && !moves_later[b]),
+                Operation::Swap(a, b) if a != b => {
+                    moves_later[a] = true;
+                    moves_later[b] = true;
+                }
+                Operation::Set(index, _) => moves_later[index] = true,
+                Operation::Cmp(..) | Operation::Swap(..) | Operation::Get(_) => {}
+            }
+        }
+
+        wasted
+    }
+
+    pub fn write(&self, mut writer: impl io::Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+
+        let name = self.sort.to_string();
+        write_number(&mut writer, name.len())?;
+        writer.write_all(name.as_bytes())?;
+
+        write_number(&mut writer, self.numbers.len())?;
+        for &number in &self.numbers {
+            write_number(&mut writer, number)?;
+        }
+
+        write_number(&mut writer, self.operations.len())?;
+        for &operation in &self.operations {
+            let (tag, operands): (u8, &[usize]) = match operation {
+                Operation::CmpTwo(a, b) => (0, &[a, b]),
+                Operation::Cmp(index, value) => (1, &[index, value]),
+                Operation::Swap(a, b) => (2, &[a, b]),
+                Operation::Get(index) => (3, &[index]),
+                Operation::Set(index, value) => (4, &[index, value]),
+            };
+
+            writer.write_all(&[tag])?;
+            for &operand in operands {
+                write_number(&mut writer, operand)?;
+            }
+        }
+
+        writer.flush()
+    }
+
+    pub fn read(mut reader: impl io::Read) -> io::Result<Trace> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a trace file"));
+        }
+
+        let mut version = [0; 2];
+        reader.read_exact(&mut version)?;
+        let version = u16::from_le_bytes(version);
+        if version != VERSION {
+            return Err(invalid_data(format!("unsupported trace version {version}")));
+        }
+
+        // Untrusted like the lengths below, but allocated at once as a name is short
+        let length = read_number(&mut reader)?;
+        if length > MAX_NAME_LENGTH {
+            return Err(invalid_data("sort name too long"));
+        }
+        let mut name = vec![0; length];
+        reader.read_exact(&mut name)?;
+        let name = String::from_utf8(name).map_err(|_| invalid_data("invalid sort name"))?;
+        let sort = name
+            .parse()
+            .map_err(|_| invalid_data(format!("unknown sort {name}")))?;
+
+        // The lengths are untrusted, so only reserve what was actually read
+        let mut numbers = Vec::new();
+        for _ in 0..read_number(&mut reader)? {
+            numbers.push(read_number(&mut reader)?);
+        }
+
+        let mut operations = Vec::new();
+        for _ in 0..read_number(&mut reader)? {
+            let mut tag = [0];
+            reader.read_exact(&mut tag)?;
+
+            let operation = match tag[0] {
+                0 => Operation::CmpTwo(read_number(&mut reader)?, read_number(&mut reader)?),
+                1 => Operation::Cmp(read_number(&mut reader)?, read_number(&mut reader)?),
+                2 => Operation::Swap(read_number(&mut reader)?, read_number(&mut reader)?),
+                3 => Operation::Get(read_number(&mut reader)?),
+                4 => Operation::Set(read_number(&mut reader)?, read_number(&mut reader)?),
+                tag => return Err(invalid_data(format!("unknown operation {tag}"))),
+            };
+
+            if !operation
+                .indices()
+                .into_iter()
+                .all(|index| index < numbers.len())
+            {
+                return Err(invalid_data("operation out of bounds"));
+            }
+
+            operations.push(operation);
+        }
+
+        Ok(Trace {
+            sort,
+            numbers,
+            operations,
+        })
+    }
+}
+
+fn write_number(writer: &mut impl io::Write, number: usize) -> io::Result<()> {
+    writer.write_all(&(number as u64).to_le_bytes())
+}
+
+fn read_number(reader: &mut impl io::Read) -> io::Result<usize> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+
+    usize::try_from(u64::from_le_bytes(bytes)).map_err(|_| invalid_data("number too large"))
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Replays a recorded trace into an array, without running the sort itself.
+///
+/// The array is snapshotted every `SNAPSHOT_INTERVAL` operations the first time the replay gets
+/// there, so seeking back only ever replays the operations since the nearest snapshot, and
+/// starting a replay costs nothing however long the trace is.
+pub struct Replay {
+    trace: sync::Arc<Trace>,
+    array_state: ArrayState,
+    position: usize,
+    /// The arrays after every multiple of `SNAPSHOT_INTERVAL` operations replayed so far.
+    snapshots: Vec<ArrayState>,
+}
+
+impl Replay {
+    pub fn new(trace: sync::Arc<Trace>) -> Replay {
+        Replay {
+            array_state: ArrayState::from_numbers(trace.numbers.clone()),
+            trace,
+            position: 0,
+            snapshots: Vec::new(),
+        }
+    }
+
+    pub fn trace(&self) -> &Trace {
+        &self.trace
+    }
+
+    pub fn sort(&self) -> sort::Sort {
+        self.trace.sort
+    }
+
+    /// The number of operations replayed so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The number of operations in the trace.
+    pub fn len(&self) -> usize {
+        self.trace.operations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trace.operations.is_empty()
+    }
+
+    pub fn finished(&self) -> bool {
+        self.position == self.len()
+    }
+
+    /// Starts over from the initial array.
+    pub fn rewind(&mut self) {
+        self.array_state = ArrayState::from_numbers(self.trace.numbers.clone());
+        self.position = 0;
+    }
+
+    /// Jumps to the array as it was after the first `position` operations, clamped to the trace.
+    pub fn seek(&mut self, position: usize) {
+        let position = std::cmp::min(position, self.len());
+
+        // Replay from the nearest snapshot, unless the current position is closer
+        let snapshot = std::cmp::min(
+            position / SNAPSHOT_INTERVAL,
+            self.snapshots.len().saturating_sub(1),
+        );
+        if position < self.position || self.position < snapshot * SNAPSHOT_INTERVAL {
+            match self.snapshots.get(snapshot) {
+                Some(array_state) => {
+                    self.array_state = array_state.clone();
+                    self.position = snapshot * SNAPSHOT_INTERVAL;
+                }
+                None => self.rewind(),
+            }
+        }
+
+        self.advance(position - self.position).unwrap_or_default();
+        self.array_state.clear_step();
+    }
+
+    /// Replays the next operations at the same pace the sort would run at, failing once done.
+    pub fn tick(&mut self, speed: f32) -> ArrayResult<()> {
+        let ticks = engine::budget(speed, self.sort().calculate_max_ticks(self.size() as u64));
+
+        self.advance(std::cmp::max(1, ticks as usize))
+    }
+
+    pub fn step(&mut self) -> ArrayResult<()> {
+        self.advance(1)
+    }
+
+    fn advance(&mut self, count: usize) -> ArrayResult<()> {
+        if self.finished() {
+            return Err(());
+        }
+
+        let end = std::cmp::min(self.position + count, self.trace.operations.len());
+        while self.position < end {
+            if self.position == self.snapshots.len() * SNAPSHOT_INTERVAL {
+                self.snapshots.push(self.array_state.clone());
+            }
+
+            let next = (self.position / SNAPSHOT_INTERVAL + 1) * SNAPSHOT_INTERVAL;
+            let next = std::cmp::min(next, end);
+            for &operation in &self.trace.operations[self.position..next] {
+                apply(&mut self.array_state, operation);
+            }
+            self.position = next;
+        }
+
+        Ok(())
+    }
+
+    pub fn read_array<T>(&self, f: impl FnOnce(&array::ArrayState) -> T) -> T {
+        f(&self.array_state)
+    }
+
+    pub fn size(&self) -> usize {
+        self.array_state.size()
+    }
+}
+
+/// Performs a recorded operation on the array, counting it like the sort did.
+pub fn apply(array_state: &mut ArrayState, operation: Operation) {
+    match operation {
+        Operation::CmpTwo(a, b) => {
+            array_state.cmp_two(a, b);
+        }
+        Operation::Cmp(index, value) => {
+            array_state.cmp(index, value);
+        }
+        Operation::Swap(a, b) => array_state.swap(a, b),
+        Operation::Get(index) => {
+            array_state.get(index);
+        }
+        Operation::Set(index, value) => array_state.set(index, value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sorting::Sorter;
+
+    fn record(sort: sort::Sort) -> (sync::Arc<Trace>, ArrayState) {
+        let mut sorter = Sorter::new(ArrayState::new(100));
+        sorter.set_sort(sort);
+        sorter.set_recording(true);
+        sorter.start_sort();
+        // Like the gui, change the array after starting the sort, but before its first operation
+        sorter.shuffle();
+        while sorter.tick(1.0).is_ok() {}
+
+        let trace = sorter
+            .trace()
+            .expect("finished run records a trace")
+            .clone();
+        (trace, sorter.read_array(ArrayState::clone))
+    }
+
+    /// The comparisons of two numbers both where sorting puts them, whether they move on or not.
+    fn compared_in_place(trace: &Trace) -> u64 {
+        let mut numbers = trace.numbers.clone();
+        let mut sorted = numbers.clone();
+        sorted.sort_unstable();
+
+        let mut compared = 0;
+        for &operation in &trace.operations {
+            match operation {
+                Operation::CmpTwo(a, b) => {
+                    compared += u64::from(numbers[a] == sorted[a] && numbers[b] == sorted[b]);
+                }
+                Operation::Swap(a, b) => numbers.swap(a, b),
+                Operation::Set(index, value) => numbers[index] = value,
+                Operation::Cmp(..) | Operation::Get(_) => {}
+            }
+        }
+
+        compared
+    }
+
+    #[test]
+    fn wasted_comparisons_never_move_again() {
+        use Operation::{Cmp, CmpTwo, Swap};
+
+        let trace = Trace {
+            sort: sort::Sort::QuickSort,
+            numbers: vec![1, 2, 2, 4],
+            operations: vec![
+                CmpTwo(0, 1),
+                Swap(1, 2),
+                CmpTwo(0, 3),
+                Cmp(0, 1),
+                CmpTwo(1, 2),
+            ],
+        };
+        // Swapping equal numbers moves them, so only the later comparisons are wasted
+        assert_eq!(trace.wasted_comparisons(), 2);
+        assert_eq!(compared_in_place(&trace), 3);
+
+        // Quick sort compares numbers equal to the pivot in place, before swapping them away
+        let mut sorter = Sorter::new(ArrayState::from_numbers(vec![2, 1, 2, 2]));
+        sorter.set_sort(sort::Sort::QuickSort);
+        sorter.set_recording(true);
+        sorter.start_sort();
+        assert_eq!(sorter.read_array(ArrayState::wasted_comparisons), None);
+        while sorter.tick(1.0).is_ok() {}
+
+        let trace = sorter.trace().unwrap();
+        assert_eq!(
+            (trace.wasted_comparisons(), compared_in_place(trace)),
+            (3, 4)
+        );
+        assert_eq!(sorter.read_array(ArrayState::wasted_comparisons), Some(3));
+
+        sorter.reset_stats();
+        assert_eq!(sorter.read_array(ArrayState::wasted_comparisons), None);
+    }
+
+    #[test]
+    fn record_then_replay() {
+        for &sort in sort::Sort::VALUES {
+            let (trace, sorted) = record(sort);
+
+            let mut bytes = Vec::new();
+            trace.write(&mut bytes).unwrap();
+            let read = Trace::read(bytes.as_slice()).unwrap();
+            assert_eq!(read, *trace, "{sort}");
+
+            let mut replay = Replay::new(sync::Arc::new(read));
+            while replay.tick(1.0).is_ok() {}
+
+            replay.read_array(|replayed| {
+                assert_eq!(replayed.numbers(), sorted.numbers(), "{sort}");
+                assert_eq!(replayed.comparisons(), sorted.comparisons(), "{sort}");
+                assert_eq!(replayed.reads(), sorted.reads(), "{sort}");
+                assert_eq!(replayed.writes(), sorted.writes(), "{sort}");
+            });
+        }
+    }
+
+    #[test]
+    fn seek_matches_stepping() {
+        let mut array_state = ArrayState::new(200);
+        array_state.reverse();
+
+        let mut recording =
+            crate::sorting::Recording::new(crate::sorting::Counting::new(array_state.clone(), 0));
+        sort::Sort::BubbleSort.sort(&mut recording, 200).unwrap();
+        let trace = sync::Arc::new(Trace {
+            sort: sort::Sort::BubbleSort,
+            numbers: array_state.numbers().to_vec(),
+            operations: recording.into_parts().1,
+        });
+        assert!(trace.operations.len() > 3 * SNAPSHOT_INTERVAL);
+
+        let mut stepped = Replay::new(trace.clone());
+        let mut seeking = Replay::new(trace);
+        // The snapshots are only taken as far as the replay got
+        assert!(seeking.snapshots.is_empty());
+        seeking.seek(SNAPSHOT_INTERVAL + 1);
+        assert_eq!(seeking.snapshots.len(), 2);
+        let positions = [
+            0,
+            SNAPSHOT_INTERVAL,
+            3 * SNAPSHOT_INTERVAL + 17,
+            SNAPSHOT_INTERVAL - 1,
+            stepped.len(),
+            2 * SNAPSHOT_INTERVAL + 5,
+            2 * SNAPSHOT_INTERVAL + 500,
+            12,
+            stepped.len() + 10,
+        ];
+
+        for position in positions {
+            stepped.rewind();
+            while stepped.position() < std::cmp::min(position, stepped.len()) {
+                stepped.step().unwrap();
+            }
+
+            seeking.seek(position);
+            assert_eq!(seeking.position(), stepped.position());
+            seeking.read_array(|seeked| {
+                stepped.read_array(|stepped| {
+                    assert_eq!(seeked.numbers(), stepped.numbers(), "{position}");
+                    assert_eq!(seeked.comparisons(), stepped.comparisons(), "{position}");
+                    assert_eq!(seeked.reads(), stepped.reads(), "{position}");
+                    assert_eq!(seeked.writes(), stepped.writes(), "{position}");
+                })
+            });
+        }
+    }
+
+    #[test]
+    fn invalid_traces() {
+        let (trace, _) = record(sort::Sort::InsertionSort);
+        let mut bytes = Vec::new();
+        trace.write(&mut bytes).unwrap();
+
+        assert!(Trace::read(&bytes[..bytes.len() - 1]).is_err());
+
+        let mut version = bytes.clone();
+        version[4] = 2;
+        assert!(Trace::read(version.as_slice()).is_err());
+
+        let mut magic = bytes.clone();
+        magic[0] = b'X';
+        assert!(Trace::read(magic.as_slice()).is_err());
+
+        // A name claiming to be huge is rejected before anything is allocated for it
+        let mut name = bytes;
+        name[6..14].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(Trace::read(name.as_slice()).is_err());
+    }
+
+    #[test]
+    fn killed_runs_are_not_recorded() {
+        let mut sorter = Sorter::new(ArrayState::new(100));
+        sorter.set_recording(true);
+        sorter.start_sort();
+        sorter.kill_sort();
+
+        assert!(sorter.trace().is_none());
+    }
+}
+
+/// Regression tests pinning the exact operations of every sort.
+///
+/// Run with `UPDATE_GOLDEN=1` to regenerate `golden_traces.txt` after intentionally changing a
+/// sort or the trace format.
+#[cfg(test)]
+mod golden {
+    use super::*;
+    use crate::sorting::{Counting, Recording};
+    use rand::{seq::SliceRandom, SeedableRng};
+    use std::collections::BTreeMap;
+
+    const SIZE: usize = 64;
+    const SEED: u64 = 0;
+
+    fn golden_path() -> std::path::PathBuf {
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/sorting/golden_traces.txt")
+    }
+
+    /// The FNV-1a hash of the encoded trace, which unlike the std hashers is stable.
+    fn fingerprint(trace: &Trace) -> u64 {
+        let mut bytes = Vec::new();
+        trace.write(&mut bytes).unwrap();
+
+        bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        })
+    }
+
+    fn record(sort: sort::Sort) -> Trace {
+        let mut numbers: Vec<usize> = (1..=SIZE).collect();
+        numbers.shuffle(&mut rand::rngs::StdRng::seed_from_u64(SEED));
+
+        let array = ArrayState::from_numbers(numbers.clone());
+        let mut recording = Recording::new(Counting::new(array, SEED));
+        sort.sort(&mut recording, SIZE).unwrap();
+
+        Trace {
+            sort,
+            numbers,
+            operations: recording.into_parts().1,
+        }
+    }
+
+    #[test]
+    fn traces_match_golden() {
+        let traces: BTreeMap<String, (usize, u64)> = sort::Sort::VALUES
+            .iter()
+            .map(|&sort| {
+                let trace = record(sort);
+                (
+                    sort.to_string(),
+                    (trace.operations.len(), fingerprint(&trace)),
+                )
+            })
+            .collect();
+
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            let golden: String = traces
+                .iter()
+                .map(|(sort, (count, hash))| format!("{sort} {count} {hash:016x}\n"))
+                .collect();
+            std::fs::write(golden_path(), golden).unwrap();
+
+            return;
+        }
+
+        let golden: BTreeMap<String, (usize, u64)> = std::fs::read_to_string(golden_path())
+            .expect("missing golden traces, run with UPDATE_GOLDEN=1 to generate them")
+            .lines()
+            .map(|line| {
+                let fields: Vec<_> = line.split_whitespace().collect();
+                let count = fields[1].parse().unwrap();
+                let hash = u64::from_str_radix(fields[2], 16).unwrap();
+
+                (fields[0].to_string(), (count, hash))
+            })
+            .collect();
+
+        for (sort, &(count, hash)) in &traces {
+            assert_eq!(
+                golden.get(sort),
+                Some(&(count, hash)),
+                "{sort} changed its operations, run with UPDATE_GOLDEN=1 if that is intended"
+            );
+        }
+        assert_eq!(golden.len(), traces.len(), "golden traces of removed sorts");
+    }
+}
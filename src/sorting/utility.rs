@@ -0,0 +1,73 @@
+use rand::Rng;
+
+use super::algorithm::{AnimatedSort, SortFuture};
+use super::ops::AsyncOps;
+use crate::array::Arrangement;
+
+/// How long rearranging the numbers takes at any size.
+const DURATION: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Rearranges the numbers through the operations, so rearranging them can be animated like a sort.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Utility {
+    /// A Fisher–Yates shuffle.
+    Shuffle,
+    /// Swapping the numbers from both ends towards the middle.
+    Reverse,
+}
+
+impl Utility {
+    /// The utility rearranging the numbers that way, if there is one.
+    pub fn of(arrangement: Arrangement) -> Option<Utility> {
+        match arrangement {
+            Arrangement::Shuffled => Some(Utility::Shuffle),
+            Arrangement::Reversed => Some(Utility::Reverse),
+            _ => None,
+        }
+    }
+
+    fn operations(&self, size: u64) -> u64 {
+        match self {
+            Utility::Shuffle => size.saturating_sub(1),
+            Utility::Reverse => size / 2,
+        }
+    }
+}
+
+impl AnimatedSort for Utility {
+    fn name(&self) -> &str {
+        match self {
+            Utility::Shuffle => "Shuffling",
+            Utility::Reverse => "Reversing",
+        }
+    }
+
+    fn run<'a>(&'a self, ops: &'a mut AsyncOps<'_>) -> SortFuture<'a> {
+        Box::pin(async move {
+            let size = ops.size();
+
+            match self {
+                // Every shuffle differs, unlike the seeded randomness of the sorts
+                Utility::Shuffle => {
+                    let mut rng = rand::thread_rng();
+                    for i in (1..size).rev() {
+                        ops.swap(i, rng.gen_range(0..=i)).await?;
+                    }
+                }
+                Utility::Reverse => {
+                    for i in 0..size / 2 {
+                        ops.swap(i, size - 1 - i).await?;
+                    }
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    fn max_ticks(&self, size: u64) -> u64 {
+        let ticks = (DURATION.as_millis() / crate::DELAY_TIME.as_millis()) as u64;
+
+        self.operations(size).div_ceil(ticks).max(1)
+    }
+}
@@ -1,211 +1,2085 @@
-use std::{cmp, marker::PhantomPinned, pin::Pin, sync, thread, time};
+use rand::{Rng, SeedableRng};
+use std::sync::atomic::{self, AtomicBool, AtomicU64};
+use std::{cmp, sync, thread, time};
 
-use super::sort;
-use crate::{
-    array::{self, ArrayState},
-    gui,
-};
+use super::algorithm::{Algorithm, SortError};
+use super::ops::{self, ArrayOps, ArrayResult, Operation};
+use super::{engine, sort, trace};
+use crate::array::{self, ArrayState};
+
+/// The most operations performed under one hold of the array, after which it is let go of and
+/// published however much of the budget is left.
+const MAX_HELD_OPERATIONS: u64 = 1_000_000;
+/// Every how many operations a batch checks how long it held the array.
+const HOLD_CHECK_INTERVAL: u64 = 1024;
 
-pub type ArrayResult<T> = Result<T, ()>;
 type SyncArray = sync::Arc<sync::Mutex<array::ArrayState>>;
 
+/// Locks the mutex, ignoring poisoning so the state a panicked sort left behind stays usable.
+fn lock<T>(mutex: &sync::Mutex<T>) -> sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(sync::PoisonError::into_inner)
+}
+
+/// Which operations use up the budget of a running sort, where the others run for free.
+///
+/// This only changes the pace, the statistics still count every operation.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Granularity {
+    #[default]
+    All,
+    Comparisons,
+    Writes,
+}
+
+impl Granularity {
+    pub const VALUES: [Granularity; 3] = [
+        Granularity::All,
+        Granularity::Comparisons,
+        Granularity::Writes,
+    ];
+
+    /// Whether the operation uses up budget.
+    pub fn paces(&self, operation: Operation) -> bool {
+        match self {
+            Granularity::All => true,
+            Granularity::Comparisons => {
+                matches!(operation, Operation::CmpTwo(..) | Operation::Cmp(..))
+            }
+            Granularity::Writes => matches!(operation, Operation::Swap(..) | Operation::Set(..)),
+        }
+    }
+}
+
+impl std::fmt::Display for Granularity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Granularity::All => "All operations",
+            Granularity::Comparisons => "Comparisons only",
+            Granularity::Writes => "Writes only",
+        })
+    }
+}
+
+/// A sort thread that panicked or whose sort failed, along with its message.
+#[derive(Debug, Clone)]
+pub struct SortPanic {
+    pub sort: Algorithm,
+    pub message: String,
+}
+
+impl std::fmt::Display for SortPanic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} panicked: {}", self.sort, self.message)
+    }
+}
+
+/// The message a sort panicked with.
+pub(super) fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::from("unknown panic")
+    }
+}
+
 struct SenderHandle {
-    thread: thread::JoinHandle<ArrayResult<()>>,
-    sender: sync::mpsc::Sender<Message>,
+    thread: thread::JoinHandle<Result<Option<trace::Trace>, SortError>>,
+    sender: Sender,
+}
+
+impl SenderHandle {
+    /// Kills the sort thread, returning whether it finished within `JOIN_TIMEOUT`.
+    fn kill(&self) -> bool {
+        self.sender.send(Message::Kill).unwrap_or_default();
+
+        let deadline = time::Instant::now() + crate::JOIN_TIMEOUT;
+        while !self.thread.is_finished() && time::Instant::now() < deadline {
+            thread::sleep(time::Duration::from_millis(1));
+        }
+
+        self.thread.is_finished()
+    }
+}
+
+/// Notices a sort that stopped performing operations although ticks keep granting it budget.
+#[derive(Default)]
+struct Watchdog {
+    operations: sync::Arc<AtomicU64>,
+    seen: u64,
+    since: Option<time::Instant>,
+}
+
+impl Watchdog {
+    /// Notes a tick, starting the clock on the operations performed so far.
+    fn ticked(&mut self) {
+        let operations = self.operations.load(atomic::Ordering::Relaxed);
+
+        if operations != self.seen || self.since.is_none() {
+            self.seen = operations;
+            self.since = Some(time::Instant::now());
+        }
+    }
+
+    /// Stops expecting progress until the next tick.
+    fn reset(&mut self) {
+        self.since = None;
+    }
+
+    fn wedged(&self) -> Option<time::Duration> {
+        self.since
+            .map(|since| since.elapsed())
+            .filter(|&elapsed| elapsed > crate::WEDGED_TIMEOUT)
+    }
+}
+
+/// How the budget of a tick turned out, for tuning the pace of the sorts.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TickStats {
+    /// The operations the tick granted.
+    pub granted: u64,
+    /// The operations performed until the next tick, including those running for free.
+    pub performed: u64,
+    /// The budget left over when the next tick replaced it.
+    pub remaining: u64,
+    /// How long the sort waited for the array's lock.
+    pub waited: time::Duration,
+}
+
+/// Measures the budget a sort thread spends and how long it waits for the array.
+///
+/// Disabled, measuring costs the sort thread a load per operation.
+#[derive(Default)]
+struct Probe {
+    enabled: AtomicBool,
+    spent: AtomicU64,
+    /// The nanoseconds waited for the array.
+    waited: AtomicU64,
+}
+
+impl Probe {
+    fn new(enabled: bool) -> Probe {
+        Probe {
+            enabled: AtomicBool::new(enabled),
+            ..Probe::default()
+        }
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled.load(atomic::Ordering::Relaxed)
+    }
+
+    /// Starts timing a wait for the array, if enabled.
+    fn start(&self) -> Option<time::Instant> {
+        self.enabled().then(time::Instant::now)
+    }
+
+    fn waited(&self, since: Option<time::Instant>) {
+        if let Some(since) = since {
+            let nanos = since.elapsed().as_nanos().try_into().unwrap_or(u64::MAX);
+            self.waited.fetch_add(nanos, atomic::Ordering::Relaxed);
+        }
+    }
+
+    fn spent(&self) {
+        if self.enabled() {
+            self.spent.fetch_add(1, atomic::Ordering::Relaxed);
+        }
+    }
+
+    /// Takes the budget spent and the time waited since the last call.
+    fn take(&self) -> (u64, time::Duration) {
+        (
+            self.spent.swap(0, atomic::Ordering::Relaxed),
+            time::Duration::from_nanos(self.waited.swap(0, atomic::Ordering::Relaxed)),
+        )
+    }
 }
 
+/// Runs sorts on a separate thread.
+///
+/// The sort thread works on `array_state` and publishes a copy of it into `snapshot` whenever it
+/// hands the lock back, so reading the array for rendering never has to wait for a running batch.
+///
+/// A sort only notices being killed when it performs an operation or waits for budget, so one
+/// stuck in a loop without operating on the array can't be stopped. Such a thread is detached
+/// after `JOIN_TIMEOUT` together with the array it holds, and keeps running until the process
+/// exits, while the sorter carries on with a copy of the last published array.
 pub struct Sorter {
-    sort: sort::Sort,
+    sort: Algorithm,
     array_state: SyncArray,
+    snapshot: SyncArray,
     handle: Option<SenderHandle>,
+    panic: Option<SortPanic>,
+    seed: u64,
+    granularity: Granularity,
+    highlights: usize,
+    recording: bool,
+    trace: Option<sync::Arc<trace::Trace>>,
+    watchdog: Watchdog,
+    probe: sync::Arc<Probe>,
+    /// The budget of the last tick and the operations performed before it, while probing.
+    probed: Option<(u64, u64)>,
+    tick_stats: Option<TickStats>,
+    /// How long a tick lasts, the longest a batch holds the array at once.
+    tick_interval: time::Duration,
 }
 
 impl Sorter {
     pub fn new(array_state: array::ArrayState) -> Sorter {
         Sorter {
-            sort: sort::Sort::default(),
+            sort: Algorithm::default(),
+            snapshot: sync::Arc::new(sync::Mutex::new(array_state.clone())),
             array_state: sync::Arc::new(sync::Mutex::new(array_state)),
             handle: None,
+            panic: None,
+            seed: rand::random(),
+            granularity: Granularity::default(),
+            highlights: 1,
+            recording: false,
+            trace: None,
+            watchdog: Watchdog::default(),
+            probe: sync::Arc::default(),
+            probed: None,
+            tick_stats: None,
+            tick_interval: crate::DELAY_TIME,
         }
     }
 
     pub fn start_sort(&mut self) {
         assert!(!self.alive(), "Sort already running");
 
-        let (sender, receiver) = sync::mpsc::channel();
+        self.panic = None;
+        self.trace = None;
+        // A fresh counter, as a detached thread may still count on the old one
+        self.watchdog = Watchdog::default();
+        self.probe = sync::Arc::new(Probe::new(self.probing()));
+        self.probed = None;
+        self.tick_stats = None;
+        self.operate_array(|array| {
+            array.set_phase(None);
+            array.clear_markers();
+            array.clear_space();
+            array.clear_annotations();
+        });
+
+        let (sender, receiver) = channel();
         let array_state = self.array_state.clone();
-        let sort = self.sort;
-        let size = self.operate_array(|array| array.size());
+        let snapshot = self.snapshot.clone();
+        let sort = self.sort.clone();
+        let seed = self.seed;
+        let granularity = self.granularity;
+        let highlights = self.highlights;
+        let operations = self.watchdog.operations.clone();
+        let probe = self.probe.clone();
+        let tick_interval = self.tick_interval;
+        let (range, size) = self.read_array(|array| (array.range(), array.size()));
+        // Traces name the sort they replay, so only built in sorts of all the numbers are recorded
+        let recording = sort
+            .builtin()
+            .filter(|_| self.recording && range == (0..size));
 
         self.handle = Some(SenderHandle {
-            thread: thread::spawn(move || sort.sort(ArrayLock::new(array_state, receiver), size)),
+            thread: thread::spawn(move || {
+                let mut array_lock = ArrayLock::new(&array_state, &snapshot, receiver, seed);
+                array_lock.operations = Some(operations);
+                array_lock.tick_interval = tick_interval;
+                array_lock.pacer().granularity = granularity;
+                array_lock.pacer().highlights = highlights;
+                array_lock.pacer().probe = probe;
+
+                match recording {
+                    Some(recorded) => {
+                        let mut recording = ops::Recording::new(array_lock);
+                        sort.run(&mut recording)?;
+                        let (array_lock, operations) = recording.into_parts();
+
+                        Ok(Some(trace::Trace {
+                            sort: recorded,
+                            numbers: array_lock.initial_numbers(),
+                            operations,
+                        }))
+                    }
+                    None => sort
+                        .run(&mut ops::Subrange::new(array_lock, range))
+                        .map(|_| None),
+                }
+            }),
             sender,
         });
     }
 
+    /// Kills the sort, detaching its thread if it doesn't stop within `JOIN_TIMEOUT`.
     pub fn kill_sort(&mut self) {
         if self.alive() {
-            let handle = std::mem::replace(&mut self.handle, None).unwrap();
+            let handle = self.handle.take().unwrap();
+
+            if handle.kill() {
+                self.join(handle);
+            } else {
+                self.detach();
+            }
+        }
+    }
+
+    /// Leaves the array to a sort thread that can't be stopped, continuing with its last snapshot.
+    fn detach(&mut self) {
+        let array_state = lock(&self.snapshot).clone();
+
+        self.array_state = sync::Arc::new(sync::Mutex::new(array_state.clone()));
+        self.snapshot = sync::Arc::new(sync::Mutex::new(array_state));
+    }
 
-            handle.sender.send(Message::Kill).unwrap();
-            handle.thread.join().unwrap().unwrap_or_default();
+    /// Joins the sort thread, remembering its trace or the panic message if it panicked.
+    fn join(&mut self, handle: SenderHandle) {
+        match handle.thread.join() {
+            Ok(Ok(trace)) => {
+                if let Some(ref trace) = trace {
+                    let wasted = trace.wasted_comparisons();
+                    self.operate_array(|array| array.set_wasted_comparisons(wasted));
+                }
+                self.trace = trace.map(sync::Arc::new);
+            }
+            Ok(Err(SortError::Stopped)) => {}
+            Ok(Err(SortError::Failed(message))) => {
+                self.panic = Some(SortPanic {
+                    sort: self.sort.clone(),
+                    message,
+                });
+            }
+            Err(payload) => {
+                self.panic = Some(SortPanic {
+                    sort: self.sort.clone(),
+                    message: panic_message(payload),
+                });
+            }
         }
     }
 
-    pub fn set_sort(&mut self, sort: sort::Sort) {
+    /// The panic of the last sort thread, if it panicked.
+    pub fn panic(&self) -> Option<&SortPanic> {
+        self.panic.as_ref()
+    }
+
+    pub fn set_sort(&mut self, sort: impl Into<Algorithm>) {
         assert!(!self.alive(), "Sort still running, cannot change");
 
-        self.sort = sort;
+        self.sort = sort.into();
+    }
+
+    pub fn sort(&self) -> &Algorithm {
+        &self.sort
+    }
+
+    /// Sets the seed for the random choices sorts make, so runs can be reproduced.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Sets which operations use up the budget of the following runs.
+    pub fn set_granularity(&mut self, granularity: Granularity) {
+        self.granularity = granularity;
+    }
+
+    pub fn granularity(&self) -> Granularity {
+        self.granularity
+    }
+
+    /// Sets how many steps of each tick are highlighted, spread evenly over its operations.
+    ///
+    /// At most `MAX_HIGHLIGHTS` are kept, and a single one only highlights the last step. This
+    /// also applies to the running sort from its next tick on.
+    pub fn set_highlights(&mut self, highlights: usize) {
+        self.highlights = highlights.clamp(1, array::MAX_HIGHLIGHTS);
+        self.send(Message::Highlights(self.highlights)).ok();
+    }
+
+    pub fn highlights(&self) -> usize {
+        self.highlights
+    }
+
+    /// Records the trace of the following runs of built in sorts, kept once a run completes.
+    pub fn set_recording(&mut self, recording: bool) {
+        self.recording = recording;
+    }
+
+    pub fn recording(&self) -> bool {
+        self.recording
+    }
+
+    /// The trace of the last run, if it was recorded and completed.
+    pub fn trace(&self) -> Option<&sync::Arc<trace::Trace>> {
+        self.trace.as_ref()
+    }
+
+    /// How long a sort granted budget by ticks went without performing an operation, once that
+    /// exceeds `WEDGED_TIMEOUT`.
+    pub fn wedged(&mut self) -> Option<time::Duration> {
+        if !self.alive() {
+            return None;
+        }
+
+        self.watchdog.wedged()
+    }
+
+    /// The operations the current or last sort performed so far.
+    pub fn operations(&self) -> u64 {
+        self.watchdog.operations.load(atomic::Ordering::Relaxed)
+    }
+
+    /// Sets how long a tick lasts, which a batch lets go of the array after, from the next sort on.
+    pub fn set_tick_interval(&mut self, tick_interval: time::Duration) {
+        self.tick_interval = tick_interval;
+    }
+
+    /// Sets whether the ticks are measured, which slows down the sort thread a little.
+    pub fn set_probing(&mut self, probing: bool) {
+        self.probe.enabled.store(probing, atomic::Ordering::Relaxed);
+        self.probed = None;
+        self.tick_stats = None;
+    }
+
+    pub fn probing(&self) -> bool {
+        self.probe.enabled()
     }
 
-    pub fn sort(&self) -> sort::Sort {
-        self.sort
+    /// How the budget of the tick before the last turned out, while probing.
+    ///
+    /// The last tick's budget is only known to be done with once the next tick replaces it.
+    pub fn tick_stats(&self) -> Option<TickStats> {
+        self.tick_stats
     }
 
     pub fn alive(&mut self) -> bool {
         if let Some(ref handle) = self.handle {
             if handle.thread.is_finished() {
-                self.handle = None;
+                let handle = self.handle.take().unwrap();
+                self.join(handle);
             }
         }
 
         self.handle.is_some()
     }
 
-    fn check_alive(&mut self, msg: &str) -> &SenderHandle {
-        assert!(self.alive(), "Sort is not running: {msg}");
+    /// Sends a message to the sort thread, failing if it has already finished.
+    fn send(&mut self, message: Message) -> ArrayResult<()> {
+        if !self.alive() {
+            return Err(());
+        }
+
+        if self.handle.as_ref().unwrap().sender.send(message).is_err() {
+            // The receiver is only dropped once the sort is done, so the thread is about to finish
+            let handle = self.handle.take().unwrap();
+            self.join(handle);
+
+            return Err(());
+        }
+
+        Ok(())
+    }
+
+    /// Grants the sort the fraction of its operations per tick at full speed.
+    pub fn tick(&mut self, speed: f32) -> ArrayResult<()> {
+        let operations = engine::budget(speed, self.sort.max_ticks(self.size() as u64));
+
+        self.tick_operations(operations)
+    }
+
+    /// Grants the sort a number of operations, at least one.
+    pub fn tick_operations(&mut self, operations: u64) -> ArrayResult<()> {
+        let operations = cmp::max(1, operations);
+        self.probe_tick(operations);
+        self.send(Message::Tick(operations))?;
+        self.watchdog.ticked();
+
+        Ok(())
+    }
+
+    /// Measures the last tick, which the granted budget is about to replace.
+    fn probe_tick(&mut self, granted: u64) {
+        if !self.probing() {
+            return;
+        }
+
+        let (spent, waited) = self.probe.take();
+        let performed = self.operations();
+        if let Some((last, before)) = self.probed {
+            self.tick_stats = Some(TickStats {
+                granted: last,
+                performed: performed - before,
+                remaining: last.saturating_sub(spent),
+                waited,
+            });
+        }
+        self.probed = Some((granted, performed));
+    }
+
+    pub fn step(&mut self) -> ArrayResult<()> {
+        self.watchdog.reset();
+
+        self.send(Message::Step)
+    }
+
+    /// Lets a sort stopped on an annotation paused on take ticks again.
+    pub fn resume(&mut self) -> ArrayResult<()> {
+        self.watchdog.reset();
 
-        self.handle.as_ref().unwrap()
+        self.send(Message::Resume)
     }
 
-    pub fn tick(&mut self, speed: f32) {
-        let speed = (speed * self.sort.calculate_max_ticks(self.size() as u64) as f32) as u64;
+    /// Scales the budget the sort has left by the factor, so a change of speed applies to the
+    /// budget already granted instead of only to the next tick's.
+    pub fn rescale(&mut self, factor: f32) -> ArrayResult<()> {
+        self.send(Message::Rescale(factor))
+    }
+
+    /// Cancels the rest of the granted budget, returning once at most one more operation ran.
+    ///
+    /// Gives up waiting after `JOIN_TIMEOUT`, as a wedged sort may never let go of the array.
+    pub fn drain(&mut self) -> ArrayResult<()> {
+        self.watchdog.reset();
+        self.send(Message::Drain)?;
+
+        // The sort thread holds the array for a whole batch and only lets go once it waits
+        let deadline = time::Instant::now() + crate::JOIN_TIMEOUT;
+        while let Err(sync::TryLockError::WouldBlock) = self.array_state.try_lock() {
+            if time::Instant::now() > deadline {
+                break;
+            }
+
+            thread::sleep(time::Duration::from_millis(1));
+        }
 
-        self.check_alive("Sorting Tick")
-            .sender
-            .send(Message::Tick(cmp::max(1, speed), time::Instant::now()))
-            .unwrap();
+        Ok(())
     }
+}
 
-    pub fn step(&mut self) {
-        self.check_alive("Sorting Step")
-            .sender
-            .send(Message::Step)
-            .unwrap();
+impl Drop for Sorter {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            // A sort notices the kill within a tick, but never hang the shutdown on one that
+            // doesn't, it gets detached instead
+            if handle.kill() {
+                handle.thread.join().ok();
+            }
+        }
     }
 }
 
 macro_rules! wrap_sorter_array_ops {
-    ($(fn $name:ident($($arg:ident: $typ:ty),*) -> $ret:ty;)+) => {
+    ($wrapper:ident: $(fn $name:ident($($arg:ident: $typ:ty),*) -> $ret:ty;)+) => {
         $(pub fn $name(&self, $($arg: $typ),*) -> $ret {
-            self.operate_array(|array| array.$name($($arg),*))
+            self.$wrapper(|array| array.$name($($arg),*))
         })+
     };
 }
+pub(super) use wrap_sorter_array_ops;
 
 impl Sorter {
+    /// Mutates the array and publishes the result, waiting for a running batch to finish.
     pub fn operate_array<T>(&self, f: impl FnOnce(&mut array::ArrayState) -> T) -> T {
-        f(&mut self.array_state.lock().unwrap())
+        let mut array_state = lock(&self.array_state);
+        let result = f(&mut array_state);
+        *lock(&self.snapshot) = array_state.clone();
+
+        result
+    }
+
+    /// Reads the most recently published state of the array.
+    pub fn read_array<T>(&self, f: impl FnOnce(&array::ArrayState) -> T) -> T {
+        f(&lock(&self.snapshot))
     }
 
     wrap_sorter_array_ops! {
-        fn size() -> usize;
+        operate_array:
         fn clear_step() -> ();
-        fn last_step() -> array::Step;
         fn shuffle() -> ();
+        fn shuffle_seeded(seed: u64) -> ();
         fn reverse() -> ();
-        fn initialize(size: usize) -> ();
-        fn array_view() -> array::ArrayView;
+        fn order() -> ();
+        fn equalize() -> ();
+        fn arrange(arrangement: array::Arrangement) -> ();
+        fn initialize(size: usize, max_value: usize) -> ();
+        fn load_numbers(numbers: Vec<usize>) -> ();
+        fn reset_stats() -> ();
+        fn set_count_distances(count: bool) -> ();
+        fn set_tweening(tweening: bool) -> ();
+        fn advance_tweens(elapsed: time::Duration) -> ();
+        fn take_dirty() -> Option<std::ops::Range<usize>>;
+    }
+
+    wrap_sorter_array_ops! {
+        read_array:
+        fn size() -> usize;
+        fn last_step() -> array::Step;
         fn comparisons() -> u64;
+        fn wasted_comparisons() -> Option<u64>;
         fn reads() -> u64;
         fn writes() -> u64;
-        fn reset_stats() -> ();
-        fn get_view() -> gui::View;
-        fn set_view(view: gui::View) -> ();
+        fn stats_snapshot() -> array::Stats;
+        fn generation() -> u64;
     }
 }
 
 #[derive(Copy, Clone)]
 enum Message {
     Kill,
+    Drain,
     Step,
-    Tick(u64, time::Instant),
+    Tick(u64),
+    /// Ends the hold on an annotation paused on.
+    Resume,
+    /// Scales the budget left by the factor.
+    Rescale(f32),
+    Highlights(usize),
+}
+
+/// What a sort thread was granted besides the messages in its channel.
+///
+/// The budget of a tick only goes through the channel when the thread used up its last one and
+/// waits for it, so the ticks of a busy sort neither queue up nor wake it.
+#[derive(Default)]
+struct Mailbox {
+    /// The operations of the latest tick the thread hasn't taken yet, zero if there are none,
+    /// or `WAITING` while the thread blocks on the channel.
+    grant: AtomicU64,
+    /// Whether messages were sent that the thread hasn't received yet, so it only checks the
+    /// channel when there is something in it.
+    sent: AtomicBool,
+}
+
+/// The grant of a thread that used up its budget and waits to be sent the next one.
+const WAITING: u64 = u64::MAX;
+
+fn channel() -> (Sender, Receiver) {
+    let (sender, receiver) = sync::mpsc::channel();
+    let mailbox = sync::Arc::new(Mailbox::default());
+
+    (
+        Sender {
+            sender,
+            mailbox: mailbox.clone(),
+        },
+        Receiver { receiver, mailbox },
+    )
 }
 
-pub struct ArrayLock {
-    array_lock: Option<sync::MutexGuard<'static, ArrayState>>,
-    array_state: sync::Arc<sync::Mutex<array::ArrayState>>,
+struct Sender {
+    sender: sync::mpsc::Sender<Message>,
+    mailbox: sync::Arc<Mailbox>,
+}
+
+impl Sender {
+    /// Sends the message, leaving a tick in the mailbox unless the thread waits for it.
+    fn send(&self, message: Message) -> Result<(), sync::mpsc::SendError<Message>> {
+        let grant = &self.mailbox.grant;
+        let order = atomic::Ordering::SeqCst;
+        match message {
+            // Behind messages the thread hasn't received yet, the tick queues up after them, as
+            // the thread takes the grant after the channel and it would otherwise be replaced
+            Message::Tick(count) if !self.mailbox.sent.load(order) => {
+                let count = count.min(WAITING - 1);
+                if grant.swap(count, order) != WAITING {
+                    return Ok(());
+                }
+
+                // Unless another message woke the thread and it took the grant since
+                if grant.compare_exchange(count, 0, order, order).is_err() {
+                    return Ok(());
+                }
+            }
+            // The grant is replaced like the budget, so a stale one never runs after these
+            Message::Tick(_) | Message::Kill | Message::Drain | Message::Step => {
+                grant
+                    .fetch_update(order, order, |grant| (grant != WAITING).then_some(0))
+                    .ok();
+            }
+            Message::Rescale(factor) => {
+                grant
+                    .fetch_update(order, order, |grant| {
+                        (grant != WAITING).then(|| engine::scaled(grant, factor))
+                    })
+                    .ok();
+            }
+            Message::Resume | Message::Highlights(_) => {}
+        }
+
+        self.sender.send(message)?;
+        self.mailbox.sent.store(true, order);
+
+        Ok(())
+    }
+}
+
+struct Receiver {
     receiver: sync::mpsc::Receiver<Message>,
-    counter: u64,
-    instant: time::Instant,
-    _pinned: PhantomPinned,
+    mailbox: sync::Arc<Mailbox>,
+}
+
+impl Receiver {
+    /// Whether messages were sent since the last call, costing only a load if not.
+    fn take_sent(&self) -> bool {
+        self.mailbox.sent.load(atomic::Ordering::Relaxed)
+            && self.mailbox.sent.swap(false, atomic::Ordering::SeqCst)
+    }
+
+    fn try_recv(&self) -> Option<Message> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Takes the tick left in the mailbox, costing only a load if there is none.
+    fn take_grant(&self) -> Option<Message> {
+        match self.mailbox.grant.load(atomic::Ordering::Relaxed) {
+            0 | WAITING => None,
+            _ => match self.mailbox.grant.swap(0, atomic::Ordering::SeqCst) {
+                0 => None,
+                grant => Some(Message::Tick(grant)),
+            },
+        }
+    }
+
+    /// Takes the tick left in the mailbox, or else blocks until a message arrives, which is the
+    /// next tick unless something else is sent first.
+    fn recv(&self) -> Message {
+        let order = atomic::Ordering::SeqCst;
+        while self
+            .mailbox
+            .grant
+            .compare_exchange(0, WAITING, order, order)
+            .is_err()
+        {
+            if let Some(tick) = self.take_grant() {
+                return tick;
+            }
+        }
+
+        let message = self.receiver.recv().unwrap_or(Message::Kill);
+        // Woken by another message, the next tick is left in the mailbox again
+        self.mailbox
+            .grant
+            .compare_exchange(WAITING, 0, order, order)
+            .ok();
+
+        message
+    }
+}
+
+/// Sorts the numbers right away on the calling thread, calling the observer after every operation.
+///
+/// Returns the sorted array together with the statistics of the run.
+pub fn run(
+    sort: sort::Sort,
+    numbers: Vec<usize>,
+    seed: u64,
+    mut observer: impl FnMut(&ArrayState),
+) -> ArrayState {
+    let size = numbers.len();
+    let mut ops =
+        ops::Counting::new(ArrayState::from_numbers(numbers), seed).with_observer(&mut observer);
+
+    sort.sort(&mut ops, size).unwrap_or_default();
+
+    ops.into_array()
 }
 
-impl ArrayLock {
-    fn new(array_state: SyncArray, receiver: sync::mpsc::Receiver<Message>) -> Pin<Box<ArrayLock>> {
-        // safety: Since this returns an owning pointer with exclusive access to the lock it will not move.
-        unsafe {
-            Pin::new_unchecked(Box::new(ArrayLock {
-                array_state,
-                array_lock: None,
+/// Hands out array operations to a running sort, pacing them by the received messages.
+///
+/// The lock borrows the mutex from the sort thread, which owns a reference to the shared state
+/// for its whole lifetime, so the held guard can never outlive the array. A whole batch of
+/// operations runs under one guard, which publishes its result to the snapshot when released.
+///
+/// The pacer is only shared with the workers of a forked sort, which borrow the lock while they
+/// exist, so the lock itself reaches it without locking.
+pub struct ArrayLock<'a> {
+    array_lock: Option<sync::MutexGuard<'a, ArrayState>>,
+    array_state: &'a sync::Mutex<array::ArrayState>,
+    snapshot: &'a sync::Mutex<array::ArrayState>,
+    size: usize,
+    pacer: sync::Mutex<Pacer>,
+    rng: rand::rngs::StdRng,
+    /// The numbers as the first operation found them, whether the lock or a worker performed it.
+    initial: sync::OnceLock<sync::Arc<Vec<usize>>>,
+    operations: Option<sync::Arc<AtomicU64>>,
+    /// The phase reported since the last operation, stored along with the next one.
+    phase: Option<array::Phase>,
+    /// The marker reported since the last operation, if any, stored along with the next one.
+    marker: Option<Option<array::Marker>>,
+    /// The indices marked since the last operation, if any, stored along with the next one.
+    marks: Option<Vec<usize>>,
+    /// The space reported since the last operation, in order, stored along with the next one.
+    space: Vec<array::SpaceReport>,
+    /// The annotations since the last operation, logged along with the next one.
+    annotations: Vec<String>,
+    /// The operations performed since acquiring the array, and when it was acquired.
+    held: (u64, time::Instant),
+    /// How long a tick lasts, the longest the array is held at once.
+    tick_interval: time::Duration,
+}
+
+impl<'a> ArrayLock<'a> {
+    fn new(
+        array_state: &'a sync::Mutex<array::ArrayState>,
+        snapshot: &'a sync::Mutex<array::ArrayState>,
+        receiver: Receiver,
+        seed: u64,
+    ) -> ArrayLock<'a> {
+        ArrayLock {
+            array_state,
+            snapshot,
+            array_lock: None,
+            size: lock(array_state).size(),
+            pacer: sync::Mutex::new(Pacer {
                 receiver,
                 counter: 0,
-                instant: time::Instant::now(),
-                _pinned: PhantomPinned,
-            }))
+                holding: false,
+                killed: false,
+                granularity: Granularity::All,
+                highlights: 1,
+                interval: 1,
+                unhighlighted: 0,
+                probe: sync::Arc::default(),
+            }),
+            rng: rand::rngs::StdRng::seed_from_u64(seed),
+            initial: sync::OnceLock::new(),
+            operations: None,
+            phase: None,
+            marker: None,
+            marks: None,
+            space: Vec::new(),
+            annotations: Vec::new(),
+            held: (0, time::Instant::now()),
+            tick_interval: crate::DELAY_TIME,
+        }
+    }
+
+    /// The numbers as the sort found them on its first operation.
+    ///
+    /// The array may still change between starting the sort and its first operation, like when
+    /// shuffling a freshly started sort.
+    fn initial_numbers(&self) -> Vec<usize> {
+        match self.initial.get() {
+            Some(numbers) => numbers.to_vec(),
+            None => lock(self.array_state).numbers().to_vec(),
         }
     }
 
-    fn perform_step<F, T>(self: &mut Pin<Box<Self>>, step: F) -> ArrayResult<T>
+    fn perform_step<F, T>(&mut self, operation: Operation, step: F) -> ArrayResult<T>
     where
         F: FnOnce(&mut array::ArrayState) -> T,
     {
-        // safety: The lock won't be moved, so this is safe.
-        let this = unsafe { self.as_mut().get_unchecked_mut() };
-
-        if this.counter == 0
-            || this.counter % crate::TIME_OUT_CHECK == 0
-                && this.instant.elapsed() > crate::DELAY_TIME
-        {
-            this.array_lock = None;
-
-            match this.receiver.recv().unwrap_or(Message::Kill) {
-                Message::Kill => return Err(()),
-                Message::Step => this.counter = 1,
-                Message::Tick(count, instant) => {
-                    this.counter = count;
-                    this.instant = instant;
-                }
+        if self.pacer().exhausted()? || self.held_too_long() || self.array_lock.is_none() {
+            if self.release() {
+                // Whoever waits for the array gets a chance at it before the batch goes on
+                thread::yield_now();
             }
+            self.pacer().wait()?;
+
+            let waiting = self.pacer().probe.start();
+            let array_state = lock(self.array_state);
+            self.pacer().probe.waited(waiting);
+            self.initial.get_or_init(|| array_state.shared_numbers());
+            self.array_lock = Some(array_state);
+            self.held = (0, time::Instant::now());
+        }
+        self.held.0 += 1;
+
+        let highlighted = self.pacer().spend(operation);
+        if let Some(ref operations) = self.operations {
+            operations.fetch_add(1, atomic::Ordering::Relaxed);
+        }
 
-            // This changes the mutex guards lifetime to be unbound.
-            //
-            // safety: This type can only be aquired in a Pin<Box<_>>, so the guard will not be invalidated.
-            // the mutex will also never be moved stay alive until the guard is dropped.
-            this.array_lock =
-                unsafe { std::mem::transmute(Some(this.array_state.lock().unwrap())) };
+        let array_state = self.array_lock.as_mut().unwrap();
+        if let Some(phase) = self.phase.take() {
+            array_state.set_phase(Some(phase));
         }
+        if let Some(marker) = self.marker.take() {
+            array_state.set_marker(marker);
+        }
+        if let Some(marks) = self.marks.take() {
+            array_state.set_marks(&marks);
+        }
+        for report in self.space.drain(..) {
+            array_state.report_space(report);
+        }
+        let (watch_hits, pause_hits) = (array_state.watch_hits(), array_state.pause_hits());
+        for text in self.annotations.drain(..) {
+            array_state.annotate(text);
+        }
+        let result = step(array_state);
+        if highlighted {
+            array_state.highlight_last_step();
+        }
+
+        // The batch ends on a watched index, so it is seen before the pace can slow down for it,
+        // and on an annotation paused on, so the sort can be paused right there
+        let watched = array_state.watch_hits() != watch_hits;
+        let paused = array_state.pause_hits() != pause_hits;
+        self.pacer().stop(watched, paused);
+
+        Ok(result)
+    }
 
-        this.counter -= 1;
+    /// Whether the batch held the array for a tick or `MAX_HELD_OPERATIONS`, so it lets go of it
+    /// in between however large its budget, keeping the gui and kills from waiting on it.
+    fn held_too_long(&self) -> bool {
+        let (operations, since) = self.held;
 
-        Ok(step(&mut *this.array_lock.as_deref_mut().unwrap()))
+        operations >= MAX_HELD_OPERATIONS
+            || (operations > 0
+                && operations.is_multiple_of(HOLD_CHECK_INTERVAL)
+                && since.elapsed() >= self.tick_interval)
+    }
+
+    /// Publishes the array and lets go of it, starting over with the highlights, returning
+    /// whether it was held.
+    fn release(&mut self) -> bool {
+        let Some(mut array_state) = self.array_lock.take() else {
+            return false;
+        };
+        *lock(self.snapshot) = array_state.clone();
+        array_state.clear_highlights();
+
+        true
+    }
+
+    fn pacer(&mut self) -> &mut Pacer {
+        self.pacer
+            .get_mut()
+            .unwrap_or_else(sync::PoisonError::into_inner)
     }
 }
 
-macro_rules! wrap_array_op {
-    ($name:ident, ($($arg:ident : $argtype:ty),*) -> $ret:ty) => {
-        pub fn $name(self: &mut Pin<Box<Self>>, $($arg:$argtype),*) -> ArrayResult<$ret> {
-            self.perform_step(|array_state_argument| {
-                array_state_argument.$name($($arg),*)
-            })
+/// The budget of operations a running sort was granted by the received messages.
+struct Pacer {
+    receiver: Receiver,
+    counter: u64,
+    /// Whether the sort stopped on an annotation paused on, ignoring ticks until resumed or
+    /// stepped, as the gui only pauses once it sees the annotation in the snapshot.
+    holding: bool,
+    /// Whether a kill was received, which every worker of a forked sort has to notice.
+    killed: bool,
+    granularity: Granularity,
+    /// How many operations of a budget are highlighted.
+    highlights: usize,
+    /// Every how many operations one is highlighted, spreading the highlights over the budget.
+    interval: u64,
+    unhighlighted: u64,
+    probe: sync::Arc<Probe>,
+}
+
+impl Pacer {
+    /// Whether the budget is used up, failing if the sort was killed.
+    fn exhausted(&mut self) -> ArrayResult<bool> {
+        if self.killed {
+            return Err(());
         }
+
+        // Messages are handled between operations too, so a drain or kill applies immediately
+        self.receive_pending()?;
+
+        Ok(self.counter == 0)
     }
-}
 
-impl ArrayLock {
-    wrap_array_op!(cmp_two, (a:usize, b:usize) -> cmp::Ordering);
-    wrap_array_op!(swap, (a:usize, b:usize) -> ());
-    wrap_array_op!(cmp, (index:usize, value:usize) -> cmp::Ordering);
-    wrap_array_op!(get, (index:usize) -> usize);
-    wrap_array_op!(set, (index:usize, value:usize) -> ());
+    /// Takes the operation from the budget, unless it runs for free, returning whether its step
+    /// is highlighted.
+    fn spend(&mut self, operation: Operation) -> bool {
+        if self.granularity.paces(operation) {
+            self.counter -= 1;
+            self.probe.spent();
+        }
+
+        if self.highlights == 1 {
+            return false;
+        }
+
+        self.unhighlighted += 1;
+        if self.unhighlighted < self.interval {
+            return false;
+        }
+
+        self.unhighlighted = 0;
+        true
+    }
+
+    /// Blocks until a new budget is granted, failing if the sort was killed.
+    fn wait(&mut self) -> ArrayResult<()> {
+        if self.killed {
+            return Err(());
+        }
+
+        // The channel is emptied before blocking, as a tick left in the mailbox is newer
+        self.receive_pending()?;
+        while self.counter == 0 {
+            self.receive(self.receiver.recv())?;
+            self.receive_pending()?;
+        }
+
+        Ok(())
+    }
+
+    /// Applies a message to the remaining budget, failing if the sort was killed.
+    ///
+    /// Every message but a change of highlights or a rescale replaces the budget, so ticks that
+    /// queued up while the gui stalled only grant the budget of the latest one and a step never
+    /// runs a stale budget.
+    fn receive(&mut self, message: Message) -> ArrayResult<()> {
+        match message {
+            Message::Kill => {
+                self.killed = true;
+                return Err(());
+            }
+            Message::Drain => self.counter = 0,
+            Message::Step => {
+                self.holding = false;
+                self.counter = 1;
+            }
+            Message::Tick(_) if self.holding => {}
+            Message::Tick(count) => {
+                self.counter = count;
+                self.interval = count.div_ceil(self.highlights as u64);
+                self.unhighlighted = 0;
+            }
+            Message::Resume => self.holding = false,
+            Message::Rescale(factor) => self.counter = engine::scaled(self.counter, factor),
+            Message::Highlights(highlights) => self.highlights = highlights,
+        }
+
+        Ok(())
+    }
+
+    /// Ends the batch on the operation just performed, holding the sort there if it hit an
+    /// annotation paused on.
+    fn stop(&mut self, watched: bool, paused: bool) {
+        if watched || paused {
+            self.counter = 0;
+        }
+        self.holding |= paused;
+    }
+
+    fn receive_pending(&mut self) -> ArrayResult<()> {
+        if self.receiver.take_sent() {
+            while let Some(message) = self.receiver.try_recv() {
+                self.receive(message)?;
+            }
+        }
+
+        // Only emptied after the channel, as the messages replacing the budget clear it first
+        if let Some(tick) = self.receiver.take_grant() {
+            self.receive(tick)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Publishes what the sort reported after its last operation along with the end of it.
+impl Drop for ArrayLock<'_> {
+    fn drop(&mut self) {
+        if !self.space.is_empty() || !self.annotations.is_empty() {
+            let array_state = self
+                .array_lock
+                .get_or_insert_with(|| lock(self.array_state));
+            for report in self.space.drain(..) {
+                array_state.report_space(report);
+            }
+            for text in self.annotations.drain(..) {
+                array_state.annotate(text);
+            }
+        }
+
+        self.release();
+    }
+}
+
+macro_rules! wrap_paced_op {
+    ($name:ident, $operation:ident, ($($arg:ident : $argtype:ty),*) -> $ret:ty) => {
+        fn $name(&mut self, $($arg:$argtype),*) -> ArrayResult<$ret> {
+            self.perform_step(Operation::$operation($($arg),*), |array_state_argument| {
+                array_state_argument.$name($($arg),*)
+            })
+        }
+    }
+}
+
+impl ArrayOps for ArrayLock<'_> {
+    wrap_paced_op!(cmp_two, CmpTwo, (a:usize, b:usize) -> cmp::Ordering);
+    wrap_paced_op!(swap, Swap, (a:usize, b:usize) -> ());
+    wrap_paced_op!(cmp, Cmp, (index:usize, value:usize) -> cmp::Ordering);
+    wrap_paced_op!(get, Get, (index:usize) -> usize);
+    wrap_paced_op!(set, Set, (index:usize, value:usize) -> ());
+
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn rng(&mut self) -> &mut rand::rngs::StdRng {
+        &mut self.rng
+    }
+
+    fn report_phase(&mut self, label: &'static str, value: usize) {
+        self.phase = Some(array::Phase { label, value });
+    }
+
+    fn report_marker(&mut self, marker: Option<array::Marker>) {
+        self.marker = Some(marker);
+    }
+
+    fn mark_indices(&mut self, indices: &[usize]) {
+        self.marks = Some(indices.to_vec());
+    }
+
+    fn report_space(&mut self, report: array::SpaceReport) {
+        self.space.push(report);
+    }
+
+    fn annotate(&mut self, text: String) {
+        self.annotations.push(text);
+    }
+
+    /// Lets go of the array once the budget is used up, so a sort computing for a while without
+    /// operating on the array doesn't hold it.
+    fn check(&mut self) -> ArrayResult<()> {
+        if self.pacer().exhausted()? {
+            self.release();
+        }
+
+        Ok(())
+    }
+
+    /// Workers that take turns on the array, while the lock lets go of it until they are done.
+    fn fork(&mut self, workers: usize) -> Option<Vec<Box<dyn ArrayOps + Send + '_>>> {
+        self.release();
+
+        let seeds: Vec<u64> = (0..workers).map(|_| self.rng.gen()).collect();
+        let operations = self.operations.as_deref();
+
+        Some(
+            seeds
+                .into_iter()
+                .enumerate()
+                .map(|(id, seed)| {
+                    Box::new(Worker {
+                        id,
+                        array_state: self.array_state,
+                        snapshot: self.snapshot,
+                        pacer: &self.pacer,
+                        initial: &self.initial,
+                        operations,
+                        size: self.size,
+                        rng: rand::rngs::StdRng::seed_from_u64(seed),
+                        phase: None,
+                        marker: None,
+                        space: Vec::new(),
+                        annotations: Vec::new(),
+                    }) as Box<dyn ArrayOps + Send>
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Array operations of a worker thread of a forked sort, sharing the budget with the others.
+///
+/// Unlike the lock, a worker only holds the array for a single operation, so the operations of
+/// the workers interleave. The worker running out of budget publishes the array for all of them.
+struct Worker<'b> {
+    id: usize,
+    array_state: &'b sync::Mutex<ArrayState>,
+    snapshot: &'b sync::Mutex<ArrayState>,
+    pacer: &'b sync::Mutex<Pacer>,
+    initial: &'b sync::OnceLock<sync::Arc<Vec<usize>>>,
+    operations: Option<&'b AtomicU64>,
+    size: usize,
+    rng: rand::rngs::StdRng,
+    phase: Option<array::Phase>,
+    marker: Option<Option<array::Marker>>,
+    space: Vec<array::SpaceReport>,
+    annotations: Vec<String>,
+}
+
+impl Worker<'_> {
+    fn perform_step<F, T>(&mut self, operation: Operation, step: F) -> ArrayResult<T>
+    where
+        F: FnOnce(&mut array::ArrayState) -> T,
+    {
+        let (highlighted, probe) = {
+            let mut pacer = lock(self.pacer);
+            if pacer.exhausted()? {
+                self.publish();
+                pacer.wait()?;
+            }
+
+            let probe = pacer.probe.enabled().then(|| pacer.probe.clone());
+            (pacer.spend(operation), probe)
+        };
+
+        if let Some(operations) = self.operations {
+            operations.fetch_add(1, atomic::Ordering::Relaxed);
+        }
+
+        let waiting = probe.as_ref().and_then(|probe| probe.start());
+        let mut array_state = lock(self.array_state);
+        if let Some(probe) = probe {
+            probe.waited(waiting);
+        }
+        self.initial.get_or_init(|| array_state.shared_numbers());
+
+        if let Some(phase) = self.phase.take() {
+            array_state.set_phase(Some(phase));
+        }
+        // The workers' buffers add up, as they are allocated at the same time
+        for report in self.space.drain(..) {
+            array_state.report_space(report);
+        }
+        let pause_hits = array_state.pause_hits();
+        for text in self.annotations.drain(..) {
+            array_state.annotate(text);
+        }
+        let marker = self.marker.take();
+        let (result, hits) = array_state.as_worker(self.id, |array_state| {
+            if let Some(marker) = marker {
+                array_state.set_marker(marker);
+            }
+            let watch_hits = array_state.watch_hits();
+            let result = step(array_state);
+            if highlighted {
+                array_state.highlight_last_step();
+            }
+
+            let watched = array_state.watch_hits() != watch_hits;
+            (result, (watched, array_state.pause_hits() != pause_hits))
+        });
+        drop(array_state);
+
+        let (watched, paused) = hits;
+        lock(self.pacer).stop(watched, paused);
+
+        Ok(result)
+    }
+
+    /// Publishes the array for all workers, starting over with the highlights.
+    fn publish(&self) {
+        let mut array_state = lock(self.array_state);
+        *lock(self.snapshot) = array_state.clone();
+        array_state.clear_highlights();
+    }
+}
+
+impl Drop for Worker<'_> {
+    fn drop(&mut self) {
+        let mut array_state = lock(self.array_state);
+        array_state.clear_worker_step(self.id);
+        array_state.as_worker(self.id, |array_state| array_state.set_marker(None));
+        // What the worker freed after its last operation isn't left for the sort going on
+        for report in self.space.drain(..) {
+            array_state.report_space(report);
+        }
+        for text in self.annotations.drain(..) {
+            array_state.annotate(text);
+        }
+        drop(array_state);
+        self.publish();
+    }
+}
+
+impl ArrayOps for Worker<'_> {
+    wrap_paced_op!(cmp_two, CmpTwo, (a:usize, b:usize) -> cmp::Ordering);
+    wrap_paced_op!(swap, Swap, (a:usize, b:usize) -> ());
+    wrap_paced_op!(cmp, Cmp, (index:usize, value:usize) -> cmp::Ordering);
+    wrap_paced_op!(get, Get, (index:usize) -> usize);
+    wrap_paced_op!(set, Set, (index:usize, value:usize) -> ());
+
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn rng(&mut self) -> &mut rand::rngs::StdRng {
+        &mut self.rng
+    }
+
+    fn report_phase(&mut self, label: &'static str, value: usize) {
+        self.phase = Some(array::Phase { label, value });
+    }
+
+    fn report_marker(&mut self, marker: Option<array::Marker>) {
+        self.marker = Some(marker);
+    }
+
+    fn report_space(&mut self, report: array::SpaceReport) {
+        self.space.push(report);
+    }
+
+    fn annotate(&mut self, text: String) {
+        self.annotations.push(text);
+    }
+
+    fn check(&mut self) -> ArrayResult<()> {
+        let mut pacer = lock(self.pacer);
+        if pacer.exhausted()? {
+            self.publish();
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_to_completion(sorter: &mut Sorter) {
+        sorter.start_sort();
+
+        while sorter.tick(1.0).is_ok() {}
+
+        assert!(sorter.panic().is_none(), "{}", sorter.panic().unwrap());
+    }
+
+    #[test]
+    fn tiny_arrays() {
+        let mut sorter = Sorter::new(ArrayState::new(0));
+
+        for &sort in sort::Sort::VALUES {
+            sorter.set_sort(sort);
+
+            for size in 0..=3 {
+                for shuffle in 0..10 {
+                    sorter.initialize(size, size);
+                    if shuffle == 0 {
+                        sorter.reverse();
+                    } else {
+                        sorter.shuffle();
+                    }
+
+                    run_to_completion(&mut sorter);
+
+                    let expected: Vec<usize> = (1..=size).collect();
+                    assert_eq!(
+                        sorter.read_array(|array| array.numbers().to_vec()),
+                        expected
+                    );
+                }
+            }
+        }
+    }
+
+    fn sort_numbers(sort: sort::Sort, numbers: &[usize]) -> Vec<usize> {
+        let mut sorter = Sorter::new(ArrayState::new(numbers.len()));
+        sorter.set_sort(sort);
+        sorter.operate_array(|array| {
+            for (index, &value) in numbers.iter().enumerate() {
+                array.set(index, value);
+            }
+        });
+
+        run_to_completion(&mut sorter);
+
+        sorter.read_array(|array| array.numbers().to_vec())
+    }
+
+    #[test]
+    fn counting_sorts_value_ranges() {
+        let inputs: [&[usize]; 4] = [
+            &[3, 1, 2, 3, 1, 5, 5, 2, 3],
+            &[5, 1, 5, 2],
+            &[7; 10],
+            &[1000, 0, 999, 12, 1000, 100, 7, 0],
+        ];
+
+        for sort in [
+            sort::Sort::CountingSort,
+            sort::Sort::RadixSort10,
+            sort::Sort::RadixSort2,
+        ] {
+            for input in inputs {
+                let mut expected = input.to_vec();
+                expected.sort();
+
+                assert_eq!(sort_numbers(sort, input), expected, "{sort} on {input:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn shaker_and_double_selection_sort() {
+        use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+        for sort in [sort::Sort::ShakerSort, sort::Sort::DoubleSelectionSort] {
+            for size in 2..200 {
+                for seed in 0..3 {
+                    let mut numbers: Vec<usize> = (1..=size).collect();
+                    numbers.shuffle(&mut StdRng::seed_from_u64(seed));
+                    let mut ops = ops::Counting::new(ArrayState::from_numbers(numbers), seed);
+                    sort.sort(&mut ops, size).unwrap();
+
+                    assert_eq!(
+                        ops.array().numbers(),
+                        (1..=size).collect::<Vec<_>>(),
+                        "{sort} with size {size} and seed {seed}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn quick_sort_pivots_on_reversed_input() {
+        let size = 500;
+        let comparisons = |sort| {
+            let mut sorter = Sorter::new(ArrayState::new(size));
+            sorter.set_sort(sort);
+            sorter.set_seed(0);
+            sorter.reverse();
+
+            run_to_completion(&mut sorter);
+
+            sorter.comparisons()
+        };
+
+        let quadratic = (size * (size - 1) / 2) as u64;
+        assert!(comparisons(sort::Sort::QuickSort) > quadratic / 2);
+        assert!(comparisons(sort::Sort::QuickSortRandom) < quadratic / 10);
+        assert!(comparisons(sort::Sort::QuickSortMedian) < quadratic / 10);
+    }
+
+    #[test]
+    fn tick_backlog_is_coalesced() {
+        let array_state = sync::Mutex::new(ArrayState::new(10));
+        let snapshot = sync::Mutex::new(ArrayState::new(10));
+        let (sender, receiver) = channel();
+
+        for _ in 0..10 {
+            sender.send(Message::Tick(5)).unwrap();
+        }
+        drop(sender);
+
+        let mut lock = ArrayLock::new(&array_state, &snapshot, receiver, 0);
+        let mut operations = 0;
+        while lock.get(0).is_ok() {
+            operations += 1;
+        }
+
+        assert_eq!(operations, 5);
+    }
+
+    #[test]
+    fn ticks_only_wake_waiting_sort() {
+        let array_state = sync::Mutex::new(ArrayState::new(10));
+        let snapshot = sync::Mutex::new(ArrayState::new(10));
+        let (sender, receiver) = channel();
+        let mailbox = sender.mailbox.clone();
+        sender.send(Message::Tick(1000)).unwrap();
+
+        let mut lock = ArrayLock::new(&array_state, &snapshot, receiver, 0);
+        for _ in 0..10 {
+            lock.get(0).unwrap();
+        }
+
+        // The next tick arriving mid-batch replaces the rest without a message or letting go
+        sender.send(Message::Tick(1000)).unwrap();
+        assert!(!mailbox.sent.load(atomic::Ordering::SeqCst));
+        lock.get(0).unwrap();
+        assert_eq!(super::lock(&snapshot).reads(), 0);
+        for _ in 1..1000 {
+            lock.get(0).unwrap();
+        }
+        drop(lock);
+
+        // Only a sort waiting for budget is sent the next tick through the channel
+        let (sender, receiver) = channel();
+        let mailbox = sender.mailbox.clone();
+        thread::scope(|scope| {
+            let sort = scope.spawn(|| {
+                let mut lock = ArrayLock::new(&array_state, &snapshot, receiver, 0);
+                let mut operations = 0;
+                while lock.get(0).is_ok() {
+                    operations += 1;
+                }
+                operations
+            });
+
+            while mailbox.grant.load(atomic::Ordering::SeqCst) != WAITING {
+                thread::yield_now();
+            }
+            sender.send(Message::Tick(5)).unwrap();
+            assert_eq!(mailbox.grant.load(atomic::Ordering::SeqCst), 0);
+            drop(sender);
+
+            assert_eq!(sort.join().unwrap(), 5);
+        });
+    }
+
+    #[test]
+    fn ticks_queue_up_behind_unreceived_messages() {
+        let array_state = sync::Mutex::new(ArrayState::new(10));
+        let snapshot = sync::Mutex::new(ArrayState::new(10));
+        let (sender, receiver) = channel();
+        let mailbox = sender.mailbox.clone();
+
+        // A tick left in the mailbox would be taken before the drain, which then cancels it
+        sender.send(Message::Drain).unwrap();
+        sender.send(Message::Tick(5)).unwrap();
+        assert_eq!(mailbox.grant.load(atomic::Ordering::SeqCst), 0);
+        drop(sender);
+
+        let mut lock = ArrayLock::new(&array_state, &snapshot, receiver, 0);
+        let mut operations = 0;
+        while lock.get(0).is_ok() {
+            operations += 1;
+        }
+        assert_eq!(operations, 5);
+    }
+
+    #[test]
+    fn granularity_paces_some_operations() {
+        let array_state = sync::Mutex::new(ArrayState::new(10));
+        let snapshot = sync::Mutex::new(ArrayState::new(10));
+        let (sender, receiver) = channel();
+        sender.send(Message::Tick(3)).unwrap();
+        drop(sender);
+
+        let mut lock = ArrayLock::new(&array_state, &snapshot, receiver, 0);
+        lock.pacer().granularity = Granularity::Comparisons;
+        for _ in 0..3 {
+            for _ in 0..10 {
+                lock.get(0).unwrap();
+                lock.set(0, 1).unwrap();
+            }
+            lock.cmp_two(0, 1).unwrap();
+        }
+
+        assert!(lock.cmp(0, 1).is_err());
+    }
+
+    #[test]
+    fn highlights_are_spread_over_tick() {
+        let array_state = sync::Mutex::new(ArrayState::new(20));
+        let snapshot = sync::Mutex::new(ArrayState::new(20));
+        let (sender, receiver) = channel();
+        sender.send(Message::Highlights(4)).unwrap();
+        sender.send(Message::Tick(20)).unwrap();
+        drop(sender);
+
+        let mut array_lock = ArrayLock::new(&array_state, &snapshot, receiver, 0);
+        let mut index = 0;
+        while array_lock.get(index % 20).is_ok() {
+            index += 1;
+        }
+        drop(array_lock);
+
+        assert_eq!(
+            lock(&snapshot).highlights(),
+            [4, 9, 14, 19].map(|index| {
+                let value = index + 1;
+                (0, array::Step::Access(array::Element { index, value }))
+            })
+        );
+        assert!(lock(&array_state).highlights().is_empty());
+    }
+
+    #[test]
+    fn granularity_keeps_stats() {
+        let mut array = ArrayState::new(100);
+        array.reverse();
+        let mut stats = Vec::new();
+
+        for granularity in Granularity::VALUES {
+            let mut sorter = Sorter::new(array.clone());
+            sorter.set_sort(sort::Sort::InsertionSort);
+            sorter.set_granularity(granularity);
+            run_to_completion(&mut sorter);
+
+            stats.push(
+                sorter.read_array(|array| (array.comparisons(), array.reads(), array.writes())),
+            );
+        }
+
+        assert!(stats.windows(2).all(|pair| pair[0] == pair[1]));
+    }
+
+    fn operations_after(messages: &[Message]) -> usize {
+        let array_state = sync::Mutex::new(ArrayState::new(10));
+        let snapshot = sync::Mutex::new(ArrayState::new(10));
+        let (sender, receiver) = channel();
+
+        sender.send(Message::Tick(1000)).unwrap();
+        let mut lock = ArrayLock::new(&array_state, &snapshot, receiver, 0);
+        for _ in 0..10 {
+            lock.get(0).unwrap();
+        }
+
+        for &message in messages {
+            sender.send(message).unwrap();
+        }
+        drop(sender);
+
+        let mut operations = 0;
+        while lock.get(0).is_ok() {
+            operations += 1;
+        }
+
+        operations
+    }
+
+    #[test]
+    fn drain_cancels_budget() {
+        assert_eq!(operations_after(&[Message::Drain]), 0);
+        assert_eq!(operations_after(&[Message::Step]), 1);
+        assert_eq!(operations_after(&[Message::Drain, Message::Step]), 1);
+        assert_eq!(operations_after(&[Message::Step, Message::Drain]), 0);
+    }
+
+    /// The operations after the sort stopped on an annotation paused on, on top of its budget.
+    fn operations_held_after(messages: &[Message]) -> usize {
+        let mut array = ArrayState::new(10);
+        array.set_pause_on(Some("pivot"));
+        let array_state = sync::Mutex::new(array);
+        let snapshot = sync::Mutex::new(ArrayState::new(10));
+        let (sender, receiver) = channel();
+
+        sender.send(Message::Tick(1000)).unwrap();
+        let mut lock = ArrayLock::new(&array_state, &snapshot, receiver, 0);
+        lock.annotate(String::from("Pivot chosen"));
+        lock.get(0).unwrap();
+
+        for &message in messages {
+            sender.send(message).unwrap();
+        }
+        drop(sender);
+
+        let mut operations = 0;
+        while lock.get(0).is_ok() {
+            operations += 1;
+        }
+
+        operations
+    }
+
+    #[test]
+    fn annotations_paused_on_hold_the_sort() {
+        assert_eq!(operations_held_after(&[]), 0);
+        assert_eq!(operations_held_after(&[Message::Tick(5)]), 0);
+        assert_eq!(operations_held_after(&[Message::Step]), 1);
+        assert_eq!(operations_held_after(&[Message::Step, Message::Tick(5)]), 5);
+        assert_eq!(
+            operations_held_after(&[Message::Tick(5), Message::Resume, Message::Tick(5)]),
+            5
+        );
+    }
+
+    #[test]
+    fn rescale_scales_remaining_budget() {
+        // Ten of the thousand operations ran before the speed changed
+        assert_eq!(operations_after(&[Message::Rescale(0.1)]), 99);
+        assert_eq!(operations_after(&[Message::Rescale(2.0)]), 1980);
+        assert_eq!(operations_after(&[Message::Rescale(0.0)]), 0);
+        assert_eq!(operations_after(&[Message::Rescale(-1.0)]), 0);
+        assert_eq!(operations_after(&[Message::Rescale(f32::NAN)]), 0);
+        // Scaling never grants more than a single tick could
+        assert_eq!(
+            operations_after(&[Message::Rescale(f32::INFINITY)]),
+            engine::MAX_TICK_OPERATIONS as usize
+        );
+        assert_eq!(
+            operations_after(&[Message::Drain, Message::Rescale(2.0)]),
+            0
+        );
+    }
+
+    #[test]
+    fn drain_stops_sort() {
+        // Bubble sort only compares or swaps, each swap writing twice
+        let operations = |sorter: &Sorter| sorter.comparisons() + sorter.writes() / 2;
+
+        let mut sorter = Sorter::new(ArrayState::new(2000));
+        sorter.shuffle();
+        sorter.start_sort();
+        sorter.tick(1.0).unwrap();
+        thread::sleep(time::Duration::from_millis(5));
+
+        sorter.drain().unwrap();
+        let drained = operations(&sorter);
+        thread::sleep(time::Duration::from_millis(50));
+        let later = operations(&sorter);
+        assert!(later - drained <= 1);
+
+        sorter.step().unwrap();
+        thread::sleep(time::Duration::from_millis(50));
+        assert_eq!(operations(&sorter) - later, 1);
+
+        sorter.kill_sort();
+    }
+
+    #[test]
+    fn headless_run() {
+        use rand::seq::SliceRandom;
+
+        let mut numbers: Vec<usize> = (1..=100).collect();
+        numbers.shuffle(&mut rand::rngs::StdRng::seed_from_u64(0));
+
+        let mut operations = 0;
+        let array = run(sort::Sort::QuickSortRandom, numbers, 0, |_| operations += 1);
+
+        assert_eq!(array.numbers(), (1..=100).collect::<Vec<_>>());
+        assert!(operations as u64 >= array.comparisons());
+        assert!(operations as u64 <= array.comparisons() + array.writes());
+    }
+
+    #[test]
+    fn operations_are_counted_per_run() {
+        let mut sorter = Sorter::new(ArrayState::new(100));
+        sorter.set_seed(0);
+        sorter.set_sort(sort::Sort::QuickSortRandom);
+
+        for _ in 0..2 {
+            sorter.shuffle();
+            let numbers = sorter.read_array(|array| array.numbers().to_vec());
+            let mut operations = 0;
+            run(sort::Sort::QuickSortRandom, numbers, 0, |_| operations += 1);
+
+            run_to_completion(&mut sorter);
+            assert_eq!(sorter.operations(), operations);
+        }
+    }
+
+    #[test]
+    fn parallel_workers_interleave() {
+        let mut sorter = Sorter::new(ArrayState::new(1000));
+        sorter.set_sort(sort::Sort::ParallelMergeSort);
+        sorter.shuffle();
+        sorter.start_sort();
+
+        let mut concurrent = false;
+        while sorter.tick_operations(50).is_ok() {
+            thread::sleep(time::Duration::from_millis(1));
+
+            let steps = sorter.read_array(|array| array.steps().to_vec());
+            concurrent |= steps
+                .iter()
+                .filter(|&&step| step != array::Step::None)
+                .count()
+                > 1;
+        }
+
+        assert!(concurrent);
+        assert_eq!(sorter.read_array(ArrayState::workers), 2);
+        assert!(sorter.panic().is_none());
+        assert_eq!(
+            sorter.read_array(|array| array.numbers().to_vec()),
+            (1..=1000).collect::<Vec<_>>()
+        );
+
+        sorter.set_sort(sort::Sort::MergeSort);
+        sorter.shuffle();
+        run_to_completion(&mut sorter);
+        assert_eq!(sorter.read_array(ArrayState::workers), 1);
+    }
+
+    #[test]
+    fn kill_stops_all_workers() {
+        let mut sorter = Sorter::new(ArrayState::new(1000));
+        sorter.set_sort(sort::Sort::ParallelMergeSort);
+        sorter.shuffle();
+        sorter.start_sort();
+        for _ in 0..5 {
+            sorter.tick_operations(100).unwrap();
+            thread::sleep(crate::DELAY_TIME);
+        }
+
+        let array_state = sorter.array_state.clone();
+        sorter.kill_sort();
+
+        assert!(!sorter.alive());
+        assert!(sorter.panic().is_none());
+        // Not detached, so no worker holds on to the array
+        assert_eq!(sync::Arc::strong_count(&array_state), 2);
+    }
+
+    #[test]
+    fn wrapped_locks_fork() {
+        let array_state = sync::Mutex::new(ArrayState::new(10));
+        let snapshot = sync::Mutex::new(ArrayState::new(10));
+        let (sender, receiver) = channel();
+
+        sender.send(Message::Tick(1000)).unwrap();
+        let lock = ArrayLock::new(&array_state, &snapshot, receiver, 0);
+        let mut recording = ops::Recording::new(ops::Checked::new(lock));
+        {
+            let mut workers = recording.fork(2).unwrap();
+            assert_eq!(workers.len(), 2);
+            workers[1].get(9).unwrap();
+            workers[0].swap(0, 1).unwrap();
+
+            // The workers keep checking the bounds
+            let out_of_bounds = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                workers[1].get(10).ok();
+            }));
+            assert!(out_of_bounds.is_err());
+        }
+
+        // And record into the trace of the lock they forked from, in the order of the workers
+        let (_, trace) = recording.into_parts();
+        assert_eq!(trace, [Operation::Swap(0, 1), Operation::Get(9)]);
+    }
+
+    #[test]
+    fn drop_stops_sort() {
+        let mut sorter = Sorter::new(ArrayState::new(1000));
+        sorter.shuffle();
+        sorter.start_sort();
+        sorter.tick(1.0).unwrap();
+
+        let array_state = sorter.array_state.clone();
+        let instant = time::Instant::now();
+        drop(sorter);
+
+        assert!(instant.elapsed() < crate::JOIN_TIMEOUT);
+        assert_eq!(sync::Arc::strong_count(&array_state), 1);
+    }
+
+    #[test]
+    fn start_kill_stress() {
+        let mut sorter = Sorter::new(ArrayState::new(100));
+
+        for &sort in sort::Sort::VALUES.iter().cycle().take(1000) {
+            sorter.set_sort(sort);
+            sorter.shuffle();
+            sorter.start_sort();
+            sorter.tick(1.0).unwrap();
+            sorter.kill_sort();
+
+            assert!(!sorter.alive());
+        }
+    }
+
+    #[test]
+    fn protocol_fuzz() {
+        use rand::{seq::SliceRandom, Rng};
+        use std::sync::atomic::{self, AtomicU64};
+
+        const THREADS: u64 = 4;
+        const DURATION: time::Duration = time::Duration::from_secs(2);
+        const WATCHDOG: time::Duration = time::Duration::from_secs(10);
+
+        let sorter = sync::Arc::new(sync::Mutex::new(Sorter::new(ArrayState::new(50))));
+        let progress = sync::Arc::new(AtomicU64::new(0));
+        let deadline = time::Instant::now() + DURATION;
+
+        let threads: Vec<_> = (0..THREADS)
+            .map(|seed| {
+                let sorter = sorter.clone();
+                let progress = progress.clone();
+
+                thread::spawn(move || {
+                    let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+
+                    while time::Instant::now() < deadline {
+                        let mut sorter = sorter.lock().unwrap();
+
+                        // Like the gui, the array only changes before a sort's first operation
+                        match rng.gen_range(0..8) {
+                            0 => {
+                                sorter.kill_sort();
+                                let sort = *sort::Sort::VALUES.choose(&mut rng).unwrap();
+                                sorter.set_sort(sort);
+                                sorter.start_sort();
+                                sorter.shuffle();
+                            }
+                            1 => {
+                                sorter.kill_sort();
+                                let size = rng.gen_range(0..100);
+                                sorter.initialize(size, size);
+                                sorter.start_sort();
+                            }
+                            2 => sorter.kill_sort(),
+                            3 => sorter.step().unwrap_or_default(),
+                            4 => sorter.drain().unwrap_or_default(),
+                            5 => {
+                                sorter.read_array(|array| array.numbers().len());
+                            }
+                            _ => sorter.tick(rng.gen()).unwrap_or_default(),
+                        }
+
+                        assert!(sorter.panic().is_none(), "{}", sorter.panic().unwrap());
+                        drop(sorter);
+
+                        progress.fetch_add(1, atomic::Ordering::Relaxed);
+                    }
+                })
+            })
+            .collect();
+
+        let mut last = (0, time::Instant::now());
+        while threads.iter().any(|thread| !thread.is_finished()) {
+            let current = progress.load(atomic::Ordering::Relaxed);
+            if current != last.0 {
+                last = (current, time::Instant::now());
+            }
+            assert!(
+                last.1.elapsed() < WATCHDOG,
+                "no progress, the sorter deadlocked"
+            );
+
+            thread::sleep(time::Duration::from_millis(10));
+        }
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let mut sorter = sorter.lock().unwrap();
+        sorter.kill_sort();
+        let mut numbers = sorter.read_array(|array| array.numbers().to_vec());
+        numbers.sort_unstable();
+        assert_eq!(numbers, (1..=numbers.len()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn tick_after_finish() {
+        let mut sorter = Sorter::new(ArrayState::new(10));
+        sorter.start_sort();
+
+        while sorter.tick(1.0).is_ok() {}
+
+        for _ in 0..1000 {
+            assert!(sorter.tick(1.0).is_err());
+            assert!(sorter.step().is_err());
+        }
+    }
+
+    #[test]
+    fn panic_is_reported() {
+        let mut sorter = Sorter::new(ArrayState::new(10));
+        sorter.set_sort(sort::Sort::BrokenSort);
+        sorter.start_sort();
+        sorter.tick(1.0).unwrap();
+
+        while sorter.alive() {}
+
+        let panic = sorter.panic().unwrap();
+        assert_eq!(panic.sort, sort::Sort::BrokenSort.into());
+        assert_eq!(panic.message, "deliberately broken sort");
+        assert_eq!(
+            sorter.last_step(),
+            array::Step::Access(array::Element { index: 0, value: 1 })
+        );
+        assert_eq!(sorter.reads(), 1);
+
+        sorter.start_sort();
+        assert!(sorter.panic().is_none());
+        sorter.kill_sort();
+    }
+
+    /// Spins without touching the array after its first read, until released.
+    struct SpinningSort(sync::Arc<atomic::AtomicBool>);
+
+    impl super::super::AnimatedSort for SpinningSort {
+        fn name(&self) -> &str {
+            "Spinning Sort"
+        }
+
+        fn run<'a>(&'a self, ops: &'a mut ops::AsyncOps<'_>) -> super::super::SortFuture<'a> {
+            Box::pin(async move {
+                ops.get(0).await?;
+                while !self.0.load(atomic::Ordering::Relaxed) {
+                    thread::sleep(time::Duration::from_millis(1));
+                }
+
+                Ok(())
+            })
+        }
+
+        fn max_ticks(&self, _size: u64) -> u64 {
+            1
+        }
+    }
+
+    #[test]
+    fn wedged_sort_is_detached() {
+        let released = sync::Arc::new(atomic::AtomicBool::new(false));
+        let mut sorter = Sorter::new(ArrayState::new(10));
+        sorter.set_sort(Algorithm::new(SpinningSort(released.clone())));
+        sorter.start_sort();
+
+        for _ in 0..10 {
+            sorter.tick(1.0).unwrap();
+            thread::sleep(crate::DELAY_TIME);
+        }
+        assert_eq!(
+            sorter.watchdog.operations.load(atomic::Ordering::Relaxed),
+            1
+        );
+        assert!(sorter.wedged().is_none());
+
+        // Pretend the ticks went unanswered for long enough
+        sorter.watchdog.since = Some(time::Instant::now() - crate::WEDGED_TIMEOUT);
+        sorter.tick(1.0).unwrap();
+        assert!(sorter.wedged().is_some());
+
+        let instant = time::Instant::now();
+        sorter.drain().unwrap();
+        sorter.kill_sort();
+        assert!(instant.elapsed() < crate::JOIN_TIMEOUT * 3);
+        assert!(!sorter.alive());
+        assert!(sorter.wedged().is_none());
+
+        // The sorter carries on with its own array
+        sorter.shuffle();
+        sorter.set_sort(sort::Sort::InsertionSort);
+        run_to_completion(&mut sorter);
+        assert_eq!(
+            sorter.read_array(|array| array.numbers().to_vec()),
+            (1..=10).collect::<Vec<_>>()
+        );
+
+        released.store(true, atomic::Ordering::Relaxed);
+    }
+
+    #[test]
+    fn huge_budget_stays_responsive() {
+        let mut sorter = Sorter::new(ArrayState::new(5000));
+        sorter.set_sort(sort::Sort::BubbleSort);
+        sorter.shuffle();
+        sorter.start_sort();
+        sorter.tick_operations(u64::MAX).unwrap();
+
+        // The batch lets go of the array every frame, publishing it without a tick arriving
+        let published_after = |sorter: &Sorter, reads: u64| {
+            let deadline = time::Instant::now() + crate::JOIN_TIMEOUT;
+            while sorter.reads() <= reads {
+                assert!(time::Instant::now() < deadline, "nothing published");
+                thread::sleep(time::Duration::from_millis(1));
+            }
+        };
+        published_after(&sorter, 0);
+        let reads = sorter.operate_array(|array| array.reads());
+        published_after(&sorter, reads);
+
+        // Joined, as a thread that doesn't notice the kill is only detached after `JOIN_TIMEOUT`
+        let started = time::Instant::now();
+        sorter.kill_sort();
+        assert!(started.elapsed() < crate::JOIN_TIMEOUT);
+        assert!(!sorter.alive());
+    }
+
+    #[test]
+    fn working_sort_is_not_wedged() {
+        let mut sorter = Sorter::new(ArrayState::new(1000));
+        sorter.shuffle();
+        sorter.start_sort();
+
+        for _ in 0..10 {
+            sorter.tick(0.1).unwrap();
+            thread::sleep(crate::DELAY_TIME);
+        }
+        sorter.watchdog.since = Some(time::Instant::now() - crate::WEDGED_TIMEOUT);
+        sorter.tick(0.1).unwrap();
+
+        assert!(sorter.wedged().is_none());
+        sorter.kill_sort();
+    }
 }
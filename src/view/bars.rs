@@ -0,0 +1,493 @@
+//! The default view, a bar as tall as its number in every column.
+//!
+//! With room for it, every number gets a bar of its own with a gap to the next, instead of
+//! sampling the numbers into the columns of pixels. Few enough bars wide enough for them are
+//! labeled with their values, and the tops of the bars compared or swapped by the last steps are
+//! connected unless there are too many bars to tell the connections apart. The tree of a heap can
+//! be drawn over few enough bars the same way, and the markers of a sort, like the boundaries of
+//! merged runs or the pivot of a partition, and the indices it marks over any.
+
+use super::{Renderer, Scene, BLACK, DIFFERS, INDICATOR_SIZE, WHITE};
+use crate::array;
+use iced::{alignment, canvas};
+
+/// The fewest device pixels a bar and its gap take up for the bars to be drawn apart.
+const MIN_BAR_WIDTH: u32 = 3;
+/// The device pixels between two bars drawn apart.
+const BAR_GAP: u32 = 1;
+/// The most bars labeled with their values.
+const MAX_LABELS: usize = 40;
+/// The smallest font size of the labels, below which the bars are too narrow to be labeled.
+const MIN_LABEL_SIZE: f32 = 8.0;
+const MAX_LABEL_SIZE: f32 = 20.0;
+/// The width of a digit relative to the font size, roughly.
+const DIGIT_WIDTH: f32 = 0.6;
+/// The most bars connected by the steps touching two of them, more would be a tangle.
+const MAX_CONNECTED: usize = 200;
+/// The dashes of the line connecting swapped bars, set apart from the arcs of comparisons.
+const SWAP_DASHES: [f32; 2] = [4.0, 4.0];
+/// The edges between the tops of parents and children in a heap.
+const HEAP_EDGES: iced::Color = iced::Color {
+    r: 1f32,
+    g: 0.3f32,
+    b: 0.8f32,
+    a: 0.8f32,
+};
+/// The boundaries of the runs being merged.
+const MERGE_BOUNDARIES: iced::Color = iced::Color {
+    r: 0.3f32,
+    g: 0.9f32,
+    b: 1f32,
+    a: 0.9f32,
+};
+/// The dashes of the boundary between the merged runs, set apart from their outer boundaries.
+const MIDDLE_DASHES: [f32; 2] = [6.0, 4.0];
+/// The outline of the pivot and the pointers of the scans partitioning around it.
+const PIVOT: iced::Color = iced::Color {
+    r: 0.6f32,
+    g: 0.4f32,
+    b: 1f32,
+    a: 1f32,
+};
+/// The lines at the indices a sort marked, like the cursors of its buckets.
+const MARKS: iced::Color = iced::Color {
+    r: 1f32,
+    g: 0.6f32,
+    b: 0.1f32,
+    a: 0.9f32,
+};
+/// The size of the triangles pointing up at the bars the scans reached.
+const POINTER_SIZE: f32 = 8.0;
+/// The shade over the numbers still in the heap, setting them apart from the extracted ones.
+const HEAP_SHADE: iced::Color = iced::Color {
+    r: 1f32,
+    g: 0.3f32,
+    b: 0.8f32,
+    a: 0.2f32,
+};
+
+pub(super) struct Bars;
+
+impl Renderer for Bars {
+    fn draw(&self, frame: &mut canvas::Frame, scene: &Scene) {
+        frame.fill_rectangle(iced::Point::ORIGIN, scene.size, BLACK);
+
+        let spacing = Spacing::of(scene.size, scene.scale_factor, scene.numbers.len());
+        let label_size = spacing
+            .as_ref()
+            .and_then(|spacing| label_size(scene, spacing.bar(0, scene.scale_factor).1));
+        // The distance between two neighbouring bars, which the sliding ones are drawn away by
+        let place = spacing
+            .as_ref()
+            .map_or(scene.size.width / scene.numbers.len() as f32, |spacing| {
+                spacing.width as f32 / scene.scale_factor
+            });
+
+        for (index, x, width) in bars(scene.size, scene.scale_factor, scene.numbers.len()) {
+            let x = x + scene.snap(scene.offset(index) * place);
+            let height = scene.snap(scene.normalized_value(index) * scene.size.height);
+            let untouched = if scene.differs(index) { DIFFERS } else { WHITE };
+            let color = self.color_at(scene, index, untouched);
+            let top = scene.size.height - height;
+
+            frame.fill_rectangle(
+                iced::Point::new(x, top),
+                iced::Size::new(width, height),
+                color,
+            );
+
+            // Within the top of the bar if it is tall enough, or else right above it
+            if let Some(size) = label_size {
+                let inside = height >= size * 1.5;
+                frame.fill_text(canvas::Text {
+                    content: scene.numbers[index].to_string(),
+                    position: iced::Point::new(
+                        x + width / 2.0,
+                        if inside {
+                            top + size / 4.0
+                        } else {
+                            top - size / 4.0
+                        },
+                    ),
+                    color: if inside { contrast(color) } else { WHITE },
+                    size,
+                    horizontal_alignment: alignment::Horizontal::Center,
+                    vertical_alignment: if inside {
+                        alignment::Vertical::Top
+                    } else {
+                        alignment::Vertical::Bottom
+                    },
+                    ..canvas::Text::default()
+                });
+            }
+        }
+
+        for (worker, &step) in scene.steps.iter().enumerate() {
+            if let Some(path) = connection(scene, step) {
+                let stroke = canvas::Stroke {
+                    line_dash: canvas::LineDash {
+                        segments: if step.is_comparison() {
+                            &[]
+                        } else {
+                            &SWAP_DASHES
+                        },
+                        offset: 0,
+                    },
+                    ..canvas::Stroke::default()
+                }
+                .with_color(self.color_for_step(scene, worker, step))
+                .with_width(2.0);
+
+                frame.stroke(&path, stroke);
+            }
+        }
+    }
+
+    fn index_at(
+        &self,
+        size: iced::Size,
+        scale_factor: f32,
+        len: usize,
+        point: iced::Point,
+    ) -> Option<usize> {
+        match Spacing::of(size, scale_factor, len) {
+            Some(spacing) => {
+                let column = ((point.x * scale_factor) as u32).checked_sub(spacing.offset)?;
+
+                Some((column / spacing.width) as usize).filter(|&index| index < len)
+            }
+            None => super::column_at(size, scale_factor, len, point),
+        }
+    }
+
+    fn outline(&self, scene: &Scene, index: usize) -> canvas::Path {
+        let height = scene.normalized_value(index) * scene.size.height;
+
+        match Spacing::of(scene.size, scene.scale_factor, scene.numbers.len()) {
+            Some(spacing) => {
+                let (x, width) = spacing.bar(index, scene.scale_factor);
+
+                super::bar_outline(scene, x, x + width, height)
+            }
+            None => super::column_outline(scene, index, height),
+        }
+    }
+
+    /// Above the top of the bar, or within it if it reaches the top of the frame.
+    fn anchor(&self, scene: &Scene, index: usize) -> iced::Point {
+        let top = top(scene, index);
+
+        iced::Point::new(top.x, f32::max(top.y - INDICATOR_SIZE, INDICATOR_SIZE))
+    }
+}
+
+/// How the bars are laid out when there's room to draw them apart, in device pixels.
+struct Spacing {
+    /// The width of a bar along with its gap.
+    width: u32,
+    /// The space left of the first bar, centering them.
+    offset: u32,
+}
+
+impl Spacing {
+    fn of(size: iced::Size, scale_factor: f32, len: usize) -> Option<Spacing> {
+        let columns = (size.width * scale_factor) as u32;
+        let width = columns.checked_div(len as u32)?;
+
+        (width >= MIN_BAR_WIDTH).then(|| Spacing {
+            width,
+            offset: (columns - width * len as u32) / 2,
+        })
+    }
+
+    /// The left edge and the width of the bar at the index, without its gap, in logical pixels.
+    fn bar(&self, index: usize, scale_factor: f32) -> (f32, f32) {
+        let x = self.offset + index as u32 * self.width;
+
+        (
+            x as f32 / scale_factor,
+            (self.width - BAR_GAP) as f32 / scale_factor,
+        )
+    }
+}
+
+/// The font size of the labels of bars as wide as given, scaled to fit the widest value, if they
+/// are to be labeled.
+fn label_size(scene: &Scene, bar_width: f32) -> Option<f32> {
+    if scene.numbers.len() > MAX_LABELS {
+        return None;
+    }
+
+    let digits = scene.max_value.to_string().len() as f32;
+    let size = f32::min(bar_width / (digits * DIGIT_WIDTH), MAX_LABEL_SIZE);
+
+    (size >= MIN_LABEL_SIZE).then_some(size)
+}
+
+/// An arc between the tops of two compared bars, or a line between two swapped ones.
+fn connection(scene: &Scene, step: array::Step) -> Option<canvas::Path> {
+    let len = scene.numbers.len();
+    let (a, b) = match step {
+        array::Step::ComparisonTwo(a, b) | array::Step::AccessTwo(a, b) => (a.index, b.index),
+        _ => return None,
+    };
+    if len > MAX_CONNECTED || a == b || a.max(b) >= len {
+        return None;
+    }
+
+    let (from, to) = (top(scene, a), top(scene, b));
+
+    Some(canvas::Path::new(|builder| {
+        builder.move_to(from);
+        if step.is_comparison() {
+            // Rising higher the further apart the bars are, but staying in the frame
+            let control = iced::Point::new(
+                (from.x + to.x) / 2.0,
+                f32::max(from.y.min(to.y) - (to.x - from.x).abs() / 2.0, 0.0),
+            );
+            builder.quadratic_curve_to(control, to);
+        } else {
+            builder.line_to(to);
+        }
+    }))
+}
+
+/// The center of the top of the bar at the index.
+fn top(scene: &Scene, index: usize) -> iced::Point {
+    let len = scene.numbers.len();
+    let x = match Spacing::of(scene.size, scene.scale_factor, len) {
+        Some(spacing) => {
+            let (x, width) = spacing.bar(index, scene.scale_factor);
+            x + width / 2.0
+        }
+        None => (index as f32 + 0.5) / len as f32 * scene.size.width,
+    };
+
+    iced::Point::new(x, (1.0 - scene.normalized_value(index)) * scene.size.height)
+}
+
+/// Where the bar at the index begins, in the middle of the gap left of it, or where the bars end
+/// past the last one.
+fn boundary(scene: &Scene, index: usize) -> f32 {
+    let len = scene.numbers.len();
+    match Spacing::of(scene.size, scene.scale_factor, len) {
+        Some(spacing) => {
+            let column = (spacing.offset + index as u32 * spacing.width) as f32;
+            (column - BAR_GAP as f32 / 2.0) / scene.scale_factor
+        }
+        None => index as f32 / len as f32 * scene.size.width,
+    }
+}
+
+/// Draws the markers, lines at the boundaries of runs being merged, dashed between the two of
+/// each merge, and the outline of a pivot with triangles pointing up at the bars its scans reached.
+/// The marked indices get a line left of their bars.
+pub(super) fn draw_markers(
+    frame: &mut canvas::Frame,
+    scene: &Scene,
+    markers: &[array::Marker],
+    marks: &[usize],
+) {
+    let len = scene.numbers.len();
+    let line = |index: usize| {
+        let x = scene.snap(boundary(scene, index.min(len)));
+        canvas::Path::line(
+            iced::Point::new(x, 0.0),
+            iced::Point::new(x, scene.size.height),
+        )
+    };
+    let pointer = |index: usize| {
+        let x = top(scene, index).x;
+        let bottom = scene.size.height;
+        canvas::Path::new(|builder| {
+            builder.move_to(iced::Point::new(x, bottom - POINTER_SIZE));
+            builder.line_to(iced::Point::new(x + POINTER_SIZE / 2.0, bottom));
+            builder.line_to(iced::Point::new(x - POINTER_SIZE / 2.0, bottom));
+            builder.close();
+        })
+    };
+
+    let stroke = canvas::Stroke::default().with_color(MARKS).with_width(1.0);
+    for &index in marks {
+        frame.stroke(&line(index), stroke);
+    }
+
+    for &marker in markers {
+        match marker {
+            array::Marker::Merge { start, middle, end } => {
+                let stroke = canvas::Stroke::default()
+                    .with_color(MERGE_BOUNDARIES)
+                    .with_width(2.0);
+
+                frame.stroke(&line(start), stroke);
+                frame.stroke(&line(end), stroke);
+                frame.stroke(
+                    &line(middle),
+                    canvas::Stroke {
+                        line_dash: canvas::LineDash {
+                            segments: &MIDDLE_DASHES,
+                            offset: 0,
+                        },
+                        ..stroke
+                    },
+                );
+            }
+            array::Marker::Partition { pivot, left, right } => {
+                if pivot.max(left).max(right) >= len {
+                    continue;
+                }
+
+                frame.stroke(
+                    &Bars.outline(scene, pivot),
+                    canvas::Stroke::default().with_color(PIVOT).with_width(2.0),
+                );
+                frame.fill(&pointer(left), PIVOT);
+                frame.fill(&pointer(right), PIVOT);
+            }
+        }
+    }
+}
+
+/// Shades the numbers in the heap and connects the top of every parent in it to its children's,
+/// counting the heap's indices from its start.
+pub(super) fn draw_heap(frame: &mut canvas::Frame, scene: &Scene, heap: std::ops::Range<usize>) {
+    let len = scene.numbers.len();
+    let heap = heap.start.min(len)..heap.end.min(len);
+    if heap.is_empty() {
+        return;
+    }
+
+    let (left, right) = (boundary(scene, heap.start), boundary(scene, heap.end));
+    frame.fill_rectangle(
+        iced::Point::new(left, 0.0),
+        iced::Size::new(right - left, scene.size.height),
+        HEAP_SHADE,
+    );
+
+    let edges = canvas::Path::new(|builder| {
+        for child in heap.start + 1..heap.end {
+            let parent = heap.start + (child - heap.start - 1) / 2;
+            builder.move_to(top(scene, parent));
+            builder.line_to(top(scene, child));
+        }
+    });
+    frame.stroke(
+        &edges,
+        canvas::Stroke::default()
+            .with_color(HEAP_EDGES)
+            .with_width(1.5),
+    );
+}
+
+/// Black or white, whichever stands out against the color.
+fn contrast(color: iced::Color) -> iced::Color {
+    if 0.2126 * color.r + 0.7152 * color.g + 0.0722 * color.b > 0.5 {
+        BLACK
+    } else {
+        WHITE
+    }
+}
+
+/// The bars of the numbers with their left edge and width in logical pixels, apart if there's
+/// room or else sampled into the columns of device pixels.
+fn bars(
+    size: iced::Size,
+    scale_factor: f32,
+    len: usize,
+) -> Box<dyn Iterator<Item = (usize, f32, f32)>> {
+    match Spacing::of(size, scale_factor, len) {
+        Some(spacing) => Box::new((0..len).map(move |index| {
+            let (x, width) = spacing.bar(index, scale_factor);
+
+            (index, x, width)
+        })),
+        None => Box::new(super::columns(size, scale_factor, len)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bars_apart_with_room() {
+        let size = iced::Size::new(100.0, 50.0);
+
+        // Three device pixels each, centered with a pixel to spare on either side
+        let spaced: Vec<_> = bars(size, 1.0, 32).collect();
+        assert_eq!(spaced.len(), 32);
+        assert_eq!(spaced[0], (0, 2.0, 2.0));
+        assert_eq!(spaced[31], (31, 95.0, 2.0));
+
+        // The gaps belong to the bar left of them
+        let index_at = |x: f32| Bars.index_at(size, 1.0, 32, iced::Point::new(x, 10.0));
+        assert_eq!(index_at(1.5), None);
+        assert_eq!(index_at(2.5), Some(0));
+        assert_eq!(index_at(4.5), Some(0));
+        assert_eq!(index_at(5.5), Some(1));
+        assert_eq!(index_at(98.5), None);
+
+        // At a finer scale there is room again
+        assert_eq!(bars(size, 1.0, 50).count(), 100);
+        assert_eq!(bars(size, 2.0, 50).count(), 50);
+    }
+
+    #[test]
+    fn pairs_are_connected() {
+        let numbers: Vec<usize> = (1..=MAX_CONNECTED + 1).collect();
+        let element = |index| array::Element { index, value: 0 };
+        let scene = |len| Scene {
+            size: iced::Size::new(400.0, 100.0),
+            scale_factor: 1.0,
+            numbers: &numbers[..len],
+            max_value: len,
+            steps: &[],
+            touched: super::super::Touched::new(&[], &[]),
+            reference: None,
+            range: 0..len,
+            offsets: &[],
+            style: super::super::Style::default(),
+        };
+
+        let compared = array::Step::ComparisonTwo(element(3), element(20));
+        let swapped = array::Step::AccessTwo(element(20), element(3));
+        assert!(connection(&scene(30), compared).is_some());
+        assert!(connection(&scene(30), swapped).is_some());
+        assert!(connection(&scene(30), array::Step::Access(element(3))).is_none());
+        assert!(connection(&scene(MAX_CONNECTED), compared).is_some());
+        assert!(connection(&scene(MAX_CONNECTED + 1), compared).is_none());
+    }
+
+    #[test]
+    fn few_wide_bars_are_labeled() {
+        let labels = |width: f32, numbers: Vec<usize>| {
+            let bounds = iced::Rectangle::new(iced::Point::ORIGIN, iced::Size::new(width, 100.0));
+            let geometry = super::super::View::Default.draw(
+                bounds,
+                1.0,
+                &numbers,
+                &[],
+                &[],
+                None,
+                0..numbers.len(),
+                &[],
+                None,
+                &[],
+                super::super::Style::default(),
+            );
+
+            geometry
+                .into_iter()
+                .map(|geometry| format!("{:?}", geometry.into_primitive()))
+                .map(|primitive| primitive.matches("Text {").count())
+                .sum::<usize>()
+        };
+
+        assert_eq!(labels(600.0, (1..=30).collect()), 30);
+        // Too many, or too narrow for their widest value
+        assert_eq!(labels(600.0, (1..=41).collect()), 0);
+        assert_eq!(labels(100.0, (1..=30).collect()), 0);
+        assert_eq!(labels(60.0, vec![1, 2, 3, 1000]), 0);
+        assert_eq!(labels(60.0, vec![1, 2, 3, 10]), 4);
+    }
+}
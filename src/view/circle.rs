@@ -0,0 +1,166 @@
+//! The numbers running clockwise around a circle, as far from its center as they are large.
+
+use super::{Renderer, Scene, BLACK, INDICATOR_SIZE, MIN_OUTLINE, WHITE};
+use iced::canvas;
+
+pub(super) struct Circle;
+
+impl Renderer for Circle {
+    fn draw(&self, frame: &mut canvas::Frame, scene: &Scene) {
+        const RECT_SIZE: iced::Size = iced::Size::new(3.0, 3.0);
+
+        let snapped =
+            |point: iced::Point| iced::Point::new(scene.snap(point.x), scene.snap(point.y));
+
+        frame.fill_rectangle(iced::Point::ORIGIN, scene.size, BLACK);
+        frame.translate(center(scene.size, scene.scale_factor) - iced::Point::ORIGIN);
+
+        let l = radius(scene.size);
+
+        for (index, point) in points(scene.numbers, scene.max_value, l, scene.style.circle_dots) {
+            frame.fill_rectangle(
+                snapped(point),
+                RECT_SIZE,
+                self.color_at(scene, index, WHITE),
+            );
+        }
+
+        // The dots above only sample the numbers, so the touched and sliding ones are drawn where
+        // they are, turned back by as much as they are still sliding
+        let touched = scene.touched.steps.iter().map(|&(index, ..)| index);
+        for index in touched.chain(scene.offsets.iter().map(|&(index, _)| index)) {
+            let point = slid(
+                point(scene.numbers, scene.max_value, l, index),
+                scene,
+                index,
+            );
+
+            frame.fill_rectangle(
+                snapped(point),
+                RECT_SIZE,
+                self.color_at(scene, index, WHITE),
+            );
+        }
+    }
+
+    fn index_at(
+        &self,
+        size: iced::Size,
+        scale_factor: f32,
+        len: usize,
+        point: iced::Point,
+    ) -> Option<usize> {
+        use std::f32::consts::TAU;
+
+        // The numbers run clockwise from the top, picking the one nearest to the angle
+        let offset = point - center(size, scale_factor);
+        let angle = offset.x.atan2(-offset.y).rem_euclid(TAU);
+
+        Some((angle / TAU * len as f32).round() as usize % len)
+    }
+
+    fn outline(&self, scene: &Scene, index: usize) -> canvas::Path {
+        let center = center(scene.size, scene.scale_factor)
+            + (point(scene.numbers, scene.max_value, radius(scene.size), index)
+                - iced::Point::ORIGIN);
+
+        canvas::Path::circle(center, MIN_OUTLINE)
+    }
+
+    /// Outside the dot along its ray, where the shape doesn't cover its neighbours.
+    fn anchor(&self, scene: &Scene, index: usize) -> iced::Point {
+        use std::f64::consts::TAU;
+
+        let (sin, cos) = (index as f64 / scene.numbers.len() as f64 * TAU).sin_cos();
+        let d = radius(scene.size) * scene.normalized_value(index) as f64
+            + (MIN_OUTLINE + INDICATOR_SIZE) as f64;
+
+        center(scene.size, scene.scale_factor)
+            + iced::Vector::new((sin * d) as f32, (-cos * d) as f32)
+    }
+}
+
+/// The center of the circle, relative to the bounds.
+pub(super) fn center(size: iced::Size, scale_factor: f32) -> iced::Point {
+    iced::Point::new(
+        super::snap(size.width / 2.0, scale_factor),
+        super::snap(size.height / 2.0, scale_factor),
+    )
+}
+
+/// The radius of the circle, which the largest number reaches.
+pub(super) fn radius(size: iced::Size) -> f64 {
+    0.4 * f32::min(size.width, size.height) as f64
+}
+
+/// Where the number at the index is drawn, relative to the center.
+pub(super) fn point(numbers: &[usize], max_value: usize, l: f64, index: usize) -> iced::Point {
+    use std::f64::consts::PI;
+
+    let (sin, cos) = (index as f64 / numbers.len() as f64 * 2.0 * PI).sin_cos();
+    let d = l * numbers[index] as f64 / max_value as f64;
+
+    iced::Point::new((sin * d) as f32, (-cos * d) as f32)
+}
+
+/// The point turned around the center by as many places as the number at the index is drawn
+/// away from it while sliding.
+fn slid(point: iced::Point, scene: &Scene, index: usize) -> iced::Point {
+    use std::f32::consts::TAU;
+
+    let offset = scene.offset(index);
+    if offset == 0.0 {
+        return point;
+    }
+
+    let (sin, cos) = (offset / scene.numbers.len() as f32 * TAU).sin_cos();
+
+    iced::Point::new(point.x * cos - point.y * sin, point.x * sin + point.y * cos)
+}
+
+/// The dots around the center along with the indices they show, sampling as many of them for every
+/// eighth of the circle however many numbers there are.
+fn points(
+    numbers: &[usize],
+    max_value: usize,
+    l: f64,
+    dots: u32,
+) -> impl Iterator<Item = (usize, iced::Point)> + '_ {
+    use std::f64::consts::FRAC_PI_4;
+
+    (0..dots).flat_map(move |i| {
+        let r = i as f64 / dots as f64;
+
+        let (mut sin, mut cos) = (r * FRAC_PI_4).sin_cos();
+        sin *= l;
+        cos *= l;
+
+        let rn = r / 8.0;
+
+        [
+            (sin, -cos),
+            (cos, -sin),
+            (cos, sin),
+            (sin, cos),
+            (-sin, cos),
+            (-cos, sin),
+            (-cos, -sin),
+            (-sin, -cos),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(move |(octant, (x, y))| {
+            // Every other octant runs backwards, so the quarters are traced from both ends
+            let quarter = (octant / 2) as f64 * 0.25;
+            let index = if octant % 2 == 0 {
+                ((quarter + rn) * numbers.len() as f64) as usize
+            } else {
+                ((quarter + 0.25 - 0.001 - rn) * numbers.len() as f64) as usize
+            };
+
+            let d = numbers[index] as f64 / max_value as f64;
+
+            (index, iced::Point::new((x * d) as f32, (y * d) as f32))
+        })
+    })
+}
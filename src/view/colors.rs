@@ -0,0 +1,65 @@
+//! A full height column in every column of pixels, colored by its number's hue.
+
+use super::{Renderer, Scene, BLACK, INDICATOR_SIZE, WHITE};
+use crate::array;
+use iced::canvas;
+
+pub(super) struct Colors;
+
+impl Renderer for Colors {
+    fn draw(&self, frame: &mut canvas::Frame, scene: &Scene) {
+        use palette::FromColor;
+
+        let height = scene.size.height;
+        let place = scene.size.width / scene.numbers.len() as f32;
+
+        for (index, x, width) in super::columns(scene.size, scene.scale_factor, scene.numbers.len())
+        {
+            let hue: iced::Color = palette::rgb::Rgb::from_color(palette::Hsv::new(
+                scene.normalized_value(index) * 360.0,
+                1f32,
+                1f32,
+            ))
+            .into();
+
+            frame.fill_rectangle(
+                iced::Point::new(
+                    x + scene.snap(scene.offset(index) * place),
+                    scene.size.height - height,
+                ),
+                iced::Size::new(width, height),
+                self.color_at(scene, index, hue),
+            );
+        }
+    }
+
+    fn index_at(
+        &self,
+        size: iced::Size,
+        scale_factor: f32,
+        len: usize,
+        point: iced::Point,
+    ) -> Option<usize> {
+        super::column_at(size, scale_factor, len, point)
+    }
+
+    fn outline(&self, scene: &Scene, index: usize) -> canvas::Path {
+        super::column_outline(scene, index, scene.size.height)
+    }
+
+    /// At the top of the column, which fills the whole height.
+    fn anchor(&self, scene: &Scene, index: usize) -> iced::Point {
+        let x = (index as f32 + 0.5) / scene.numbers.len() as f32 * scene.size.width;
+
+        iced::Point::new(x, INDICATOR_SIZE)
+    }
+
+    /// Workers aren't told apart, their hues would get lost among the numbers'.
+    fn color_for_step(&self, _: &Scene, _: usize, step: array::Step) -> iced::Color {
+        if step.is_comparison() {
+            WHITE
+        } else {
+            BLACK
+        }
+    }
+}
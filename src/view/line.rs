@@ -0,0 +1,164 @@
+//! A line running through the numbers, as high as they are large, filled down to the baseline
+//! unless the style leaves it empty.
+//!
+//! With more numbers than columns of device pixels, the line spans the smallest and the largest
+//! of the numbers in every column, so the noise of random values isn't sampled away. The touched
+//! numbers are emphasized as vertices on the line.
+
+use super::{Renderer, Scene, BLACK, DIFFERS, DIMMED, INDICATOR_SIZE, MIN_OUTLINE, WHITE};
+use iced::canvas;
+
+/// The radius of the vertices at the touched numbers.
+const VERTEX_RADIUS: f32 = 3.0;
+const LINE_WIDTH: f32 = 1.5;
+/// The area below the line, dim enough for the line to stand out.
+const FILL: iced::Color = iced::Color {
+    r: 1f32,
+    g: 1f32,
+    b: 1f32,
+    a: 0.25f32,
+};
+
+pub(super) struct Line;
+
+impl Renderer for Line {
+    fn draw(&self, frame: &mut canvas::Frame, scene: &Scene) {
+        frame.fill_rectangle(iced::Point::ORIGIN, scene.size, BLACK);
+
+        // Too narrow for a single column, like a minimized window
+        let points = points(scene);
+        let (Some(&first), Some(&last)) = (points.first(), points.last()) else {
+            return;
+        };
+
+        if scene.style.line_fill {
+            let area = canvas::Path::new(|builder| {
+                builder.move_to(iced::Point::new(first.x, scene.size.height));
+                for &point in &points {
+                    builder.line_to(point);
+                }
+                builder.line_to(iced::Point::new(last.x, scene.size.height));
+                builder.close();
+            });
+            frame.fill(&area, FILL);
+        }
+
+        let line = canvas::Path::new(|builder| {
+            builder.move_to(first);
+            for &point in &points[1..] {
+                builder.line_to(point);
+            }
+        });
+        frame.stroke(
+            &line,
+            canvas::Stroke::default()
+                .with_color(WHITE)
+                .with_width(LINE_WIDTH),
+        );
+
+        // A single path can't be dimmed in parts, so whatever is outside the range is shaded over
+        let shade = iced::Color {
+            a: 1.0 - DIMMED,
+            ..BLACK
+        };
+        let len = scene.numbers.len() as f32;
+        let start = scene.range.start as f32 / len * scene.size.width;
+        let end = scene.range.end as f32 / len * scene.size.width;
+        if start > 0.0 {
+            frame.fill_rectangle(
+                iced::Point::ORIGIN,
+                iced::Size::new(start, scene.size.height),
+                shade,
+            );
+        }
+        if end < scene.size.width {
+            frame.fill_rectangle(
+                iced::Point::new(end, 0.0),
+                iced::Size::new(scene.size.width - end, scene.size.height),
+                shade,
+            );
+        }
+
+        // Only where every number has a vertex of its own, as many would bury the line
+        if scene.reference.is_some() && !decimated(scene) {
+            for index in (0..scene.numbers.len()).filter(|&index| scene.differs(index)) {
+                frame.fill(
+                    &canvas::Path::circle(vertex(scene, index), VERTEX_RADIUS),
+                    self.color_at(scene, index, DIFFERS),
+                );
+            }
+        }
+
+        for &(index, ..) in &scene.touched.steps {
+            if index < scene.numbers.len() {
+                frame.fill(
+                    &canvas::Path::circle(vertex(scene, index), VERTEX_RADIUS),
+                    self.color_at(scene, index, WHITE),
+                );
+            }
+        }
+    }
+
+    fn index_at(
+        &self,
+        size: iced::Size,
+        scale_factor: f32,
+        len: usize,
+        point: iced::Point,
+    ) -> Option<usize> {
+        super::column_at(size, scale_factor, len, point)
+    }
+
+    fn outline(&self, scene: &Scene, index: usize) -> canvas::Path {
+        canvas::Path::circle(vertex(scene, index), MIN_OUTLINE)
+    }
+
+    /// Above the vertex, or right below the top of the frame if the vertex is at it.
+    fn anchor(&self, scene: &Scene, index: usize) -> iced::Point {
+        let vertex = vertex(scene, index);
+
+        iced::Point::new(
+            vertex.x,
+            f32::max(vertex.y - MIN_OUTLINE - INDICATOR_SIZE, INDICATOR_SIZE),
+        )
+    }
+}
+
+/// Whether there are more numbers than columns of device pixels to draw them in.
+fn decimated(scene: &Scene) -> bool {
+    scene.numbers.len() > (scene.size.width * scene.scale_factor) as usize
+}
+
+/// Where the line passes the number at the index, in the middle of its share of the width and
+/// as many places away as it is still sliding.
+fn vertex(scene: &Scene, index: usize) -> iced::Point {
+    let len = scene.numbers.len() as f32;
+
+    iced::Point::new(
+        (index as f32 + 0.5 + scene.offset(index)) / len * scene.size.width,
+        (1.0 - scene.normalized_value(index)) * scene.size.height,
+    )
+}
+
+/// The points the line runs through, a vertex at every number, or with more numbers than
+/// columns the largest and then the smallest of the numbers in every column.
+fn points(scene: &Scene) -> Vec<iced::Point> {
+    if !decimated(scene) {
+        return (0..scene.numbers.len())
+            .map(|index| vertex(scene, index))
+            .collect();
+    }
+
+    let columns = (scene.size.width * scene.scale_factor) as usize;
+    let width = 1.0 / scene.scale_factor;
+    let y = |value: usize| (1.0 - value as f32 / scene.max_value as f32) * scene.size.height;
+
+    super::column_extents(scene.numbers, columns)
+        .enumerate()
+        .flat_map(|(column, (min, max))| {
+            let x = (column as f32 + 0.5) * width;
+
+            [iced::Point::new(x, y(max)), iced::Point::new(x, y(min))]
+        })
+        .collect()
+}
@@ -0,0 +1,973 @@
+//! Drawing the array, independent of the rest of the gui so a frame can be rendered headless.
+
+use crate::array;
+use crate::settings::{Indicators, Settings, Theme};
+use iced::canvas;
+
+mod bars;
+mod circle;
+mod colors;
+mod line;
+pub mod raster;
+
+const WHITE: iced::Color = iced::Color::WHITE;
+const BLACK: iced::Color = iced::Color::BLACK;
+pub const RED: iced::Color = iced::Color {
+    r: 1f32,
+    g: 0f32,
+    b: 0f32,
+    a: 1f32,
+};
+pub const GREEN: iced::Color = iced::Color {
+    r: 0f32,
+    g: 1f32,
+    b: 0f32,
+    a: 1f32,
+};
+/// The comparisons and accesses of the high contrast theme, as far apart in luminance as two
+/// colors standing out against the black background can be.
+const HIGH_CONTRAST_COMPARISON: iced::Color = iced::Color {
+    r: 1f32,
+    g: 1f32,
+    b: 0f32,
+    a: 1f32,
+};
+const HIGH_CONTRAST_ACCESS: iced::Color = iced::Color {
+    r: 0f32,
+    g: 0.3f32,
+    b: 1f32,
+    a: 1f32,
+};
+/// The outline of the pinned element.
+const PINNED: iced::Color = iced::Color {
+    r: 1f32,
+    g: 1f32,
+    b: 0f32,
+    a: 1f32,
+};
+/// The tint of the bars differing from the reference run.
+const DIFFERS: iced::Color = iced::Color {
+    r: 0.4f32,
+    g: 0.6f32,
+    b: 1f32,
+    a: 1f32,
+};
+/// The outline of the watched elements, which slow the sort down when touched.
+const WATCHED: iced::Color = iced::Color {
+    r: 1f32,
+    g: 0.5f32,
+    b: 0f32,
+    a: 1f32,
+};
+/// How bright the numbers outside the sorted range are drawn.
+const DIMMED: f32 = 0.35;
+/// The most numbers the tree of a heap is drawn over, its edges would be a tangle with more.
+pub const MAX_HEAP_NUMBERS: usize = 127;
+/// The least width of the outline of the pinned element, to be seen among many.
+const MIN_OUTLINE: f32 = 5.0;
+/// The size of the triangles and squares telling comparisons and accesses apart by their shape.
+const INDICATOR_SIZE: f32 = 8.0;
+/// The highlights of the workers of a sort running on several at once, repeating if needed.
+const WORKER_COLORS: [iced::Color; 4] = [
+    iced::Color {
+        r: 0f32,
+        g: 0.8f32,
+        b: 1f32,
+        a: 1f32,
+    },
+    iced::Color {
+        r: 1f32,
+        g: 0.3f32,
+        b: 0.8f32,
+        a: 1f32,
+    },
+    iced::Color {
+        r: 1f32,
+        g: 0.85f32,
+        b: 0f32,
+        a: 1f32,
+    },
+    iced::Color {
+        r: 0.6f32,
+        g: 0.4f32,
+        b: 1f32,
+        a: 1f32,
+    },
+];
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum View {
+    #[default]
+    Default,
+    Colors,
+    Circle,
+    Line,
+}
+
+impl View {
+    const VALUES: [View; 4] = [View::Default, View::Colors, View::Circle, View::Line];
+
+    pub fn values() -> &'static [View] {
+        View::VALUES.as_slice()
+    }
+}
+
+impl std::fmt::Display for View {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// How every view draws the numbers, as the settings select it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Style {
+    /// The dots the circle draws for every eighth of it.
+    pub circle_dots: u32,
+    /// Whether the line is filled down to the baseline.
+    pub line_fill: bool,
+    /// Whether the steps are told apart by their colors, by the shapes drawn at them, or both.
+    pub indicators: Indicators,
+    /// Whether the steps are colored as far apart in luminance as possible.
+    pub high_contrast: bool,
+}
+
+impl Style {
+    pub fn of(settings: &Settings) -> Style {
+        Style {
+            circle_dots: settings.circle_dots,
+            line_fill: settings.line_fill,
+            indicators: settings.indicators,
+            high_contrast: settings.theme == Theme::HighContrast,
+        }
+    }
+}
+
+impl Default for Style {
+    fn default() -> Style {
+        Style::of(&Settings::default())
+    }
+}
+
+impl View {
+    /// Draws the numbers into the bounds, highlighting the last steps of the workers and the
+    /// sampled steps, and outlining the watched and the pinned indices.
+    ///
+    /// The bars are tinted where the numbers differ from the reference ones, which are those of
+    /// another run after as many operations, and dimmed outside the range the sort sorts. The
+    /// sliding numbers are drawn as many places away from their indices as their offsets say.
+    ///
+    /// The scale factor is the number of device pixels per logical pixel, so a number is drawn in
+    /// every column of device pixels and edges are snapped to them.
+    ///
+    /// The steps are told apart by their colors, or by a triangle at every compared number and a
+    /// square at every accessed one along with an outline, or both, as the style selects.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw(
+        &self,
+        bounds: iced::Rectangle,
+        scale_factor: f32,
+        numbers: &[usize],
+        steps: &[array::Step],
+        highlights: &[(usize, array::Step)],
+        reference: Option<&[usize]>,
+        range: std::ops::Range<usize>,
+        watched: &[usize],
+        pinned: Option<usize>,
+        offsets: &[(usize, f32)],
+        style: Style,
+    ) -> Vec<canvas::Geometry> {
+        if numbers.is_empty() {
+            return Vec::new();
+        }
+
+        let renderer = self.renderer();
+        let scene = Scene {
+            size: bounds.size(),
+            scale_factor,
+            numbers,
+            max_value: numbers.iter().copied().max().unwrap_or(0).max(1),
+            steps,
+            touched: Touched::new(steps, highlights),
+            reference: reference.filter(|reference| reference.len() == numbers.len()),
+            range,
+            offsets,
+            style,
+        };
+
+        let mut frame = canvas::Frame::new(scene.size);
+        renderer.draw(&mut frame, &scene);
+        let mut geometry = vec![frame.into_geometry()];
+
+        if style.indicators.shapes() && !scene.touched.steps.is_empty() {
+            let mut frame = canvas::Frame::new(scene.size);
+            renderer.draw_indicators(&mut frame, &scene);
+            geometry.push(frame.into_geometry());
+        }
+
+        let watched = watched.iter().filter(|&&index| index < numbers.len());
+        if watched.clone().next().is_some() {
+            let mut frame = canvas::Frame::new(scene.size);
+            let stroke = canvas::Stroke::default()
+                .with_color(WATCHED)
+                .with_width(1.0);
+
+            for &index in watched {
+                frame.stroke(&renderer.outline(&scene, index), stroke);
+            }
+            geometry.push(frame.into_geometry());
+        }
+
+        if let Some(index) = pinned.filter(|&index| index < numbers.len()) {
+            let mut frame = canvas::Frame::new(scene.size);
+            let stroke = canvas::Stroke::default().with_color(PINNED).with_width(1.0);
+
+            frame.stroke(&renderer.outline(&scene, index), stroke);
+            geometry.push(frame.into_geometry());
+        }
+
+        geometry
+    }
+
+    /// Draws the implicit binary tree of the heap in the range over the bars, which the heap's
+    /// first index is the root of, and shades the numbers still in it.
+    ///
+    /// Only the bars have tops to connect, and only up to `MAX_HEAP_NUMBERS` of them.
+    pub fn draw_heap(
+        &self,
+        bounds: iced::Rectangle,
+        scale_factor: f32,
+        numbers: &[usize],
+        heap: std::ops::Range<usize>,
+    ) -> Option<canvas::Geometry> {
+        if *self != View::Default || numbers.is_empty() || numbers.len() > MAX_HEAP_NUMBERS {
+            return None;
+        }
+
+        let scene = Scene::plain(bounds.size(), scale_factor, numbers);
+        let mut frame = canvas::Frame::new(scene.size);
+        bars::draw_heap(&mut frame, &scene, heap);
+
+        Some(frame.into_geometry())
+    }
+
+    /// Draws the markers and the marked indices of the sort over the bars, which only the bars
+    /// line up with.
+    pub fn draw_markers(
+        &self,
+        bounds: iced::Rectangle,
+        scale_factor: f32,
+        numbers: &[usize],
+        markers: &[array::Marker],
+        marks: &[usize],
+    ) -> Option<canvas::Geometry> {
+        if *self != View::Default || numbers.is_empty() || markers.is_empty() && marks.is_empty() {
+            return None;
+        }
+
+        let scene = Scene::plain(bounds.size(), scale_factor, numbers);
+        let mut frame = canvas::Frame::new(scene.size);
+        bars::draw_markers(&mut frame, &scene, markers, marks);
+
+        Some(frame.into_geometry())
+    }
+
+    /// The index of the number drawn at the point within the bounds, undoing the mapping of
+    /// `draw`.
+    pub fn index_at(
+        &self,
+        bounds: iced::Rectangle,
+        scale_factor: f32,
+        len: usize,
+        point: iced::Point,
+    ) -> Option<usize> {
+        if len == 0 || !bounds.contains(point) {
+            return None;
+        }
+
+        let point = point - iced::Vector::new(bounds.x, bounds.y);
+
+        self.renderer()
+            .index_at(bounds.size(), scale_factor, len, point)
+    }
+
+    fn renderer(&self) -> &'static dyn Renderer {
+        match self {
+            View::Default => &bars::Bars,
+            View::Colors => &colors::Colors,
+            View::Circle => &circle::Circle,
+            View::Line => &line::Line,
+        }
+    }
+}
+
+/// How a view draws the numbers, implemented by each of them in its own module.
+trait Renderer: Sync {
+    /// Draws the numbers into the frame, which is the size of the scene.
+    fn draw(&self, frame: &mut canvas::Frame, scene: &Scene);
+
+    /// The index of the number drawn at the point, relative to the scene's top left corner.
+    fn index_at(
+        &self,
+        size: iced::Size,
+        scale_factor: f32,
+        len: usize,
+        point: iced::Point,
+    ) -> Option<usize>;
+
+    /// The outline of the number at the index, stroked to pin it.
+    fn outline(&self, scene: &Scene, index: usize) -> canvas::Path;
+
+    /// The center of the shape indicating a step at the index, next to the number it touched
+    /// rather than over it.
+    fn anchor(&self, scene: &Scene, index: usize) -> iced::Point;
+
+    /// The color of a worker's step, telling comparisons and accesses apart unless several
+    /// workers operated in this run, which are told apart instead.
+    fn color_for_step(&self, scene: &Scene, worker: usize, step: array::Step) -> iced::Color {
+        match (step.is_comparison(), scene.style.high_contrast) {
+            _ if scene.touched.workers > 1 => worker_color(worker),
+            (true, false) => GREEN,
+            (false, false) => RED,
+            (true, true) => HIGH_CONTRAST_COMPARISON,
+            (false, true) => HIGH_CONTRAST_ACCESS,
+        }
+    }
+
+    /// The color of the number at the index, which is the color of the step touching it if any
+    /// and the steps are told apart by color, and dimmed if it is outside the sorted range.
+    fn color_at(&self, scene: &Scene, index: usize, untouched: iced::Color) -> iced::Color {
+        let touched = scene
+            .touched
+            .at(index)
+            .filter(|_| scene.style.indicators.colors());
+
+        match touched {
+            Some((worker, step)) => self.color_for_step(scene, worker, step),
+            None if !scene.range.contains(&index) => iced::Color {
+                r: untouched.r * DIMMED,
+                g: untouched.g * DIMMED,
+                b: untouched.b * DIMMED,
+                ..untouched
+            },
+            None => untouched,
+        }
+    }
+
+    /// Draws a triangle at every compared number and a square at every accessed one, outlining
+    /// them as well, in the colors of their steps if those tell them apart too.
+    fn draw_indicators(&self, frame: &mut canvas::Frame, scene: &Scene) {
+        const HALF: f32 = INDICATOR_SIZE / 2.0;
+
+        for &(index, worker, step) in &scene.touched.steps {
+            if index >= scene.numbers.len() {
+                continue;
+            }
+
+            let color = if scene.style.indicators.colors() {
+                self.color_for_step(scene, worker, step)
+            } else {
+                WHITE
+            };
+            let center = self.anchor(scene, index);
+            // Pointing down at the number, like the marker of a position
+            let shape = if step.is_comparison() {
+                canvas::Path::new(|builder| {
+                    builder.move_to(iced::Point::new(center.x, center.y + HALF));
+                    builder.line_to(iced::Point::new(center.x + HALF, center.y - HALF));
+                    builder.line_to(iced::Point::new(center.x - HALF, center.y - HALF));
+                    builder.close();
+                })
+            } else {
+                canvas::Path::rectangle(
+                    iced::Point::new(center.x - HALF, center.y - HALF),
+                    iced::Size::new(INDICATOR_SIZE, INDICATOR_SIZE),
+                )
+            };
+
+            frame.fill(&shape, color);
+            // Set apart from whatever they are drawn over
+            frame.stroke(&shape, canvas::Stroke::default().with_color(BLACK));
+            frame.stroke(
+                &self.outline(scene, index),
+                canvas::Stroke::default().with_color(color).with_width(1.0),
+            );
+        }
+    }
+}
+
+/// Everything a renderer draws a frame from, in logical pixels relative to the bounds.
+struct Scene<'a> {
+    size: iced::Size,
+    scale_factor: f32,
+    numbers: &'a [usize],
+    /// The largest of the numbers, which is drawn the largest.
+    max_value: usize,
+    /// The last steps of the workers.
+    steps: &'a [array::Step],
+    touched: Touched,
+    /// The numbers of a reference run, as many as the drawn ones.
+    reference: Option<&'a [usize]>,
+    /// The range the sort sorts, outside of which the numbers are dimmed.
+    range: std::ops::Range<usize>,
+    /// How many places away from their indices the sliding numbers are drawn.
+    offsets: &'a [(usize, f32)],
+    style: Style,
+}
+
+impl<'a> Scene<'a> {
+    /// The numbers without any steps, reference or range, to draw over.
+    fn plain(size: iced::Size, scale_factor: f32, numbers: &'a [usize]) -> Scene<'a> {
+        Scene {
+            size,
+            scale_factor,
+            numbers,
+            max_value: numbers.iter().copied().max().unwrap_or(0).max(1),
+            steps: &[],
+            touched: Touched::new(&[], &[]),
+            reference: None,
+            range: 0..numbers.len(),
+            offsets: &[],
+            style: Style::default(),
+        }
+    }
+
+    /// How many places away from its index the number at the index is drawn, while it slides.
+    fn offset(&self, index: usize) -> f32 {
+        self.offsets
+            .iter()
+            .find(|&&(sliding, _)| sliding == index)
+            .map_or(0.0, |&(_, offset)| offset)
+    }
+
+    /// Whether the number at the index differs from the reference run's.
+    fn differs(&self, index: usize) -> bool {
+        self.reference
+            .is_some_and(|reference| reference[index] != self.numbers[index])
+    }
+
+    /// The number at the index relative to the largest one, in `0.0..=1.0`.
+    fn normalized_value(&self, index: usize) -> f32 {
+        self.numbers[index] as f32 / self.max_value as f32
+    }
+
+    /// Snaps a logical coordinate to the scene's device pixels.
+    fn snap(&self, coordinate: f32) -> f32 {
+        snap(coordinate, self.scale_factor)
+    }
+}
+
+/// The columns of device pixels across the width along with the index of the number each shows,
+/// with their left edge and width in logical pixels.
+fn columns(
+    size: iced::Size,
+    scale_factor: f32,
+    len: usize,
+) -> impl Iterator<Item = (usize, f32, f32)> {
+    let columns = (size.width * scale_factor) as u32;
+    let width = 1.0 / scale_factor;
+
+    (0..columns).map(move |column| {
+        let index = (column as f32 / columns as f32 * len as f32) as usize;
+
+        (index, column as f32 * width, width)
+    })
+}
+
+/// The smallest and the largest of the numbers falling into each of the columns, with at least one
+/// number in every column, so many numbers can be drawn in few columns without sampling them.
+fn column_extents(numbers: &[usize], columns: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let (len, columns) = (numbers.len(), columns.min(numbers.len()));
+
+    (0..columns).map(move |column| {
+        let covered = &numbers[column * len / columns..(column + 1) * len / columns];
+
+        (
+            covered.iter().copied().min().unwrap_or(0),
+            covered.iter().copied().max().unwrap_or(0),
+        )
+    })
+}
+
+/// The index of the number shown in the column of device pixels at the point, undoing `columns`.
+fn column_at(size: iced::Size, scale_factor: f32, len: usize, point: iced::Point) -> Option<usize> {
+    let columns = (size.width * scale_factor) as u32;
+    let column = (point.x * scale_factor) as u32;
+
+    (column < columns).then(|| (column as f32 / columns as f32 * len as f32) as usize)
+}
+
+/// The outline of the column of the number at the index rising to the height from the bottom.
+fn column_outline(scene: &Scene, index: usize, height: f32) -> canvas::Path {
+    let len = scene.numbers.len() as f32;
+    let left = index as f32 / len * scene.size.width;
+    let right = (index + 1) as f32 / len * scene.size.width;
+
+    bar_outline(scene, left, right, height)
+}
+
+/// The outline of a bar between the edges rising to the height from the bottom, at least a few
+/// pixels wide to be seen among many.
+fn bar_outline(scene: &Scene, left: f32, right: f32, height: f32) -> canvas::Path {
+    let center = (left + right) / 2.0;
+    let width = f32::max(right - left, MIN_OUTLINE);
+
+    canvas::Path::rectangle(
+        iced::Point::new(
+            scene.snap(center - width / 2.0),
+            scene.snap(scene.size.height - height),
+        ),
+        iced::Size::new(scene.snap(width), scene.snap(height)),
+    )
+}
+
+/// Rounds the logical coordinate to a device pixel, so neighbouring shapes don't blur into seams.
+fn snap(coordinate: f32, scale_factor: f32) -> f32 {
+    (coordinate * scale_factor).round() / scale_factor
+}
+
+/// The steps touching the highlighted indices, sorted by index for looking up every drawn element
+/// without going through all the steps.
+struct Touched {
+    steps: Vec<(usize, usize, array::Step)>,
+    workers: usize,
+}
+
+impl Touched {
+    fn new(steps: &[array::Step], highlights: &[(usize, array::Step)]) -> Touched {
+        let mut touched: Vec<_> = steps
+            .iter()
+            .copied()
+            .enumerate()
+            .chain(highlights.iter().rev().copied())
+            .flat_map(|(worker, step)| step.indices().map(move |index| (index, worker, step)))
+            .collect();
+
+        // The sort is stable, so the step taking precedence for an index stays first
+        touched.sort_by_key(|&(index, ..)| index);
+        touched.dedup_by_key(|&mut (index, ..)| index);
+
+        Touched {
+            steps: touched,
+            workers: steps.len(),
+        }
+    }
+
+    /// The first worker that touched the index with its last operation along with that step, or
+    /// else the latest sampled step touching it.
+    fn at(&self, index: usize) -> Option<(usize, array::Step)> {
+        self.steps
+            .binary_search_by_key(&index, |&(index, ..)| index)
+            .ok()
+            .map(|position| {
+                let (_, worker, step) = self.steps[position];
+                (worker, step)
+            })
+    }
+}
+
+/// The color telling a worker of a sort running on several at once apart.
+pub fn worker_color(worker: usize) -> iced::Color {
+    WORKER_COLORS[worker % WORKER_COLORS.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use array::{Element, Step};
+
+    #[test]
+    fn columns_cover_device_pixels() {
+        let bounds = iced::Rectangle::new(iced::Point::ORIGIN, iced::Size::new(100.0, 50.0));
+
+        for scale_factor in [1.0, 1.5, 2.0] {
+            let columns: Vec<_> = columns(bounds.size(), scale_factor, 1000).collect();
+            assert_eq!(columns.len() as f32, 100.0 * scale_factor);
+
+            // Every column starts where the previous ended, on a device pixel
+            for (&(_, x, width), &(_, next, _)) in columns.iter().zip(&columns[1..]) {
+                assert!((x + width - next).abs() < 1e-4);
+                assert!((next * scale_factor - (next * scale_factor).round()).abs() < 1e-3);
+            }
+            assert!(columns.windows(2).all(|pair| pair[0].0 < pair[1].0));
+            assert!(columns.last().unwrap().0 < 1000);
+        }
+    }
+
+    #[test]
+    fn column_extents_cover_all_numbers() {
+        let numbers = [3, 9, 1, 4, 4, 7, 2];
+
+        let extents: Vec<_> = column_extents(&numbers, 3).collect();
+        assert_eq!(extents, [(3, 9), (1, 4), (2, 7)]);
+        // A column for every number at most, never an empty one
+        assert_eq!(column_extents(&numbers, 10).count(), numbers.len());
+        assert_eq!(column_extents(&[], 10).count(), 0);
+    }
+
+    #[test]
+    fn line_is_filled_as_styled() {
+        let bounds = iced::Rectangle::new(iced::Point::ORIGIN, iced::Size::new(100.0, 50.0));
+        let draw = |numbers: &[usize], line_fill| {
+            let style = Style {
+                line_fill,
+                ..Style::default()
+            };
+            let geometry = View::Line.draw(
+                bounds,
+                1.0,
+                numbers,
+                &[],
+                &[],
+                None,
+                0..numbers.len(),
+                &[],
+                None,
+                &[],
+                style,
+            );
+
+            format!(
+                "{:?}",
+                geometry.into_iter().next().unwrap().into_primitive()
+            )
+        };
+
+        // Both with a vertex at every number and with the columns spanning many
+        for numbers in [(1..=50).collect::<Vec<_>>(), (1..=1000).rev().collect()] {
+            assert!(draw(&numbers, false).len() < draw(&numbers, true).len());
+        }
+
+        // Without a single column there is no line to draw
+        let narrow = iced::Rectangle::new(iced::Point::ORIGIN, iced::Size::new(0.5, 50.0));
+        View::Line.draw(
+            narrow,
+            1.0,
+            &[1, 2],
+            &[],
+            &[],
+            None,
+            0..2,
+            &[],
+            None,
+            &[],
+            Style::default(),
+        );
+    }
+
+    fn access(index: usize) -> Step {
+        Step::Access(Element { index, value: 0 })
+    }
+
+    #[test]
+    fn index_at_inverts_drawing() {
+        let bounds =
+            iced::Rectangle::new(iced::Point::new(20.0, 40.0), iced::Size::new(200.0, 100.0));
+        let numbers: Vec<usize> = (1..=50).rev().collect();
+        let origin = iced::Vector::new(bounds.x, bounds.y);
+
+        for (index, x, width) in columns(bounds.size(), 1.5, numbers.len()) {
+            let point = iced::Point::new(x + width / 2.0, 50.0) + origin;
+            assert_eq!(
+                View::Colors.index_at(bounds, 1.5, numbers.len(), point),
+                Some(index)
+            );
+        }
+
+        for index in 0..numbers.len() {
+            let size = bounds.size();
+            let point = circle::center(size, 1.0)
+                + (circle::point(&numbers, 50, circle::radius(size), index) - iced::Point::ORIGIN)
+                + origin;
+            assert_eq!(
+                View::Circle.index_at(bounds, 1.0, numbers.len(), point),
+                Some(index)
+            );
+        }
+
+        let outside = iced::Point::new(10.0, 10.0);
+        assert_eq!(
+            View::Default.index_at(bounds, 1.0, numbers.len(), outside),
+            None
+        );
+    }
+
+    #[test]
+    fn heaps_are_drawn_over_few_bars() {
+        let bounds = iced::Rectangle::new(iced::Point::ORIGIN, iced::Size::new(400.0, 100.0));
+        let numbers: Vec<usize> = (1..=MAX_HEAP_NUMBERS + 1).collect();
+        let edges = |view: View, numbers: &[usize], heap| {
+            view.draw_heap(bounds, 1.0, numbers, heap)
+                .map(|geometry| format!("{:?}", geometry.into_primitive()))
+        };
+
+        // Only the bars, with as many numbers as fit a readable tree
+        assert!(edges(View::Default, &numbers[..MAX_HEAP_NUMBERS], 0..100).is_some());
+        assert!(edges(View::Default, &numbers, 0..100).is_none());
+        assert!(edges(View::Circle, &numbers[..10], 0..10).is_none());
+
+        // The heap shrinks as numbers are extracted, the tree along with it
+        let small = edges(View::Default, &numbers[..10], 2..5).unwrap();
+        let large = edges(View::Default, &numbers[..10], 2..9).unwrap();
+        assert!(small.len() < large.len());
+    }
+
+    #[test]
+    fn markers_are_drawn_over_bars() {
+        let bounds = iced::Rectangle::new(iced::Point::ORIGIN, iced::Size::new(400.0, 100.0));
+        let numbers: Vec<usize> = (1..=1000).collect();
+        let markers = [
+            array::Marker::Merge {
+                start: 0,
+                middle: 500,
+                end: 1000,
+            },
+            array::Marker::Partition {
+                pivot: 999,
+                left: 10,
+                right: 900,
+            },
+        ];
+        let draw = |view: View, markers: &[array::Marker], marks: &[usize]| {
+            view.draw_markers(bounds, 1.0, &numbers, markers, marks)
+                .map(|geometry| format!("{:?}", geometry.into_primitive()))
+        };
+
+        assert!(draw(View::Default, &markers[..1], &[]).is_some());
+        assert!(draw(View::Default, &[], &[]).is_none());
+        assert!(draw(View::Colors, &markers, &[0, 10]).is_none());
+
+        // The pivot is outlined and both scans get a pointer
+        let merge = draw(View::Default, &markers[..1], &[]).unwrap();
+        let both = draw(View::Default, &markers, &[]).unwrap();
+        assert!(merge.len() < both.len());
+
+        // Marks are drawn without any markers, a line each
+        let marks = draw(View::Default, &[], &[0, 500, 1000]).unwrap();
+        assert!(draw(View::Default, &[], &[500]).unwrap().len() < marks.len());
+    }
+
+    #[test]
+    fn steps_are_told_apart_by_shape() {
+        let bounds = iced::Rectangle::new(iced::Point::ORIGIN, iced::Size::new(400.0, 100.0));
+        let numbers: Vec<usize> = (1..=100).collect();
+        let element = |index| Element { index, value: 0 };
+        let steps = [Step::ComparisonTwo(element(10), element(20))];
+        let draw = |view: View, indicators| {
+            let style = Style {
+                indicators,
+                ..Style::default()
+            };
+
+            view.draw(
+                bounds,
+                1.0,
+                &numbers,
+                &steps,
+                &[],
+                None,
+                0..100,
+                &[],
+                None,
+                &[],
+                style,
+            )
+            .into_iter()
+            .map(|geometry| format!("{:?}", geometry.into_primitive()))
+            .collect::<Vec<_>>()
+        };
+
+        for &view in View::values() {
+            // The shapes are drawn over the numbers, which are only colored by their steps
+            // alongside them
+            assert_eq!(draw(view, Indicators::Color).len(), 1, "{view}");
+            let (shape, both) = (draw(view, Indicators::Shape), draw(view, Indicators::Both));
+            assert_eq!(shape.len(), 2, "{view}");
+            assert_ne!(shape[0], both[0], "{view}");
+            assert_eq!(both[0], draw(view, Indicators::Color)[0], "{view}");
+        }
+    }
+
+    #[test]
+    fn last_steps_take_precedence() {
+        let steps = [access(3), access(5)];
+        let highlights = [
+            (0, access(1)),
+            (1, access(3)),
+            (0, access(1)),
+            (1, access(7)),
+        ];
+        let touched = Touched::new(&steps, &highlights);
+
+        assert_eq!(touched.at(3), Some((0, access(3))));
+        assert_eq!(touched.at(5), Some((1, access(5))));
+        assert_eq!(touched.at(7), Some((1, access(7))));
+        assert_eq!(touched.at(1), Some((0, access(1))));
+        assert_eq!(touched.at(2), None);
+        assert_eq!(touched.steps.len(), 4);
+    }
+}
+
+/// Regression tests pinning the exact geometry every view draws, along with reference images of it
+/// rasterized in software which can be looked at.
+///
+/// Run with `UPDATE_GOLDEN=1` to regenerate `golden_frames.txt` and the images in `golden/` after
+/// intentionally changing how a view draws.
+#[cfg(test)]
+mod golden {
+    use super::*;
+    use crate::pixels;
+    use array::ArrayState;
+    use rand::{seq::SliceRandom, SeedableRng};
+    use std::collections::BTreeMap;
+
+    const SIZE: usize = 256;
+    const SEED: u64 = 0;
+    const SCALE_FACTORS: [f32; 2] = [1.0, 1.5];
+
+    /// The size of the reference images, a column for every number.
+    const IMAGE_SIZE: iced::Size = iced::Size::new(SIZE as f32, 128.0);
+    /// The largest difference of a channel of a pixel to still count as matching.
+    const TOLERANCE: u8 = 8;
+    /// The share of pixels allowed not to match, as the tessellation may shift edges slightly.
+    const MISMATCHES: f32 = 0.002;
+
+    fn golden_path() -> std::path::PathBuf {
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/view/golden_frames.txt")
+    }
+
+    fn image_path(view: View) -> std::path::PathBuf {
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src/view/golden")
+            .join(format!("{}.ppm", view.to_string().to_lowercase()))
+    }
+
+    /// A seeded shuffle with a comparison as the last step, a few sampled ones and a pinned index.
+    fn frame(view: View, bounds: iced::Rectangle, scale_factor: f32) -> Vec<canvas::Geometry> {
+        let mut numbers: Vec<usize> = (1..=SIZE).collect();
+        numbers.shuffle(&mut rand::rngs::StdRng::seed_from_u64(SEED));
+
+        let mut array = ArrayState::from_numbers(numbers);
+        for index in [3, 100, 200] {
+            array.swap(index, index + 1);
+            array.highlight_last_step();
+        }
+        array.cmp_two(10, 20);
+
+        view.draw(
+            bounds,
+            scale_factor,
+            array.numbers(),
+            array.steps(),
+            array.highlights(),
+            None,
+            array.range(),
+            &[],
+            Some(42),
+            &[],
+            Style::default(),
+        )
+    }
+
+    /// The FNV-1a hash of the tessellated geometry, which unlike the std hashers is stable.
+    fn fingerprint(geometry: Vec<canvas::Geometry>) -> u64 {
+        let primitives: Vec<_> = geometry
+            .into_iter()
+            .map(canvas::Geometry::into_primitive)
+            .collect();
+
+        format!("{primitives:?}")
+            .bytes()
+            .fold(0xcbf29ce484222325, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+            })
+    }
+
+    #[test]
+    fn frames_match_golden() {
+        let bounds =
+            iced::Rectangle::new(iced::Point::new(0.0, 50.0), iced::Size::new(640.0, 360.0));
+        let frames: BTreeMap<String, u64> = View::values()
+            .iter()
+            .flat_map(|&view| {
+                SCALE_FACTORS.map(|scale_factor| {
+                    (
+                        format!("{view}@{scale_factor}"),
+                        fingerprint(frame(view, bounds, scale_factor)),
+                    )
+                })
+            })
+            .collect();
+
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            let golden: String = frames
+                .iter()
+                .map(|(frame, hash)| format!("{frame} {hash:016x}\n"))
+                .collect();
+            std::fs::write(golden_path(), golden).unwrap();
+
+            return;
+        }
+
+        let golden: BTreeMap<String, u64> = std::fs::read_to_string(golden_path())
+            .expect("missing golden frames, run with UPDATE_GOLDEN=1 to generate them")
+            .lines()
+            .map(|line| {
+                let (frame, hash) = line.split_once(' ').unwrap();
+
+                (frame.to_string(), u64::from_str_radix(hash, 16).unwrap())
+            })
+            .collect();
+
+        for (frame, hash) in &frames {
+            assert_eq!(
+                golden.get(frame),
+                Some(hash),
+                "{frame} changed its geometry, run with UPDATE_GOLDEN=1 if that is intended"
+            );
+        }
+        assert_eq!(golden.len(), frames.len(), "golden frames of removed views");
+    }
+
+    #[test]
+    fn images_match_golden() {
+        let bounds = iced::Rectangle::new(iced::Point::ORIGIN, IMAGE_SIZE);
+        let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+
+        for &view in View::values() {
+            let image = raster::Image::rasterize(frame(view, bounds, 1.0), IMAGE_SIZE);
+            let path = image_path(view);
+
+            if update {
+                std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+                std::fs::write(&path, image.to_ppm()).unwrap();
+
+                continue;
+            }
+
+            let golden = std::fs::read(&path).unwrap_or_else(|_| {
+                panic!("missing golden image of {view}, run with UPDATE_GOLDEN=1 to generate it")
+            });
+            let golden = pixels::Image::read(golden.as_slice()).unwrap();
+            assert_eq!(golden.width(), image.width(), "{view}");
+            assert_eq!(golden.height(), image.height(), "{view}");
+
+            let mismatches = (0..image.height())
+                .flat_map(|y| (0..image.width()).map(move |x| (x, y)))
+                .filter(|&(x, y)| {
+                    image
+                        .pixel(x, y)
+                        .iter()
+                        .zip(golden.pixel(x, y))
+                        .any(|(channel, golden)| channel.abs_diff(golden) > TOLERANCE)
+                })
+                .count();
+
+            assert!(
+                mismatches as f32 <= MISMATCHES * (image.width() * image.height()) as f32,
+                "{view} differs from {} in {mismatches} pixels, run with UPDATE_GOLDEN=1 if that is \
+                 intended",
+                path.display()
+            );
+        }
+    }
+}
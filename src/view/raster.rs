@@ -0,0 +1,233 @@
+//! Rasterizing drawn views in software, to save them as images without a window.
+
+use iced::canvas;
+
+/// An sRGB image filled by rasterizing the triangle meshes of drawn geometry.
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Image {
+    pub fn rasterize(geometry: Vec<canvas::Geometry>, size: iced::Size) -> Image {
+        let (width, height) = (size.width as usize, size.height as usize);
+        let mut pixels = vec![[0.0; 3]; width * height];
+
+        for geometry in geometry {
+            fill(
+                &mut pixels,
+                width,
+                &geometry.into_primitive(),
+                iced::Vector::new(0.0, 0.0),
+            );
+        }
+
+        // The meshes are colored in linear RGB, as described in
+        // https://en.wikipedia.org/wiki/SRGB#The_forward_transformation
+        let srgb = |u: f32| {
+            let u = if u <= 0.0031308 {
+                u * 12.92
+            } else {
+                1.055 * u.powf(1.0 / 2.4) - 0.055
+            };
+
+            (u.clamp(0.0, 1.0) * 255.0).round() as u8
+        };
+
+        Image {
+            width,
+            height,
+            pixels: pixels
+                .into_iter()
+                .map(|pixel: [f32; 3]| pixel.map(srgb))
+                .collect(),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 3] {
+        self.pixels[y * self.width + x]
+    }
+
+    /// The image as a binary portable pixmap, which needs no encoder and most viewers open.
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut ppm = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        ppm.extend(self.pixels.iter().flatten());
+
+        ppm
+    }
+
+    /// The image as a PNG, its pixels stored uncompressed so no deflate encoder is needed.
+    pub fn to_png(&self) -> Vec<u8> {
+        let mut header = Vec::with_capacity(13);
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        // 8 bit RGB, with the default compression and filtering and without interlacing
+        header.extend([8, 2, 0, 0, 0]);
+
+        // Every row starts with its filter, none
+        let mut data = Vec::with_capacity(self.height * (self.width * 3 + 1));
+        for row in self.pixels.chunks_exact(self.width.max(1)) {
+            data.push(0);
+            data.extend(row.iter().flatten());
+        }
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        chunk(&mut png, b"IHDR", &header);
+        chunk(&mut png, b"IDAT", &zlib_stored(&data));
+        chunk(&mut png, b"IEND", &[]);
+
+        png
+    }
+}
+
+/// Appends a PNG chunk, checked by the CRC of its type and data.
+fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    png.extend(kind);
+    png.extend(data);
+    png.extend(crc32([kind.as_slice(), data].concat().iter()).to_be_bytes());
+}
+
+/// The data as a zlib stream of stored deflate blocks, which hold up to 65535 bytes each.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut zlib = vec![0x78, 0x01];
+    let blocks: Vec<_> = data.chunks(u16::MAX as usize).collect();
+
+    for (i, block) in blocks.iter().enumerate() {
+        zlib.push((i + 1 == blocks.len()) as u8);
+        zlib.extend((block.len() as u16).to_le_bytes());
+        zlib.extend((!(block.len() as u16)).to_le_bytes());
+        zlib.extend(*block);
+    }
+    // An empty stream still needs a final block
+    if blocks.is_empty() {
+        zlib.extend([1, 0, 0, 0xff, 0xff]);
+    }
+
+    zlib.extend(adler32(data).to_be_bytes());
+
+    zlib
+}
+
+fn crc32<'a>(bytes: impl Iterator<Item = &'a u8>) -> u32 {
+    !bytes.fold(!0, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u32, |crc, _| {
+            (crc >> 1) ^ (0xedb88320 & (crc & 1).wrapping_neg())
+        })
+    })
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (a, b) = bytes.iter().fold((1, 0), |(a, b), &byte| {
+        let a = (a + byte as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+
+    b << 16 | a
+}
+
+/// Blends the meshes of the primitive into the linear pixels, sampling them at their centers.
+fn fill(
+    pixels: &mut [[f32; 3]],
+    width: usize,
+    primitive: &iced_graphics::Primitive,
+    offset: iced::Vector,
+) {
+    use iced_graphics::Primitive;
+
+    let height = pixels.len() / width;
+
+    match primitive {
+        Primitive::Group { primitives } => {
+            for primitive in primitives {
+                fill(pixels, width, primitive, offset);
+            }
+        }
+        Primitive::Translate {
+            translation,
+            content,
+        } => fill(pixels, width, content, offset + *translation),
+        // The frames are the size of the image, so there is nothing to clip
+        Primitive::Clip { content, .. } => fill(pixels, width, content, offset),
+        Primitive::Cached { cache } => fill(pixels, width, cache, offset),
+        Primitive::Mesh2D { buffers, .. } => {
+            for &triangle in buffers.indices.as_chunks::<3>().0 {
+                let vertices = triangle.map(|index| buffers.vertices[index as usize]);
+                let [a, b, c] = vertices.map(|vertex| {
+                    iced::Point::new(vertex.position[0], vertex.position[1]) + offset
+                });
+
+                let edge = |from: iced::Point, to: iced::Point, point: iced::Point| {
+                    (to.x - from.x) * (point.y - from.y) - (to.y - from.y) * (point.x - from.x)
+                };
+                let area = edge(a, b, c);
+                if area == 0.0 {
+                    continue;
+                }
+
+                let [r, g, b_, alpha] = vertices[0].color;
+                let range = |min: f32, max: f32, len: usize| {
+                    (min.floor().max(0.0) as usize)..(max.ceil().max(0.0) as usize).min(len)
+                };
+
+                for y in range(a.y.min(b.y).min(c.y), a.y.max(b.y).max(c.y), height) {
+                    for x in range(a.x.min(b.x).min(c.x), a.x.max(b.x).max(c.x), width) {
+                        let center = iced::Point::new(x as f32 + 0.5, y as f32 + 0.5);
+                        let inside = [edge(a, b, center), edge(b, c, center), edge(c, a, center)]
+                            .iter()
+                            .all(|side| side * area.signum() >= 0.0);
+
+                        if inside {
+                            let pixel = &mut pixels[y * width + x];
+                            for (channel, color) in pixel.iter_mut().zip([r, g, b_]) {
+                                *channel = color * alpha + *channel * (1.0 - alpha);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(b"123456789".iter()), 0xcbf43926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+    }
+
+    #[test]
+    fn png_chunks() {
+        let image = Image {
+            width: 2,
+            height: 1,
+            pixels: vec![[255, 0, 0], [0, 0, 255]],
+        };
+        let png = image.to_png();
+
+        assert_eq!(png[..8], *b"\x89PNG\r\n\x1a\n");
+        assert_eq!(png[12..16], *b"IHDR");
+        assert_eq!(png[16..24], [0, 0, 0, 2, 0, 0, 0, 1]);
+        // The well known end of every PNG
+        assert_eq!(png[png.len() - 12..], *b"\0\0\0\0IEND\xae\x42\x60\x82");
+
+        let data = &png[41..png.len() - 16];
+        assert_eq!(data[..3], [0x78, 0x01, 1]);
+        assert_eq!(data[3..7], [7, 0, 0xf8, 0xff]);
+        assert_eq!(data[7..14], [0, 255, 0, 0, 0, 0, 255]);
+    }
+}